@@ -0,0 +1,102 @@
+//! Asset bundle
+//!
+//! Assets are looked up by name, e.g. `"images/logo.png"`. Names first
+//! resolve to bytes registered with `register` (typically embedded with
+//! `include_bytes!`), then to files under the asset root directory. The root
+//! defaults to `$KOBALT_ASSETS_DIR`, or `assets` in the working directory.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+/// Environment variable that overrides the default asset root
+pub const ASSETS_DIR_ENV: &str = "KOBALT_ASSETS_DIR";
+
+struct AssetBundle {
+    root: PathBuf,
+    embedded: HashMap<String, &'static [u8]>,
+}
+
+fn bundle() -> &'static RwLock<AssetBundle> {
+    static BUNDLE: OnceLock<RwLock<AssetBundle>> = OnceLock::new();
+    BUNDLE.get_or_init(|| {
+        let root = std::env::var_os(ASSETS_DIR_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("assets"));
+        RwLock::new(AssetBundle {
+            root,
+            embedded: HashMap::new(),
+        })
+    })
+}
+
+/// Registers in-memory bytes for an asset name
+///
+/// # Example
+///
+/// ```
+/// use kobalt_core::assets;
+///
+/// assets::register("greeting.txt", b"hello");
+/// assert_eq!(assets::load("greeting.txt").unwrap(), b"hello");
+/// ```
+pub fn register(name: &str, bytes: &'static [u8]) {
+    bundle()
+        .write()
+        .unwrap()
+        .embedded
+        .insert(name.to_string(), bytes);
+}
+
+/// Sets the directory that file-backed assets are loaded from
+pub fn set_root<P: Into<PathBuf>>(root: P) {
+    bundle().write().unwrap().root = root.into();
+}
+
+/// Returns the directory that file-backed assets are loaded from
+pub fn root() -> PathBuf {
+    bundle().read().unwrap().root.clone()
+}
+
+/// Loads the bytes of an asset
+///
+/// Names may not escape the asset root (absolute paths and `..` are rejected).
+pub fn load(name: &str) -> io::Result<Vec<u8>> {
+    let path = {
+        let bundle = bundle().read().unwrap();
+        if let Some(bytes) = bundle.embedded.get(name) {
+            return Ok(bytes.to_vec());
+        }
+        let relative = Path::new(name);
+        let escapes = relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid asset name: {}", name),
+            ));
+        }
+        bundle.root.join(relative)
+    };
+    std::fs::read(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_asset_takes_precedence() {
+        register("tests/embedded.bin", &[1, 2, 3]);
+        assert_eq!(load("tests/embedded.bin").unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_rejects_escaping_names() {
+        let err = load("../secret").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(load("/etc/passwd").is_err());
+    }
+}
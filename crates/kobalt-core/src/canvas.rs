@@ -0,0 +1,283 @@
+//! Immediate-mode drawing API for custom painters
+//!
+//! `Canvas` wraps a `PaintContext` with a Flutter-style drawing interface:
+//! shapes, paths and text drawn through a current transform and clip that
+//! can be saved and restored.
+
+use crate::image::ImageData;
+use crate::paint::{Paint, PaintContext};
+use crate::path::Path;
+use crate::types::{Color, Point, Rect, Transform};
+
+/// Saved canvas state
+#[derive(Debug, Clone, Copy)]
+struct CanvasState {
+    transform: Transform,
+    clip: Option<Rect>,
+    depth: usize,
+}
+
+/// A drawing surface handed to custom painters
+///
+/// Coordinates are local to the painted widget: `(0, 0)` is its top-left
+/// corner. Transforms apply to everything drawn afterwards. Clips are
+/// axis-aligned; clipping under a rotation uses the rotated rectangle's
+/// bounding box. Text and images follow the transform's translation and
+/// scale but are not rotated.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_core::canvas::Canvas;
+/// use kobalt_core::paint::{Paint, PaintContext};
+/// use kobalt_core::path::StrokeStyle;
+/// use kobalt_core::types::{Color, Point, Rect};
+///
+/// let mut ctx = PaintContext::new();
+/// {
+///     let mut canvas = Canvas::new(&mut ctx, Point::new(10.0, 10.0));
+///     canvas.draw_circle(Point::new(50.0, 50.0), 40.0, &Paint::fill(Color::BLUE));
+///     canvas.save();
+///     canvas.rotate(0.5);
+///     canvas.draw_rect(
+///         Rect::new(0.0, 0.0, 20.0, 20.0),
+///         &Paint::stroke(Color::WHITE, StrokeStyle::new(2.0)),
+///     );
+///     canvas.restore();
+/// }
+/// assert_eq!(ctx.finish().len(), 2);
+/// ```
+pub struct Canvas<'a> {
+    ctx: &'a mut PaintContext,
+    state: CanvasState,
+    saved: Vec<CanvasState>,
+    base_depth: usize,
+}
+
+impl<'a> Canvas<'a> {
+    /// Creates a canvas whose origin is at `origin` in the context's coordinates
+    pub fn new(ctx: &'a mut PaintContext, origin: Point) -> Self {
+        let depth = ctx.depth();
+        Self {
+            ctx,
+            state: CanvasState {
+                transform: Transform::translate(origin.x, origin.y),
+                clip: None,
+                depth,
+            },
+            saved: Vec::new(),
+            base_depth: depth,
+        }
+    }
+
+    /// Saves the current transform and clip
+    pub fn save(&mut self) {
+        self.saved.push(self.state);
+    }
+
+    /// Restores the most recently saved transform and clip
+    pub fn restore(&mut self) {
+        if let Some(state) = self.saved.pop() {
+            self.pop_clips_to(state.depth);
+            self.state = state;
+        }
+    }
+
+    /// Returns the number of saved states
+    pub fn save_count(&self) -> usize {
+        self.saved.len()
+    }
+
+    /// Returns the current transform (local to context coordinates)
+    pub fn current_transform(&self) -> Transform {
+        self.state.transform
+    }
+
+    /// Translates subsequent drawing
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        self.transform(&Transform::translate(dx, dy));
+    }
+
+    /// Scales subsequent drawing
+    pub fn scale(&mut self, sx: f32, sy: f32) {
+        self.transform(&Transform::scale(sx, sy));
+    }
+
+    /// Rotates subsequent drawing clockwise by `radians`
+    pub fn rotate(&mut self, radians: f32) {
+        self.transform(&Transform::rotate(radians));
+    }
+
+    /// Applies an arbitrary transform to subsequent drawing
+    pub fn transform(&mut self, transform: &Transform) {
+        self.state.transform = transform.then(&self.state.transform);
+    }
+
+    /// Restricts subsequent drawing to `rect` until the next `restore`
+    pub fn clip_rect(&mut self, rect: Rect) {
+        let device_rect = self.state.transform.transform_rect(&rect);
+        let clip = match self.state.clip {
+            Some(current) => current
+                .intersection(&device_rect)
+                .unwrap_or(Rect::new(device_rect.x, device_rect.y, 0.0, 0.0)),
+            None => device_rect,
+        };
+        self.state.clip = Some(clip);
+        self.ctx.push_clip(clip);
+    }
+
+    /// Draws a rectangle
+    pub fn draw_rect(&mut self, rect: Rect, paint: &Paint) {
+        if paint.is_fill() && self.state.transform.is_axis_aligned() {
+            let device_rect = self.state.transform.transform_rect(&rect);
+            self.ctx.draw_rect(device_rect, paint.color);
+        } else {
+            self.draw_path(&Path::rect(rect), paint);
+        }
+    }
+
+    /// Draws a rectangle with rounded corners
+    pub fn draw_round_rect(&mut self, rect: Rect, radius: f32, paint: &Paint) {
+        let mut path = Path::new();
+        path.add_round_rect(rect, radius);
+        self.draw_path(&path, paint);
+    }
+
+    /// Draws a circle
+    pub fn draw_circle(&mut self, center: Point, radius: f32, paint: &Paint) {
+        self.draw_path(&Path::circle(center, radius), paint);
+    }
+
+    /// Draws an ellipse inscribed in `rect`
+    pub fn draw_oval(&mut self, rect: Rect, paint: &Paint) {
+        let mut path = Path::new();
+        path.add_oval(rect);
+        self.draw_path(&path, paint);
+    }
+
+    /// Draws a straight line (uses the paint's stroke, or a 1px stroke for fills)
+    pub fn draw_line(&mut self, from: Point, to: Point, paint: &Paint) {
+        let mut path = Path::new();
+        path.move_to(from).line_to(to);
+        let paint = if paint.is_fill() {
+            Paint::stroke(paint.color, Default::default())
+        } else {
+            paint.clone()
+        };
+        self.draw_path(&path, &paint);
+    }
+
+    /// Draws a path
+    pub fn draw_path(&mut self, path: &Path, paint: &Paint) {
+        self.ctx.draw_path(path, paint, self.state.transform);
+    }
+
+    /// Draws text with its top-left corner at `position`
+    pub fn draw_text(&mut self, text: &str, position: Point, font_size: f32, color: Color) {
+        let transform = &self.state.transform;
+        let scale = if transform.is_axis_aligned() {
+            transform.a.abs().min(transform.d.abs())
+        } else {
+            transform.max_scale()
+        };
+        self.ctx.draw_text(
+            text,
+            transform.transform_point(position),
+            font_size * scale,
+            color,
+        );
+    }
+
+    /// Draws the `src` region of an image (in image pixels) into `dst`
+    pub fn draw_image_rect(&mut self, image: &ImageData, src: Rect, dst: Rect, tint: Option<Color>) {
+        let dst = self.state.transform.transform_rect(&dst);
+        self.ctx.draw_image(image, src, dst, tint);
+    }
+
+    /// Closes clips opened after the given context depth
+    fn pop_clips_to(&mut self, depth: usize) {
+        while self.ctx.depth() > depth {
+            self.ctx.pop_clip();
+        }
+    }
+}
+
+impl Drop for Canvas<'_> {
+    fn drop(&mut self) {
+        let depth = self.base_depth;
+        self.pop_clips_to(depth);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paint::DrawCommand;
+
+    #[test]
+    fn test_canvas_offsets_by_origin() {
+        let mut ctx = PaintContext::new();
+        {
+            let mut canvas = Canvas::new(&mut ctx, Point::new(10.0, 20.0));
+            canvas.draw_rect(Rect::new(0.0, 0.0, 5.0, 5.0), &Paint::fill(Color::RED));
+        }
+        let list = ctx.finish();
+        assert_eq!(
+            list.commands()[0],
+            DrawCommand::Rect {
+                rect: Rect::new(10.0, 20.0, 5.0, 5.0),
+                color: Color::RED,
+            }
+        );
+    }
+
+    #[test]
+    fn test_canvas_rotated_rect_becomes_path() {
+        let mut ctx = PaintContext::new();
+        {
+            let mut canvas = Canvas::new(&mut ctx, Point::zero());
+            canvas.rotate(0.3);
+            canvas.draw_rect(Rect::new(0.0, 0.0, 5.0, 5.0), &Paint::fill(Color::RED));
+        }
+        assert!(matches!(ctx.finish().commands()[0], DrawCommand::Path { .. }));
+    }
+
+    #[test]
+    fn test_canvas_save_restore_transform_and_clip() {
+        let mut ctx = PaintContext::new();
+        {
+            let mut canvas = Canvas::new(&mut ctx, Point::zero());
+            canvas.save();
+            canvas.translate(5.0, 5.0);
+            canvas.clip_rect(Rect::new(0.0, 0.0, 10.0, 10.0));
+            canvas.restore();
+            assert!(canvas.current_transform().is_identity());
+            canvas.clip_rect(Rect::new(0.0, 0.0, 1.0, 1.0));
+        }
+        let list = ctx.finish();
+        assert_eq!(
+            list.commands(),
+            &[
+                DrawCommand::PushClip { rect: Rect::new(5.0, 5.0, 10.0, 10.0) },
+                DrawCommand::PopClip,
+                DrawCommand::PushClip { rect: Rect::new(0.0, 0.0, 1.0, 1.0) },
+                DrawCommand::PopClip,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_canvas_nested_clips_intersect() {
+        let mut ctx = PaintContext::new();
+        {
+            let mut canvas = Canvas::new(&mut ctx, Point::zero());
+            canvas.clip_rect(Rect::new(0.0, 0.0, 10.0, 10.0));
+            canvas.clip_rect(Rect::new(5.0, 5.0, 10.0, 10.0));
+        }
+        let list = ctx.finish();
+        assert_eq!(
+            list.commands()[1],
+            DrawCommand::PushClip { rect: Rect::new(5.0, 5.0, 5.0, 5.0) }
+        );
+    }
+}
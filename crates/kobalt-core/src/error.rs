@@ -0,0 +1,78 @@
+//! Errors reported by Kobalt
+//!
+//! Setting up a window and a GPU device can fail in ways an app may want to
+//! report or recover from, so every setup path returns a `KobaltError`
+//! instead of panicking. Backend errors are carried as messages to keep this
+//! crate free of platform and GPU dependencies.
+
+use std::fmt;
+
+/// An error from setting up or running a Kobalt app
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KobaltError {
+    /// No GPU adapter is compatible with the window surface
+    NoAdapter(String),
+    /// The window surface could not be created
+    SurfaceCreation(String),
+    /// The GPU device could not be created
+    DeviceCreation(String),
+    /// The GPU device was lost and could not be recreated
+    DeviceLost(String),
+    /// The window could not be created
+    WindowCreation(String),
+    /// The platform event loop failed
+    EventLoop(String),
+    /// `KobaltApp::run` was called without a home widget
+    MissingHome,
+    /// A font could not be loaded
+    FontLoad(String),
+    /// A rendered frame could not be read back from the GPU
+    Readback(String),
+    /// An image could not be encoded or written
+    ImageSave(String),
+    /// A key chord such as "Ctrl+S" could not be parsed
+    InvalidShortcut(String),
+}
+
+impl fmt::Display for KobaltError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAdapter(message) => write!(f, "no suitable GPU adapter found: {}", message),
+            Self::SurfaceCreation(message) => write!(f, "failed to create the window surface: {}", message),
+            Self::DeviceCreation(message) => write!(f, "failed to create the GPU device: {}", message),
+            Self::DeviceLost(message) => write!(f, "the GPU device was lost: {}", message),
+            Self::WindowCreation(message) => write!(f, "failed to create the window: {}", message),
+            Self::EventLoop(message) => write!(f, "event loop error: {}", message),
+            Self::MissingHome => write!(f, "home widget not set, call .home() before .run()"),
+            Self::FontLoad(message) => write!(f, "failed to load font: {}", message),
+            Self::Readback(message) => write!(f, "failed to read back the frame: {}", message),
+            Self::ImageSave(message) => write!(f, "failed to save image: {}", message),
+            Self::InvalidShortcut(message) => write!(f, "invalid shortcut: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for KobaltError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            KobaltError::MissingHome.to_string(),
+            "home widget not set, call .home() before .run()"
+        );
+        assert_eq!(
+            KobaltError::FontLoad("bad table".to_string()).to_string(),
+            "failed to load font: bad table"
+        );
+    }
+
+    #[test]
+    fn test_boxes_as_std_error() {
+        let error: Box<dyn std::error::Error> = KobaltError::NoAdapter("none".to_string()).into();
+        assert!(error.to_string().contains("GPU adapter"));
+    }
+}
@@ -0,0 +1,386 @@
+//! Input events
+//!
+//! Platform-neutral pointer and keyboard events. The runtime translates
+//! platform input into these and hands them to the `WidgetHost`, which
+//! delivers them to widgets through `Widget::handle_event`. Positions are
+//! in logical pixels in the coordinate space of the root widget.
+
+use crate::types::Point;
+
+/// An input event
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// A pointer (mouse, touch or pen) went down, moved, or went up
+    Pointer(PointerEvent),
+    /// A key was pressed or released
+    Key(KeyEvent),
+}
+
+/// What happened to a pointer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerPhase {
+    /// The pointer made contact (a button was pressed or a finger touched)
+    Down,
+    /// The pointer moved while in contact, or its buttons changed
+    Move,
+    /// The pointer stopped making contact
+    Up,
+    /// The platform cancelled the pointer, e.g. because a system gesture
+    /// took over
+    Cancel,
+    /// The pointer moved without making contact, e.g. a mouse with no
+    /// button pressed
+    Hover,
+    /// The pointer's wheel or touchpad scrolled by `PointerEvent::scroll_delta`
+    Scroll,
+    /// The pointer left the window, e.g. the mouse moved outside it
+    Exit,
+}
+
+/// The kind of device a pointer belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerKind {
+    Mouse,
+    Touch,
+    Pen,
+}
+
+/// The buttons held down on a pointer, as a set of bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PointerButtons(u8);
+
+impl PointerButtons {
+    /// No buttons
+    pub const NONE: Self = Self(0);
+    /// The left mouse button, a finger touching, or a pen touching
+    pub const PRIMARY: Self = Self(1);
+    /// The right mouse button
+    pub const SECONDARY: Self = Self(1 << 1);
+    /// The middle mouse button
+    pub const MIDDLE: Self = Self(1 << 2);
+    /// The "back" side button
+    pub const BACK: Self = Self(1 << 3);
+    /// The "forward" side button
+    pub const FORWARD: Self = Self(1 << 4);
+
+    /// Returns true if no button is held
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if every button in `other` is held
+    pub fn contains(&self, other: PointerButtons) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns these buttons plus `other`
+    pub fn with(self, other: PointerButtons) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Returns these buttons without `other`
+    pub fn without(self, other: PointerButtons) -> Self {
+        Self(self.0 & !other.0)
+    }
+}
+
+/// A pointer event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointerEvent {
+    /// Identifies the pointer across its down, move and up events
+    pub id: u64,
+    /// What happened
+    pub phase: PointerPhase,
+    /// The kind of device
+    pub kind: PointerKind,
+    /// The buttons held after the event
+    pub buttons: PointerButtons,
+    /// Position in logical pixels
+    pub position: Point,
+    /// Pressure from 0 to 1; 1 for devices that do not report pressure
+    /// while in contact, 0 otherwise
+    pub pressure: f32,
+    /// Distance scrolled in logical pixels for `PointerPhase::Scroll`;
+    /// positive values scroll towards the end (right and down)
+    pub scroll_delta: Point,
+}
+
+impl PointerEvent {
+    /// Creates an event for a mouse pointer, with the primary button held
+    /// for `Down` and `Move`
+    pub fn new(id: u64, phase: PointerPhase, position: Point) -> Self {
+        let in_contact = matches!(phase, PointerPhase::Down | PointerPhase::Move);
+        Self {
+            id,
+            phase,
+            kind: PointerKind::Mouse,
+            buttons: if in_contact {
+                PointerButtons::PRIMARY
+            } else {
+                PointerButtons::NONE
+            },
+            position,
+            pressure: if in_contact { 1.0 } else { 0.0 },
+            scroll_delta: Point::zero(),
+        }
+    }
+
+    /// Creates a scroll event
+    pub fn scroll(id: u64, position: Point, delta: Point) -> Self {
+        Self::new(id, PointerPhase::Scroll, position).scroll_delta(delta)
+    }
+
+    /// Sets the kind of device
+    pub fn kind(mut self, kind: PointerKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Sets the buttons held
+    pub fn buttons(mut self, buttons: PointerButtons) -> Self {
+        self.buttons = buttons;
+        self
+    }
+
+    /// Sets the pressure
+    pub fn pressure(mut self, pressure: f32) -> Self {
+        self.pressure = pressure;
+        self
+    }
+
+    /// Sets the distance scrolled
+    pub fn scroll_delta(mut self, delta: Point) -> Self {
+        self.scroll_delta = delta;
+        self
+    }
+}
+
+/// A key, as interpreted by the keyboard layout
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LogicalKey {
+    /// A key that produces text, e.g. "a" or "A"
+    Character(String),
+    Enter,
+    Space,
+    Tab,
+    Escape,
+    Backspace,
+    Delete,
+    Insert,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Shift,
+    Control,
+    Alt,
+    /// The Windows, Command or Super key
+    Super,
+    CapsLock,
+    /// A function key, from F1 up
+    Function(u8),
+    /// A key without a meaning Kobalt knows about
+    Unidentified,
+}
+
+/// A key by its position on the keyboard, independent of the layout
+///
+/// Named after the key at that position on a US keyboard, e.g. `KeyZ` is
+/// the key labelled "Y" on a German keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhysicalKey {
+    KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM,
+    KeyN, KeyO, KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyY, KeyZ,
+    Digit0, Digit1, Digit2, Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9,
+    Enter, Space, Tab, Escape, Backspace, Delete, Insert,
+    ArrowUp, ArrowDown, ArrowLeft, ArrowRight, Home, End, PageUp, PageDown,
+    ShiftLeft, ShiftRight, ControlLeft, ControlRight, AltLeft, AltRight, SuperLeft, SuperRight, CapsLock,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Minus, Equal, BracketLeft, BracketRight, Backslash, Semicolon, Quote, Backquote, Comma, Period, Slash,
+    /// A key without a position Kobalt knows about
+    Unidentified,
+}
+
+/// The modifier keys held, as a set of bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    /// No modifiers
+    pub const NONE: Self = Self(0);
+    pub const SHIFT: Self = Self(1);
+    pub const CONTROL: Self = Self(1 << 1);
+    pub const ALT: Self = Self(1 << 2);
+    /// The Windows, Command or Super key
+    pub const SUPER: Self = Self(1 << 3);
+    /// The modifier of the platform's standard shortcuts: Command on macOS
+    /// and iOS, Control elsewhere
+    pub const PRIMARY: Self = if cfg!(any(target_os = "macos", target_os = "ios")) {
+        Self::SUPER
+    } else {
+        Self::CONTROL
+    };
+
+    /// Returns true if no modifier is held
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if every modifier in `other` is held
+    pub fn contains(&self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns these modifiers plus `other`
+    pub fn with(self, other: Modifiers) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Returns these modifiers without `other`
+    pub fn without(self, other: Modifiers) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Returns true if Shift is held
+    pub fn shift(&self) -> bool {
+        self.contains(Self::SHIFT)
+    }
+
+    /// Returns true if Control is held
+    pub fn control(&self) -> bool {
+        self.contains(Self::CONTROL)
+    }
+
+    /// Returns true if Alt is held
+    pub fn alt(&self) -> bool {
+        self.contains(Self::ALT)
+    }
+
+    /// Returns true if Super is held
+    pub fn super_key(&self) -> bool {
+        self.contains(Self::SUPER)
+    }
+}
+
+/// Whether a key went down or up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyState {
+    Pressed,
+    Released,
+}
+
+/// A keyboard event
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyEvent {
+    /// The key, as interpreted by the keyboard layout
+    pub key: LogicalKey,
+    /// The position of the key on the keyboard
+    pub physical_key: PhysicalKey,
+    /// Whether the key went down or up
+    pub state: KeyState,
+    /// The modifiers held when the event happened
+    pub modifiers: Modifiers,
+    /// True for the repeated presses of a key being held down
+    pub repeat: bool,
+}
+
+impl KeyEvent {
+    /// Creates a key press with no modifiers
+    pub fn pressed(key: LogicalKey) -> Self {
+        Self::new(key, KeyState::Pressed)
+    }
+
+    /// Creates a key release with no modifiers
+    pub fn released(key: LogicalKey) -> Self {
+        Self::new(key, KeyState::Released)
+    }
+
+    fn new(key: LogicalKey, state: KeyState) -> Self {
+        Self {
+            key,
+            physical_key: PhysicalKey::Unidentified,
+            state,
+            modifiers: Modifiers::NONE,
+            repeat: false,
+        }
+    }
+
+    /// Sets the physical key
+    pub fn physical_key(mut self, physical_key: PhysicalKey) -> Self {
+        self.physical_key = physical_key;
+        self
+    }
+
+    /// Sets the modifiers held
+    pub fn modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    /// Marks the event as a repeat
+    pub fn repeat(mut self, repeat: bool) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Returns true for a press, including repeats
+    pub fn is_pressed(&self) -> bool {
+        self.state == KeyState::Pressed
+    }
+}
+
+/// Whether a widget consumed an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventResult {
+    /// The event was consumed and is not offered to other widgets
+    Handled,
+    /// The event is offered to the next widget
+    Ignored,
+}
+
+impl EventResult {
+    /// Returns true if the event was consumed
+    pub fn is_handled(&self) -> bool {
+        matches!(self, EventResult::Handled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pointer_buttons() {
+        let buttons = PointerButtons::PRIMARY.with(PointerButtons::SECONDARY);
+        assert!(buttons.contains(PointerButtons::PRIMARY));
+        assert!(!buttons.contains(PointerButtons::MIDDLE));
+        assert_eq!(buttons.without(PointerButtons::PRIMARY), PointerButtons::SECONDARY);
+        assert!(buttons.without(buttons).is_empty());
+    }
+
+    #[test]
+    fn test_key_event_builders() {
+        let event = KeyEvent::pressed(LogicalKey::Tab)
+            .physical_key(PhysicalKey::Tab)
+            .modifiers(Modifiers::SHIFT.with(Modifiers::CONTROL))
+            .repeat(true);
+        assert!(event.is_pressed() && event.repeat);
+        assert!(event.modifiers.shift() && event.modifiers.control() && !event.modifiers.alt());
+        assert!(!KeyEvent::released(LogicalKey::Tab).is_pressed());
+    }
+
+    #[test]
+    fn test_new_pointer_event_defaults() {
+        let down = PointerEvent::new(1, PointerPhase::Down, Point::new(1.0, 2.0));
+        assert_eq!(down.buttons, PointerButtons::PRIMARY);
+        assert_eq!(down.pressure, 1.0);
+
+        let hover = PointerEvent::new(1, PointerPhase::Hover, Point::zero());
+        assert!(hover.buttons.is_empty());
+        assert_eq!(hover.pressure, 0.0);
+    }
+}
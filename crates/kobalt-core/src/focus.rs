@@ -0,0 +1,513 @@
+//! Keyboard focus - which widget receives key events
+//!
+//! A widget that can take keyboard focus owns a `FocusNode` and returns it
+//! from `Widget::focus_node`. At most one node has the primary focus; key
+//! events go to the widget owning it first and then bubble to its
+//! ancestors, innermost first, until one handles them. With nothing
+//! focused, key events go to the root widget only.
+//!
+//! The focus tree is not stored: the nodes and their nesting are found by
+//! walking the widget tree (`FocusTree::build`), so it always matches the
+//! widgets on screen. Nodes created with `FocusNode::scope` group their
+//! descendants, e.g. a dialog: Tab and Shift+Tab move between the nodes of
+//! the scope containing the focus, and focusing a scope focuses its first
+//! node instead.
+//!
+//! Tab order is the paint order of the widgets unless nodes are given a
+//! traversal order, which works like HTML's `tabindex`: nodes with an order
+//! come first, lowest first, followed by the rest in paint order.
+//!
+//! The primary focus is kept per thread, on the UI thread. The
+//! `WidgetHost` applies autofocus and scope requests when it paints a frame
+//! (`update`), and drops the focus of nodes that are no longer in the tree.
+
+use crate::hit_test::WidgetPath;
+use crate::repaint::{self, RepaintHandle};
+use crate::scheduler;
+use crate::types::Rect;
+use crate::widget::Widget;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::rc::Rc;
+
+/// Called with true when a node gains the primary focus and false when it
+/// loses it
+type FocusListener = Rc<dyn Fn(bool)>;
+
+struct NodeState {
+    scope: bool,
+    can_request_focus: Cell<bool>,
+    skip_traversal: Cell<bool>,
+    autofocus: Cell<bool>,
+    /// Autofocus is applied once, the first time the node is in the tree
+    autofocus_seen: Cell<bool>,
+    traversal_order: Cell<Option<f32>>,
+    listeners: RefCell<Vec<FocusListener>>,
+    /// The repaint boundary enclosing the node's widget when the tree was
+    /// last walked
+    boundary: RefCell<Option<RepaintHandle>>,
+}
+
+/// A handle to a place in the focus tree
+///
+/// Clones share the same node, so a widget can keep one to call
+/// `request_focus` from a callback.
+#[derive(Clone)]
+pub struct FocusNode(Rc<NodeState>);
+
+impl FocusNode {
+    /// Creates a node that can be focused
+    pub fn new() -> Self {
+        Self::create(false)
+    }
+
+    /// Creates a scope node, grouping the nodes below it for traversal
+    pub fn scope() -> Self {
+        Self::create(true)
+    }
+
+    fn create(scope: bool) -> Self {
+        Self(Rc::new(NodeState {
+            scope,
+            can_request_focus: Cell::new(true),
+            skip_traversal: Cell::new(false),
+            autofocus: Cell::new(false),
+            autofocus_seen: Cell::new(false),
+            traversal_order: Cell::new(None),
+            listeners: RefCell::new(Vec::new()),
+            boundary: RefCell::new(None),
+        }))
+    }
+
+    /// Returns true if this is a scope node
+    pub fn is_scope(&self) -> bool {
+        self.0.scope
+    }
+
+    /// Returns true if this node has the primary focus
+    pub fn has_focus(&self) -> bool {
+        PRIMARY.with(|primary| primary.borrow().as_ref() == Some(self))
+    }
+
+    /// Gives this node the primary focus, unless it cannot be focused
+    ///
+    /// A scope passes the focus on to its first node once the host paints
+    /// the next frame.
+    pub fn request_focus(&self) {
+        if self.can_request_focus() {
+            set_primary_focus(Some(self.clone()));
+        }
+    }
+
+    /// Removes the primary focus from this node, if it has it
+    pub fn unfocus(&self) {
+        if self.has_focus() {
+            set_primary_focus(None);
+        }
+    }
+
+    /// Returns whether this node can be focused
+    pub fn can_request_focus(&self) -> bool {
+        self.0.can_request_focus.get()
+    }
+
+    /// Sets whether this node can be focused; disabling it removes its focus
+    pub fn set_can_request_focus(&self, can_request_focus: bool) {
+        self.0.can_request_focus.set(can_request_focus);
+        if !can_request_focus {
+            self.unfocus();
+        }
+    }
+
+    /// Returns whether Tab skips this node
+    pub fn skip_traversal(&self) -> bool {
+        self.0.skip_traversal.get()
+    }
+
+    /// Sets whether Tab skips this node; it can still be focused directly
+    pub fn set_skip_traversal(&self, skip_traversal: bool) {
+        self.0.skip_traversal.set(skip_traversal);
+    }
+
+    /// Returns whether this node takes the focus when it first appears
+    pub fn autofocus(&self) -> bool {
+        self.0.autofocus.get()
+    }
+
+    /// Sets whether this node takes the focus when it first appears and
+    /// nothing else is focused
+    pub fn set_autofocus(&self, autofocus: bool) {
+        self.0.autofocus.set(autofocus);
+    }
+
+    /// Returns the explicit Tab order of this node
+    pub fn traversal_order(&self) -> Option<f32> {
+        self.0.traversal_order.get()
+    }
+
+    /// Sets the Tab order of this node; see the module documentation
+    pub fn set_traversal_order(&self, order: Option<f32>) {
+        self.0.traversal_order.set(order);
+    }
+
+    /// Calls `listener` with true when this node gains the primary focus
+    /// and with false when it loses it
+    pub fn add_listener(&self, listener: impl Fn(bool) + 'static) {
+        self.0.listeners.borrow_mut().push(Rc::new(listener));
+    }
+
+    /// Tells the listeners about a focus change inside the node's repaint
+    /// boundary, and requests a paint there for focus highlights
+    fn notify(&self, focused: bool) {
+        let listeners = self.0.listeners.borrow().clone();
+        let boundary = self.0.boundary.borrow().clone();
+        repaint::with_boundary(boundary, || {
+            for listener in listeners {
+                listener(focused);
+            }
+            scheduler::request_paint();
+        });
+    }
+}
+
+impl Default for FocusNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for FocusNode {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for FocusNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FocusNode")
+            .field("scope", &self.is_scope())
+            .field("has_focus", &self.has_focus())
+            .field("traversal_order", &self.traversal_order())
+            .finish()
+    }
+}
+
+thread_local! {
+    static PRIMARY: RefCell<Option<FocusNode>> = const { RefCell::new(None) };
+}
+
+/// Returns the node with the primary focus
+pub fn primary_focus() -> Option<FocusNode> {
+    PRIMARY.with(|primary| primary.borrow().clone())
+}
+
+/// Moves the primary focus, notifying the nodes that lose and gain it and
+/// requesting a repaint of both so focus highlights are updated
+pub fn set_primary_focus(node: Option<FocusNode>) {
+    let previous = PRIMARY.with(|primary| {
+        let mut primary = primary.borrow_mut();
+        if *primary == node {
+            return None;
+        }
+        Some(std::mem::replace(&mut *primary, node.clone()))
+    });
+    let Some(previous) = previous else {
+        return;
+    };
+    if let Some(previous) = previous {
+        previous.notify(false);
+    }
+    if let Some(node) = node {
+        node.notify(true);
+    }
+}
+
+/// A focus node found in the widget tree
+pub struct FocusEntry<'a> {
+    /// The widget owning the node
+    pub widget: &'a dyn Widget,
+    pub node: FocusNode,
+    /// The path from the root to the widget
+    pub path: WidgetPath,
+    /// The bounds the widget is painted in
+    pub bounds: Rect,
+    /// The index of the nearest enclosing node, if any
+    pub parent: Option<usize>,
+}
+
+/// The focus nodes of a widget tree, in paint order
+pub struct FocusTree<'a> {
+    entries: Vec<FocusEntry<'a>>,
+}
+
+impl<'a> FocusTree<'a> {
+    /// Collects the focus nodes of a laid-out widget tree
+    pub fn build(root: &'a dyn Widget, bounds: Rect) -> Self {
+        let mut entries = Vec::new();
+        collect(root, bounds, &mut WidgetPath::new(), None, None, &mut entries);
+        Self { entries }
+    }
+
+    /// Returns the nodes in paint order
+    pub fn entries(&self) -> &[FocusEntry<'a>] {
+        &self.entries
+    }
+
+    /// Returns the index of a node, if it is in the tree
+    pub fn find(&self, node: &FocusNode) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.node == *node)
+    }
+
+    /// Returns true if the node at `index` is below the node at `ancestor`
+    pub fn is_descendant(&self, index: usize, ancestor: usize) -> bool {
+        let mut current = self.entries[index].parent;
+        while let Some(parent) = current {
+            if parent == ancestor {
+                return true;
+            }
+            current = self.entries[parent].parent;
+        }
+        false
+    }
+
+    /// Returns the index of the nearest scope enclosing the node at `index`
+    pub fn enclosing_scope(&self, index: usize) -> Option<usize> {
+        let mut current = self.entries[index].parent;
+        while let Some(parent) = current {
+            if self.entries[parent].node.is_scope() {
+                return Some(parent);
+            }
+            current = self.entries[parent].parent;
+        }
+        None
+    }
+
+    /// Returns the nodes Tab moves between inside a scope, or in the whole
+    /// tree for `None`, in Tab order
+    pub fn traversal_order(&self, scope: Option<usize>) -> Vec<usize> {
+        let (mut ordered, unordered): (Vec<usize>, Vec<usize>) = (0..self.entries.len())
+            .filter(|&index| {
+                let node = &self.entries[index].node;
+                !node.is_scope()
+                    && node.can_request_focus()
+                    && !node.skip_traversal()
+                    && scope.is_none_or(|scope| self.is_descendant(index, scope))
+            })
+            .partition(|&index| self.entries[index].node.traversal_order().is_some());
+        ordered.sort_by(|&a, &b| {
+            let order = |index: usize| self.entries[index].node.traversal_order().unwrap_or_default();
+            order(a).total_cmp(&order(b))
+        });
+        ordered.extend(unordered);
+        ordered
+    }
+
+    /// Returns the node Tab (`forward`) or Shift+Tab would focus next,
+    /// wrapping around inside the scope of the focused node
+    pub fn next_focus(&self, focused: Option<&FocusNode>, forward: bool) -> Option<FocusNode> {
+        let current = focused.and_then(|node| self.find(node));
+        let scope = current.and_then(|index| match self.entries[index].node.is_scope() {
+            true => Some(index),
+            false => self.enclosing_scope(index),
+        });
+        let order = self.traversal_order(scope);
+        if order.is_empty() {
+            return None;
+        }
+        let len = order.len();
+        let next = match order.iter().position(|&index| Some(index) == current) {
+            Some(position) if forward => (position + 1) % len,
+            Some(position) => (position + len - 1) % len,
+            None if forward => 0,
+            None => len - 1,
+        };
+        Some(self.entries[order[next]].node.clone())
+    }
+
+    /// Returns the node a scope passes the focus on to: its first
+    /// autofocus node, or else its first node in Tab order
+    fn scope_focus(&self, scope: usize) -> Option<FocusNode> {
+        let order = self.traversal_order(Some(scope));
+        let first = order
+            .iter()
+            .find(|&&index| self.entries[index].node.autofocus())
+            .or(order.first())?;
+        Some(self.entries[*first].node.clone())
+    }
+}
+
+fn collect<'a>(
+    widget: &'a dyn Widget,
+    bounds: Rect,
+    path: &mut WidgetPath,
+    mut parent: Option<usize>,
+    boundary: Option<&RepaintHandle>,
+    entries: &mut Vec<FocusEntry<'a>>,
+) {
+    if let Some(node) = widget.focus_node() {
+        node.0.boundary.replace(boundary.cloned());
+        entries.push(FocusEntry {
+            widget,
+            node: node.clone(),
+            path: path.clone(),
+            bounds,
+            parent,
+        });
+        parent = Some(entries.len() - 1);
+    }
+    let boundary = widget.repaint_handle().or(boundary);
+    let mut index = 0;
+    widget.visit_children(bounds, &mut |child, child_bounds| {
+        path.push(index);
+        collect(child, child_bounds, path, parent, boundary, entries);
+        path.pop();
+        index += 1;
+    });
+}
+
+/// Brings the primary focus in line with a laid-out widget tree
+///
+/// Drops the focus of a node that is no longer in the tree, passes the
+/// focus of a scope on to its first node, and applies autofocus if nothing
+/// is focused. Called by the `WidgetHost` when it paints a frame.
+pub fn update(root: &dyn Widget, bounds: Rect) {
+    let tree = FocusTree::build(root, bounds);
+    if let Some(node) = primary_focus() {
+        match tree.find(&node) {
+            None => set_primary_focus(None),
+            Some(index) if node.is_scope() => set_primary_focus(tree.scope_focus(index)),
+            Some(_) => {}
+        }
+    }
+
+    let mut autofocus = None;
+    for (index, entry) in tree.entries().iter().enumerate() {
+        let node = &entry.node;
+        if node.autofocus() && !node.0.autofocus_seen.replace(true) && autofocus.is_none() {
+            autofocus = Some(index);
+        }
+    }
+    if let Some(index) = autofocus.filter(|_| primary_focus().is_none()) {
+        let node = &tree.entries()[index].node;
+        if node.is_scope() {
+            set_primary_focus(tree.scope_focus(index));
+        } else {
+            node.request_focus();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Size;
+
+    /// A widget owning a focus node, with children laid out side by side
+    struct Node {
+        node: FocusNode,
+        children: Vec<Node>,
+    }
+
+    impl Widget for Node {
+        fn widget_type(&self) -> &'static str {
+            "Node"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+            for child in &self.children {
+                visitor(child, bounds);
+            }
+        }
+
+        fn focus_node(&self) -> Option<&FocusNode> {
+            Some(&self.node)
+        }
+    }
+
+    fn node(node: &FocusNode, children: Vec<Node>) -> Node {
+        Node {
+            node: node.clone(),
+            children,
+        }
+    }
+
+    const BOUNDS: Rect = Rect {
+        x: 0.0,
+        y: 0.0,
+        width: 10.0,
+        height: 10.0,
+    };
+
+    #[test]
+    fn test_tab_order_wraps_around() {
+        let (root, a, b) = (FocusNode::scope(), FocusNode::new(), FocusNode::new());
+        let tree_root = node(&root, vec![node(&a, vec![]), node(&b, vec![])]);
+        let tree = FocusTree::build(&tree_root, BOUNDS);
+        assert_eq!(tree.next_focus(None, true), Some(a.clone()));
+        assert_eq!(tree.next_focus(Some(&a), true), Some(b.clone()));
+        assert_eq!(tree.next_focus(Some(&b), true), Some(a.clone()));
+        assert_eq!(tree.next_focus(Some(&a), false), Some(b.clone()));
+        assert_eq!(tree.next_focus(None, false), Some(b));
+    }
+
+    #[test]
+    fn test_traversal_order_comes_first() {
+        let (a, b, c) = (FocusNode::new(), FocusNode::new(), FocusNode::new());
+        c.set_traversal_order(Some(2.0));
+        b.set_traversal_order(Some(1.0));
+        let root = node(&FocusNode::scope(), vec![node(&a, vec![]), node(&b, vec![]), node(&c, vec![])]);
+        let tree = FocusTree::build(&root, BOUNDS);
+        assert_eq!(tree.traversal_order(None), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn test_tab_stays_inside_scope() {
+        let (outside, dialog, first, second) = (FocusNode::new(), FocusNode::scope(), FocusNode::new(), FocusNode::new());
+        let root = node(
+            &FocusNode::scope(),
+            vec![node(&outside, vec![]), node(&dialog, vec![node(&first, vec![]), node(&second, vec![])])],
+        );
+        let tree = FocusTree::build(&root, BOUNDS);
+        assert_eq!(tree.next_focus(Some(&second), true), Some(first));
+    }
+
+    #[test]
+    fn test_update_applies_autofocus_once() {
+        let (a, b) = (FocusNode::new(), FocusNode::new());
+        b.set_autofocus(true);
+        let root = node(&FocusNode::scope(), vec![node(&a, vec![]), node(&b, vec![])]);
+        update(&root, BOUNDS);
+        assert!(b.has_focus());
+
+        b.unfocus();
+        update(&root, BOUNDS);
+        assert_eq!(primary_focus(), None);
+    }
+
+    #[test]
+    fn test_update_drops_focus_of_removed_nodes_and_delegates_scopes() {
+        let (scope, a) = (FocusNode::scope(), FocusNode::new());
+        let root = node(&scope, vec![node(&a, vec![])]);
+        scope.request_focus();
+        update(&root, BOUNDS);
+        assert!(a.has_focus());
+
+        update(&node(&scope, vec![]), BOUNDS);
+        assert_eq!(primary_focus(), None);
+    }
+
+    #[test]
+    fn test_listeners_are_notified() {
+        let (a, b) = (FocusNode::new(), FocusNode::new());
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let log = changes.clone();
+        a.add_listener(move |focused| log.borrow_mut().push(focused));
+        a.request_focus();
+        b.request_focus();
+        assert_eq!(*changes.borrow(), vec![true, false]);
+    }
+}
@@ -0,0 +1,472 @@
+//! Gesture arena - disambiguating gestures that want the same pointer
+//!
+//! A pointer going down may start several gestures at once: a tap on a
+//! button inside a scroll view is either a tap for the button or the start
+//! of a drag for the scroll view. As in Flutter, every recognizer that is
+//! interested in a pointer joins that pointer's arena when it goes down.
+//! Once the down event has been delivered the arena is closed, and from then
+//! on recognizers accept or reject the pointer as it moves: the first to
+//! accept wins and all others are rejected. A recognizer left alone in the
+//! arena wins as well. If nobody has won when the pointer goes up, the arena
+//! is swept and the recognizer that joined first - the innermost, since
+//! events are delivered innermost first - wins.
+//!
+//! A recognizer can hold an arena to postpone the sweep, e.g. a double-tap
+//! recognizer waiting for the second tap. Recognizers waiting for time to
+//! pass, such as a long press, register a deadline instead of a timer; the
+//! `WidgetHost` fires due deadlines when it builds a frame, and the runtime
+//! sleeps until `next_deadline` and then builds a frame.
+//!
+//! Arenas and deadlines are kept per thread, on the UI thread.
+
+use crate::event::PointerEvent;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Distance in logical pixels a pointer may move before it is no longer a
+/// tap and may start a drag
+pub const DEFAULT_TOUCH_SLOP: f32 = 18.0;
+
+/// Time a pointer has to stay down to be a long press
+pub const DEFAULT_LONG_PRESS_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest time between the first tap going up and the second going down
+/// of a double tap
+pub const DEFAULT_DOUBLE_TAP_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// Largest distance between the two taps of a double tap
+pub const DEFAULT_DOUBLE_TAP_SLOP: f32 = 100.0;
+
+/// Thresholds used by gesture recognizers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureSettings {
+    /// Distance a pointer may move before it is no longer a tap or long
+    /// press and may start a drag, pan or scale
+    pub touch_slop: f32,
+    /// Time a pointer has to stay down to be a long press
+    pub long_press_delay: Duration,
+    /// Longest time between the two taps of a double tap
+    pub double_tap_timeout: Duration,
+    /// Largest distance between the two taps of a double tap
+    pub double_tap_slop: f32,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self {
+            touch_slop: DEFAULT_TOUCH_SLOP,
+            long_press_delay: DEFAULT_LONG_PRESS_DELAY,
+            double_tap_timeout: DEFAULT_DOUBLE_TAP_TIMEOUT,
+            double_tap_slop: DEFAULT_DOUBLE_TAP_SLOP,
+        }
+    }
+}
+
+/// Whether a recognizer claims or gives up a pointer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GestureDisposition {
+    Accepted,
+    Rejected,
+}
+
+/// Turns the events of one or more pointers into a gesture
+///
+/// Recognizers are shared with the arenas they join, so they are kept in an
+/// `Rc` and use interior mutability. They must not hold a borrow of their
+/// state while calling into this module, which may call back into them.
+pub trait GestureRecognizer {
+    /// Offers a pointer that went down; returns true if the recognizer
+    /// tracks it, in which case it is added to the pointer's arena
+    fn add_pointer(&self, event: &PointerEvent, settings: &GestureSettings) -> bool;
+
+    /// Handles a later event of a pointer; events of pointers the
+    /// recognizer does not track are ignored
+    fn handle_event(&self, event: &PointerEvent);
+
+    /// Called when the recognizer wins the arena of a pointer
+    fn accept_gesture(&self, pointer: u64);
+
+    /// Called when the recognizer loses the arena of a pointer
+    fn reject_gesture(&self, pointer: u64);
+
+    /// Returns the frame time at which `check_deadline` has to be called
+    fn deadline(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Called from the first frame at or after the deadline
+    fn check_deadline(&self, _now: Duration) {}
+}
+
+/// The recognizers competing for one pointer
+struct Arena {
+    members: Vec<Rc<dyn GestureRecognizer>>,
+    open: bool,
+    held: bool,
+    sweep_pending: bool,
+    eager_winner: Option<Rc<dyn GestureRecognizer>>,
+}
+
+impl Arena {
+    fn new() -> Self {
+        Self {
+            members: Vec::new(),
+            open: true,
+            held: false,
+            sweep_pending: false,
+            eager_winner: None,
+        }
+    }
+}
+
+/// A decision to deliver once the arenas are no longer borrowed
+type Outcome = (Rc<dyn GestureRecognizer>, GestureDisposition);
+
+thread_local! {
+    static ARENAS: RefCell<HashMap<u64, Arena>> = RefCell::new(HashMap::new());
+    static DEADLINES: RefCell<Vec<Rc<dyn GestureRecognizer>>> = const { RefCell::new(Vec::new()) };
+}
+
+fn same(a: &dyn GestureRecognizer, b: &dyn GestureRecognizer) -> bool {
+    std::ptr::addr_eq(a, b)
+}
+
+fn with_arenas<R>(f: impl FnOnce(&mut HashMap<u64, Arena>) -> R) -> R {
+    ARENAS.with(|arenas| f(&mut arenas.borrow_mut()))
+}
+
+fn deliver(pointer: u64, outcomes: Vec<Outcome>) {
+    for (member, disposition) in outcomes {
+        match disposition {
+            GestureDisposition::Accepted => member.accept_gesture(pointer),
+            GestureDisposition::Rejected => member.reject_gesture(pointer),
+        }
+    }
+}
+
+/// Adds a recognizer to the arena of a pointer, creating the arena if needed
+pub fn add(pointer: u64, member: Rc<dyn GestureRecognizer>) {
+    with_arenas(|arenas| {
+        let arena = arenas.entry(pointer).or_insert_with(Arena::new);
+        if !arena.members.iter().any(|existing| same(&**existing, &*member)) {
+            arena.members.push(member);
+        }
+    });
+}
+
+/// Closes the arena of a pointer to new members; called once its down
+/// event has been delivered
+pub fn close(pointer: u64) {
+    let outcomes = with_arenas(|arenas| match arenas.get_mut(&pointer) {
+        Some(arena) => {
+            arena.open = false;
+            try_resolve(arenas, pointer)
+        }
+        None => Vec::new(),
+    });
+    deliver(pointer, outcomes);
+}
+
+/// Resolves the arena of a pointer that went up in favour of its first
+/// member, unless it is held
+pub fn sweep(pointer: u64) {
+    let outcomes = with_arenas(|arenas| {
+        let arena = arenas.get_mut(&pointer)?;
+        if arena.held {
+            arena.sweep_pending = true;
+            return None;
+        }
+        let arena = arenas.remove(&pointer)?;
+        Some(
+            arena
+                .members
+                .into_iter()
+                .enumerate()
+                .map(|(index, member)| {
+                    let disposition = if index == 0 {
+                        GestureDisposition::Accepted
+                    } else {
+                        GestureDisposition::Rejected
+                    };
+                    (member, disposition)
+                })
+                .collect(),
+        )
+    });
+    deliver(pointer, outcomes.unwrap_or_default());
+}
+
+/// Postpones sweeping the arena of a pointer until `release`
+pub fn hold(pointer: u64) {
+    with_arenas(|arenas| {
+        if let Some(arena) = arenas.get_mut(&pointer) {
+            arena.held = true;
+        }
+    });
+}
+
+/// Releases a held arena, sweeping it if its pointer went up meanwhile
+pub fn release(pointer: u64) {
+    let sweep_pending = with_arenas(|arenas| match arenas.get_mut(&pointer) {
+        Some(arena) => {
+            arena.held = false;
+            arena.sweep_pending
+        }
+        None => false,
+    });
+    if sweep_pending {
+        sweep(pointer);
+    }
+}
+
+/// Rejects every recognizer in the arena of a pointer, e.g. because the
+/// platform cancelled it
+pub fn cancel(pointer: u64) {
+    let outcomes = with_arenas(|arenas| match arenas.remove(&pointer) {
+        Some(arena) => arena
+            .members
+            .into_iter()
+            .map(|member| (member, GestureDisposition::Rejected))
+            .collect(),
+        None => Vec::new(),
+    });
+    deliver(pointer, outcomes);
+}
+
+/// Claims or gives up a pointer on behalf of a recognizer in its arena
+///
+/// A recognizer accepting while the arena is still open wins when it
+/// closes. Does nothing if the arena has already been decided.
+pub fn resolve(pointer: u64, member: &dyn GestureRecognizer, disposition: GestureDisposition) {
+    let outcomes = with_arenas(|arenas| {
+        let Some(arena) = arenas.get_mut(&pointer) else {
+            return Vec::new();
+        };
+        let Some(index) = arena.members.iter().position(|existing| same(&**existing, member)) else {
+            return Vec::new();
+        };
+        match disposition {
+            GestureDisposition::Accepted if arena.open => {
+                arena.eager_winner.get_or_insert_with(|| arena.members[index].clone());
+                Vec::new()
+            }
+            GestureDisposition::Accepted => {
+                let winner = arena.members[index].clone();
+                resolve_in_favor_of(arenas, pointer, &winner)
+            }
+            GestureDisposition::Rejected => {
+                let rejected = arena.members.remove(index);
+                if arena.eager_winner.as_ref().is_some_and(|winner| same(&**winner, member)) {
+                    arena.eager_winner = None;
+                }
+                let mut outcomes = vec![(rejected, GestureDisposition::Rejected)];
+                if !arena.open {
+                    outcomes.extend(try_resolve(arenas, pointer));
+                }
+                outcomes
+            }
+        }
+    });
+    deliver(pointer, outcomes);
+}
+
+/// Returns true if a pointer has an undecided arena
+pub fn has_arena(pointer: u64) -> bool {
+    with_arenas(|arenas| arenas.contains_key(&pointer))
+}
+
+/// Decides a closed arena if it has an eager winner or at most one member
+fn try_resolve(arenas: &mut HashMap<u64, Arena>, pointer: u64) -> Vec<Outcome> {
+    let arena = &arenas[&pointer];
+    if let Some(winner) = arena.eager_winner.clone() {
+        return resolve_in_favor_of(arenas, pointer, &winner);
+    }
+    match arena.members.len() {
+        0 => {
+            arenas.remove(&pointer);
+            Vec::new()
+        }
+        1 => {
+            let winner = arena.members[0].clone();
+            resolve_in_favor_of(arenas, pointer, &winner)
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Removes an arena, rejecting every member but the winner, which is
+/// accepted last
+fn resolve_in_favor_of(
+    arenas: &mut HashMap<u64, Arena>,
+    pointer: u64,
+    winner: &Rc<dyn GestureRecognizer>,
+) -> Vec<Outcome> {
+    let Some(arena) = arenas.remove(&pointer) else {
+        return Vec::new();
+    };
+    let mut outcomes: Vec<Outcome> = arena
+        .members
+        .into_iter()
+        .filter(|member| !same(&**member, &**winner))
+        .map(|member| (member, GestureDisposition::Rejected))
+        .collect();
+    outcomes.push((winner.clone(), GestureDisposition::Accepted));
+    outcomes
+}
+
+/// Registers a recognizer whose `deadline` is set, so that it is checked
+/// by the frames at or after it
+pub fn schedule_deadline(recognizer: Rc<dyn GestureRecognizer>) {
+    DEADLINES.with(|deadlines| {
+        let mut deadlines = deadlines.borrow_mut();
+        if !deadlines.iter().any(|existing| same(&**existing, &*recognizer)) {
+            deadlines.push(recognizer);
+        }
+    });
+}
+
+/// Calls `check_deadline` on every registered recognizer whose deadline has
+/// passed at `now`
+pub fn fire_deadlines(now: Duration) {
+    let due: Vec<_> = DEADLINES.with(|deadlines| {
+        let mut deadlines = deadlines.borrow_mut();
+        deadlines.retain(|recognizer| recognizer.deadline().is_some());
+        deadlines
+            .iter()
+            .filter(|recognizer| recognizer.deadline().is_some_and(|deadline| deadline <= now))
+            .cloned()
+            .collect()
+    });
+    for recognizer in due {
+        recognizer.check_deadline(now);
+    }
+}
+
+/// Returns true if a registered recognizer is waiting for a deadline
+pub fn has_pending_deadlines() -> bool {
+    next_deadline().is_some()
+}
+
+/// Returns the earliest frame time a registered recognizer is waiting for
+pub fn next_deadline() -> Option<Duration> {
+    DEADLINES.with(|deadlines| deadlines.borrow().iter().filter_map(|recognizer| recognizer.deadline()).min())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Records the last decision it was told about
+    #[derive(Default)]
+    struct Member {
+        accepted: Cell<bool>,
+        rejected: Cell<bool>,
+        deadline: Cell<Option<Duration>>,
+    }
+
+    impl GestureRecognizer for Member {
+        fn add_pointer(&self, _event: &PointerEvent, _settings: &GestureSettings) -> bool {
+            true
+        }
+
+        fn handle_event(&self, _event: &PointerEvent) {}
+
+        fn accept_gesture(&self, _pointer: u64) {
+            self.accepted.set(true);
+        }
+
+        fn reject_gesture(&self, _pointer: u64) {
+            self.rejected.set(true);
+        }
+
+        fn deadline(&self) -> Option<Duration> {
+            self.deadline.get()
+        }
+
+        fn check_deadline(&self, _now: Duration) {
+            self.deadline.set(None);
+        }
+    }
+
+    fn members(pointer: u64, count: usize) -> Vec<Rc<Member>> {
+        (0..count)
+            .map(|_| {
+                let member = Rc::new(Member::default());
+                add(pointer, member.clone());
+                member
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_single_member_wins_on_close() {
+        let only = members(1, 1);
+        close(1);
+        assert!(only[0].accepted.get());
+        assert!(!has_arena(1));
+    }
+
+    #[test]
+    fn test_first_to_accept_wins() {
+        let both = members(2, 2);
+        close(2);
+        assert!(!both[0].accepted.get() && !both[1].accepted.get());
+
+        resolve(2, &*both[1], GestureDisposition::Accepted);
+        assert!(both[0].rejected.get());
+        assert!(both[1].accepted.get());
+        assert!(!has_arena(2));
+    }
+
+    #[test]
+    fn test_eager_winner_wins_on_close() {
+        let both = members(3, 2);
+        resolve(3, &*both[1], GestureDisposition::Accepted);
+        assert!(!both[1].accepted.get());
+        close(3);
+        assert!(both[1].accepted.get() && both[0].rejected.get());
+    }
+
+    #[test]
+    fn test_last_member_left_wins() {
+        let both = members(4, 2);
+        close(4);
+        resolve(4, &*both[0], GestureDisposition::Rejected);
+        assert!(both[0].rejected.get());
+        assert!(both[1].accepted.get());
+    }
+
+    #[test]
+    fn test_sweep_picks_first_member_unless_held() {
+        let both = members(5, 2);
+        close(5);
+        hold(5);
+        sweep(5);
+        assert!(has_arena(5));
+
+        release(5);
+        assert!(both[0].accepted.get());
+        assert!(both[1].rejected.get());
+        assert!(!has_arena(5));
+    }
+
+    #[test]
+    fn test_next_deadline_is_the_earliest() {
+        let later = Rc::new(Member::default());
+        later.deadline.set(Some(Duration::from_millis(800)));
+        let sooner = Rc::new(Member::default());
+        sooner.deadline.set(Some(Duration::from_millis(500)));
+        schedule_deadline(later.clone());
+        schedule_deadline(sooner.clone());
+        assert_eq!(next_deadline(), Some(Duration::from_millis(500)));
+
+        fire_deadlines(Duration::from_millis(600));
+        assert_eq!(sooner.deadline.get(), None);
+        assert_eq!(next_deadline(), Some(Duration::from_millis(800)));
+        fire_deadlines(Duration::from_millis(800));
+        assert_eq!(next_deadline(), None);
+    }
+}
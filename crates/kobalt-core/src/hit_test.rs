@@ -0,0 +1,295 @@
+//! Hit testing - finding the widgets under a point
+//!
+//! Hit testing walks the laid-out tree from the root, testing children
+//! front to back (the reverse of paint order), so a child painted on top of
+//! its siblings, e.g. the last child of a `Stack`, is hit first. Each widget
+//! decides how it takes part through `Widget::hit_test_behavior`, and can
+//! override `Widget::hit_test` or `Widget::hit_test_children` to clip the
+//! test, map the point through a transform, or ignore pointers entirely.
+//!
+//! The result lists every widget hit, innermost and frontmost first, as the
+//! path of child indices leading to it from the root. Pointer events are
+//! delivered in that order.
+
+use crate::types::{Point, Rect, Transform};
+use crate::widget::Widget;
+
+/// Child indices leading from the root to a widget, as passed to the
+/// visitor of `Widget::visit_children`
+pub type WidgetPath = Vec<usize>;
+
+/// How a widget responds to a point inside its bounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HitTestBehavior {
+    /// Hit only if one of its children is hit
+    DeferToChild,
+    /// Always hit inside its bounds, hiding the widgets behind it
+    #[default]
+    Opaque,
+    /// Always hit inside its bounds, but let the widgets behind it be hit
+    /// as well
+    Translucent,
+}
+
+/// A widget that was hit
+#[derive(Debug, Clone, PartialEq)]
+pub struct HitTestEntry {
+    /// The path from the root to the widget
+    pub path: WidgetPath,
+    /// The bounds the widget was laid out in, in its own coordinates
+    pub bounds: Rect,
+    /// Maps root coordinates to the widget's coordinates
+    pub transform: Transform,
+}
+
+/// The widgets under a point, innermost and frontmost first
+#[derive(Debug, Clone, PartialEq)]
+pub struct HitTestResult {
+    entries: Vec<HitTestEntry>,
+    path: WidgetPath,
+    transform: Transform,
+}
+
+impl HitTestResult {
+    /// Creates an empty result
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            path: WidgetPath::new(),
+            transform: Transform::identity(),
+        }
+    }
+
+    /// Returns the widgets hit
+    pub fn entries(&self) -> &[HitTestEntry] {
+        &self.entries
+    }
+
+    /// Returns the paths of the widgets hit
+    pub fn paths(&self) -> Vec<WidgetPath> {
+        self.entries.iter().map(|entry| entry.path.clone()).collect()
+    }
+
+    /// Returns true if nothing was hit
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of widgets hit
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Records the widget currently being tested as hit
+    pub fn add(&mut self, bounds: Rect) {
+        self.entries.push(HitTestEntry {
+            path: self.path.clone(),
+            bounds,
+            transform: self.transform,
+        });
+    }
+
+    /// Runs `test` for the child at `index` of the widget being tested
+    pub fn with_child<R>(&mut self, index: usize, test: impl FnOnce(&mut Self) -> R) -> R {
+        self.path.push(index);
+        let result = test(self);
+        self.path.pop();
+        result
+    }
+
+    /// Runs `test` with points mapped through `transform`, which maps the
+    /// current coordinates to those of the children
+    pub fn with_transform<R>(&mut self, transform: &Transform, test: impl FnOnce(&mut Self) -> R) -> R {
+        let previous = self.transform;
+        self.transform = previous.then(transform);
+        let result = test(self);
+        self.transform = previous;
+        result
+    }
+}
+
+impl Default for HitTestResult {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hit tests a tree laid out in `bounds`
+pub fn hit_test(root: &dyn Widget, bounds: Rect, position: Point) -> HitTestResult {
+    let mut result = HitTestResult::new();
+    root.hit_test(&mut result, bounds, position);
+    result
+}
+
+/// Tests a widget and its children, applying its `hit_test_behavior`; the
+/// default of `Widget::hit_test`
+///
+/// Points outside the widget's bounds never reach its children, so
+/// children are clipped to their parents for hit testing.
+pub fn hit_test_widget<W: Widget + ?Sized>(widget: &W, result: &mut HitTestResult, bounds: Rect, position: Point) -> bool {
+    if !bounds.contains_point(position) {
+        return false;
+    }
+    let behavior = widget.hit_test_behavior();
+    let hit = widget.hit_test_children(result, bounds, position) || behavior == HitTestBehavior::Opaque;
+    if hit || behavior == HitTestBehavior::Translucent {
+        result.add(bounds);
+    }
+    hit
+}
+
+/// Tests the children of a widget front to back, stopping at the first one
+/// that is hit; the default of `Widget::hit_test_children`
+pub fn hit_test_children<W: Widget + ?Sized>(
+    widget: &W,
+    result: &mut HitTestResult,
+    bounds: Rect,
+    position: Point,
+) -> bool {
+    let mut children = Vec::new();
+    widget.visit_children(bounds, &mut |child, child_bounds| children.push((child, child_bounds)));
+    children
+        .into_iter()
+        .enumerate()
+        .rev()
+        .any(|(index, (child, child_bounds))| {
+            result.with_child(index, |result| child.hit_test(result, child_bounds, position))
+        })
+}
+
+/// Returns the widget at the end of a path, or `None` if the tree no longer
+/// has a widget there
+pub fn widget_at<'a>(root: &'a dyn Widget, bounds: Rect, path: &[usize]) -> Option<&'a dyn Widget> {
+    widgets_along(root, bounds, path)?.pop().map(|(widget, _)| widget)
+}
+
+/// Returns the widgets along a path with the bounds they are painted in,
+/// from the root to the widget at its end, or `None` if the tree no longer
+/// has a widget there
+pub fn widgets_along<'a>(root: &'a dyn Widget, bounds: Rect, path: &[usize]) -> Option<Vec<(&'a dyn Widget, Rect)>> {
+    let mut widgets = vec![(root, bounds)];
+    for &index in path {
+        let (widget, widget_bounds) = widgets[widgets.len() - 1];
+        let mut found = None;
+        let mut current = 0;
+        widget.visit_children(widget_bounds, &mut |child, child_bounds| {
+            if current == index {
+                found = Some((child, child_bounds));
+            }
+            current += 1;
+        });
+        widgets.push(found?);
+    }
+    Some(widgets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A leaf with a configurable behavior
+    struct Leaf(HitTestBehavior);
+
+    impl Widget for Leaf {
+        fn widget_type(&self) -> &'static str {
+            "Leaf"
+        }
+
+        fn layout(&self, constraints: crate::types::Size) -> crate::types::Size {
+            constraints
+        }
+
+        fn hit_test_behavior(&self) -> HitTestBehavior {
+            self.0
+        }
+    }
+
+    /// Overlays its children in the same bounds, the last on top
+    struct Overlay(Vec<Box<dyn Widget>>, Option<Transform>);
+
+    impl Widget for Overlay {
+        fn widget_type(&self) -> &'static str {
+            "Overlay"
+        }
+
+        fn layout(&self, constraints: crate::types::Size) -> crate::types::Size {
+            constraints
+        }
+
+        fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+            for child in &self.0 {
+                visitor(&**child, bounds);
+            }
+        }
+
+        fn hit_test_behavior(&self) -> HitTestBehavior {
+            HitTestBehavior::DeferToChild
+        }
+
+        fn hit_test_children(&self, result: &mut HitTestResult, bounds: Rect, position: Point) -> bool {
+            match self.1.and_then(|transform| transform.inverse()) {
+                Some(inverse) => result.with_transform(&inverse, |result| {
+                    hit_test_children(self, result, bounds, inverse.transform_point(position))
+                }),
+                None => hit_test_children(self, result, bounds, position),
+            }
+        }
+    }
+
+    fn overlay(children: Vec<HitTestBehavior>) -> Overlay {
+        Overlay(
+            children
+                .into_iter()
+                .map(|behavior| Box::new(Leaf(behavior)) as Box<dyn Widget>)
+                .collect(),
+            None,
+        )
+    }
+
+    const BOUNDS: Rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+
+    #[test]
+    fn test_opaque_child_hides_children_behind() {
+        let tree = overlay(vec![HitTestBehavior::Opaque, HitTestBehavior::Opaque]);
+        let result = hit_test(&tree, BOUNDS, Point::new(5.0, 5.0));
+        assert_eq!(result.paths(), vec![vec![1], vec![]]);
+    }
+
+    #[test]
+    fn test_translucent_child_lets_children_behind_be_hit() {
+        let tree = overlay(vec![HitTestBehavior::Opaque, HitTestBehavior::Translucent]);
+        let result = hit_test(&tree, BOUNDS, Point::new(5.0, 5.0));
+        assert_eq!(result.paths(), vec![vec![1], vec![0], vec![]]);
+    }
+
+    #[test]
+    fn test_defer_to_child_without_children_is_not_hit() {
+        let tree = overlay(vec![HitTestBehavior::DeferToChild]);
+        assert!(hit_test(&tree, BOUNDS, Point::new(5.0, 5.0)).is_empty());
+        assert!(hit_test(&tree, BOUNDS, Point::new(15.0, 5.0)).is_empty());
+    }
+
+    #[test]
+    fn test_transforms_map_the_point() {
+        // The children are painted 20 pixels to the right
+        let mut tree = overlay(vec![HitTestBehavior::Opaque]);
+        tree.1 = Some(Transform::translate(20.0, 0.0));
+        let outer = Overlay(vec![Box::new(tree)], None);
+        let bounds = Rect::new(0.0, 0.0, 40.0, 10.0);
+
+        assert!(hit_test(&outer, bounds, Point::new(25.0, 5.0)).paths().contains(&vec![0, 0]));
+        let result = hit_test(&outer, bounds, Point::new(5.0, 5.0));
+        assert!(result.is_empty(), "{:?}", result);
+
+        let result = hit_test(&outer, bounds, Point::new(25.0, 5.0));
+        assert_eq!(result.entries()[0].transform.transform_point(Point::new(25.0, 5.0)), Point::new(5.0, 5.0));
+    }
+
+    #[test]
+    fn test_widget_at() {
+        let tree = overlay(vec![HitTestBehavior::Opaque, HitTestBehavior::Translucent]);
+        assert_eq!(widget_at(&tree, BOUNDS, &[]).unwrap().widget_type(), "Overlay");
+        assert_eq!(widget_at(&tree, BOUNDS, &[1]).unwrap().hit_test_behavior(), HitTestBehavior::Translucent);
+        assert!(widget_at(&tree, BOUNDS, &[2]).is_none());
+    }
+}
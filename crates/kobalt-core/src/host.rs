@@ -0,0 +1,667 @@
+//! Widget host - owns the root widget and its last painted frame
+//!
+//! The host keeps the display list of the previous frame, so a redraw that
+//! was not caused by a frame request (e.g. the window being uncovered) can
+//! be rendered again without walking the widget tree.
+//!
+//! It also delivers input events to the widgets as they were laid out in
+//! that frame. The runtime and `WidgetTester` both drive the tree through a
+//! host, so tests exercise the same paths as a running app.
+
+use crate::event::{Event, EventResult, PointerPhase};
+use crate::focus::{self, FocusTree};
+use crate::gesture;
+use crate::hit_test::{self, HitTestResult};
+use crate::layout_cache;
+use crate::mouse::{MouseCursor, MouseTracker};
+use crate::paint::{DisplayList, PaintContext};
+use crate::repaint;
+use crate::scheduler::{self, FrameRequest};
+use crate::shortcuts::{self, NextFocusIntent, PreviousFocusIntent, ShortcutMap};
+use crate::types::{Point, Rect, Size};
+use crate::widget::Widget;
+use std::any::Any;
+use std::collections::HashMap;
+
+/// Owns the root widget and paints it only when needed
+pub struct WidgetHost {
+    root: Box<dyn Widget>,
+    display_list: Option<DisplayList>,
+    size: Size,
+    paint_count: u64,
+    /// The widgets hit by each pointer that is down
+    pointers: HashMap<u64, HitTestResult>,
+    /// Shortcuts for keys no widget handles
+    default_shortcuts: ShortcutMap,
+    /// The widgets under each mouse
+    mouse: MouseTracker,
+}
+
+impl WidgetHost {
+    /// Creates a host for a root widget
+    pub fn new(root: Box<dyn Widget>) -> Self {
+        Self {
+            root,
+            display_list: None,
+            size: Size::zero(),
+            paint_count: 0,
+            pointers: HashMap::new(),
+            default_shortcuts: ShortcutMap::defaults(),
+            mouse: MouseTracker::new(),
+        }
+    }
+
+    /// Returns the root widget
+    pub fn root(&self) -> &dyn Widget {
+        &*self.root
+    }
+
+    /// Returns how many times the widget tree has been painted
+    pub fn paint_count(&self) -> u64 {
+        self.paint_count
+    }
+
+    /// Returns the display list of the last frame, if it is still current
+    pub fn display_list(&self) -> Option<&DisplayList> {
+        self.display_list.as_ref()
+    }
+
+    /// Returns the size of the last frame
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the bounds of the root widget in the last frame
+    pub fn root_bounds(&self) -> Rect {
+        Rect::new(0.0, 0.0, self.size.width, self.size.height)
+    }
+
+    /// Returns the display list for a frame of the given size
+    ///
+    /// The widget tree is painted again only if the request asks for it, the
+    /// size changed, or nothing has been painted yet; otherwise the previous
+    /// display list is reused. Painting lays out again only the widgets
+    /// whose layout was marked as out of date, unless the request reports
+    /// an environment change, which invalidates every cached layout and
+    /// retained subtree.
+    ///
+    /// Tasks posted to this thread's scheduler by background work run
+    /// first, followed by the gesture deadlines that have passed by the
+    /// frame time. Pending deadlines do not request frames; the runtime
+    /// builds one when `gesture::next_deadline` is reached.
+    pub fn frame(&mut self, size: Size, request: FrameRequest) -> &DisplayList {
+        // Background work finished since the last frame, e.g. image decodes
+        scheduler::run_posted();
+
+        if request.environment {
+            // The window or the media query changed, so no cached layout or
+            // retained subtree can be trusted
+            layout_cache::invalidate_all();
+            repaint::invalidate_all();
+        }
+        if !request.is_empty() || size != self.size {
+            self.display_list = None;
+        }
+
+        // Gestures waiting for time to pass, e.g. a long press
+        gesture::fire_deadlines(scheduler::frame_time());
+
+        self.display_list.get_or_insert_with(|| {
+            // The root widget is given the whole window as its bounds
+            let bounds = Rect::new(0.0, 0.0, size.width, size.height);
+            // Autofocus and focus requests take effect before painting, so
+            // focus highlights show in this frame
+            focus::update(&*self.root, bounds);
+            let mut ctx = PaintContext::new();
+            self.root.paint(&mut ctx, bounds);
+            self.size = size;
+            self.paint_count += 1;
+            // The widgets may have moved under a cursor at rest
+            self.mouse.update_all(&*self.root, bounds);
+            ctx.finish()
+        })
+    }
+
+    /// Returns the cursor to show, as decided by the widgets under the
+    /// mouse that moved last
+    pub fn cursor(&self) -> MouseCursor {
+        self.mouse.current_cursor()
+    }
+
+    /// Returns the tracker of the widgets under each mouse
+    pub fn mouse_tracker(&self) -> &MouseTracker {
+        &self.mouse
+    }
+
+    /// Returns the widgets under a point in the last frame
+    pub fn hit_test(&self, position: Point) -> HitTestResult {
+        hit_test::hit_test(&*self.root, self.root_bounds(), position)
+    }
+
+    /// Delivers an input event to the widget tree as laid out in the last
+    /// frame
+    ///
+    /// A pointer event goes to the widgets hit by the pointer, innermost
+    /// and frontmost first, until one handles it; positions are mapped into
+    /// each widget's coordinates. The widgets hit when a pointer goes down
+    /// keep receiving its events until it goes up or is cancelled, even if
+    /// it leaves them; hover and scroll events always go to the widgets
+    /// under the pointer. The pointer's gesture arena is closed after its
+    /// down event and swept after its up event. Mouse targets are told
+    /// about a pointer entering them before its event is delivered; exit
+    /// events only update them and are not delivered to widgets.
+    ///
+    /// A key event goes to the focused widget and then its ancestors,
+    /// innermost first, until one handles it, or only to the root if
+    /// nothing is focused. A widget whose shortcuts bind the key handles it
+    /// if one of these widgets has an enabled action for the intent (see
+    /// `kobalt_core::shortcuts`). Keys nobody handles go through
+    /// `ShortcutMap::defaults`; without an action of their own, Tab and
+    /// Shift+Tab move the focus to the next or previous widget in Tab
+    /// order.
+    pub fn dispatch_event(&mut self, event: &Event) -> EventResult {
+        let bounds = self.root_bounds();
+        match event {
+            Event::Pointer(pointer) => {
+                self.mouse.update_with_event(&*self.root, bounds, pointer);
+                let hits = match pointer.phase {
+                    PointerPhase::Exit => return EventResult::Ignored,
+                    PointerPhase::Down => {
+                        let hits = self.hit_test(pointer.position);
+                        self.pointers.insert(pointer.id, hits.clone());
+                        hits
+                    }
+                    PointerPhase::Move => match self.pointers.get(&pointer.id) {
+                        Some(hits) => hits.clone(),
+                        None => self.hit_test(pointer.position),
+                    },
+                    PointerPhase::Up | PointerPhase::Cancel => match self.pointers.remove(&pointer.id) {
+                        Some(hits) => hits,
+                        None => self.hit_test(pointer.position),
+                    },
+                    PointerPhase::Hover | PointerPhase::Scroll => self.hit_test(pointer.position),
+                };
+
+                let mut result = EventResult::Ignored;
+                for entry in hits.entries() {
+                    let Some(widgets) = hit_test::widgets_along(&*self.root, bounds, &entry.path) else {
+                        // Removed since the pointer went down
+                        continue;
+                    };
+                    let (widget, _) = widgets[widgets.len() - 1];
+                    let mut local = *pointer;
+                    local.position = entry.transform.transform_point(pointer.position);
+                    let handled = repaint::with_boundary(repaint::enclosing_boundary(&widgets), || {
+                        widget.handle_event(&Event::Pointer(local), entry.bounds)
+                    });
+                    if handled.is_handled() {
+                        result = EventResult::Handled;
+                        break;
+                    }
+                }
+
+                // Every recognizer interested in the pointer has joined its
+                // arena by now
+                match pointer.phase {
+                    PointerPhase::Down => gesture::close(pointer.id),
+                    PointerPhase::Up => gesture::sweep(pointer.id),
+                    PointerPhase::Cancel => gesture::cancel(pointer.id),
+                    _ => {}
+                }
+                result
+            }
+            Event::Key(key) => {
+                let tree = FocusTree::build(&*self.root, bounds);
+                let focused = focus::primary_focus();
+                let targets = focused
+                    .as_ref()
+                    .and_then(|node| tree.find(node))
+                    .and_then(|index| hit_test::widgets_along(&*self.root, bounds, &tree.entries()[index].path))
+                    .unwrap_or_else(|| vec![(&*self.root, bounds)]);
+                // Paint requests go to the boundary enclosing each target
+                let boundaries: Vec<_> = (1..=targets.len())
+                    .map(|len| repaint::enclosing_boundary(&targets[..len]))
+                    .rev()
+                    .collect();
+                let targets: Vec<_> = targets.into_iter().rev().collect();
+                let widgets = || targets.iter().map(|(widget, _)| *widget);
+                for (&(widget, bounds), boundary) in targets.iter().zip(&boundaries) {
+                    let handled = repaint::with_boundary(boundary.clone(), || {
+                        if widget.handle_event(event, bounds).is_handled() {
+                            return true;
+                        }
+                        let intent = widget.shortcuts().and_then(|shortcuts| shortcuts.lookup(key));
+                        intent.is_some_and(|intent| shortcuts::invoke(widgets(), intent))
+                    });
+                    if handled {
+                        return EventResult::Handled;
+                    }
+                }
+
+                let Some(intent) = self.default_shortcuts.lookup(key) else {
+                    return EventResult::Ignored;
+                };
+                let boundary = boundaries.first().cloned().flatten();
+                if repaint::with_boundary(boundary, || shortcuts::invoke(widgets(), intent)) {
+                    return EventResult::Handled;
+                }
+                let intent: &dyn Any = intent;
+                let forward = if intent.is::<NextFocusIntent>() {
+                    true
+                } else if intent.is::<PreviousFocusIntent>() {
+                    false
+                } else {
+                    return EventResult::Ignored;
+                };
+                match tree.next_focus(focused.as_ref(), forward) {
+                    Some(next) => {
+                        next.request_focus();
+                        EventResult::Handled
+                    }
+                    None => EventResult::Ignored,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{KeyEvent, LogicalKey, Modifiers, PointerEvent};
+    use crate::focus::FocusNode;
+    use crate::layout_cache::LayoutCache;
+    use crate::state::ValueNotifier;
+    use crate::types::{Color, Transform};
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    struct Block;
+
+    impl Widget for Block {
+        fn widget_type(&self) -> &'static str {
+            "Block"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+            ctx.draw_rect(bounds, Color::WHITE);
+        }
+    }
+
+    /// Lays out itself and its children through a cache, counting the
+    /// layouts actually computed; the children share its bounds
+    struct Cached {
+        cache: LayoutCache,
+        layouts: Rc<Cell<usize>>,
+        children: Vec<Box<dyn Widget>>,
+    }
+
+    fn cached(cache: LayoutCache, layouts: &Rc<Cell<usize>>, children: Vec<Box<dyn Widget>>) -> Cached {
+        Cached {
+            cache,
+            layouts: layouts.clone(),
+            children,
+        }
+    }
+
+    impl Widget for Cached {
+        fn widget_type(&self) -> &'static str {
+            "Cached"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            self.cache.layout(constraints, |constraints| {
+                self.layouts.set(self.layouts.get() + 1);
+                for child in &self.children {
+                    child.layout(constraints);
+                }
+                constraints
+            })
+        }
+
+        fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+            self.layout(bounds.size());
+            for child in &self.children {
+                child.paint(ctx, bounds);
+            }
+        }
+    }
+
+    const IDLE: FrameRequest = FrameRequest {
+        layout: false,
+        paint: false,
+        environment: false,
+    };
+    const PAINT: FrameRequest = FrameRequest {
+        layout: false,
+        paint: true,
+        environment: false,
+    };
+    const LAYOUT: FrameRequest = FrameRequest {
+        layout: true,
+        paint: true,
+        environment: false,
+    };
+    const ENVIRONMENT: FrameRequest = FrameRequest {
+        layout: true,
+        paint: true,
+        environment: true,
+    };
+
+    #[test]
+    fn test_first_frame_paints() {
+        let mut host = WidgetHost::new(Box::new(Block));
+        assert_eq!(host.frame(Size::new(10.0, 10.0), IDLE).len(), 1);
+        assert_eq!(host.paint_count(), 1);
+    }
+
+    #[test]
+    fn test_idle_frame_reuses_display_list() {
+        let mut host = WidgetHost::new(Box::new(Block));
+        host.frame(Size::new(10.0, 10.0), PAINT);
+        host.frame(Size::new(10.0, 10.0), IDLE);
+        assert_eq!(host.paint_count(), 1);
+
+        host.frame(Size::new(10.0, 10.0), PAINT);
+        assert_eq!(host.paint_count(), 2);
+    }
+
+    #[test]
+    fn test_resize_repaints() {
+        let mut host = WidgetHost::new(Box::new(Block));
+        host.frame(Size::new(10.0, 10.0), IDLE);
+        host.frame(Size::new(20.0, 10.0), IDLE);
+        assert_eq!(host.paint_count(), 2);
+    }
+
+    #[test]
+    fn test_only_environment_changes_invalidate_layout_caches() {
+        let layouts = Rc::new(Cell::new(0));
+        let mut host = WidgetHost::new(Box::new(cached(LayoutCache::new(), &layouts, Vec::new())));
+        host.frame(Size::new(10.0, 10.0), PAINT);
+        host.frame(Size::new(10.0, 10.0), LAYOUT);
+        assert_eq!(layouts.get(), 1);
+
+        host.frame(Size::new(10.0, 10.0), ENVIRONMENT);
+        assert_eq!(layouts.get(), 2);
+    }
+
+    #[test]
+    fn test_state_change_lays_out_only_inside_relayout_boundary() {
+        let counts: [Rc<Cell<usize>>; 4] = Default::default();
+        let [root_layouts, boundary_layouts, leaf_layouts, sibling_layouts] = &counts;
+        let leaf = cached(LayoutCache::new(), leaf_layouts, Vec::new());
+        let handle = leaf.cache.handle();
+        let boundary = cached(LayoutCache::new().relayout_boundary(true), boundary_layouts, vec![Box::new(leaf)]);
+        let sibling = cached(LayoutCache::new(), sibling_layouts, Vec::new());
+        let root = cached(LayoutCache::new(), root_layouts, vec![Box::new(boundary), Box::new(sibling)]);
+        let mut host = WidgetHost::new(Box::new(root));
+
+        // The leaf's size depends on the state
+        let state = ValueNotifier::new(0);
+        state.add_listener(move |_| handle.mark_needs_layout());
+
+        let size = Size::new(10.0, 10.0);
+        host.frame(size, LAYOUT);
+        let layouts = || counts.iter().map(|count| count.get()).collect::<Vec<_>>();
+        assert_eq!(layouts(), [1, 1, 1, 1]);
+
+        state.set(1);
+        host.frame(size, LAYOUT);
+        assert_eq!(host.paint_count(), 2);
+        assert_eq!(layouts(), [1, 2, 2, 1]);
+    }
+
+    /// The pointer events each recorder received, in order
+    type Log = Rc<RefCell<Vec<(&'static str, PointerPhase)>>>;
+
+    /// Records the events it receives; handles them if `consume` is set
+    struct Recorder {
+        name: &'static str,
+        consume: bool,
+        log: Log,
+    }
+
+    impl Widget for Recorder {
+        fn widget_type(&self) -> &'static str {
+            "Recorder"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn handle_event(&self, event: &Event, _bounds: Rect) -> EventResult {
+            if let Event::Pointer(pointer) = event {
+                self.log.borrow_mut().push((self.name, pointer.phase));
+            }
+            if self.consume {
+                EventResult::Handled
+            } else {
+                EventResult::Ignored
+            }
+        }
+    }
+
+    /// A recorder with a child covering its left half
+    struct Parent {
+        recorder: Recorder,
+        child: Recorder,
+    }
+
+    impl Widget for Parent {
+        fn widget_type(&self) -> &'static str {
+            "Parent"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+            visitor(&self.child, Rect::new(bounds.x, bounds.y, bounds.width / 2.0, bounds.height));
+        }
+
+        fn handle_event(&self, event: &Event, bounds: Rect) -> EventResult {
+            self.recorder.handle_event(event, bounds)
+        }
+    }
+
+    fn pointer(phase: PointerPhase, x: f32) -> Event {
+        Event::Pointer(PointerEvent::new(1, phase, Point::new(x, 5.0)))
+    }
+
+    fn parent_host(child_consumes: bool) -> (WidgetHost, Log) {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let recorder = |name, consume| Recorder {
+            name,
+            consume,
+            log: log.clone(),
+        };
+        let mut host = WidgetHost::new(Box::new(Parent {
+            recorder: recorder("parent", true),
+            child: recorder("child", child_consumes),
+        }));
+        host.frame(Size::new(20.0, 10.0), PAINT);
+        (host, log)
+    }
+
+    #[test]
+    fn test_pointer_events_bubble_outwards() {
+        let (mut host, log) = parent_host(false);
+        assert_eq!(host.dispatch_event(&pointer(PointerPhase::Down, 5.0)), EventResult::Handled);
+        assert_eq!(*log.borrow(), vec![("child", PointerPhase::Down), ("parent", PointerPhase::Down)]);
+
+        log.borrow_mut().clear();
+        host.dispatch_event(&pointer(PointerPhase::Up, 15.0));
+        assert_eq!(*log.borrow(), vec![("child", PointerPhase::Up), ("parent", PointerPhase::Up)]);
+    }
+
+    #[test]
+    fn test_handled_pointer_events_stop() {
+        let (mut host, log) = parent_host(true);
+        host.dispatch_event(&pointer(PointerPhase::Down, 5.0));
+        host.dispatch_event(&pointer(PointerPhase::Down, 15.0));
+        assert_eq!(*log.borrow(), vec![("child", PointerPhase::Down), ("parent", PointerPhase::Down)]);
+
+        log.borrow_mut().clear();
+        assert_eq!(host.dispatch_event(&pointer(PointerPhase::Up, 50.0)), EventResult::Handled);
+        assert_eq!(host.dispatch_event(&pointer(PointerPhase::Down, 50.0)), EventResult::Ignored);
+        assert_eq!(*log.borrow(), vec![("parent", PointerPhase::Up)]);
+    }
+
+    #[test]
+    fn test_hover_is_not_captured() {
+        let (mut host, log) = parent_host(false);
+        host.dispatch_event(&pointer(PointerPhase::Down, 5.0));
+        host.dispatch_event(&pointer(PointerPhase::Hover, 15.0));
+        host.dispatch_event(&Event::Pointer(PointerEvent::scroll(1, Point::new(15.0, 5.0), Point::new(0.0, 10.0))));
+        assert_eq!(
+            log.borrow()[2..],
+            [("parent", PointerPhase::Hover), ("parent", PointerPhase::Scroll)]
+        );
+    }
+
+    /// Records the positions of the pointer events it receives
+    struct Probe(Rc<RefCell<Vec<Point>>>);
+
+    impl Widget for Probe {
+        fn widget_type(&self) -> &'static str {
+            "Probe"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn handle_event(&self, event: &Event, _bounds: Rect) -> EventResult {
+            if let Event::Pointer(pointer) = event {
+                self.0.borrow_mut().push(pointer.position);
+            }
+            EventResult::Handled
+        }
+    }
+
+    /// Paints its child 10 pixels to the right
+    struct Shifted(Probe);
+
+    impl Widget for Shifted {
+        fn widget_type(&self) -> &'static str {
+            "Shifted"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+            visitor(&self.0, bounds);
+        }
+
+        fn hit_test_children(&self, result: &mut HitTestResult, bounds: Rect, position: Point) -> bool {
+            let inverse = Transform::translate(-10.0, 0.0);
+            result.with_transform(&inverse, |result| {
+                crate::hit_test::hit_test_children(self, result, bounds, inverse.transform_point(position))
+            })
+        }
+    }
+
+    #[test]
+    fn test_pointer_positions_are_mapped_into_the_widget() {
+        let positions = Rc::new(RefCell::new(Vec::new()));
+        let mut host = WidgetHost::new(Box::new(Shifted(Probe(positions.clone()))));
+        host.frame(Size::new(20.0, 10.0), PAINT);
+
+        host.dispatch_event(&pointer(PointerPhase::Down, 15.0));
+        host.dispatch_event(&pointer(PointerPhase::Up, 18.0));
+        assert_eq!(*positions.borrow(), vec![Point::new(5.0, 5.0), Point::new(8.0, 5.0)]);
+    }
+
+    /// A focusable row of focusable children, recording the key events it
+    /// receives
+    struct Focusable {
+        name: &'static str,
+        node: FocusNode,
+        log: Rc<RefCell<Vec<&'static str>>>,
+        children: Vec<Focusable>,
+    }
+
+    impl Widget for Focusable {
+        fn widget_type(&self) -> &'static str {
+            "Focusable"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+            for child in &self.children {
+                visitor(child, bounds);
+            }
+        }
+
+        fn handle_event(&self, event: &Event, _bounds: Rect) -> EventResult {
+            if let Event::Key(_) = event {
+                self.log.borrow_mut().push(self.name);
+            }
+            EventResult::Ignored
+        }
+
+        fn focus_node(&self) -> Option<&FocusNode> {
+            Some(&self.node)
+        }
+    }
+
+    fn focus_host() -> (WidgetHost, [FocusNode; 2], Rc<RefCell<Vec<&'static str>>>) {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let nodes = [FocusNode::new(), FocusNode::new()];
+        let focusable = |name, node: &FocusNode, children| Focusable {
+            name,
+            node: node.clone(),
+            log: log.clone(),
+            children,
+        };
+        let root = focusable(
+            "root",
+            &FocusNode::scope(),
+            vec![focusable("first", &nodes[0], vec![]), focusable("second", &nodes[1], vec![])],
+        );
+        let mut host = WidgetHost::new(Box::new(root));
+        host.frame(Size::new(20.0, 10.0), PAINT);
+        (host, nodes, log)
+    }
+
+    #[test]
+    fn test_key_events_bubble_from_focused_widget() {
+        let (mut host, nodes, log) = focus_host();
+        host.dispatch_event(&Event::Key(KeyEvent::pressed(LogicalKey::Enter)));
+        assert_eq!(*log.borrow(), vec!["root"]);
+
+        log.borrow_mut().clear();
+        nodes[1].request_focus();
+        host.dispatch_event(&Event::Key(KeyEvent::pressed(LogicalKey::Enter)));
+        assert_eq!(*log.borrow(), vec!["second", "root"]);
+    }
+
+    #[test]
+    fn test_tab_moves_focus() {
+        let (mut host, nodes, _) = focus_host();
+        let tab = KeyEvent::pressed(LogicalKey::Tab);
+        assert!(host.dispatch_event(&Event::Key(tab.clone())).is_handled());
+        assert!(nodes[0].has_focus());
+        host.dispatch_event(&Event::Key(tab.clone()));
+        assert!(nodes[1].has_focus());
+        host.dispatch_event(&Event::Key(tab.clone().modifiers(Modifiers::SHIFT)));
+        assert!(nodes[0].has_focus());
+
+        // Ctrl+Tab and releases do not move the focus
+        host.dispatch_event(&Event::Key(tab.modifiers(Modifiers::CONTROL)));
+        host.dispatch_event(&Event::Key(KeyEvent::released(LogicalKey::Tab)));
+        assert!(nodes[0].has_focus());
+    }
+}
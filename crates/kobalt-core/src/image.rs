@@ -0,0 +1,103 @@
+//! Decoded image data shared between widgets and renderers
+
+use crate::types::Size;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_IMAGE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A decoded image in straight (non-premultiplied) RGBA8
+///
+/// Cloning is cheap: pixels are shared. Every image gets a unique id when
+/// it is created, which renderers use to key their GPU texture caches, so
+/// two `ImageData` compare equal only if one is a clone of the other.
+#[derive(Clone)]
+pub struct ImageData {
+    id: u64,
+    width: u32,
+    height: u32,
+    pixels: Arc<[u8]>,
+}
+
+impl ImageData {
+    /// Creates an image from RGBA8 pixels in row-major order
+    ///
+    /// Returns None if `pixels` does not hold exactly `width * height * 4` bytes.
+    pub fn from_rgba8(width: u32, height: u32, pixels: Vec<u8>) -> Option<Self> {
+        if pixels.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+        Some(Self {
+            id: NEXT_IMAGE_ID.fetch_add(1, Ordering::Relaxed),
+            width,
+            height,
+            pixels: pixels.into(),
+        })
+    }
+
+    /// Returns the unique id of this image
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the size in pixels
+    pub fn size(&self) -> Size {
+        Size::new(self.width as f32, self.height as f32)
+    }
+
+    /// Returns the RGBA8 pixels
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Returns the number of bytes used by the pixels
+    pub fn byte_len(&self) -> usize {
+        self.pixels.len()
+    }
+}
+
+impl PartialEq for ImageData {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl fmt::Debug for ImageData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageData")
+            .field("id", &self.id)
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_data_validates_length() {
+        assert!(ImageData::from_rgba8(2, 2, vec![0; 16]).is_some());
+        assert!(ImageData::from_rgba8(2, 2, vec![0; 15]).is_none());
+    }
+
+    #[test]
+    fn test_image_data_ids_are_unique() {
+        let a = ImageData::from_rgba8(1, 1, vec![0; 4]).unwrap();
+        let b = ImageData::from_rgba8(1, 1, vec![0; 4]).unwrap();
+        assert_ne!(a, b);
+        assert_eq!(a, a.clone());
+    }
+}
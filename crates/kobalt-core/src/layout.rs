@@ -1,9 +1,12 @@
 //! Layout primitives and alignment enums
 
+use crate::types::{Length, Rect, Size};
+
 /// Main axis alignment (vertical for Column, horizontal for Row)
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum MainAxisAlignment {
     /// Place children at the start of the axis
+    #[default]
     Start,
     /// Place children at the end of the axis
     End,
@@ -17,16 +20,11 @@ pub enum MainAxisAlignment {
     SpaceEvenly,
 }
 
-impl Default for MainAxisAlignment {
-    fn default() -> Self {
-        Self::Start
-    }
-}
-
 /// Cross axis alignment (horizontal for Column, vertical for Row)
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum CrossAxisAlignment {
     /// Align children to the start of the cross axis
+    #[default]
     Start,
     /// Align children to the end of the cross axis
     End,
@@ -36,24 +34,23 @@ pub enum CrossAxisAlignment {
     Stretch,
 }
 
-impl Default for CrossAxisAlignment {
-    fn default() -> Self {
-        Self::Start
-    }
-}
-
 /// Edge insets for padding/margin
+///
+/// Each side is a `Length`, so insets can be given in `dp`, `sp` or `px`;
+/// the accessors resolve them to logical pixels against the current
+/// `MediaQuery`.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct EdgeInsets {
-    pub top: f32,
-    pub right: f32,
-    pub bottom: f32,
-    pub left: f32,
+    pub top: Length,
+    pub right: Length,
+    pub bottom: Length,
+    pub left: Length,
 }
 
 impl EdgeInsets {
     /// Creates edge insets with all sides equal
-    pub fn all(value: f32) -> Self {
+    pub fn all(value: impl Into<Length>) -> Self {
+        let value = value.into();
         Self {
             top: value,
             right: value,
@@ -63,7 +60,9 @@ impl EdgeInsets {
     }
 
     /// Creates edge insets with symmetric values
-    pub fn symmetric(vertical: f32, horizontal: f32) -> Self {
+    pub fn symmetric(vertical: impl Into<Length>, horizontal: impl Into<Length>) -> Self {
+        let vertical = vertical.into();
+        let horizontal = horizontal.into();
         Self {
             top: vertical,
             right: horizontal,
@@ -73,33 +72,53 @@ impl EdgeInsets {
     }
 
     /// Creates edge insets with individual values
-    pub fn only(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+    pub fn only(
+        top: impl Into<Length>,
+        right: impl Into<Length>,
+        bottom: impl Into<Length>,
+        left: impl Into<Length>,
+    ) -> Self {
         Self {
-            top,
-            right,
-            bottom,
-            left,
+            top: top.into(),
+            right: right.into(),
+            bottom: bottom.into(),
+            left: left.into(),
         }
     }
 
     /// Creates zero edge insets
     pub fn zero() -> Self {
-        Self {
-            top: 0.0,
-            right: 0.0,
-            bottom: 0.0,
-            left: 0.0,
-        }
+        Self::all(0.0)
+    }
+
+    /// Returns the top inset in logical pixels
+    pub fn top(&self) -> f32 {
+        self.top.to_logical()
     }
 
-    /// Returns the total horizontal insets
+    /// Returns the right inset in logical pixels
+    pub fn right(&self) -> f32 {
+        self.right.to_logical()
+    }
+
+    /// Returns the bottom inset in logical pixels
+    pub fn bottom(&self) -> f32 {
+        self.bottom.to_logical()
+    }
+
+    /// Returns the left inset in logical pixels
+    pub fn left(&self) -> f32 {
+        self.left.to_logical()
+    }
+
+    /// Returns the total horizontal insets in logical pixels
     pub fn horizontal(&self) -> f32 {
-        self.left + self.right
+        self.left() + self.right()
     }
 
-    /// Returns the total vertical insets
+    /// Returns the total vertical insets in logical pixels
     pub fn vertical(&self) -> f32 {
-        self.top + self.bottom
+        self.top() + self.bottom()
     }
 }
 
@@ -108,3 +127,197 @@ impl Default for EdgeInsets {
         Self::zero()
     }
 }
+
+/// How a box of one size is inscribed into a box of another size
+///
+/// Mirrors Flutter's `BoxFit`; used by `Image` to fit pixels into the
+/// widget's bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BoxFit {
+    /// Stretch to fill the target, distorting the aspect ratio
+    Fill,
+    /// As large as possible while staying entirely inside the target
+    #[default]
+    Contain,
+    /// As small as possible while covering the entire target (crops)
+    Cover,
+    /// Match the target width, cropping or letterboxing vertically
+    FitWidth,
+    /// Match the target height, cropping or letterboxing horizontally
+    FitHeight,
+    /// No scaling; the source is centered (per alignment) and cropped
+    None,
+    /// Like `Contain`, but never scales up
+    ScaleDown,
+}
+
+/// Source and destination sizes computed by `BoxFit::apply`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FittedSizes {
+    /// Portion of the input that is shown
+    pub source: Size,
+    /// Size the shown portion occupies in the output
+    pub destination: Size,
+}
+
+impl BoxFit {
+    /// Fits a box of size `input` into a box of size `output`
+    pub fn apply(self, input: Size, output: Size) -> FittedSizes {
+        if input.is_empty() || output.is_empty() {
+            return FittedSizes {
+                source: Size::zero(),
+                destination: Size::zero(),
+            };
+        }
+
+        let input_aspect = input.width / input.height;
+        let output_aspect = output.width / output.height;
+        let (source, destination) = match self {
+            BoxFit::Fill => (input, output),
+            BoxFit::Contain => {
+                if output_aspect > input_aspect {
+                    (input, Size::new(output.height * input_aspect, output.height))
+                } else {
+                    (input, Size::new(output.width, output.width / input_aspect))
+                }
+            }
+            BoxFit::Cover => {
+                if output_aspect > input_aspect {
+                    (Size::new(input.width, input.width / output_aspect), output)
+                } else {
+                    (Size::new(input.height * output_aspect, input.height), output)
+                }
+            }
+            BoxFit::FitWidth => {
+                if output_aspect > input_aspect {
+                    (Size::new(input.width, input.width / output_aspect), output)
+                } else {
+                    (input, Size::new(output.width, output.width / input_aspect))
+                }
+            }
+            BoxFit::FitHeight => {
+                if output_aspect > input_aspect {
+                    (input, Size::new(output.height * input_aspect, output.height))
+                } else {
+                    (Size::new(input.height * output_aspect, input.height), output)
+                }
+            }
+            BoxFit::None => {
+                let source = Size::new(input.width.min(output.width), input.height.min(output.height));
+                (source, source)
+            }
+            BoxFit::ScaleDown => {
+                let mut destination = input;
+                if destination.height > output.height {
+                    destination = Size::new(output.height * input_aspect, output.height);
+                }
+                if destination.width > output.width {
+                    destination = Size::new(output.width, output.width / input_aspect);
+                }
+                (input, destination)
+            }
+        };
+        FittedSizes { source, destination }
+    }
+}
+
+/// A point within a rectangle, from (-1, -1) at the top-left to (1, 1) at
+/// the bottom-right
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Alignment {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Alignment {
+    pub const TOP_LEFT: Alignment = Alignment::new(-1.0, -1.0);
+    pub const TOP_CENTER: Alignment = Alignment::new(0.0, -1.0);
+    pub const TOP_RIGHT: Alignment = Alignment::new(1.0, -1.0);
+    pub const CENTER_LEFT: Alignment = Alignment::new(-1.0, 0.0);
+    pub const CENTER: Alignment = Alignment::new(0.0, 0.0);
+    pub const CENTER_RIGHT: Alignment = Alignment::new(1.0, 0.0);
+    pub const BOTTOM_LEFT: Alignment = Alignment::new(-1.0, 1.0);
+    pub const BOTTOM_CENTER: Alignment = Alignment::new(0.0, 1.0);
+    pub const BOTTOM_RIGHT: Alignment = Alignment::new(1.0, 1.0);
+
+    /// Creates an alignment
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    /// Places a box of the given size inside `rect` according to this alignment
+    pub fn inscribe(&self, size: Size, rect: Rect) -> Rect {
+        let half_dx = (rect.width - size.width) / 2.0;
+        let half_dy = (rect.height - size.height) / 2.0;
+        Rect::new(
+            rect.x + half_dx + self.x * half_dx,
+            rect.y + half_dy + self.y * half_dy,
+            size.width,
+            size.height,
+        )
+    }
+}
+
+impl Default for Alignment {
+    fn default() -> Self {
+        Self::CENTER
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Units;
+
+    #[test]
+    fn test_edge_insets_accept_units() {
+        let insets = EdgeInsets::symmetric(4.dp(), 2.5);
+        assert_eq!(insets.left, Length::Dp(2.5));
+        assert_eq!(insets.horizontal(), 5.0);
+        assert_eq!(insets.vertical(), 8.0);
+        assert_eq!(EdgeInsets::zero().horizontal(), 0.0);
+    }
+
+    #[test]
+    fn test_box_fit_contain_and_cover() {
+        let input = Size::new(200.0, 100.0);
+        let output = Size::new(100.0, 100.0);
+
+        let contain = BoxFit::Contain.apply(input, output);
+        assert_eq!(contain.source, input);
+        assert_eq!(contain.destination, Size::new(100.0, 50.0));
+
+        let cover = BoxFit::Cover.apply(input, output);
+        assert_eq!(cover.source, Size::new(100.0, 100.0));
+        assert_eq!(cover.destination, output);
+    }
+
+    #[test]
+    fn test_box_fit_none_and_scale_down() {
+        let input = Size::new(50.0, 300.0);
+        let output = Size::new(100.0, 100.0);
+
+        let none = BoxFit::None.apply(input, output);
+        assert_eq!(none.source, Size::new(50.0, 100.0));
+        assert_eq!(none.destination, Size::new(50.0, 100.0));
+
+        let scale_down = BoxFit::ScaleDown.apply(Size::new(10.0, 10.0), output);
+        assert_eq!(scale_down.destination, Size::new(10.0, 10.0));
+    }
+
+    #[test]
+    fn test_box_fit_fit_width() {
+        let fitted = BoxFit::FitWidth.apply(Size::new(100.0, 100.0), Size::new(200.0, 100.0));
+        assert_eq!(fitted.source, Size::new(100.0, 50.0));
+        assert_eq!(fitted.destination, Size::new(200.0, 100.0));
+    }
+
+    #[test]
+    fn test_alignment_inscribe() {
+        let rect = Rect::new(10.0, 10.0, 100.0, 50.0);
+        let size = Size::new(20.0, 10.0);
+        assert_eq!(Alignment::TOP_LEFT.inscribe(size, rect), Rect::new(10.0, 10.0, 20.0, 10.0));
+        assert_eq!(Alignment::CENTER.inscribe(size, rect), Rect::new(50.0, 30.0, 20.0, 10.0));
+        assert_eq!(Alignment::BOTTOM_RIGHT.inscribe(size, rect), Rect::new(90.0, 50.0, 20.0, 10.0));
+    }
+}
@@ -0,0 +1,291 @@
+//! Layout caching and relayout boundaries
+//!
+//! Container widgets keep a `LayoutCache` so that asking for their size
+//! again with the same constraints does not walk their subtree. While a
+//! cached widget lays out its children, the children's caches link to it as
+//! their parent; marking a node with `LayoutHandle::mark_needs_layout`
+//! invalidates it and its ancestors up to the nearest relayout boundary, a
+//! node whose size does not depend on its children.
+//!
+//! A widget whose size depends on some state marks its handle when the
+//! state changes; the next frame then lays out only the marked nodes. All
+//! caches on the UI thread are invalidated at once by `invalidate_all`,
+//! which the runtime reserves for changes that may affect every widget,
+//! such as a new window size, scale factor or `MediaQuery`.
+
+use crate::scheduler;
+use crate::types::Size;
+use std::cell::{Cell, RefCell};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+thread_local! {
+    /// Changes whenever `invalidate_all` is called
+    static EPOCH: Cell<u64> = const { Cell::new(0) };
+    /// The node whose children are currently being laid out
+    static CURRENT: RefCell<Option<Arc<LayoutNode>>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Default)]
+struct LayoutNode {
+    needs_layout: AtomicBool,
+    relayout_boundary: AtomicBool,
+    parent: Mutex<Weak<LayoutNode>>,
+}
+
+impl LayoutNode {
+    /// Marks this node and its ancestors up to the nearest relayout boundary
+    fn mark_needs_layout(self: &Arc<Self>) {
+        let mut node = self.clone();
+        loop {
+            node.needs_layout.store(true, Ordering::Release);
+            if node.relayout_boundary.load(Ordering::Acquire) {
+                return;
+            }
+            let parent = node.parent.lock().unwrap().upgrade();
+            match parent {
+                Some(parent) => node = parent,
+                None => return,
+            }
+        }
+    }
+}
+
+/// Marks a cached layout as out of date
+///
+/// Clones refer to the same node, so a handle can be given to whatever
+/// changes the widget's size (e.g. a background load finishing).
+#[derive(Debug, Clone)]
+pub struct LayoutHandle {
+    node: Arc<LayoutNode>,
+}
+
+impl LayoutHandle {
+    /// Invalidates this node and its ancestors up to the nearest relayout
+    /// boundary, and requests a frame
+    pub fn mark_needs_layout(&self) {
+        self.node.mark_needs_layout();
+        scheduler::request_paint();
+    }
+
+    /// Returns true if the node has been marked since its last layout
+    pub fn needs_layout(&self) -> bool {
+        self.node.needs_layout.load(Ordering::Acquire)
+    }
+}
+
+impl PartialEq for LayoutHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.node, &other.node)
+    }
+}
+
+impl Eq for LayoutHandle {}
+
+struct Entry<T> {
+    epoch: u64,
+    constraints: Size,
+    size: Size,
+    data: T,
+}
+
+/// Remembers a widget's last layout, keyed by the incoming constraints
+///
+/// `T` is extra data computed along with the size, such as the sizes of the
+/// children, which the widget can reuse when painting.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_core::layout_cache::LayoutCache;
+/// use kobalt_core::types::Size;
+///
+/// let cache = LayoutCache::new();
+/// let mut computed = 0;
+/// for _ in 0..3 {
+///     cache.layout(Size::new(100.0, 100.0), |constraints| {
+///         computed += 1;
+///         constraints
+///     });
+/// }
+/// assert_eq!(computed, 1);
+/// ```
+pub struct LayoutCache<T = ()> {
+    node: Arc<LayoutNode>,
+    entry: RefCell<Option<Entry<T>>>,
+}
+
+impl<T: Clone> LayoutCache<T> {
+    /// Creates an empty cache
+    pub fn new() -> Self {
+        Self {
+            node: Arc::new(LayoutNode::default()),
+            entry: RefCell::new(None),
+        }
+    }
+
+    /// Sets whether the widget's size is independent of its children, which
+    /// stops invalidation from spreading past it
+    pub fn relayout_boundary(self, boundary: bool) -> Self {
+        self.node.relayout_boundary.store(boundary, Ordering::Release);
+        self
+    }
+
+    /// Returns true if this node is a relayout boundary
+    pub fn is_relayout_boundary(&self) -> bool {
+        self.node.relayout_boundary.load(Ordering::Acquire)
+    }
+
+    /// Returns a handle that invalidates this cache
+    pub fn handle(&self) -> LayoutHandle {
+        LayoutHandle {
+            node: self.node.clone(),
+        }
+    }
+
+    /// Clears the cached layout
+    pub fn invalidate(&self) {
+        self.entry.borrow_mut().take();
+    }
+
+    /// Returns the cached size for `constraints`, or computes it with
+    /// `compute` and caches it together with its data
+    ///
+    /// Layouts performed by `compute` register this node as their parent.
+    pub fn layout_with<F>(&self, constraints: Size, compute: F) -> Size
+    where
+        F: FnOnce(Size) -> (Size, T),
+    {
+        if let Some(entry) = self.valid_entry().as_ref()
+            && entry.constraints == constraints
+        {
+            return entry.size;
+        }
+
+        // Whoever is laying out this node is its parent
+        let parent = CURRENT.with(|current| current.borrow().as_ref().map(Arc::downgrade));
+        *self.node.parent.lock().unwrap() = parent.unwrap_or_default();
+        self.node.needs_layout.store(false, Ordering::Release);
+
+        let previous = CURRENT.with(|current| current.replace(Some(self.node.clone())));
+        let (size, data) = compute(constraints);
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+
+        *self.entry.borrow_mut() = Some(Entry {
+            epoch: EPOCH.with(Cell::get),
+            constraints,
+            size,
+            data,
+        });
+        size
+    }
+
+    /// Returns the data of the last layout if it is still valid and
+    /// produced `size`
+    pub fn data_for_size(&self, size: Size) -> Option<T> {
+        self.valid_entry()
+            .as_ref()
+            .filter(|entry| entry.size == size)
+            .map(|entry| entry.data.clone())
+    }
+
+    /// Returns the cached entry unless it has been invalidated
+    fn valid_entry(&self) -> std::cell::Ref<'_, Option<Entry<T>>> {
+        let stale = self.node.needs_layout.load(Ordering::Acquire)
+            || self
+                .entry
+                .borrow()
+                .as_ref()
+                .is_some_and(|entry| entry.epoch != EPOCH.with(Cell::get));
+        if stale {
+            self.entry.borrow_mut().take();
+        }
+        self.entry.borrow()
+    }
+}
+
+impl LayoutCache<()> {
+    /// Returns the cached size for `constraints`, or computes it
+    pub fn layout<F>(&self, constraints: Size, compute: F) -> Size
+    where
+        F: FnOnce(Size) -> Size,
+    {
+        self.layout_with(constraints, |constraints| (compute(constraints), ()))
+    }
+}
+
+impl<T: Clone> Default for LayoutCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Invalidates every layout cache on this thread
+pub fn invalidate_all() {
+    EPOCH.with(|epoch| epoch.set(epoch.get() + 1));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONSTRAINTS: Size = Size {
+        width: 100.0,
+        height: 100.0,
+    };
+
+    #[test]
+    fn test_cache_is_keyed_by_constraints() {
+        let cache = LayoutCache::new();
+        let mut computed = 0;
+        let mut layout = |constraints| {
+            cache.layout(constraints, |constraints| {
+                computed += 1;
+                constraints
+            })
+        };
+
+        layout(CONSTRAINTS);
+        layout(CONSTRAINTS);
+        layout(Size::new(50.0, 50.0));
+        assert_eq!(computed, 2);
+    }
+
+    #[test]
+    fn test_data_for_size() {
+        let cache: LayoutCache<Vec<Size>> = LayoutCache::new();
+        cache.layout_with(CONSTRAINTS, |_| (Size::new(10.0, 20.0), vec![Size::new(10.0, 20.0)]));
+
+        assert_eq!(cache.data_for_size(Size::new(10.0, 20.0)).unwrap().len(), 1);
+        assert!(cache.data_for_size(Size::new(10.0, 30.0)).is_none());
+    }
+
+    #[test]
+    fn test_invalidation_stops_at_relayout_boundary() {
+        let root = LayoutCache::new();
+        let boundary = LayoutCache::new().relayout_boundary(true);
+        let inner = LayoutCache::new();
+        let leaf = LayoutCache::new();
+
+        root.layout(CONSTRAINTS, |c| {
+            boundary.layout(c, |c| inner.layout(c, |c| leaf.layout(c, |c| c)))
+        });
+
+        leaf.handle().mark_needs_layout();
+        assert!(leaf.handle().needs_layout());
+        assert!(inner.handle().needs_layout());
+        assert!(boundary.handle().needs_layout());
+        assert!(!root.handle().needs_layout());
+
+        // The root keeps its cached size; the boundary lays out again
+        let mut recomputed = 0;
+        root.layout(CONSTRAINTS, |c| c);
+        boundary.layout(CONSTRAINTS, |c| {
+            recomputed += 1;
+            c
+        });
+        assert_eq!(recomputed, 1);
+        assert!(!boundary.handle().needs_layout());
+    }
+
+}
@@ -4,10 +4,21 @@
 //!
 //! This crate provides:
 //! - Widget tree and composition
-//! - Layout system (Column, Row, Flexbox)
+//! - Layout system (Column, Row, Flexbox) with layout caching
+//! - Painting (display lists, vector paths, canvas, images, image filters)
+//! - Asset bundle
 //! - State management (LiveData, ValueNotifier, ChangeNotifier)
 //! - MVVM infrastructure
-//! - Common types (Size, Rect, Color)
+//! - Frame scheduling (render on demand) and repaint boundaries
+//! - Input events, hit testing and the widget host that delivers them
+//! - Gesture recognizers disambiguated through a gesture arena
+//! - Keyboard focus, Tab traversal and key event routing
+//! - Keyboard shortcuts bound to intents and actions
+//! - Hover tracking and mouse cursors
+//! - Headless interaction tests (`WidgetTester`, with the `testing` feature)
+//! - Typed errors (`KobaltError`)
+//! - Common types (Size, Rect, Color) and units (dp, sp, px)
+//! - Media query (window size, pixel ratio, text scale)
 //!
 //! ## Architecture
 //!
@@ -20,9 +31,30 @@ pub mod types;
 pub mod state;
 pub mod widget;
 pub mod layout;
+pub mod layout_cache;
+pub mod media_query;
+pub mod paint;
+pub mod path;
+pub mod canvas;
+pub mod image;
+pub mod assets;
+pub mod scheduler;
+pub mod repaint;
+pub mod error;
+pub mod event;
+pub mod hit_test;
+pub mod gesture;
+pub mod recognizer;
+pub mod focus;
+pub mod shortcuts;
+pub mod mouse;
+pub mod host;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 // Re-export common layout types for convenience
 pub use layout::{MainAxisAlignment, CrossAxisAlignment, EdgeInsets};
+pub use error::KobaltError;
 
 #[cfg(test)]
 mod tests {
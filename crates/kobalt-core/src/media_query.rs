@@ -0,0 +1,185 @@
+//! Media query - the environment widgets are laid out in
+//!
+//! Similar to Flutter's `MediaQuery`: the runtime publishes the window size,
+//! the device pixel ratio, the user's text scale factor and the safe-area
+//! padding before every frame, and `Length`s in `sp` or `px` are resolved
+//! against it at layout time. Changing the environment lays out the whole
+//! tree again.
+//!
+//! The environment is shared by the whole app. Tests can give their thread
+//! an environment of its own with `MediaQuery::set_thread_local` (with the
+//! `testing` feature).
+
+use crate::layout::EdgeInsets;
+use crate::scheduler;
+use crate::types::Size;
+#[cfg(any(test, feature = "testing"))]
+use std::cell::RefCell;
+use std::sync::{Mutex, OnceLock};
+
+#[cfg(any(test, feature = "testing"))]
+thread_local! {
+    /// The environment of this thread, if it does not use the app's
+    static THREAD_LOCAL: RefCell<Option<MediaQueryData>> = const { RefCell::new(None) };
+}
+
+/// A snapshot of the environment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MediaQueryData {
+    /// Window size in logical pixels
+    pub size: Size,
+    /// Physical pixels per logical pixel
+    pub device_pixel_ratio: f32,
+    /// Multiplier applied to `sp` lengths (an accessibility setting)
+    pub text_scale_factor: f32,
+    /// Parts of the window obscured by the system, e.g. a notch, status bar
+    /// or home indicator, in logical pixels
+    pub padding: EdgeInsets,
+}
+
+impl MediaQueryData {
+    /// Creates an environment with a text scale factor of 1
+    pub fn new(size: Size, device_pixel_ratio: f32) -> Self {
+        Self {
+            size,
+            device_pixel_ratio,
+            text_scale_factor: 1.0,
+            padding: EdgeInsets::zero(),
+        }
+    }
+
+    /// Sets the text scale factor
+    pub fn text_scale_factor(mut self, text_scale_factor: f32) -> Self {
+        self.text_scale_factor = text_scale_factor;
+        self
+    }
+
+    /// Sets the safe-area padding
+    pub fn padding(mut self, padding: EdgeInsets) -> Self {
+        self.padding = padding;
+        self
+    }
+}
+
+impl Default for MediaQueryData {
+    fn default() -> Self {
+        Self::new(Size::zero(), 1.0)
+    }
+}
+
+/// Access to the environment of the running app
+pub struct MediaQuery;
+
+impl MediaQuery {
+    fn current() -> &'static Mutex<MediaQueryData> {
+        static CURRENT: OnceLock<Mutex<MediaQueryData>> = OnceLock::new();
+        CURRENT.get_or_init(|| Mutex::new(MediaQueryData::default()))
+    }
+
+    /// Returns the current environment
+    pub fn of() -> MediaQueryData {
+        #[cfg(any(test, feature = "testing"))]
+        if let Some(data) = THREAD_LOCAL.with(|data| *data.borrow()) {
+            return data;
+        }
+        *Self::current().lock().unwrap()
+    }
+
+    /// Gives this thread an environment of its own, starting as `data`, or
+    /// makes it use the app's environment again with None, and returns the
+    /// environment it replaces
+    ///
+    /// `WidgetTester` uses this so that tests running in parallel can use
+    /// different sizes.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn set_thread_local(data: Option<MediaQueryData>) -> Option<MediaQueryData> {
+        THREAD_LOCAL.with(|current| current.replace(data))
+    }
+
+    /// Replaces the environment, laying out the whole tree again if it changed
+    pub fn set(data: MediaQueryData) {
+        Self::update(|current| *current = data);
+    }
+
+    /// Updates the window size and device pixel ratio, keeping the text
+    /// scale factor and padding
+    pub fn set_window(size: Size, device_pixel_ratio: f32) {
+        Self::update(|current| {
+            current.size = size;
+            current.device_pixel_ratio = device_pixel_ratio;
+        });
+    }
+
+    /// Sets the user's text scale factor
+    pub fn set_text_scale_factor(text_scale_factor: f32) {
+        if text_scale_factor > 0.0 && text_scale_factor.is_finite() {
+            Self::update(|current| current.text_scale_factor = text_scale_factor);
+        }
+    }
+
+    /// Sets the safe-area padding
+    pub fn set_padding(padding: EdgeInsets) {
+        Self::update(|current| current.padding = padding);
+    }
+
+    fn update(change: impl FnOnce(&mut MediaQueryData)) {
+        let apply = |data: &mut MediaQueryData| {
+            let previous = *data;
+            change(data);
+            *data != previous
+        };
+        #[cfg(any(test, feature = "testing"))]
+        if THREAD_LOCAL.with(|data| data.borrow().is_some()) {
+            if THREAD_LOCAL.with(|data| apply(data.borrow_mut().as_mut().unwrap())) {
+                scheduler::request_environment_change();
+            }
+            return;
+        }
+        let mut current = Self::current().lock().unwrap();
+        if apply(&mut current) {
+            scheduler::request_environment_change();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_text_scale_factor() {
+        // The only test touching the shared environment
+        MediaQuery::set_window(Size::new(640.0, 480.0), 2.0);
+        MediaQuery::set_text_scale_factor(1.25);
+        MediaQuery::set_text_scale_factor(-1.0);
+        MediaQuery::set_padding(EdgeInsets::only(44.0, 0.0, 34.0, 0.0));
+        MediaQuery::set_window(Size::new(640.0, 480.0), 2.0);
+
+        let media = MediaQuery::of();
+        assert_eq!(media.size, Size::new(640.0, 480.0));
+        assert_eq!(media.device_pixel_ratio, 2.0);
+        assert_eq!(media.text_scale_factor, 1.25);
+        assert_eq!(media.padding.top(), 44.0);
+        assert_eq!(media.padding.bottom(), 34.0);
+    }
+
+    #[test]
+    fn test_thread_local_environment() {
+        let scheduler = std::sync::Arc::new(scheduler::FrameScheduler::new());
+        let previous_scheduler = scheduler::set_thread_scheduler(Some(scheduler.clone()));
+        let previous = MediaQuery::set_thread_local(Some(MediaQueryData::new(Size::new(100.0, 50.0), 3.0)));
+
+        MediaQuery::set_text_scale_factor(2.0);
+        let media = MediaQuery::of();
+        assert_eq!((media.size, media.device_pixel_ratio), (Size::new(100.0, 50.0), 3.0));
+        assert_eq!(media.text_scale_factor, 2.0);
+        assert!(scheduler.take_frame_request().environment);
+
+        // Setting the same values again changes nothing
+        MediaQuery::set_text_scale_factor(2.0);
+        assert!(scheduler.take_frame_request().is_empty());
+
+        MediaQuery::set_thread_local(previous);
+        scheduler::set_thread_scheduler(previous_scheduler);
+    }
+}
@@ -0,0 +1,343 @@
+//! Mouse tracking - hover regions and cursors
+//!
+//! A widget that wants to know when a mouse is over it returns a
+//! `MouseTarget` from `Widget::mouse_target`. The `MouseTracker` keeps, for
+//! every device that can hover (mice and styluses, not fingers), the
+//! targets under it, found by hit testing its position. Targets the device
+//! moves into are told `on_enter`, targets it leaves `on_exit`, and the
+//! innermost target with an opinion decides the cursor.
+//!
+//! The tracker is updated by each event of a device and again after every
+//! repaint, as the widgets may have moved under a cursor that did not, e.g.
+//! when a list scrolls. A device that leaves the window
+//! (`PointerPhase::Exit`) exits all its targets.
+
+use crate::event::{PointerEvent, PointerKind, PointerPhase};
+use crate::hit_test::{self, WidgetPath};
+use crate::repaint::{self, RepaintHandle};
+use crate::types::Rect;
+use crate::widget::Widget;
+use std::collections::HashMap;
+
+/// The shape of the mouse cursor, named after Flutter's system cursors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MouseCursor {
+    /// The platform's default arrow
+    #[default]
+    Basic,
+    /// Something clickable, usually a pointing hand
+    Click,
+    /// Selectable text, usually an I-beam
+    Text,
+    /// An action that is not allowed
+    Forbidden,
+    /// Busy, the app does not respond to input
+    Wait,
+    /// Busy, but the app still responds to input
+    Progress,
+    /// Help is available
+    Help,
+    /// Precise selection, usually a crosshair
+    Precise,
+    /// Something can be moved
+    Move,
+    /// Something can be grabbed
+    Grab,
+    /// Something is being grabbed
+    Grabbing,
+    /// Resizing horizontally
+    ResizeLeftRight,
+    /// Resizing vertically
+    ResizeUpDown,
+    /// Resizing diagonally from the top left
+    ResizeUpLeftDownRight,
+    /// Resizing diagonally from the top right
+    ResizeUpRightDownLeft,
+    /// Resizing a column
+    ResizeColumn,
+    /// Resizing a row
+    ResizeRow,
+    ZoomIn,
+    ZoomOut,
+    /// No cursor is shown
+    Hidden,
+}
+
+/// A widget that tracks the mouse over it
+pub trait MouseTarget {
+    /// Returns the cursor to show over this widget, or `None` to let the
+    /// widgets around it decide
+    fn cursor(&self) -> Option<MouseCursor> {
+        None
+    }
+
+    /// Called when a device moves onto this widget
+    fn on_enter(&self, _event: &PointerEvent) {}
+
+    /// Called when a device moves off this widget, leaves the window, or
+    /// this widget moves away from under it
+    fn on_exit(&self, _event: &PointerEvent) {}
+}
+
+/// A target under a device, identified by its path and address
+#[derive(Debug, Clone, PartialEq)]
+struct Hovered {
+    path: WidgetPath,
+    address: usize,
+}
+
+/// What the tracker knows about one device
+#[derive(Debug, Clone)]
+struct Device {
+    /// The device's last event, for updates after a repaint
+    event: PointerEvent,
+    /// The targets under it, innermost first
+    targets: Vec<Hovered>,
+    cursor: MouseCursor,
+}
+
+fn address(widget: &dyn Widget) -> usize {
+    widget as *const dyn Widget as *const () as usize
+}
+
+/// Keeps track of the `MouseTarget`s under each device that can hover
+#[derive(Debug, Default)]
+pub struct MouseTracker {
+    devices: HashMap<u64, Device>,
+    /// The device with the latest event
+    last: Option<u64>,
+}
+
+impl MouseTracker {
+    /// Creates a tracker with no devices
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if any device is over a target
+    pub fn has_targets(&self) -> bool {
+        self.devices.values().any(|device| !device.targets.is_empty())
+    }
+
+    /// Returns the number of targets under a device
+    pub fn target_count(&self, device: u64) -> usize {
+        self.devices.get(&device).map_or(0, |device| device.targets.len())
+    }
+
+    /// Returns the cursor to show for a device, or the basic cursor if the
+    /// device is not tracked
+    pub fn cursor(&self, device: u64) -> MouseCursor {
+        self.devices.get(&device).map_or(MouseCursor::Basic, |device| device.cursor)
+    }
+
+    /// Returns the cursor of the device that moved last, for platforms
+    /// with a single cursor
+    pub fn current_cursor(&self) -> MouseCursor {
+        self.last.map_or(MouseCursor::Basic, |device| self.cursor(device))
+    }
+
+    /// Updates the targets of the device an event belongs to
+    ///
+    /// Touches are ignored, as fingers do not hover.
+    pub fn update_with_event(&mut self, root: &dyn Widget, bounds: Rect, event: &PointerEvent) {
+        if event.kind == PointerKind::Touch {
+            return;
+        }
+        self.last = Some(event.id);
+        if event.phase == PointerPhase::Exit {
+            if let Some(device) = self.devices.remove(&event.id) {
+                exit(root, bounds, &device.targets, event);
+            }
+            return;
+        }
+        let previous = self.devices.remove(&event.id).map(|device| device.targets).unwrap_or_default();
+        let device = track(root, bounds, event, &previous);
+        self.devices.insert(event.id, device);
+    }
+
+    /// Updates the targets of every device at its last position; called
+    /// after the widgets were laid out again
+    pub fn update_all(&mut self, root: &dyn Widget, bounds: Rect) {
+        for device in self.devices.values_mut() {
+            *device = track(root, bounds, &device.event, &device.targets);
+        }
+    }
+}
+
+/// Finds the targets under a device, telling the ones it left and entered
+fn track(root: &dyn Widget, bounds: Rect, event: &PointerEvent, previous: &[Hovered]) -> Device {
+    let hits = hit_test::hit_test(root, bounds, event.position);
+    let mut targets = Vec::new();
+    let mut cursor = None;
+    for entry in hits.entries() {
+        let Some(widget) = hit_test::widget_at(root, bounds, &entry.path) else {
+            continue;
+        };
+        if let Some(target) = widget.mouse_target() {
+            cursor = cursor.or(target.cursor());
+            targets.push(Hovered {
+                path: entry.path.clone(),
+                address: address(widget),
+            });
+        }
+    }
+
+    let left: Vec<Hovered> = previous.iter().filter(|target| !targets.contains(target)).cloned().collect();
+    exit(root, bounds, &left, event);
+    // Outermost first, the reverse of exits
+    for hovered in targets.iter().rev().filter(|target| !previous.contains(target)) {
+        if let Some((target, boundary)) = resolve(root, bounds, hovered) {
+            repaint::with_boundary(boundary, || target.on_enter(event));
+        }
+    }
+
+    Device {
+        event: *event,
+        targets,
+        cursor: cursor.unwrap_or_default(),
+    }
+}
+
+/// Tells targets, innermost first, that a device left them
+fn exit(root: &dyn Widget, bounds: Rect, targets: &[Hovered], event: &PointerEvent) {
+    for hovered in targets {
+        if let Some((target, boundary)) = resolve(root, bounds, hovered) {
+            repaint::with_boundary(boundary, || target.on_exit(event));
+        }
+    }
+}
+
+/// Finds a target again, with the repaint boundary enclosing it; `None` if
+/// it is no longer in the tree
+fn resolve<'a>(
+    root: &'a dyn Widget,
+    bounds: Rect,
+    hovered: &Hovered,
+) -> Option<(&'a dyn MouseTarget, Option<RepaintHandle>)> {
+    let widgets = hit_test::widgets_along(root, bounds, &hovered.path)?;
+    let (widget, _) = widgets[widgets.len() - 1];
+    if address(widget) != hovered.address {
+        return None;
+    }
+    Some((widget.mouse_target()?, repaint::enclosing_boundary(&widgets)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Point, Size};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    /// A target covering its bounds, with an optional child covering the
+    /// left half
+    struct Region {
+        name: &'static str,
+        cursor: Option<MouseCursor>,
+        log: Log,
+        child: Option<Box<Region>>,
+    }
+
+    impl Widget for Region {
+        fn widget_type(&self) -> &'static str {
+            "Region"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+            if let Some(child) = &self.child {
+                visitor(&**child, Rect::new(bounds.x, bounds.y, bounds.width / 2.0, bounds.height));
+            }
+        }
+
+        fn mouse_target(&self) -> Option<&dyn MouseTarget> {
+            Some(self)
+        }
+    }
+
+    impl MouseTarget for Region {
+        fn cursor(&self) -> Option<MouseCursor> {
+            self.cursor
+        }
+
+        fn on_enter(&self, _event: &PointerEvent) {
+            self.log.borrow_mut().push(format!("enter {}", self.name));
+        }
+
+        fn on_exit(&self, _event: &PointerEvent) {
+            self.log.borrow_mut().push(format!("exit {}", self.name));
+        }
+    }
+
+    fn regions(log: &Log) -> Region {
+        Region {
+            name: "outer",
+            cursor: Some(MouseCursor::Click),
+            log: log.clone(),
+            child: Some(Box::new(Region {
+                name: "inner",
+                cursor: Some(MouseCursor::Text),
+                log: log.clone(),
+                child: None,
+            })),
+        }
+    }
+
+    const BOUNDS: Rect = Rect {
+        x: 0.0,
+        y: 0.0,
+        width: 20.0,
+        height: 10.0,
+    };
+
+    fn hover(x: f32) -> PointerEvent {
+        PointerEvent::new(0, PointerPhase::Hover, Point::new(x, 5.0))
+    }
+
+    #[test]
+    fn test_enter_and_exit() {
+        let log = Log::default();
+        let root = regions(&log);
+        let mut tracker = MouseTracker::new();
+
+        tracker.update_with_event(&root, BOUNDS, &hover(5.0));
+        assert_eq!(*log.borrow(), vec!["enter outer", "enter inner"]);
+        assert_eq!(tracker.cursor(0), MouseCursor::Text);
+
+        tracker.update_with_event(&root, BOUNDS, &hover(15.0));
+        assert_eq!(log.borrow()[2..], ["exit inner"]);
+        assert_eq!(tracker.cursor(0), MouseCursor::Click);
+
+        tracker.update_with_event(&root, BOUNDS, &PointerEvent::new(0, PointerPhase::Exit, Point::new(15.0, 5.0)));
+        assert_eq!(log.borrow()[3..], ["exit outer"]);
+        assert!(!tracker.has_targets());
+        assert_eq!(tracker.current_cursor(), MouseCursor::Basic);
+    }
+
+    #[test]
+    fn test_touches_do_not_hover() {
+        let log = Log::default();
+        let mut tracker = MouseTracker::new();
+        tracker.update_with_event(&regions(&log), BOUNDS, &hover(5.0).kind(PointerKind::Touch));
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_update_all_after_layout_change() {
+        let log = Log::default();
+        let root = regions(&log);
+        let mut tracker = MouseTracker::new();
+        tracker.update_with_event(&root, BOUNDS, &hover(8.0));
+        log.borrow_mut().clear();
+
+        // The regions grew narrower under the cursor
+        tracker.update_all(&root, Rect::new(0.0, 0.0, 12.0, 10.0));
+        assert_eq!(*log.borrow(), vec!["exit inner"]);
+        assert_eq!(tracker.target_count(0), 1);
+    }
+}
@@ -0,0 +1,532 @@
+//! Painting primitives and display lists
+//!
+//! Widgets describe their appearance by recording draw commands into a
+//! `PaintContext`. The resulting `DisplayList` is platform-agnostic: it
+//! contains no GPU types and is consumed by `kobalt-render` to produce the
+//! actual frame.
+//!
+//! This module provides:
+//! - `DrawCommand`: A single recorded paint operation
+//! - `DisplayList`: An ordered list of draw commands
+//! - `PaintContext`: The recorder handed to `Widget::paint`
+//! - `Paint`: How a path is filled or stroked
+//! - Images: decoded `ImageData` drawn into a destination rectangle
+//! - `ImageFilter` / `ColorMatrix`: Pixel filters (blur, color transforms)
+//! - Repaint boundaries: retained sub-lists cached by the renderer
+
+use crate::image::ImageData;
+use crate::path::{Path, StrokeStyle};
+use crate::types::{Color, Point, Rect, Transform};
+use std::sync::Arc;
+
+/// Whether a shape is filled or stroked
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaintStyle {
+    /// Fill the interior using the path's fill rule
+    Fill,
+    /// Stroke the outline
+    Stroke(StrokeStyle),
+}
+
+/// Describes how a shape is drawn, similar to Flutter's `Paint`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paint {
+    pub color: Color,
+    pub style: PaintStyle,
+}
+
+impl Paint {
+    /// Creates a fill paint
+    pub fn fill(color: Color) -> Self {
+        Self {
+            color,
+            style: PaintStyle::Fill,
+        }
+    }
+
+    /// Creates a stroke paint
+    pub fn stroke(color: Color, stroke: StrokeStyle) -> Self {
+        Self {
+            color,
+            style: PaintStyle::Stroke(stroke),
+        }
+    }
+
+    /// Returns true if this paint fills shapes
+    pub fn is_fill(&self) -> bool {
+        matches!(self.style, PaintStyle::Fill)
+    }
+}
+
+impl Default for Paint {
+    fn default() -> Self {
+        Self::fill(Color::BLACK)
+    }
+}
+
+/// A filter applied to already-rasterized pixels
+///
+/// Similar to Flutter's `ImageFilter`. Filters are used by backdrop filters
+/// (which filter what has been painted behind a widget) and by filtered
+/// layers (which filter a widget's own output).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageFilter {
+    /// Separable Gaussian blur with independent horizontal and vertical sigmas
+    Blur { sigma_x: f32, sigma_y: f32 },
+    /// 4x5 color matrix applied to every pixel
+    ColorMatrix(ColorMatrix),
+    /// Applies `inner` first, then `outer` to the result
+    Compose {
+        outer: Box<ImageFilter>,
+        inner: Box<ImageFilter>,
+    },
+}
+
+impl ImageFilter {
+    /// Creates a Gaussian blur filter
+    pub fn blur(sigma_x: f32, sigma_y: f32) -> Self {
+        Self::Blur {
+            sigma_x: sigma_x.max(0.0),
+            sigma_y: sigma_y.max(0.0),
+        }
+    }
+
+    /// Creates a color matrix filter
+    pub fn color_matrix(matrix: ColorMatrix) -> Self {
+        Self::ColorMatrix(matrix)
+    }
+
+    /// Creates a filter that applies `inner` and then `outer`
+    pub fn compose(outer: ImageFilter, inner: ImageFilter) -> Self {
+        Self::Compose {
+            outer: Box::new(outer),
+            inner: Box::new(inner),
+        }
+    }
+
+    /// Returns the same filter for content scaled by `factor`
+    ///
+    /// Blur radii grow with the content; color matrices are unaffected.
+    pub fn scale(&self, factor: f32) -> Self {
+        match self {
+            Self::Blur { sigma_x, sigma_y } => Self::blur(sigma_x * factor, sigma_y * factor),
+            Self::ColorMatrix(matrix) => Self::ColorMatrix(*matrix),
+            Self::Compose { outer, inner } => Self::compose(outer.scale(factor), inner.scale(factor)),
+        }
+    }
+
+    /// Returns how far (in logical pixels) this filter can spread content
+    /// beyond its input bounds, as (horizontal, vertical)
+    ///
+    /// A Gaussian kernel is effectively zero beyond three standard deviations.
+    pub fn outset(&self) -> (f32, f32) {
+        match self {
+            Self::Blur { sigma_x, sigma_y } => ((sigma_x * 3.0).ceil(), (sigma_y * 3.0).ceil()),
+            Self::ColorMatrix(_) => (0.0, 0.0),
+            Self::Compose { outer, inner } => {
+                let (ox, oy) = outer.outset();
+                let (ix, iy) = inner.outset();
+                (ox + ix, oy + iy)
+            }
+        }
+    }
+
+    /// Flattens this filter into the sequence of passes it requires, in order
+    pub fn passes(&self) -> Vec<&ImageFilter> {
+        match self {
+            Self::Compose { outer, inner } => {
+                let mut passes = inner.passes();
+                passes.extend(outer.passes());
+                passes
+            }
+            filter => vec![filter],
+        }
+    }
+}
+
+/// A 4x5 row-major color matrix
+///
+/// Each output channel is computed as
+/// `out = m[0] * r + m[1] * g + m[2] * b + m[3] * a + m[4]`
+/// with the offset column expressed in the 0.0 to 1.0 range.
+/// This is the same layout as Flutter's `ColorFilter.matrix`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorMatrix(pub [f32; 20]);
+
+impl ColorMatrix {
+    /// Returns the identity matrix (leaves colors unchanged)
+    pub const fn identity() -> Self {
+        Self([
+            1.0, 0.0, 0.0, 0.0, 0.0, //
+            0.0, 1.0, 0.0, 0.0, 0.0, //
+            0.0, 0.0, 1.0, 0.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0, 0.0, //
+        ])
+    }
+
+    /// Returns a saturation matrix (0.0 is grayscale, 1.0 is unchanged)
+    pub fn saturation(amount: f32) -> Self {
+        // Rec. 709 luminance weights
+        let (lr, lg, lb) = (0.2126, 0.7152, 0.0722);
+        let s = amount;
+        let i = 1.0 - s;
+        Self([
+            lr * i + s, lg * i, lb * i, 0.0, 0.0, //
+            lr * i, lg * i + s, lb * i, 0.0, 0.0, //
+            lr * i, lg * i, lb * i + s, 0.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0, 0.0, //
+        ])
+    }
+
+    /// Returns a grayscale matrix
+    pub fn grayscale() -> Self {
+        Self::saturation(0.0)
+    }
+
+    /// Returns a matrix that multiplies every channel by the given color
+    pub fn modulate(color: Color) -> Self {
+        Self([
+            color.r, 0.0, 0.0, 0.0, 0.0, //
+            0.0, color.g, 0.0, 0.0, 0.0, //
+            0.0, 0.0, color.b, 0.0, 0.0, //
+            0.0, 0.0, 0.0, color.a, 0.0, //
+        ])
+    }
+
+    /// Applies this matrix to a single color
+    pub fn apply(&self, color: Color) -> Color {
+        let m = &self.0;
+        let input = [color.r, color.g, color.b, color.a];
+        let mut out = [0.0; 4];
+        for (row, value) in out.iter_mut().enumerate() {
+            let r = &m[row * 5..row * 5 + 5];
+            *value = (r[0] * input[0] + r[1] * input[1] + r[2] * input[2] + r[3] * input[3] + r[4])
+                .clamp(0.0, 1.0);
+        }
+        Color::new(out[0], out[1], out[2], out[3])
+    }
+}
+
+impl Default for ColorMatrix {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// A single recorded paint operation
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    /// Fills a rectangle with a solid color
+    Rect { rect: Rect, color: Color },
+    /// Draws a run of text with its top-left corner at `position`
+    Text {
+        content: String,
+        position: Point,
+        font_size: f32,
+        color: Color,
+    },
+    /// Fills or strokes a path after applying `transform` to it
+    Path {
+        path: Path,
+        paint: Paint,
+        transform: Transform,
+    },
+    /// Draws the `src` region of an image (in image pixels) into `dst`,
+    /// multiplying every pixel by `tint` if set
+    Image {
+        image: ImageData,
+        src: Rect,
+        dst: Rect,
+        tint: Option<Color>,
+    },
+    /// Restricts subsequent commands to `rect` (intersected with any
+    /// enclosing clip) until the matching `PopClip`
+    PushClip { rect: Rect },
+    /// Ends the most recent `PushClip`
+    PopClip,
+    /// Filters everything painted so far inside `rect`
+    BackdropFilter { rect: Rect, filter: ImageFilter },
+    /// Starts an offscreen layer; commands until the matching `PopLayer`
+    /// are rendered into it and then filtered onto the parent
+    PushLayer { bounds: Rect, filter: ImageFilter },
+    /// Ends the most recent `PushLayer`
+    PopLayer,
+    /// Draws a retained subtree recorded with its top-left corner at the
+    /// origin into `rect`
+    ///
+    /// The renderer caches the rasterized `list` under `id` and reuses it
+    /// for as long as `version` stays the same.
+    RepaintBoundary {
+        id: u64,
+        version: u64,
+        rect: Rect,
+        list: Arc<DisplayList>,
+    },
+}
+
+/// An ordered list of draw commands produced by painting a widget tree
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplayList {
+    commands: Vec<DrawCommand>,
+}
+
+impl DisplayList {
+    /// Creates an empty display list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a command
+    pub fn push(&mut self, command: DrawCommand) {
+        self.commands.push(command);
+    }
+
+    /// Returns the recorded commands in paint order
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    /// Returns the number of recorded commands
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns true if nothing has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Removes all commands
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+}
+
+/// Recorder handed to widgets during the paint phase
+///
+/// # Example
+///
+/// ```
+/// use kobalt_core::paint::{ImageFilter, PaintContext};
+/// use kobalt_core::types::{Color, Rect};
+///
+/// let mut ctx = PaintContext::new();
+/// ctx.draw_rect(Rect::new(0.0, 0.0, 100.0, 50.0), Color::RED);
+/// ctx.backdrop_filter(Rect::new(0.0, 0.0, 100.0, 20.0), ImageFilter::blur(4.0, 4.0));
+///
+/// let list = ctx.finish();
+/// assert_eq!(list.len(), 2);
+/// ```
+#[derive(Debug, Default)]
+pub struct PaintContext {
+    list: DisplayList,
+    scopes: Vec<Scope>,
+}
+
+/// A scope opened on a `PaintContext` that must be closed again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    Layer,
+    Clip,
+}
+
+impl PaintContext {
+    /// Creates a new, empty paint context
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fills a rectangle with a solid color
+    pub fn draw_rect(&mut self, rect: Rect, color: Color) {
+        if rect.is_empty() || color.a <= 0.0 {
+            return;
+        }
+        self.list.push(DrawCommand::Rect { rect, color });
+    }
+
+    /// Draws text with its top-left corner at `position`
+    pub fn draw_text(&mut self, content: &str, position: Point, font_size: f32, color: Color) {
+        if content.is_empty() {
+            return;
+        }
+        self.list.push(DrawCommand::Text {
+            content: content.to_string(),
+            position,
+            font_size,
+            color,
+        });
+    }
+
+    /// Fills or strokes a path
+    pub fn draw_path(&mut self, path: &Path, paint: &Paint, transform: Transform) {
+        if path.is_empty() || paint.color.a <= 0.0 {
+            return;
+        }
+        self.list.push(DrawCommand::Path {
+            path: path.clone(),
+            paint: paint.clone(),
+            transform,
+        });
+    }
+
+    /// Draws the `src` region of an image (in image pixels) into `dst`
+    ///
+    /// If `tint` is set, every pixel is multiplied by it.
+    pub fn draw_image(&mut self, image: &ImageData, src: Rect, dst: Rect, tint: Option<Color>) {
+        if src.is_empty() || dst.is_empty() || tint.is_some_and(|t| t.a <= 0.0) {
+            return;
+        }
+        self.list.push(DrawCommand::Image {
+            image: image.clone(),
+            src,
+            dst,
+            tint,
+        });
+    }
+
+    /// Restricts subsequent drawing to `rect` until `pop_clip`
+    pub fn push_clip(&mut self, rect: Rect) {
+        self.scopes.push(Scope::Clip);
+        self.list.push(DrawCommand::PushClip { rect });
+    }
+
+    /// Ends the most recent clip
+    pub fn pop_clip(&mut self) {
+        self.pop_scope(Scope::Clip);
+    }
+
+    /// Applies `filter` to everything painted so far inside `rect`
+    pub fn backdrop_filter(&mut self, rect: Rect, filter: ImageFilter) {
+        if rect.is_empty() {
+            return;
+        }
+        self.list.push(DrawCommand::BackdropFilter { rect, filter });
+    }
+
+    /// Starts a filtered layer covering `bounds`
+    ///
+    /// Every command recorded until the matching `pop_layer` is rendered
+    /// offscreen, then `filter` is applied and the result is composited.
+    pub fn push_layer(&mut self, bounds: Rect, filter: ImageFilter) {
+        self.scopes.push(Scope::Layer);
+        self.list.push(DrawCommand::PushLayer { bounds, filter });
+    }
+
+    /// Ends the most recent filtered layer
+    pub fn pop_layer(&mut self) {
+        self.pop_scope(Scope::Layer);
+    }
+
+    /// Draws a retained subtree (see `kobalt_core::repaint`)
+    ///
+    /// `list` must have been recorded with bounds at the origin and the size
+    /// of `rect`.
+    pub fn draw_repaint_boundary(&mut self, id: u64, version: u64, rect: Rect, list: Arc<DisplayList>) {
+        if rect.is_empty() || list.is_empty() {
+            return;
+        }
+        self.list.push(DrawCommand::RepaintBoundary { id, version, rect, list });
+    }
+
+    /// Returns the number of clips and layers currently open
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Finishes recording and returns the display list
+    ///
+    /// Any clips or layers left open are closed automatically.
+    pub fn finish(mut self) -> DisplayList {
+        while let Some(scope) = self.scopes.last().copied() {
+            self.pop_scope(scope);
+        }
+        self.list
+    }
+
+    /// Closes the innermost scope, which must be of the expected kind
+    fn pop_scope(&mut self, expected: Scope) {
+        debug_assert_eq!(self.scopes.last(), Some(&expected), "unbalanced {:?} pop", expected);
+        if self.scopes.last() == Some(&expected) {
+            self.scopes.pop();
+            self.list.push(match expected {
+                Scope::Layer => DrawCommand::PopLayer,
+                Scope::Clip => DrawCommand::PopClip,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint_context_records_in_order() {
+        let mut ctx = PaintContext::new();
+        ctx.draw_rect(Rect::new(0.0, 0.0, 10.0, 10.0), Color::RED);
+        ctx.draw_text("Hi", Point::new(1.0, 2.0), 16.0, Color::WHITE);
+
+        let list = ctx.finish();
+        assert_eq!(list.len(), 2);
+        assert!(matches!(list.commands()[0], DrawCommand::Rect { .. }));
+        assert!(matches!(list.commands()[1], DrawCommand::Text { .. }));
+    }
+
+    #[test]
+    fn test_paint_context_skips_invisible() {
+        let mut ctx = PaintContext::new();
+        ctx.draw_rect(Rect::new(0.0, 0.0, 0.0, 10.0), Color::RED);
+        ctx.draw_rect(Rect::new(0.0, 0.0, 10.0, 10.0), Color::TRANSPARENT);
+        ctx.draw_text("", Point::zero(), 16.0, Color::WHITE);
+        assert!(ctx.finish().is_empty());
+    }
+
+    #[test]
+    fn test_finish_closes_open_layers() {
+        let mut ctx = PaintContext::new();
+        ctx.push_layer(Rect::new(0.0, 0.0, 10.0, 10.0), ImageFilter::blur(2.0, 2.0));
+        ctx.draw_rect(Rect::new(0.0, 0.0, 10.0, 10.0), Color::RED);
+
+        let list = ctx.finish();
+        assert_eq!(list.commands().last(), Some(&DrawCommand::PopLayer));
+    }
+
+    #[test]
+    fn test_finish_closes_scopes_innermost_first() {
+        let mut ctx = PaintContext::new();
+        ctx.push_layer(Rect::new(0.0, 0.0, 10.0, 10.0), ImageFilter::blur(2.0, 2.0));
+        ctx.push_clip(Rect::new(0.0, 0.0, 5.0, 5.0));
+        assert_eq!(ctx.depth(), 2);
+
+        let list = ctx.finish();
+        let tail = &list.commands()[list.len() - 2..];
+        assert_eq!(tail, &[DrawCommand::PopClip, DrawCommand::PopLayer]);
+    }
+
+    #[test]
+    fn test_filter_outset_and_passes() {
+        let filter = ImageFilter::compose(
+            ImageFilter::color_matrix(ColorMatrix::grayscale()),
+            ImageFilter::blur(2.0, 1.0),
+        );
+        assert_eq!(filter.outset(), (6.0, 3.0));
+
+        let passes = filter.passes();
+        assert_eq!(passes.len(), 2);
+        assert!(matches!(passes[0], ImageFilter::Blur { .. }));
+        assert!(matches!(passes[1], ImageFilter::ColorMatrix(_)));
+
+        assert_eq!(filter.scale(2.0).outset(), (12.0, 6.0));
+    }
+
+    #[test]
+    fn test_color_matrix_apply() {
+        let color = Color::new(0.2, 0.4, 0.6, 1.0);
+        assert_eq!(ColorMatrix::identity().apply(color), color);
+
+        let gray = ColorMatrix::grayscale().apply(Color::RED);
+        assert!((gray.r - gray.g).abs() < 1e-6);
+        assert!((gray.g - gray.b).abs() < 1e-6);
+        assert_eq!(gray.a, 1.0);
+    }
+}
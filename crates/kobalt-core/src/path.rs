@@ -0,0 +1,668 @@
+//! Vector paths
+//!
+//! This module provides a resolution-independent path type similar to
+//! Flutter's `Path` and the HTML canvas path API:
+//! - `Path`: A sequence of move/line/quad/cubic/close commands
+//! - `FillRule`: How overlapping subpaths are filled
+//! - `StrokeStyle`: Width, caps, joins and dashes for stroking
+//!
+//! Paths are platform-agnostic. `kobalt-render` tessellates them into
+//! triangles on the CPU.
+
+use crate::types::{Point, Rect, Transform};
+use std::f32::consts::{FRAC_PI_2, PI};
+
+/// How the interior of a path is determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// A point is inside if the winding number is non-zero
+    #[default]
+    NonZero,
+    /// A point is inside if a ray from it crosses an odd number of edges
+    EvenOdd,
+}
+
+/// Shape used at the ends of open stroked subpaths
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrokeCap {
+    /// The stroke ends exactly at the endpoint
+    #[default]
+    Butt,
+    /// The stroke ends with a semicircle
+    Round,
+    /// The stroke extends half its width beyond the endpoint
+    Square,
+}
+
+/// Shape used where two stroked segments meet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrokeJoin {
+    /// Sharp corner, limited by the miter limit
+    #[default]
+    Miter,
+    /// Rounded corner
+    Round,
+    /// Flattened corner
+    Bevel,
+}
+
+/// Stroke configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrokeStyle {
+    /// Stroke width in logical pixels
+    pub width: f32,
+    /// Cap used at the ends of open subpaths and dashes
+    pub cap: StrokeCap,
+    /// Join used between segments
+    pub join: StrokeJoin,
+    /// Maximum ratio of miter length to stroke width before falling back to a bevel
+    pub miter_limit: f32,
+    /// Alternating dash and gap lengths (empty for a solid stroke)
+    pub dash_array: Vec<f32>,
+    /// Distance into the dash pattern at which the stroke starts
+    pub dash_offset: f32,
+}
+
+impl StrokeStyle {
+    /// Creates a solid stroke of the given width
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            cap: StrokeCap::default(),
+            join: StrokeJoin::default(),
+            miter_limit: 4.0,
+            dash_array: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+
+    /// Sets the cap style
+    pub fn cap(mut self, cap: StrokeCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// Sets the join style
+    pub fn join(mut self, join: StrokeJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    /// Sets the miter limit
+    pub fn miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+
+    /// Sets the dash pattern and its starting offset
+    pub fn dash(mut self, dash_array: Vec<f32>, dash_offset: f32) -> Self {
+        self.dash_array = dash_array;
+        self.dash_offset = dash_offset;
+        self
+    }
+
+    /// Returns true if this stroke uses a usable dash pattern
+    pub fn is_dashed(&self) -> bool {
+        !self.dash_array.is_empty()
+            && self.dash_array.iter().all(|len| *len >= 0.0)
+            && self.dash_array.iter().sum::<f32>() > 0.0
+    }
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// A single path command
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    /// Starts a new subpath at the given point
+    MoveTo(Point),
+    /// Draws a straight line to the given point
+    LineTo(Point),
+    /// Draws a quadratic Bézier curve
+    QuadTo(Point, Point),
+    /// Draws a cubic Bézier curve
+    CubicTo(Point, Point, Point),
+    /// Closes the current subpath
+    Close,
+}
+
+/// A flattened subpath made of straight segments
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline {
+    pub points: Vec<Point>,
+    pub closed: bool,
+}
+
+/// A vector path made of lines and Bézier curves
+///
+/// # Example
+///
+/// ```
+/// use kobalt_core::path::Path;
+/// use kobalt_core::types::Point;
+///
+/// let mut triangle = Path::new();
+/// triangle
+///     .move_to(Point::new(0.0, 0.0))
+///     .line_to(Point::new(100.0, 0.0))
+///     .line_to(Point::new(50.0, 80.0))
+///     .close();
+///
+/// assert!(triangle.contains(Point::new(50.0, 20.0)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    commands: Vec<PathCommand>,
+    fill_rule: FillRule,
+    /// Start of the current subpath
+    start: Point,
+    /// Current pen position
+    current: Option<Point>,
+}
+
+impl Default for Path {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Path {
+    /// Creates an empty path
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            fill_rule: FillRule::default(),
+            start: Point::zero(),
+            current: None,
+        }
+    }
+
+    /// Creates a path containing a rectangle
+    pub fn rect(rect: Rect) -> Self {
+        let mut path = Self::new();
+        path.add_rect(rect);
+        path
+    }
+
+    /// Creates a path containing a circle
+    pub fn circle(center: Point, radius: f32) -> Self {
+        let mut path = Self::new();
+        path.add_circle(center, radius);
+        path
+    }
+
+    /// Returns the commands of this path
+    pub fn commands(&self) -> &[PathCommand] {
+        &self.commands
+    }
+
+    /// Returns true if the path has no commands
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Returns the fill rule
+    pub fn fill_rule(&self) -> FillRule {
+        self.fill_rule
+    }
+
+    /// Sets the fill rule
+    pub fn set_fill_rule(&mut self, fill_rule: FillRule) -> &mut Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    /// Starts a new subpath at `point`
+    pub fn move_to(&mut self, point: Point) -> &mut Self {
+        self.commands.push(PathCommand::MoveTo(point));
+        self.start = point;
+        self.current = Some(point);
+        self
+    }
+
+    /// Adds a straight line to `point`
+    pub fn line_to(&mut self, point: Point) -> &mut Self {
+        self.ensure_subpath();
+        self.commands.push(PathCommand::LineTo(point));
+        self.current = Some(point);
+        self
+    }
+
+    /// Adds a quadratic Bézier curve to `to` with the given control point
+    pub fn quad_to(&mut self, control: Point, to: Point) -> &mut Self {
+        self.ensure_subpath();
+        self.commands.push(PathCommand::QuadTo(control, to));
+        self.current = Some(to);
+        self
+    }
+
+    /// Adds a cubic Bézier curve to `to` with the given control points
+    pub fn cubic_to(&mut self, control1: Point, control2: Point, to: Point) -> &mut Self {
+        self.ensure_subpath();
+        self.commands.push(PathCommand::CubicTo(control1, control2, to));
+        self.current = Some(to);
+        self
+    }
+
+    /// Adds a circular arc around `center`
+    ///
+    /// Angles are in radians, measured clockwise from the positive x axis
+    /// (y points down). If the path already has a current point, a line is
+    /// drawn from it to the start of the arc, like the HTML canvas `arc()`.
+    pub fn arc(&mut self, center: Point, radius: f32, start_angle: f32, sweep_angle: f32) -> &mut Self {
+        let start = Point::new(
+            center.x + radius * start_angle.cos(),
+            center.y + radius * start_angle.sin(),
+        );
+        if self.current.is_some() {
+            self.line_to(start);
+        } else {
+            self.move_to(start);
+        }
+        self.arc_segments(center, radius, radius, start_angle, sweep_angle);
+        self
+    }
+
+    /// Closes the current subpath with a straight line to its start
+    pub fn close(&mut self) -> &mut Self {
+        if self.current.is_some() {
+            self.commands.push(PathCommand::Close);
+            self.current = Some(self.start);
+        }
+        self
+    }
+
+    /// Adds a closed rectangle subpath
+    pub fn add_rect(&mut self, rect: Rect) -> &mut Self {
+        self.move_to(Point::new(rect.x, rect.y))
+            .line_to(Point::new(rect.right(), rect.y))
+            .line_to(Point::new(rect.right(), rect.bottom()))
+            .line_to(Point::new(rect.x, rect.bottom()))
+            .close()
+    }
+
+    /// Adds a closed rectangle subpath with rounded corners
+    pub fn add_round_rect(&mut self, rect: Rect, radius: f32) -> &mut Self {
+        let r = radius.min(rect.width / 2.0).min(rect.height / 2.0).max(0.0);
+        if r == 0.0 {
+            return self.add_rect(rect);
+        }
+        self.move_to(Point::new(rect.x + r, rect.y));
+        self.line_to(Point::new(rect.right() - r, rect.y));
+        self.arc_segments(Point::new(rect.right() - r, rect.y + r), r, r, -FRAC_PI_2, FRAC_PI_2);
+        self.line_to(Point::new(rect.right(), rect.bottom() - r));
+        self.arc_segments(Point::new(rect.right() - r, rect.bottom() - r), r, r, 0.0, FRAC_PI_2);
+        self.line_to(Point::new(rect.x + r, rect.bottom()));
+        self.arc_segments(Point::new(rect.x + r, rect.bottom() - r), r, r, FRAC_PI_2, FRAC_PI_2);
+        self.line_to(Point::new(rect.x, rect.y + r));
+        self.arc_segments(Point::new(rect.x + r, rect.y + r), r, r, PI, FRAC_PI_2);
+        self.close()
+    }
+
+    /// Adds a closed ellipse subpath inscribed in `rect`
+    pub fn add_oval(&mut self, rect: Rect) -> &mut Self {
+        let center = rect.center();
+        let (rx, ry) = (rect.width / 2.0, rect.height / 2.0);
+        self.move_to(Point::new(center.x + rx, center.y));
+        self.arc_segments(center, rx, ry, 0.0, 2.0 * PI);
+        self.close()
+    }
+
+    /// Adds a closed circle subpath
+    pub fn add_circle(&mut self, center: Point, radius: f32) -> &mut Self {
+        self.add_oval(Rect::new(
+            center.x - radius,
+            center.y - radius,
+            radius * 2.0,
+            radius * 2.0,
+        ))
+    }
+
+    /// Appends all subpaths of another path
+    pub fn add_path(&mut self, other: &Path) -> &mut Self {
+        for command in &other.commands {
+            match *command {
+                PathCommand::MoveTo(p) => self.move_to(p),
+                PathCommand::LineTo(p) => self.line_to(p),
+                PathCommand::QuadTo(c, p) => self.quad_to(c, p),
+                PathCommand::CubicTo(c1, c2, p) => self.cubic_to(c1, c2, p),
+                PathCommand::Close => self.close(),
+            };
+        }
+        self
+    }
+
+    /// Returns a copy of this path with every point transformed
+    pub fn transformed(&self, transform: &Transform) -> Path {
+        let t = |p: Point| transform.transform_point(p);
+        let commands = self
+            .commands
+            .iter()
+            .map(|command| match *command {
+                PathCommand::MoveTo(p) => PathCommand::MoveTo(t(p)),
+                PathCommand::LineTo(p) => PathCommand::LineTo(t(p)),
+                PathCommand::QuadTo(c, p) => PathCommand::QuadTo(t(c), t(p)),
+                PathCommand::CubicTo(c1, c2, p) => PathCommand::CubicTo(t(c1), t(c2), t(p)),
+                PathCommand::Close => PathCommand::Close,
+            })
+            .collect();
+        Path {
+            commands,
+            fill_rule: self.fill_rule,
+            start: t(self.start),
+            current: self.current.map(t),
+        }
+    }
+
+    /// Returns the bounding box of all points, including control points
+    pub fn bounds(&self) -> Rect {
+        let mut min = Point::new(f32::INFINITY, f32::INFINITY);
+        let mut max = Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+        let mut include = |p: Point| {
+            min = Point::new(min.x.min(p.x), min.y.min(p.y));
+            max = Point::new(max.x.max(p.x), max.y.max(p.y));
+        };
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo(p) | PathCommand::LineTo(p) => include(p),
+                PathCommand::QuadTo(c, p) => {
+                    include(c);
+                    include(p);
+                }
+                PathCommand::CubicTo(c1, c2, p) => {
+                    include(c1);
+                    include(c2);
+                    include(p);
+                }
+                PathCommand::Close => {}
+            }
+        }
+        if min.x > max.x {
+            return Rect::zero();
+        }
+        Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+
+    /// Approximates the path with straight segments
+    ///
+    /// `tolerance` is the maximum distance between the curve and its
+    /// approximation, in the path's units.
+    pub fn flatten(&self, tolerance: f32) -> Vec<Polyline> {
+        let tolerance = tolerance.max(0.001);
+        let mut polylines = Vec::new();
+        let mut current: Option<Polyline> = None;
+        let mut last = Point::zero();
+
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo(p) => {
+                    if let Some(line) = current.take() {
+                        polylines.push(line);
+                    }
+                    current = Some(Polyline {
+                        points: vec![p],
+                        closed: false,
+                    });
+                    last = p;
+                }
+                PathCommand::LineTo(p) => {
+                    current.get_or_insert_with(|| Polyline { points: vec![last], closed: false }).points.push(p);
+                    last = p;
+                }
+                PathCommand::QuadTo(c, p) => {
+                    let line = current.get_or_insert_with(|| Polyline { points: vec![last], closed: false });
+                    let segments = curve_segments(&[last, c, p], tolerance);
+                    for i in 1..=segments {
+                        let t = i as f32 / segments as f32;
+                        line.points.push(quad_point(last, c, p, t));
+                    }
+                    last = p;
+                }
+                PathCommand::CubicTo(c1, c2, p) => {
+                    let line = current.get_or_insert_with(|| Polyline { points: vec![last], closed: false });
+                    let segments = curve_segments(&[last, c1, c2, p], tolerance);
+                    for i in 1..=segments {
+                        let t = i as f32 / segments as f32;
+                        line.points.push(cubic_point(last, c1, c2, p, t));
+                    }
+                    last = p;
+                }
+                PathCommand::Close => {
+                    if let Some(mut line) = current.take() {
+                        line.closed = true;
+                        last = line.points[0];
+                        polylines.push(line);
+                    }
+                }
+            }
+        }
+        if let Some(line) = current.take() {
+            polylines.push(line);
+        }
+        polylines
+    }
+
+    /// Returns true if `point` is inside the filled path
+    pub fn contains(&self, point: Point) -> bool {
+        let mut winding = 0i32;
+        for polyline in self.flatten(0.25) {
+            let points = &polyline.points;
+            // Fill treats every subpath as implicitly closed
+            for i in 0..points.len() {
+                let a = points[i];
+                let b = points[(i + 1) % points.len()];
+                if a.y <= point.y {
+                    if b.y > point.y && cross(a, b, point) > 0.0 {
+                        winding += 1;
+                    }
+                } else if b.y <= point.y && cross(a, b, point) < 0.0 {
+                    winding -= 1;
+                }
+            }
+        }
+        match self.fill_rule {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+
+    /// Splits the path into dashes following the given pattern
+    ///
+    /// The result contains one open subpath per dash and is meant to be
+    /// stroked. Returns a copy of the path if the pattern is unusable.
+    pub fn dashed(&self, dash_array: &[f32], dash_offset: f32, tolerance: f32) -> Path {
+        let total: f32 = dash_array.iter().sum();
+        if dash_array.is_empty() || total <= 0.0 || dash_array.iter().any(|len| *len < 0.0) {
+            return self.clone();
+        }
+        // An odd-length pattern is repeated to make it even, as in SVG
+        let pattern: Vec<f32> = if dash_array.len() % 2 == 1 {
+            dash_array.iter().chain(dash_array.iter()).copied().collect()
+        } else {
+            dash_array.to_vec()
+        };
+        let total = total * (pattern.len() / dash_array.len()) as f32;
+
+        let mut result = Path::new();
+        result.fill_rule = self.fill_rule;
+
+        for polyline in self.flatten(tolerance) {
+            let mut points = polyline.points.clone();
+            if polyline.closed {
+                points.push(points[0]);
+            }
+
+            // Find the starting position within the pattern
+            let mut offset = dash_offset.rem_euclid(total);
+            let mut index = 0;
+            while offset >= pattern[index] {
+                offset -= pattern[index];
+                index = (index + 1) % pattern.len();
+            }
+            let mut remaining = pattern[index] - offset;
+            let mut drawing = index % 2 == 0;
+            if drawing {
+                result.move_to(points[0]);
+            }
+
+            for window in points.windows(2) {
+                let (mut a, b) = (window[0], window[1]);
+                let mut length = a.distance_to(&b);
+                while length > 0.0 {
+                    if remaining >= length {
+                        if drawing {
+                            result.line_to(b);
+                        }
+                        remaining -= length;
+                        length = 0.0;
+                    } else {
+                        let t = remaining / length;
+                        let split = Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+                        if drawing {
+                            result.line_to(split);
+                        } else {
+                            result.move_to(split);
+                        }
+                        drawing = !drawing;
+                        length -= remaining;
+                        a = split;
+                        index = (index + 1) % pattern.len();
+                        remaining = pattern[index];
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Starts a subpath at the last point (or the origin) if none is open
+    fn ensure_subpath(&mut self) {
+        if self.current.is_none() {
+            self.move_to(Point::zero());
+        } else if matches!(self.commands.last(), Some(PathCommand::Close)) {
+            self.move_to(self.start);
+        }
+    }
+
+    /// Adds elliptical arc segments as cubic Béziers (at most 90° each)
+    fn arc_segments(&mut self, center: Point, rx: f32, ry: f32, start_angle: f32, sweep_angle: f32) {
+        let count = (sweep_angle.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+        let step = sweep_angle / count as f32;
+        let k = 4.0 / 3.0 * (step / 4.0).tan();
+        let mut angle = start_angle;
+        for _ in 0..count {
+            let (sin0, cos0) = angle.sin_cos();
+            let (sin1, cos1) = (angle + step).sin_cos();
+            let p0 = Point::new(center.x + rx * cos0, center.y + ry * sin0);
+            let p3 = Point::new(center.x + rx * cos1, center.y + ry * sin1);
+            let c1 = Point::new(p0.x - k * rx * sin0, p0.y + k * ry * cos0);
+            let c2 = Point::new(p3.x + k * rx * sin1, p3.y - k * ry * cos1);
+            self.cubic_to(c1, c2, p3);
+            angle += step;
+        }
+    }
+}
+
+/// Estimates how many straight segments a Bézier curve needs
+fn curve_segments(points: &[Point], tolerance: f32) -> usize {
+    let hull: f32 = points.windows(2).map(|w| w[0].distance_to(&w[1])).sum();
+    ((hull / tolerance).sqrt().ceil() as usize).clamp(1, 256)
+}
+
+fn quad_point(p0: Point, p1: Point, p2: Point, t: f32) -> Point {
+    let mt = 1.0 - t;
+    Point::new(
+        mt * mt * p0.x + 2.0 * mt * t * p1.x + t * t * p2.x,
+        mt * mt * p0.y + 2.0 * mt * t * p1.y + t * t * p2.y,
+    )
+}
+
+fn cubic_point(p0: Point, p1: Point, p2: Point, p3: Point, t: f32) -> Point {
+    let mt = 1.0 - t;
+    let (a, b, c, d) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+    Point::new(
+        a * p0.x + b * p1.x + c * p2.x + d * p3.x,
+        a * p0.y + b * p1.y + c * p2.y + d * p3.y,
+    )
+}
+
+/// Returns the z component of (b - a) x (p - a)
+fn cross(a: Point, b: Point, p: Point) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (p.x - a.x) * (b.y - a.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_to_without_move_starts_at_origin() {
+        let mut path = Path::new();
+        path.line_to(Point::new(10.0, 0.0));
+        assert_eq!(path.commands()[0], PathCommand::MoveTo(Point::zero()));
+    }
+
+    #[test]
+    fn test_rect_bounds_and_contains() {
+        let path = Path::rect(Rect::new(10.0, 10.0, 20.0, 30.0));
+        assert_eq!(path.bounds(), Rect::new(10.0, 10.0, 20.0, 30.0));
+        assert!(path.contains(Point::new(15.0, 20.0)));
+        assert!(!path.contains(Point::new(5.0, 20.0)));
+    }
+
+    #[test]
+    fn test_circle_flattening_stays_on_circle() {
+        let path = Path::circle(Point::new(50.0, 50.0), 20.0);
+        let polylines = path.flatten(0.1);
+        assert_eq!(polylines.len(), 1);
+        assert!(polylines[0].closed);
+        for p in &polylines[0].points {
+            let d = p.distance_to(&Point::new(50.0, 50.0));
+            assert!((d - 20.0).abs() < 0.1, "point {:?} is {} from center", p, d);
+        }
+    }
+
+    #[test]
+    fn test_fill_rules() {
+        let mut path = Path::new();
+        path.add_rect(Rect::new(0.0, 0.0, 100.0, 100.0));
+        path.add_rect(Rect::new(25.0, 25.0, 50.0, 50.0));
+        assert!(path.contains(Point::new(50.0, 50.0)));
+
+        path.set_fill_rule(FillRule::EvenOdd);
+        assert!(!path.contains(Point::new(50.0, 50.0)));
+        assert!(path.contains(Point::new(10.0, 10.0)));
+    }
+
+    #[test]
+    fn test_dashed_line() {
+        let mut path = Path::new();
+        path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(100.0, 0.0));
+
+        let dashed = path.dashed(&[10.0, 10.0], 0.0, 0.25);
+        let dashes = dashed.flatten(0.25);
+        assert_eq!(dashes.len(), 5);
+        assert_eq!(dashes[0].points, vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0)]);
+        assert_eq!(dashes[4].points, vec![Point::new(80.0, 0.0), Point::new(90.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_dashed_with_offset_starts_in_gap() {
+        let mut path = Path::new();
+        path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(30.0, 0.0));
+
+        let dashes = path.dashed(&[10.0, 10.0], 15.0, 0.25).flatten(0.25);
+        assert_eq!(dashes[0].points[0], Point::new(5.0, 0.0));
+    }
+
+    #[test]
+    fn test_transformed() {
+        let path = Path::rect(Rect::new(0.0, 0.0, 10.0, 10.0));
+        let moved = path.transformed(&Transform::translate(5.0, 5.0));
+        assert_eq!(moved.bounds(), Rect::new(5.0, 5.0, 10.0, 10.0));
+    }
+}
@@ -0,0 +1,1105 @@
+//! Gesture recognizers for taps, long presses, drags and scales
+//!
+//! Each recognizer turns the events of the pointers it tracks into
+//! callbacks, competing with other recognizers in the gesture arena (see
+//! `crate::gesture`). Recognizers are usually created by the
+//! `GestureDetector` widget, which feeds them the pointer events it receives.
+//! Positions are in the coordinates the events were delivered in, i.e. those
+//! of the detecting widget, and event times are the frame time published by
+//! the runtime.
+
+use crate::event::{PointerButtons, PointerEvent, PointerKind, PointerPhase};
+use crate::gesture::{self, GestureDisposition, GestureRecognizer, GestureSettings};
+use crate::scheduler;
+use crate::types::Point;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// A gesture callback
+type Callback<T> = Box<dyn Fn(T)>;
+
+/// Only the samples this recent are used to estimate a velocity
+const VELOCITY_WINDOW: Duration = Duration::from_millis(100);
+
+/// Where a tap happened
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TapDetails {
+    /// Position of the pointer
+    pub position: Point,
+    /// Kind of device that tapped
+    pub kind: PointerKind,
+}
+
+/// Where a long press happened
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LongPressDetails {
+    /// Position of the pointer
+    pub position: Point,
+}
+
+/// Where a drag started
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragStartDetails {
+    /// Position of the pointer when it went down
+    pub position: Point,
+}
+
+/// How far a drag moved
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragUpdateDetails {
+    /// Position of the pointer
+    pub position: Point,
+    /// Movement since the last update, restricted to the drag's axis
+    pub delta: Point,
+}
+
+/// How a drag ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragEndDetails {
+    /// Velocity of the pointer in logical pixels per second when it went
+    /// up, restricted to the drag's axis
+    pub velocity: Point,
+}
+
+/// Where a scale started
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleStartDetails {
+    /// Center of the pointers in contact
+    pub focal_point: Point,
+    /// Number of pointers in contact
+    pub pointer_count: usize,
+}
+
+/// How a scale changed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleUpdateDetails {
+    /// Center of the pointers in contact
+    pub focal_point: Point,
+    /// Movement of the focal point since the last update
+    pub focal_point_delta: Point,
+    /// Scale relative to the start of the gesture
+    pub scale: f32,
+    /// Clockwise rotation in radians relative to the start of the gesture
+    pub rotation: f32,
+    /// Number of pointers in contact
+    pub pointer_count: usize,
+}
+
+/// How a scale ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleEndDetails {
+    /// Number of pointers still in contact
+    pub pointer_count: usize,
+}
+
+/// The axis a drag follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DragAxis {
+    Horizontal,
+    Vertical,
+    /// Any direction, i.e. a pan
+    Free,
+}
+
+impl DragAxis {
+    /// Restricts a vector to this axis
+    fn project(&self, vector: Point) -> Point {
+        match self {
+            DragAxis::Horizontal => Point::new(vector.x, 0.0),
+            DragAxis::Vertical => Point::new(0.0, vector.y),
+            DragAxis::Free => vector,
+        }
+    }
+
+    /// Returns the distance a movement covers along this axis
+    fn distance(&self, vector: Point) -> f32 {
+        self.project(vector).distance_to(&Point::zero())
+    }
+}
+
+/// Estimates the velocity of a pointer from its recent positions
+#[derive(Debug, Clone, Default)]
+pub struct VelocityTracker {
+    samples: VecDeque<(Duration, Point)>,
+}
+
+impl VelocityTracker {
+    /// Creates a tracker with no samples
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the position of the pointer at a time
+    pub fn add(&mut self, time: Duration, position: Point) {
+        self.samples.push_back((time, position));
+        while let Some(&(oldest, _)) = self.samples.front() {
+            if time.saturating_sub(oldest) <= VELOCITY_WINDOW {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+
+    /// Returns the velocity in logical pixels per second over the recent
+    /// samples, or zero if they span no time
+    pub fn velocity(&self) -> Point {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(&(start, from)), Some(&(end, to))) if end > start => {
+                (to - from).scale(1.0 / (end - start).as_secs_f32())
+            }
+            _ => Point::zero(),
+        }
+    }
+}
+
+/// Returns true if a pointer went down with the primary button, i.e. the
+/// left mouse button, a finger or a pen
+fn is_primary(event: &PointerEvent) -> bool {
+    event.phase == PointerPhase::Down && event.buttons.contains(PointerButtons::PRIMARY)
+}
+
+/// The pointer a single-pointer recognizer tracks
+#[derive(Debug, Clone, Copy)]
+struct Tracked {
+    pointer: u64,
+    kind: PointerKind,
+    down: Point,
+}
+
+/// Recognizes a tap: a pointer going down and up without moving further
+/// than the touch slop
+///
+/// `on_tap` is called once the recognizer has won the arena and the pointer
+/// is up, which may be later than the pointer going up if, e.g., a double
+/// tap is still possible.
+#[derive(Default)]
+pub struct TapGestureRecognizer {
+    on_tap_down: Option<Callback<TapDetails>>,
+    on_tap: Option<Callback<TapDetails>>,
+    on_tap_cancel: Option<Box<dyn Fn()>>,
+    state: RefCell<TapState>,
+}
+
+#[derive(Default)]
+struct TapState {
+    tracked: Option<Tracked>,
+    slop: f32,
+    up: Option<Point>,
+    won: bool,
+}
+
+impl TapGestureRecognizer {
+    /// Creates a recognizer without callbacks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the callback for a pointer going down that may become a tap
+    pub fn on_tap_down(mut self, on_tap_down: impl Fn(TapDetails) + 'static) -> Self {
+        self.on_tap_down = Some(Box::new(on_tap_down));
+        self
+    }
+
+    /// Sets the callback for a completed tap
+    pub fn on_tap(mut self, on_tap: impl Fn(TapDetails) + 'static) -> Self {
+        self.on_tap = Some(Box::new(on_tap));
+        self
+    }
+
+    /// Sets the callback for a pointer reported to `on_tap_down` that did
+    /// not become a tap
+    pub fn on_tap_cancel(mut self, on_tap_cancel: impl Fn() + 'static) -> Self {
+        self.on_tap_cancel = Some(Box::new(on_tap_cancel));
+        self
+    }
+
+    fn tracks(&self, pointer: u64) -> bool {
+        self.state.borrow().tracked.is_some_and(|tracked| tracked.pointer == pointer)
+    }
+
+    /// Stops tracking the pointer and reports the cancellation
+    fn cancel(&self) {
+        self.state.replace(TapState::default());
+        if let Some(on_tap_cancel) = &self.on_tap_cancel {
+            on_tap_cancel();
+        }
+    }
+
+    /// Reports the tap if the recognizer has won and the pointer is up
+    fn check_up(&self) {
+        let details = {
+            let state = self.state.borrow();
+            match (state.tracked, state.up) {
+                (Some(tracked), Some(position)) if state.won => TapDetails {
+                    position,
+                    kind: tracked.kind,
+                },
+                _ => return,
+            }
+        };
+        self.state.replace(TapState::default());
+        if let Some(on_tap) = &self.on_tap {
+            on_tap(details);
+        }
+    }
+}
+
+impl GestureRecognizer for TapGestureRecognizer {
+    fn add_pointer(&self, event: &PointerEvent, settings: &GestureSettings) -> bool {
+        if !is_primary(event) || self.state.borrow().tracked.is_some() {
+            return false;
+        }
+        self.state.replace(TapState {
+            tracked: Some(Tracked {
+                pointer: event.id,
+                kind: event.kind,
+                down: event.position,
+            }),
+            slop: settings.touch_slop,
+            up: None,
+            won: false,
+        });
+        if let Some(on_tap_down) = &self.on_tap_down {
+            on_tap_down(TapDetails {
+                position: event.position,
+                kind: event.kind,
+            });
+        }
+        true
+    }
+
+    fn handle_event(&self, event: &PointerEvent) {
+        if !self.tracks(event.id) {
+            return;
+        }
+        match event.phase {
+            PointerPhase::Move => {
+                let moved_too_far = {
+                    let state = self.state.borrow();
+                    state.tracked.is_some_and(|tracked| event.position.distance_to(&tracked.down) > state.slop)
+                };
+                if moved_too_far {
+                    self.cancel();
+                    gesture::resolve(event.id, self, GestureDisposition::Rejected);
+                }
+            }
+            PointerPhase::Up => {
+                self.state.borrow_mut().up = Some(event.position);
+                self.check_up();
+            }
+            PointerPhase::Cancel => {
+                self.cancel();
+                gesture::resolve(event.id, self, GestureDisposition::Rejected);
+            }
+            _ => {}
+        }
+    }
+
+    fn accept_gesture(&self, pointer: u64) {
+        if self.tracks(pointer) {
+            self.state.borrow_mut().won = true;
+            self.check_up();
+        }
+    }
+
+    fn reject_gesture(&self, pointer: u64) {
+        if self.tracks(pointer) {
+            self.cancel();
+        }
+    }
+}
+
+/// Recognizes two taps in quick succession close to each other
+///
+/// After the first tap the recognizer holds its arena, so a competing tap
+/// recognizer only wins once the double-tap timeout has passed.
+#[derive(Default)]
+pub struct DoubleTapGestureRecognizer {
+    on_double_tap: Option<Callback<TapDetails>>,
+    state: RefCell<DoubleTapState>,
+}
+
+#[derive(Default)]
+struct DoubleTapState {
+    settings: GestureSettings,
+    /// The pointer and position of the first tap, once it went up
+    first: Option<(u64, Point)>,
+    /// The pointer currently down
+    current: Option<Tracked>,
+    deadline: Option<Duration>,
+}
+
+impl DoubleTapGestureRecognizer {
+    /// Creates a recognizer without callbacks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the callback for a double tap, called with the position of the
+    /// second tap
+    pub fn on_double_tap(mut self, on_double_tap: impl Fn(TapDetails) + 'static) -> Self {
+        self.on_double_tap = Some(Box::new(on_double_tap));
+        self
+    }
+
+    fn tracks(&self, pointer: u64) -> bool {
+        let state = self.state.borrow();
+        state.current.is_some_and(|current| current.pointer == pointer)
+            || state.first.is_some_and(|(first, _)| first == pointer)
+    }
+
+    /// Gives up both taps, letting the first tap's arena be swept
+    fn reset(&self) {
+        let state = self.state.take();
+        if let Some((first, _)) = state.first {
+            gesture::resolve(first, self, GestureDisposition::Rejected);
+            gesture::release(first);
+        }
+        if let Some(current) = state.current {
+            gesture::resolve(current.pointer, self, GestureDisposition::Rejected);
+        }
+    }
+}
+
+impl GestureRecognizer for DoubleTapGestureRecognizer {
+    fn add_pointer(&self, event: &PointerEvent, settings: &GestureSettings) -> bool {
+        if !is_primary(event) || self.state.borrow().current.is_some() {
+            return false;
+        }
+        let too_far = self
+            .state
+            .borrow()
+            .first
+            .is_some_and(|(_, position)| event.position.distance_to(&position) > settings.double_tap_slop);
+        if too_far {
+            // Start over with this as the first tap
+            self.reset();
+        }
+        let mut state = self.state.borrow_mut();
+        state.settings = *settings;
+        state.current = Some(Tracked {
+            pointer: event.id,
+            kind: event.kind,
+            down: event.position,
+        });
+        true
+    }
+
+    fn handle_event(&self, event: &PointerEvent) {
+        let Some(current) = self.state.borrow().current.filter(|current| current.pointer == event.id) else {
+            return;
+        };
+        match event.phase {
+            PointerPhase::Move => {
+                let slop = self.state.borrow().settings.touch_slop;
+                if event.position.distance_to(&current.down) > slop {
+                    self.reset();
+                }
+            }
+            PointerPhase::Up => {
+                let first = self.state.borrow().first;
+                match first {
+                    None => {
+                        let mut state = self.state.borrow_mut();
+                        state.first = Some((event.id, event.position));
+                        state.current = None;
+                        state.deadline = Some(scheduler::frame_time() + state.settings.double_tap_timeout);
+                        drop(state);
+                        gesture::hold(event.id);
+                    }
+                    Some((first, _)) => {
+                        self.state.take();
+                        gesture::resolve(first, self, GestureDisposition::Accepted);
+                        gesture::resolve(event.id, self, GestureDisposition::Accepted);
+                        if let Some(on_double_tap) = &self.on_double_tap {
+                            on_double_tap(TapDetails {
+                                position: event.position,
+                                kind: current.kind,
+                            });
+                        }
+                    }
+                }
+            }
+            PointerPhase::Cancel => self.reset(),
+            _ => {}
+        }
+    }
+
+    fn accept_gesture(&self, _pointer: u64) {}
+
+    fn reject_gesture(&self, pointer: u64) {
+        if self.tracks(pointer) {
+            self.reset();
+        }
+    }
+
+    fn deadline(&self) -> Option<Duration> {
+        self.state.borrow().deadline
+    }
+
+    fn check_deadline(&self, now: Duration) {
+        if self.deadline().is_some_and(|deadline| deadline <= now) {
+            // Too slow for a double tap
+            self.reset();
+        }
+    }
+}
+
+/// Recognizes a pointer staying down without moving further than the touch
+/// slop for the long-press delay
+#[derive(Default)]
+pub struct LongPressGestureRecognizer {
+    on_long_press: Option<Callback<LongPressDetails>>,
+    on_long_press_end: Option<Callback<LongPressDetails>>,
+    state: RefCell<LongPressState>,
+}
+
+#[derive(Default)]
+struct LongPressState {
+    tracked: Option<Tracked>,
+    slop: f32,
+    deadline: Option<Duration>,
+    pressed: bool,
+}
+
+impl LongPressGestureRecognizer {
+    /// Creates a recognizer without callbacks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the callback for the long-press delay passing
+    pub fn on_long_press(mut self, on_long_press: impl Fn(LongPressDetails) + 'static) -> Self {
+        self.on_long_press = Some(Box::new(on_long_press));
+        self
+    }
+
+    /// Sets the callback for the pointer going up after a long press
+    pub fn on_long_press_end(mut self, on_long_press_end: impl Fn(LongPressDetails) + 'static) -> Self {
+        self.on_long_press_end = Some(Box::new(on_long_press_end));
+        self
+    }
+
+    /// Stops tracking the pointer and gives it up unless the long press
+    /// happened
+    fn stop(&self, pointer: u64) {
+        let state = self.state.take();
+        if !state.pressed {
+            gesture::resolve(pointer, self, GestureDisposition::Rejected);
+        }
+    }
+}
+
+impl GestureRecognizer for LongPressGestureRecognizer {
+    fn add_pointer(&self, event: &PointerEvent, settings: &GestureSettings) -> bool {
+        if !is_primary(event) || self.state.borrow().tracked.is_some() {
+            return false;
+        }
+        self.state.replace(LongPressState {
+            tracked: Some(Tracked {
+                pointer: event.id,
+                kind: event.kind,
+                down: event.position,
+            }),
+            slop: settings.touch_slop,
+            deadline: Some(scheduler::frame_time() + settings.long_press_delay),
+            pressed: false,
+        });
+        true
+    }
+
+    fn handle_event(&self, event: &PointerEvent) {
+        let (tracked, slop, pressed) = {
+            let state = self.state.borrow();
+            match state.tracked {
+                Some(tracked) if tracked.pointer == event.id => (tracked, state.slop, state.pressed),
+                _ => return,
+            }
+        };
+        match event.phase {
+            PointerPhase::Move if !pressed && event.position.distance_to(&tracked.down) > slop => {
+                self.stop(event.id);
+            }
+            PointerPhase::Up | PointerPhase::Cancel => {
+                self.stop(event.id);
+                if pressed && let Some(on_long_press_end) = &self.on_long_press_end {
+                    on_long_press_end(LongPressDetails {
+                        position: event.position,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn accept_gesture(&self, _pointer: u64) {}
+
+    fn reject_gesture(&self, pointer: u64) {
+        let tracks = self.state.borrow().tracked.is_some_and(|tracked| tracked.pointer == pointer);
+        if tracks {
+            self.state.take();
+        }
+    }
+
+    fn deadline(&self) -> Option<Duration> {
+        self.state.borrow().deadline
+    }
+
+    fn check_deadline(&self, now: Duration) {
+        let tracked = {
+            let mut state = self.state.borrow_mut();
+            match (state.tracked, state.deadline) {
+                (Some(tracked), Some(deadline)) if deadline <= now => {
+                    state.deadline = None;
+                    state.pressed = true;
+                    tracked
+                }
+                _ => return,
+            }
+        };
+        gesture::resolve(tracked.pointer, self, GestureDisposition::Accepted);
+        // Rejected when a competitor had already won
+        let still_tracked = self.state.borrow().tracked.is_some();
+        if still_tracked && let Some(on_long_press) = &self.on_long_press {
+            on_long_press(LongPressDetails { position: tracked.down });
+        }
+    }
+}
+
+/// Recognizes a pointer moving further than the touch slop along an axis
+///
+/// Use `DragAxis::Free` for a pan. Once the recognizer has won, every move
+/// is reported, restricted to the axis.
+pub struct DragGestureRecognizer {
+    axis: DragAxis,
+    on_start: Option<Callback<DragStartDetails>>,
+    on_update: Option<Callback<DragUpdateDetails>>,
+    on_end: Option<Callback<DragEndDetails>>,
+    state: RefCell<DragState>,
+}
+
+#[derive(Default)]
+struct DragState {
+    tracked: Option<Tracked>,
+    slop: f32,
+    last: Point,
+    /// Movement before the recognizer won
+    pending: Point,
+    accepted: bool,
+    velocity: VelocityTracker,
+}
+
+impl DragGestureRecognizer {
+    /// Creates a recognizer for drags along an axis, without callbacks
+    pub fn new(axis: DragAxis) -> Self {
+        Self {
+            axis,
+            on_start: None,
+            on_update: None,
+            on_end: None,
+            state: RefCell::new(DragState::default()),
+        }
+    }
+
+    /// Sets the callback for the drag starting
+    pub fn on_start(mut self, on_start: impl Fn(DragStartDetails) + 'static) -> Self {
+        self.on_start = Some(Box::new(on_start));
+        self
+    }
+
+    /// Sets the callback for the pointer moving
+    pub fn on_update(mut self, on_update: impl Fn(DragUpdateDetails) + 'static) -> Self {
+        self.on_update = Some(Box::new(on_update));
+        self
+    }
+
+    /// Sets the callback for the pointer going up
+    pub fn on_end(mut self, on_end: impl Fn(DragEndDetails) + 'static) -> Self {
+        self.on_end = Some(Box::new(on_end));
+        self
+    }
+
+    /// Returns the axis
+    pub fn get_axis(&self) -> DragAxis {
+        self.axis
+    }
+
+    fn update(&self, position: Point, delta: Point) {
+        let delta = self.axis.project(delta);
+        if delta != Point::zero()
+            && let Some(on_update) = &self.on_update
+        {
+            on_update(DragUpdateDetails { position, delta });
+        }
+    }
+}
+
+impl Default for DragGestureRecognizer {
+    fn default() -> Self {
+        Self::new(DragAxis::Free)
+    }
+}
+
+impl GestureRecognizer for DragGestureRecognizer {
+    fn add_pointer(&self, event: &PointerEvent, settings: &GestureSettings) -> bool {
+        if !is_primary(event) || self.state.borrow().tracked.is_some() {
+            return false;
+        }
+        let mut velocity = VelocityTracker::new();
+        velocity.add(scheduler::frame_time(), event.position);
+        self.state.replace(DragState {
+            tracked: Some(Tracked {
+                pointer: event.id,
+                kind: event.kind,
+                down: event.position,
+            }),
+            slop: settings.touch_slop,
+            last: event.position,
+            pending: Point::zero(),
+            accepted: false,
+            velocity,
+        });
+        true
+    }
+
+    fn handle_event(&self, event: &PointerEvent) {
+        let tracks = self.state.borrow().tracked.is_some_and(|tracked| tracked.pointer == event.id);
+        if !tracks {
+            return;
+        }
+        match event.phase {
+            PointerPhase::Move => {
+                let (accepted, delta, pending, slop) = {
+                    let mut state = self.state.borrow_mut();
+                    state.velocity.add(scheduler::frame_time(), event.position);
+                    let delta = event.position - state.last;
+                    state.last = event.position;
+                    if !state.accepted {
+                        state.pending = state.pending + delta;
+                    }
+                    (state.accepted, delta, state.pending, state.slop)
+                };
+                if accepted {
+                    self.update(event.position, delta);
+                } else if self.axis.distance(pending) > slop {
+                    gesture::resolve(event.id, self, GestureDisposition::Accepted);
+                }
+            }
+            PointerPhase::Up | PointerPhase::Cancel => {
+                let state = self.state.take();
+                if !state.accepted {
+                    gesture::resolve(event.id, self, GestureDisposition::Rejected);
+                } else if let Some(on_end) = &self.on_end {
+                    let velocity = if event.phase == PointerPhase::Up {
+                        self.axis.project(state.velocity.velocity())
+                    } else {
+                        Point::zero()
+                    };
+                    on_end(DragEndDetails { velocity });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn accept_gesture(&self, pointer: u64) {
+        let (down, last, pending) = {
+            let mut state = self.state.borrow_mut();
+            match state.tracked {
+                Some(tracked) if tracked.pointer == pointer && !state.accepted => {
+                    state.accepted = true;
+                    (tracked.down, state.last, std::mem::replace(&mut state.pending, Point::zero()))
+                }
+                _ => return,
+            }
+        };
+        if let Some(on_start) = &self.on_start {
+            on_start(DragStartDetails { position: down });
+        }
+        // Report the movement that happened while the arena was undecided
+        self.update(last, pending);
+    }
+
+    fn reject_gesture(&self, pointer: u64) {
+        let tracks = self.state.borrow().tracked.is_some_and(|tracked| tracked.pointer == pointer);
+        if tracks {
+            self.state.take();
+        }
+    }
+}
+
+/// Recognizes one or more pointers moving together: their focal point
+/// panning, the distance between them scaling and the angle between the
+/// first two rotating
+///
+/// A scale is a superset of a pan, so a widget should not recognize both.
+#[derive(Default)]
+pub struct ScaleGestureRecognizer {
+    on_start: Option<Callback<ScaleStartDetails>>,
+    on_update: Option<Callback<ScaleUpdateDetails>>,
+    on_end: Option<Callback<ScaleEndDetails>>,
+    state: RefCell<ScaleState>,
+}
+
+#[derive(Default)]
+struct ScaleState {
+    slop: f32,
+    pointers: Vec<(u64, Point)>,
+    accepted: bool,
+    initial_focal_point: Point,
+    focal_point: Point,
+    /// Span and angle when the pointers last changed, and the scale and
+    /// rotation reached by then
+    initial_span: f32,
+    initial_angle: Option<f32>,
+    base_scale: f32,
+    base_rotation: f32,
+}
+
+impl ScaleState {
+    fn focal_point(&self) -> Point {
+        let sum = self.pointers.iter().fold(Point::zero(), |sum, (_, position)| sum + *position);
+        sum.scale(1.0 / self.pointers.len().max(1) as f32)
+    }
+
+    /// Average distance of the pointers from their focal point
+    fn span(&self) -> f32 {
+        let focal_point = self.focal_point();
+        let total: f32 = self.pointers.iter().map(|(_, position)| position.distance_to(&focal_point)).sum();
+        total / self.pointers.len().max(1) as f32
+    }
+
+    /// Angle of the line from the first pointer to the second
+    fn angle(&self) -> Option<f32> {
+        match self.pointers.as_slice() {
+            [(_, first), (_, second), ..] => Some((second.y - first.y).atan2(second.x - first.x)),
+            _ => None,
+        }
+    }
+
+    fn scale(&self) -> f32 {
+        if self.initial_span > 0.0 {
+            self.base_scale * self.span() / self.initial_span
+        } else {
+            self.base_scale
+        }
+    }
+
+    fn rotation(&self) -> f32 {
+        match (self.initial_angle, self.angle()) {
+            (Some(initial), Some(angle)) => self.base_rotation + angle - initial,
+            _ => self.base_rotation,
+        }
+    }
+
+    /// Starts measuring from the current pointers, after one was added or
+    /// removed, keeping the scale and rotation reached so far
+    fn rebase(&mut self) {
+        self.base_scale = self.scale();
+        self.base_rotation = self.rotation();
+        self.initial_span = self.span();
+        self.initial_angle = self.angle();
+        self.focal_point = self.focal_point();
+    }
+}
+
+impl ScaleGestureRecognizer {
+    /// Creates a recognizer without callbacks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the callback for the scale starting
+    pub fn on_start(mut self, on_start: impl Fn(ScaleStartDetails) + 'static) -> Self {
+        self.on_start = Some(Box::new(on_start));
+        self
+    }
+
+    /// Sets the callback for the pointers moving
+    pub fn on_update(mut self, on_update: impl Fn(ScaleUpdateDetails) + 'static) -> Self {
+        self.on_update = Some(Box::new(on_update));
+        self
+    }
+
+    /// Sets the callback for the last pointer going up
+    pub fn on_end(mut self, on_end: impl Fn(ScaleEndDetails) + 'static) -> Self {
+        self.on_end = Some(Box::new(on_end));
+        self
+    }
+}
+
+impl GestureRecognizer for ScaleGestureRecognizer {
+    fn add_pointer(&self, event: &PointerEvent, settings: &GestureSettings) -> bool {
+        if !is_primary(event) {
+            return false;
+        }
+        let mut state = self.state.borrow_mut();
+        if state.pointers.is_empty() {
+            *state = ScaleState {
+                slop: settings.touch_slop,
+                initial_focal_point: event.position,
+                base_scale: 1.0,
+                ..ScaleState::default()
+            };
+        }
+        state.pointers.push((event.id, event.position));
+        state.rebase();
+        true
+    }
+
+    fn handle_event(&self, event: &PointerEvent) {
+        let tracks = self.state.borrow().pointers.iter().any(|(pointer, _)| *pointer == event.id);
+        if !tracks {
+            return;
+        }
+        match event.phase {
+            PointerPhase::Move => {
+                let (accepted, update, slop_exceeded, pointers) = {
+                    let mut state = self.state.borrow_mut();
+                    if let Some(entry) = state.pointers.iter_mut().find(|(pointer, _)| *pointer == event.id) {
+                        entry.1 = event.position;
+                    }
+                    let focal_point = state.focal_point();
+                    let update = ScaleUpdateDetails {
+                        focal_point,
+                        focal_point_delta: focal_point - state.focal_point,
+                        scale: state.scale(),
+                        rotation: state.rotation(),
+                        pointer_count: state.pointers.len(),
+                    };
+                    state.focal_point = focal_point;
+                    let slop_exceeded = focal_point.distance_to(&state.initial_focal_point) > state.slop
+                        || (state.span() - state.initial_span).abs() > state.slop;
+                    let pointers: Vec<u64> = state.pointers.iter().map(|(pointer, _)| *pointer).collect();
+                    (state.accepted, update, slop_exceeded, pointers)
+                };
+                if accepted {
+                    // Claims pointers added after the scale started
+                    gesture::resolve(event.id, self, GestureDisposition::Accepted);
+                    if let Some(on_update) = &self.on_update {
+                        on_update(update);
+                    }
+                } else if slop_exceeded {
+                    for pointer in pointers {
+                        gesture::resolve(pointer, self, GestureDisposition::Accepted);
+                    }
+                }
+            }
+            PointerPhase::Up | PointerPhase::Cancel => {
+                let (accepted, remaining) = {
+                    let mut state = self.state.borrow_mut();
+                    state.pointers.retain(|(pointer, _)| *pointer != event.id);
+                    state.rebase();
+                    (state.accepted, state.pointers.len())
+                };
+                if !accepted {
+                    gesture::resolve(event.id, self, GestureDisposition::Rejected);
+                } else if remaining == 0 {
+                    self.state.take();
+                    if let Some(on_end) = &self.on_end {
+                        on_end(ScaleEndDetails { pointer_count: 0 });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn accept_gesture(&self, pointer: u64) {
+        let start = {
+            let mut state = self.state.borrow_mut();
+            if state.accepted || !state.pointers.iter().any(|(tracked, _)| *tracked == pointer) {
+                return;
+            }
+            state.accepted = true;
+            ScaleStartDetails {
+                focal_point: state.focal_point,
+                pointer_count: state.pointers.len(),
+            }
+        };
+        if let Some(on_start) = &self.on_start {
+            on_start(start);
+        }
+    }
+
+    fn reject_gesture(&self, pointer: u64) {
+        let mut state = self.state.borrow_mut();
+        if !state.accepted {
+            state.pointers.retain(|(tracked, _)| *tracked != pointer);
+            if !state.pointers.is_empty() {
+                state.rebase();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn pointer(id: u64, phase: PointerPhase, x: f32, y: f32) -> PointerEvent {
+        PointerEvent::new(id, phase, Point::new(x, y))
+    }
+
+    /// Sends a down event to recognizers and closes the arena, as the host
+    /// does
+    fn down(recognizers: &[Rc<dyn GestureRecognizer>], event: PointerEvent) {
+        for recognizer in recognizers {
+            if recognizer.add_pointer(&event, &GestureSettings::default()) {
+                gesture::add(event.id, recognizer.clone());
+            }
+        }
+        gesture::close(event.id);
+    }
+
+    fn send(recognizers: &[Rc<dyn GestureRecognizer>], event: PointerEvent) {
+        for recognizer in recognizers {
+            recognizer.handle_event(&event);
+        }
+        if event.phase == PointerPhase::Up {
+            gesture::sweep(event.id);
+        }
+    }
+
+    #[test]
+    fn test_tap_beats_drag_without_movement() {
+        let taps = Rc::new(Cell::new(0));
+        let starts = Rc::new(Cell::new(0));
+        let (tap_count, start_count) = (taps.clone(), starts.clone());
+        let recognizers: Vec<Rc<dyn GestureRecognizer>> = vec![
+            Rc::new(TapGestureRecognizer::new().on_tap(move |_| tap_count.set(tap_count.get() + 1))),
+            Rc::new(DragGestureRecognizer::new(DragAxis::Vertical).on_start(move |_| start_count.set(start_count.get() + 1))),
+        ];
+
+        down(&recognizers, pointer(1, PointerPhase::Down, 10.0, 10.0));
+        send(&recognizers, pointer(1, PointerPhase::Move, 12.0, 14.0));
+        send(&recognizers, pointer(1, PointerPhase::Up, 12.0, 14.0));
+        assert_eq!((taps.get(), starts.get()), (1, 0));
+    }
+
+    #[test]
+    fn test_drag_beats_tap_past_slop() {
+        let cancels = Rc::new(Cell::new(0));
+        let deltas = Rc::new(RefCell::new(Vec::new()));
+        let (cancel_count, updates) = (cancels.clone(), deltas.clone());
+        let recognizers: Vec<Rc<dyn GestureRecognizer>> = vec![
+            Rc::new(TapGestureRecognizer::new().on_tap_cancel(move || cancel_count.set(cancel_count.get() + 1))),
+            Rc::new(
+                DragGestureRecognizer::new(DragAxis::Vertical)
+                    .on_update(move |details| updates.borrow_mut().push(details.delta)),
+            ),
+        ];
+
+        down(&recognizers, pointer(1, PointerPhase::Down, 10.0, 10.0));
+        send(&recognizers, pointer(1, PointerPhase::Move, 15.0, 40.0));
+        send(&recognizers, pointer(1, PointerPhase::Move, 15.0, 50.0));
+        send(&recognizers, pointer(1, PointerPhase::Up, 15.0, 50.0));
+        assert_eq!(cancels.get(), 1);
+        // The movement before winning is reported first, restricted to the axis
+        assert_eq!(*deltas.borrow(), vec![Point::new(0.0, 30.0), Point::new(0.0, 10.0)]);
+    }
+
+    #[test]
+    fn test_drags_compete_by_axis() {
+        let horizontal = Rc::new(Cell::new(false));
+        let vertical = Rc::new(Cell::new(false));
+        let (horizontal_started, vertical_started) = (horizontal.clone(), vertical.clone());
+        let recognizers: Vec<Rc<dyn GestureRecognizer>> = vec![
+            Rc::new(DragGestureRecognizer::new(DragAxis::Horizontal).on_start(move |_| horizontal_started.set(true))),
+            Rc::new(DragGestureRecognizer::new(DragAxis::Vertical).on_start(move |_| vertical_started.set(true))),
+        ];
+
+        down(&recognizers, pointer(1, PointerPhase::Down, 0.0, 0.0));
+        send(&recognizers, pointer(1, PointerPhase::Move, 5.0, 40.0));
+        send(&recognizers, pointer(1, PointerPhase::Up, 5.0, 40.0));
+        assert!(vertical.get());
+        assert!(!horizontal.get());
+    }
+
+    #[test]
+    fn test_double_tap_delays_tap_until_timeout() {
+        scheduler::set_frame_time(Duration::ZERO);
+        let taps = Rc::new(Cell::new(0));
+        let double_taps = Rc::new(Cell::new(0));
+        let (tap_count, double_tap_count) = (taps.clone(), double_taps.clone());
+        let double_tap: Rc<dyn GestureRecognizer> = Rc::new(
+            DoubleTapGestureRecognizer::new().on_double_tap(move |_| double_tap_count.set(double_tap_count.get() + 1)),
+        );
+        let recognizers = vec![
+            Rc::new(TapGestureRecognizer::new().on_tap(move |_| tap_count.set(tap_count.get() + 1)))
+                as Rc<dyn GestureRecognizer>,
+            double_tap.clone(),
+        ];
+
+        // Two taps: a double tap and no tap
+        for id in [1, 2] {
+            down(&recognizers, pointer(id, PointerPhase::Down, 10.0, 10.0));
+            send(&recognizers, pointer(id, PointerPhase::Up, 10.0, 10.0));
+        }
+        assert_eq!((taps.get(), double_taps.get()), (0, 1));
+
+        // One tap: a tap once the timeout has passed
+        down(&recognizers, pointer(3, PointerPhase::Down, 10.0, 10.0));
+        send(&recognizers, pointer(3, PointerPhase::Up, 10.0, 10.0));
+        assert_eq!(taps.get(), 0);
+        double_tap.check_deadline(gesture::DEFAULT_DOUBLE_TAP_TIMEOUT);
+        assert_eq!((taps.get(), double_taps.get()), (1, 1));
+    }
+
+    #[test]
+    fn test_long_press_after_delay() {
+        scheduler::set_frame_time(Duration::ZERO);
+        let presses = Rc::new(RefCell::new(Vec::new()));
+        let recorded = presses.clone();
+        let long_press = Rc::new(
+            LongPressGestureRecognizer::new().on_long_press(move |details| recorded.borrow_mut().push(details.position)),
+        );
+        let recognizers = vec![
+            Rc::new(TapGestureRecognizer::new()) as Rc<dyn GestureRecognizer>,
+            long_press.clone(),
+        ];
+
+        down(&recognizers, pointer(1, PointerPhase::Down, 10.0, 10.0));
+        long_press.check_deadline(Duration::from_millis(499));
+        assert!(presses.borrow().is_empty());
+        long_press.check_deadline(gesture::DEFAULT_LONG_PRESS_DELAY);
+        assert_eq!(*presses.borrow(), vec![Point::new(10.0, 10.0)]);
+        assert!(!gesture::has_arena(1));
+    }
+
+    #[test]
+    fn test_pinch_scales_and_rotates() {
+        let updates = Rc::new(RefCell::new(Vec::new()));
+        let recorded = updates.clone();
+        let recognizers: Vec<Rc<dyn GestureRecognizer>> =
+            vec![Rc::new(ScaleGestureRecognizer::new().on_update(move |details| recorded.borrow_mut().push(details)))];
+
+        down(&recognizers, pointer(1, PointerPhase::Down, 0.0, 0.0));
+        down(&recognizers, pointer(2, PointerPhase::Down, 100.0, 0.0));
+        // Spread to twice the distance, then rotate a quarter turn
+        send(&recognizers, pointer(2, PointerPhase::Move, 200.0, 0.0));
+        send(&recognizers, pointer(2, PointerPhase::Move, 0.0, 200.0));
+
+        let updates = updates.borrow();
+        let last = updates.last().unwrap();
+        assert_eq!(last.pointer_count, 2);
+        assert!((last.scale - 2.0).abs() < 1e-4, "{:?}", last);
+        assert!((last.rotation - std::f32::consts::FRAC_PI_2).abs() < 1e-4, "{:?}", last);
+        assert_eq!(last.focal_point, Point::new(0.0, 100.0));
+    }
+
+    #[test]
+    fn test_velocity_tracker() {
+        let mut tracker = VelocityTracker::new();
+        tracker.add(Duration::ZERO, Point::zero());
+        assert_eq!(tracker.velocity(), Point::zero());
+        tracker.add(Duration::from_millis(50), Point::new(10.0, 0.0));
+        assert_eq!(tracker.velocity(), Point::new(200.0, 0.0));
+        // Old samples are dropped
+        tracker.add(Duration::from_millis(500), Point::new(10.0, 5.0));
+        assert_eq!(tracker.velocity(), Point::zero());
+    }
+}
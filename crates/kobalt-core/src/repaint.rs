@@ -0,0 +1,210 @@
+//! Repaint boundaries
+//!
+//! A repaint boundary records its subtree into a separate display list and
+//! keeps it until the subtree is marked dirty. The renderer caches the
+//! boundary's pixels under its id and only rasterizes it again when the
+//! recorded content version changes, so static parts of the UI cost a single
+//! composite per frame.
+//!
+//! Boundaries are invalidated by marking their `RepaintHandle`, or all at
+//! once with `invalidate_all`, which the runtime reserves for changes that
+//! may affect every subtree, such as a new window size or `MediaQuery`.
+//! Marking a boundary marks the boundaries enclosing it as well, since their
+//! recordings contain its old content version.
+//!
+//! While a boundary's subtree is painting or handling an event, the
+//! boundary is this thread's `current_boundary`, and `scheduler::request_paint`
+//! marks it. The `WidgetHost` sets it when delivering pointer, key, hover
+//! and focus changes, so widgets with paint-only state (e.g. a hovered
+//! button) only need to request a paint. Changes made at other times, such
+//! as from a timer, must mark the boundary's handle themselves.
+//!
+//! Boundaries are only added where the app places a `RepaintBoundary`;
+//! nothing inserts them automatically yet, e.g. around scrolling content.
+
+use crate::scheduler;
+use crate::types::Rect;
+use crate::widget::Widget;
+use std::cell::{Cell, RefCell};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// Changes whenever `invalidate_all` is called
+    static EPOCH: Cell<u64> = const { Cell::new(0) };
+    /// The boundary enclosing the widgets being painted or handling an event
+    static CURRENT: RefCell<Option<RepaintHandle>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug)]
+struct HandleState {
+    id: u64,
+    needs_paint: AtomicBool,
+    /// The boundary this one was last painted inside
+    parent: Mutex<Option<RepaintHandle>>,
+}
+
+/// Identifies a repaint boundary and marks it dirty
+///
+/// Clones share the same boundary, so a handle can be given to whatever
+/// drives the subtree's changes (e.g. an animation).
+#[derive(Debug, Clone)]
+pub struct RepaintHandle {
+    state: Arc<HandleState>,
+}
+
+impl RepaintHandle {
+    /// Creates a handle with a new, unique boundary id
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(HandleState {
+                id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+                needs_paint: AtomicBool::new(false),
+                parent: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Returns the boundary id
+    pub fn id(&self) -> u64 {
+        self.state.id
+    }
+
+    /// Marks the subtree and the boundaries enclosing it as changed and
+    /// requests a frame
+    pub fn mark_needs_paint(&self) {
+        self.mark();
+        scheduler::schedule_paint();
+    }
+
+    /// Marks this boundary and the ones enclosing it
+    fn mark(&self) {
+        let mut current = Some(self.clone());
+        while let Some(handle) = current {
+            handle.state.needs_paint.store(true, Ordering::Release);
+            current = handle.state.parent.lock().unwrap().clone();
+        }
+    }
+
+    /// Records the boundary this one is painted inside; called by the
+    /// boundary when it paints
+    pub fn set_parent(&self, parent: Option<RepaintHandle>) {
+        *self.state.parent.lock().unwrap() = parent;
+    }
+
+    /// Returns true if the subtree has been marked, clearing the mark
+    pub fn take_needs_paint(&self) -> bool {
+        self.state.needs_paint.swap(false, Ordering::AcqRel)
+    }
+}
+
+impl Default for RepaintHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Invalidates every repaint boundary on this thread
+pub fn invalidate_all() {
+    EPOCH.with(|epoch| epoch.set(epoch.get() + 1));
+}
+
+/// Returns a counter that changes whenever `invalidate_all` is called on
+/// this thread
+pub fn epoch() -> u64 {
+    EPOCH.with(Cell::get)
+}
+
+/// Returns a new content version for a freshly recorded boundary
+pub fn next_version() -> u64 {
+    NEXT_VERSION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Returns the boundary enclosing the widgets being painted or handling an
+/// event on this thread, if any
+pub fn current_boundary() -> Option<RepaintHandle> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Runs `f` with `boundary` as the current boundary of this thread
+pub fn with_boundary<R>(boundary: Option<RepaintHandle>, f: impl FnOnce() -> R) -> R {
+    let previous = CURRENT.with(|current| current.replace(boundary));
+    let result = f();
+    CURRENT.with(|current| *current.borrow_mut() = previous);
+    result
+}
+
+/// Marks the current boundary of this thread, if any
+pub(crate) fn mark_current_boundary() {
+    if let Some(boundary) = current_boundary() {
+        boundary.mark();
+    }
+}
+
+/// Returns the nearest boundary enclosing the last of `widgets`, which lead
+/// from the root to it (see `hit_test::widgets_along`)
+pub fn enclosing_boundary(widgets: &[(&dyn Widget, Rect)]) -> Option<RepaintHandle> {
+    let (_, ancestors) = widgets.split_last()?;
+    ancestors
+        .iter()
+        .rev()
+        .find_map(|(widget, _)| widget.repaint_handle().cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handles_have_unique_ids() {
+        let a = RepaintHandle::new();
+        let b = RepaintHandle::new();
+        assert_ne!(a.id(), b.id());
+        assert_eq!(a.clone().id(), a.id());
+    }
+
+    #[test]
+    fn test_mark_is_shared_and_taken_once() {
+        let handle = RepaintHandle::new();
+        assert!(!handle.take_needs_paint());
+
+        handle.clone().mark_needs_paint();
+        assert!(handle.take_needs_paint());
+        assert!(!handle.take_needs_paint());
+    }
+
+    #[test]
+    fn test_mark_reaches_enclosing_boundaries() {
+        let outer = RepaintHandle::new();
+        let inner = RepaintHandle::new();
+        inner.set_parent(Some(outer.clone()));
+
+        inner.mark_needs_paint();
+        assert!(inner.take_needs_paint());
+        assert!(outer.take_needs_paint());
+
+        outer.mark_needs_paint();
+        assert!(!inner.take_needs_paint());
+    }
+
+    #[test]
+    fn test_paint_requests_mark_the_current_boundary() {
+        let handle = RepaintHandle::new();
+        with_boundary(Some(handle.clone()), scheduler::request_paint);
+        assert!(handle.take_needs_paint());
+        assert!(current_boundary().is_none());
+
+        scheduler::request_paint();
+        assert!(!handle.take_needs_paint());
+    }
+
+    #[test]
+    fn test_invalidate_all_changes_epoch() {
+        let before = epoch();
+        invalidate_all();
+        assert_ne!(epoch(), before);
+    }
+}
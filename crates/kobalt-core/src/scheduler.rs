@@ -0,0 +1,346 @@
+//! Frame scheduling
+//!
+//! The runtime does not redraw continuously: it sleeps until something asks
+//! for a frame. State containers (`LiveData`, `ValueNotifier`,
+//! `ChangeNotifier`) request a layout when they change, background work such
+//! as image decoding posts its result to the UI thread, and animations call
+//! `request_paint` from every frame they are still running in.
+//!
+//! A layout request only lays out the widgets whose `LayoutHandle` was
+//! marked. Changes that may affect every widget, such as a new window size,
+//! scale factor or `MediaQuery`, are requested with
+//! `request_environment_change`, which drops all cached layouts and
+//! recordings.
+//!
+//! Requests may come from any thread. The first request after a frame wakes
+//! the event loop through the waker the runtime registers. Work finished on
+//! another thread is handed back with `FrameScheduler::post` and runs on the
+//! UI thread when the next frame starts (`run_posted`). Tests can route
+//! the requests made on their thread to a scheduler of their own with
+//! `set_thread_scheduler` (with the `testing` feature).
+//!
+//! Before building a frame the runtime publishes its time stamp, which
+//! animations read with `frame_time`. Tests drive it with a fake clock.
+
+use crate::repaint;
+use std::cell::Cell;
+#[cfg(any(test, feature = "testing"))]
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+const NEEDS_PAINT: u8 = 1;
+const NEEDS_LAYOUT: u8 = 2;
+const ENVIRONMENT_CHANGED: u8 = 4;
+
+type Waker = Box<dyn Fn() + Send + Sync>;
+
+/// Work handed to the UI thread
+type Task = Box<dyn FnOnce() + Send>;
+
+/// What the next frame has to redo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameRequest {
+    /// Widget sizes may have changed
+    pub layout: bool,
+    /// The widget tree has to be painted again
+    pub paint: bool,
+    /// Every cached layout and recording is out of date
+    pub environment: bool,
+}
+
+impl FrameRequest {
+    /// Returns true if nothing was requested
+    pub fn is_empty(&self) -> bool {
+        !self.layout && !self.paint && !self.environment
+    }
+}
+
+/// Collects frame requests until the runtime takes them
+pub struct FrameScheduler {
+    dirty: AtomicU8,
+    waker: Mutex<Option<Waker>>,
+    tasks: Mutex<Vec<Task>>,
+}
+
+impl FrameScheduler {
+    /// Creates a scheduler with nothing requested
+    pub fn new() -> Self {
+        Self {
+            dirty: AtomicU8::new(0),
+            waker: Mutex::new(None),
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the scheduler used by the running app
+    ///
+    /// It starts with a layout requested so the first frame is always drawn.
+    pub fn global() -> &'static FrameScheduler {
+        global()
+    }
+
+    /// Requests a frame that repaints the widget tree
+    pub fn request_paint(&self) {
+        self.request(NEEDS_PAINT);
+    }
+
+    /// Requests a frame that lays out and repaints the widget tree
+    pub fn request_layout(&self) {
+        self.request(NEEDS_LAYOUT | NEEDS_PAINT);
+    }
+
+    /// Requests a frame that lays out and repaints the whole widget tree,
+    /// discarding every cached layout and recording
+    pub fn request_environment_change(&self) {
+        self.request(ENVIRONMENT_CHANGED | NEEDS_LAYOUT | NEEDS_PAINT);
+    }
+
+    fn request(&self, flags: u8) {
+        let previous = self.dirty.fetch_or(flags, Ordering::AcqRel);
+        if previous == 0
+            && let Some(waker) = self.waker.lock().unwrap().as_ref()
+        {
+            waker();
+        }
+    }
+
+    /// Queues `task` to run on the UI thread when the next frame starts, and
+    /// requests that frame
+    pub fn post(&self, task: impl FnOnce() + Send + 'static) {
+        self.tasks.lock().unwrap().push(Box::new(task));
+        self.request_paint();
+    }
+
+    /// Runs the posted tasks, in the order they were posted
+    pub fn run_posted(&self) {
+        let tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+        for task in tasks {
+            task();
+        }
+    }
+
+    /// Returns true if a frame has been requested and not yet taken
+    pub fn has_pending_frame(&self) -> bool {
+        self.dirty.load(Ordering::Acquire) != 0
+    }
+
+    /// Returns and clears the pending request
+    ///
+    /// Take the request before painting, so that requests made while painting
+    /// (e.g. by a running animation) schedule the following frame.
+    pub fn take_frame_request(&self) -> FrameRequest {
+        let flags = self.dirty.swap(0, Ordering::AcqRel);
+        FrameRequest {
+            layout: flags & NEEDS_LAYOUT != 0,
+            paint: flags & NEEDS_PAINT != 0,
+            environment: flags & ENVIRONMENT_CHANGED != 0,
+        }
+    }
+
+    /// Sets the callback that wakes the event loop when a frame is requested
+    pub fn set_waker<F>(&self, waker: F)
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        *self.waker.lock().unwrap() = Some(Box::new(waker));
+    }
+
+    /// Removes the waker
+    pub fn clear_waker(&self) {
+        *self.waker.lock().unwrap() = None;
+    }
+}
+
+impl Default for FrameScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn global() -> &'static Arc<FrameScheduler> {
+    static SCHEDULER: OnceLock<Arc<FrameScheduler>> = OnceLock::new();
+    SCHEDULER.get_or_init(|| {
+        let scheduler = FrameScheduler::new();
+        scheduler.dirty.store(NEEDS_LAYOUT | NEEDS_PAINT, Ordering::Relaxed);
+        Arc::new(scheduler)
+    })
+}
+
+#[cfg(any(test, feature = "testing"))]
+thread_local! {
+    /// The scheduler taking this thread's requests instead of the global one
+    static THREAD_SCHEDULER: RefCell<Option<Arc<FrameScheduler>>> = const { RefCell::new(None) };
+}
+
+/// Makes the requests made on this thread through the functions below go
+/// to `scheduler` instead of the global scheduler, or to the global one
+/// again with None, and returns the scheduler it replaces
+///
+/// `WidgetTester` uses this so that tests running in parallel do not take
+/// each other's frame requests.
+#[cfg(any(test, feature = "testing"))]
+pub fn set_thread_scheduler(scheduler: Option<Arc<FrameScheduler>>) -> Option<Arc<FrameScheduler>> {
+    THREAD_SCHEDULER.with(|current| current.replace(scheduler))
+}
+
+/// Calls `f` with the scheduler taking this thread's requests
+fn with_scheduler(f: impl FnOnce(&FrameScheduler)) {
+    #[cfg(any(test, feature = "testing"))]
+    if let Some(scheduler) = THREAD_SCHEDULER.with(|current| current.borrow().clone()) {
+        return f(&scheduler);
+    }
+    f(FrameScheduler::global())
+}
+
+/// Returns the scheduler taking this thread's requests, usually the global
+/// one, e.g. to post the result of background work back to this thread
+pub fn current() -> Arc<FrameScheduler> {
+    #[cfg(any(test, feature = "testing"))]
+    if let Some(scheduler) = THREAD_SCHEDULER.with(|current| current.borrow().clone()) {
+        return scheduler;
+    }
+    global().clone()
+}
+
+/// Runs the tasks posted to the scheduler of this thread; called by the
+/// `WidgetHost` when a frame starts
+pub fn run_posted() {
+    with_scheduler(FrameScheduler::run_posted);
+}
+
+/// Requests a repaint from the scheduler of this thread, usually the
+/// global one
+///
+/// Made while a repaint boundary's subtree is painting or handling an
+/// event, the request also marks the boundary (see
+/// `repaint::current_boundary`).
+pub fn request_paint() {
+    repaint::mark_current_boundary();
+    schedule_paint();
+}
+
+/// Requests a repaint without marking the current repaint boundary
+pub(crate) fn schedule_paint() {
+    with_scheduler(FrameScheduler::request_paint);
+}
+
+/// Requests a layout and repaint from the scheduler of this thread
+pub fn request_layout() {
+    with_scheduler(FrameScheduler::request_layout);
+}
+
+/// Requests a full layout and repaint from the scheduler of this thread
+pub fn request_environment_change() {
+    with_scheduler(FrameScheduler::request_environment_change);
+}
+
+thread_local! {
+    static FRAME_TIME: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+/// Returns the time stamp of the frame being built, measured from when the
+/// app started
+///
+/// Frames are built on the UI thread, which is the only thread this is
+/// meaningful on.
+pub fn frame_time() -> Duration {
+    FRAME_TIME.with(Cell::get)
+}
+
+/// Sets the time stamp of the frame about to be built
+pub fn set_frame_time(time: Duration) {
+    FRAME_TIME.with(|frame_time| frame_time.set(time));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn test_take_clears_request() {
+        let scheduler = FrameScheduler::new();
+        assert!(!scheduler.has_pending_frame());
+
+        scheduler.request_paint();
+        assert!(scheduler.has_pending_frame());
+        assert_eq!(
+            scheduler.take_frame_request(),
+            FrameRequest {
+                layout: false,
+                paint: true,
+                environment: false,
+            }
+        );
+        assert!(scheduler.take_frame_request().is_empty());
+    }
+
+    #[test]
+    fn test_layout_implies_paint() {
+        let scheduler = FrameScheduler::new();
+        scheduler.request_layout();
+        let request = scheduler.take_frame_request();
+        assert!(request.layout && request.paint);
+        assert!(!request.environment);
+    }
+
+    #[test]
+    fn test_environment_change_implies_layout() {
+        let scheduler = FrameScheduler::new();
+        scheduler.request_environment_change();
+        let request = scheduler.take_frame_request();
+        assert!(request.environment && request.layout && request.paint);
+    }
+
+    #[test]
+    fn test_waker_runs_once_per_frame() {
+        let scheduler = FrameScheduler::new();
+        let wakes = Arc::new(AtomicUsize::new(0));
+        let counter = wakes.clone();
+        scheduler.set_waker(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        scheduler.request_paint();
+        scheduler.request_layout();
+        assert_eq!(wakes.load(Ordering::SeqCst), 1);
+
+        scheduler.take_frame_request();
+        scheduler.request_paint();
+        assert_eq!(wakes.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_thread_scheduler_takes_requests() {
+        let scheduler = Arc::new(FrameScheduler::new());
+        let previous = set_thread_scheduler(Some(scheduler.clone()));
+        request_layout();
+        set_thread_scheduler(previous);
+
+        let request = scheduler.take_frame_request();
+        assert!(request.layout && request.paint);
+    }
+
+    #[test]
+    fn test_posted_tasks_run_on_the_owning_thread() {
+        let scheduler = Arc::new(FrameScheduler::new());
+        let previous = set_thread_scheduler(Some(scheduler.clone()));
+        let ran = Arc::new(AtomicUsize::new(0));
+
+        let owner = current();
+        let counter = ran.clone();
+        std::thread::spawn(move || owner.post(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }))
+        .join()
+        .unwrap();
+        assert!(scheduler.take_frame_request().paint);
+        assert_eq!(ran.load(Ordering::SeqCst), 0);
+
+        run_posted();
+        set_thread_scheduler(previous);
+        assert_eq!(ran.load(Ordering::SeqCst), 1);
+    }
+}
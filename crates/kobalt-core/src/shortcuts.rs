@@ -0,0 +1,381 @@
+//! Shortcuts and actions - key chords bound to intents
+//!
+//! As in Flutter, a keyboard shortcut does not call a handler directly. A
+//! `ShortcutMap` turns a key chord such as Ctrl+S into an `Intent`, a value
+//! describing what the user wants ("save"), and an `ActionMap` turns the
+//! intent into an `Action` that does it. Widgets provide their maps through
+//! `Widget::shortcuts` and `Widget::actions`.
+//!
+//! A key event bubbles from the focused widget to its ancestors. When it
+//! reaches a widget whose shortcuts bind it, the intent is offered to the
+//! actions of the focused widget and its ancestors, innermost first, and
+//! the first enabled action for it is invoked. This way the same chord
+//! does different things depending on what is focused, e.g. Escape closing
+//! the dialog the focus is in. If no action handles the intent, the event
+//! keeps bubbling.
+//!
+//! Shortcuts for desktop apps are usually written with `Modifiers::PRIMARY`
+//! ("Mod" when parsed), Command on Apple platforms and Control elsewhere,
+//! so one binding gives Cmd+Z on a Mac and Ctrl+Z on Windows and Linux.
+
+use crate::error::KobaltError;
+use crate::event::{KeyEvent, LogicalKey, Modifiers};
+use crate::widget::Widget;
+use std::any::{Any, TypeId};
+use std::fmt;
+use std::str::FromStr;
+
+/// What a shortcut asks for, independent of how it is done
+///
+/// Intents are plain types, e.g. `struct SaveIntent;`, and may carry data.
+pub trait Intent: Any + fmt::Debug {}
+
+/// Activates the focused widget, e.g. presses a button; bound to Enter and
+/// Space by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ActivateIntent;
+
+impl Intent for ActivateIntent {}
+
+/// Dismisses whatever has the focus, e.g. closes a dialog; bound to Escape
+/// by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DismissIntent;
+
+impl Intent for DismissIntent {}
+
+/// Moves the focus to the next widget; bound to Tab by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NextFocusIntent;
+
+impl Intent for NextFocusIntent {}
+
+/// Moves the focus to the previous widget; bound to Shift+Tab by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PreviousFocusIntent;
+
+impl Intent for PreviousFocusIntent {}
+
+/// A key pressed together with an exact set of modifiers, e.g. Ctrl+S
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub key: LogicalKey,
+    pub modifiers: Modifiers,
+}
+
+impl KeyChord {
+    /// Creates a chord of a key without modifiers
+    pub fn new(key: LogicalKey) -> Self {
+        Self {
+            key,
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    /// Creates a chord of a character key without modifiers
+    pub fn character(character: char) -> Self {
+        Self::new(LogicalKey::Character(character.to_lowercase().collect()))
+    }
+
+    /// Adds modifiers to the chord
+    pub fn with(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = self.modifiers.with(modifiers);
+        self
+    }
+
+    /// Adds the platform's primary modifier, Command or Control
+    pub fn primary(self) -> Self {
+        self.with(Modifiers::PRIMARY)
+    }
+
+    /// Parses a chord such as "Ctrl+Shift+Z", "Mod+S" or "Escape"
+    ///
+    /// Modifiers and key names are separated by `+` and ignore case. The
+    /// modifiers are `Shift`, `Ctrl` (`Control`), `Alt` (`Option`), `Cmd`
+    /// (`Command`, `Super`, `Meta`, `Win`) and `Mod` (`Primary`,
+    /// `CmdOrCtrl`) for the platform's primary modifier. Keys are single
+    /// characters, `Plus`, or the names of `LogicalKey`'s variants, with
+    /// `Esc`, `Del`, `Up`, `Down`, `Left` and `Right` as short forms and
+    /// `F1` to `F24` for function keys.
+    pub fn parse(text: &str) -> Result<Self, KobaltError> {
+        let invalid = |reason: &str| KobaltError::InvalidShortcut(format!("{:?}: {}", text, reason));
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let key = parts.pop().filter(|key| !key.is_empty()).ok_or_else(|| invalid("missing key"))?;
+
+        let mut modifiers = Modifiers::NONE;
+        for part in parts {
+            let modifier = match part.to_ascii_lowercase().as_str() {
+                "shift" => Modifiers::SHIFT,
+                "ctrl" | "control" => Modifiers::CONTROL,
+                "alt" | "option" => Modifiers::ALT,
+                "cmd" | "command" | "super" | "meta" | "win" => Modifiers::SUPER,
+                "mod" | "primary" | "cmdorctrl" => Modifiers::PRIMARY,
+                _ => return Err(invalid(&format!("unknown modifier {:?}", part))),
+            };
+            modifiers = modifiers.with(modifier);
+        }
+
+        let mut characters = key.chars();
+        let key = match (characters.next(), characters.next()) {
+            (Some(character), None) => return Ok(Self::character(character).with(modifiers)),
+            _ => parse_key(key).ok_or_else(|| invalid(&format!("unknown key {:?}", key)))?,
+        };
+        Ok(Self::new(key).with(modifiers))
+    }
+
+    /// Returns true if a key event presses this chord
+    ///
+    /// Releases never match, repeats do. Characters are compared ignoring
+    /// case, as holding Shift changes the character reported.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        if !event.is_pressed() || event.modifiers != self.modifiers {
+            return false;
+        }
+        match (&self.key, &event.key) {
+            (LogicalKey::Character(chord), LogicalKey::Character(pressed)) => chord.to_lowercase() == pressed.to_lowercase(),
+            (chord, pressed) => chord == pressed,
+        }
+    }
+}
+
+fn parse_key(name: &str) -> Option<LogicalKey> {
+    let key = match name.to_ascii_lowercase().as_str() {
+        "plus" => LogicalKey::Character("+".to_string()),
+        "enter" | "return" => LogicalKey::Enter,
+        "space" => LogicalKey::Space,
+        "tab" => LogicalKey::Tab,
+        "escape" | "esc" => LogicalKey::Escape,
+        "backspace" => LogicalKey::Backspace,
+        "delete" | "del" => LogicalKey::Delete,
+        "insert" => LogicalKey::Insert,
+        "arrowup" | "up" => LogicalKey::ArrowUp,
+        "arrowdown" | "down" => LogicalKey::ArrowDown,
+        "arrowleft" | "left" => LogicalKey::ArrowLeft,
+        "arrowright" | "right" => LogicalKey::ArrowRight,
+        "home" => LogicalKey::Home,
+        "end" => LogicalKey::End,
+        "pageup" => LogicalKey::PageUp,
+        "pagedown" => LogicalKey::PageDown,
+        function => {
+            let number: u8 = function.strip_prefix('f')?.parse().ok()?;
+            return (1..=24).contains(&number).then_some(LogicalKey::Function(number));
+        }
+    };
+    Some(key)
+}
+
+impl From<LogicalKey> for KeyChord {
+    fn from(key: LogicalKey) -> Self {
+        Self::new(key)
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = KobaltError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::parse(text)
+    }
+}
+
+/// Key chords bound to intents
+#[derive(Default)]
+pub struct ShortcutMap {
+    bindings: Vec<(KeyChord, Box<dyn Intent>)>,
+}
+
+impl ShortcutMap {
+    /// Creates an empty map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the bindings every app has, used when no widget's shortcuts
+    /// handle a key: Tab and Shift+Tab move the focus, Enter and Space
+    /// activate, Escape dismisses
+    pub fn defaults() -> Self {
+        Self::new()
+            .bind(LogicalKey::Tab, NextFocusIntent)
+            .bind(KeyChord::new(LogicalKey::Tab).with(Modifiers::SHIFT), PreviousFocusIntent)
+            .bind(LogicalKey::Enter, ActivateIntent)
+            .bind(LogicalKey::Space, ActivateIntent)
+            .bind(LogicalKey::Escape, DismissIntent)
+    }
+
+    /// Binds a chord to an intent; a later binding of the same chord
+    /// replaces an earlier one
+    pub fn bind(mut self, chord: impl Into<KeyChord>, intent: impl Intent) -> Self {
+        let chord = chord.into();
+        self.bindings.retain(|(bound, _)| *bound != chord);
+        self.bindings.push((chord, Box::new(intent)));
+        self
+    }
+
+    /// Returns the intent a key event asks for, if any
+    pub fn lookup(&self, event: &KeyEvent) -> Option<&dyn Intent> {
+        self.bindings
+            .iter()
+            .find(|(chord, _)| chord.matches(event))
+            .map(|(_, intent)| &**intent)
+    }
+
+    /// Returns the bound chords and their intents
+    pub fn bindings(&self) -> impl Iterator<Item = (&KeyChord, &dyn Intent)> {
+        self.bindings.iter().map(|(chord, intent)| (chord, &**intent))
+    }
+}
+
+/// Called with the intent an action was invoked for
+type InvokeCallback = Box<dyn Fn(&dyn Any)>;
+
+/// Decides whether an action can currently be invoked
+type EnabledCallback = Box<dyn Fn() -> bool>;
+
+/// Does what one type of intent asks for
+pub struct Action {
+    intent: TypeId,
+    invoke: InvokeCallback,
+    enabled: Option<EnabledCallback>,
+}
+
+impl Action {
+    /// Creates an action for intents of type `T`
+    pub fn new<T: Intent>(on_invoke: impl Fn(&T) + 'static) -> Self {
+        Self {
+            intent: TypeId::of::<T>(),
+            invoke: Box::new(move |intent| {
+                if let Some(intent) = intent.downcast_ref::<T>() {
+                    on_invoke(intent);
+                }
+            }),
+            enabled: None,
+        }
+    }
+
+    /// Makes the action available only while `enabled` returns true; a
+    /// disabled action lets the intent reach actions further out
+    pub fn enabled_when(mut self, enabled: impl Fn() -> bool + 'static) -> Self {
+        self.enabled = Some(Box::new(enabled));
+        self
+    }
+
+    /// Returns true if the action can currently be invoked
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.as_ref().is_none_or(|enabled| enabled())
+    }
+
+    /// Returns true if the action is for intents of this intent's type
+    pub fn handles(&self, intent: &dyn Intent) -> bool {
+        let intent: &dyn Any = intent;
+        intent.type_id() == self.intent
+    }
+
+    /// Does what the intent asks for
+    pub fn invoke(&self, intent: &dyn Intent) {
+        (self.invoke)(intent);
+    }
+}
+
+/// Actions for different types of intents
+#[derive(Default)]
+pub struct ActionMap {
+    actions: Vec<Action>,
+}
+
+impl ActionMap {
+    /// Creates an empty map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an action; it replaces an earlier action for the same intent
+    pub fn action(mut self, action: Action) -> Self {
+        self.actions.retain(|existing| existing.intent != action.intent);
+        self.actions.push(action);
+        self
+    }
+
+    /// Adds an action for intents of type `T`
+    pub fn on<T: Intent>(self, on_invoke: impl Fn(&T) + 'static) -> Self {
+        self.action(Action::new(on_invoke))
+    }
+
+    /// Returns the enabled action for an intent, if any
+    pub fn find(&self, intent: &dyn Intent) -> Option<&Action> {
+        self.actions
+            .iter()
+            .find(|action| action.handles(intent) && action.is_enabled())
+    }
+
+    /// Invokes the enabled action for an intent; returns false if there
+    /// is none
+    pub fn invoke(&self, intent: &dyn Intent) -> bool {
+        self.find(intent).map(|action| action.invoke(intent)).is_some()
+    }
+}
+
+/// Invokes the first enabled action for an intent among the actions of
+/// `widgets`, given innermost first; returns false if none handles it
+pub fn invoke<'a>(widgets: impl IntoIterator<Item = &'a dyn Widget>, intent: &dyn Intent) -> bool {
+    widgets
+        .into_iter()
+        .filter_map(|widget| widget.actions())
+        .any(|actions| actions.invoke(intent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Debug)]
+    struct SaveIntent;
+
+    impl Intent for SaveIntent {}
+
+    #[test]
+    fn test_parse() {
+        let chord = KeyChord::parse("Ctrl + Shift + Z").unwrap();
+        assert_eq!(chord, KeyChord::character('z').with(Modifiers::CONTROL.with(Modifiers::SHIFT)));
+        assert_eq!(KeyChord::parse("mod+s").unwrap(), KeyChord::character('s').primary());
+        assert_eq!("Cmd+Plus".parse::<KeyChord>().unwrap().key, LogicalKey::Character("+".to_string()));
+        assert_eq!(KeyChord::parse("F5").unwrap().key, LogicalKey::Function(5));
+        assert_eq!(KeyChord::parse("Esc").unwrap(), KeyChord::new(LogicalKey::Escape));
+        assert!(KeyChord::parse("Hyper+S").is_err());
+        assert!(KeyChord::parse("Ctrl+").is_err());
+        assert!(KeyChord::parse("F25").is_err());
+    }
+
+    #[test]
+    fn test_matches_exact_modifiers_ignoring_case() {
+        let chord = KeyChord::character('z').with(Modifiers::CONTROL.with(Modifiers::SHIFT));
+        let pressed = |modifiers| KeyEvent::pressed(LogicalKey::Character("Z".to_string())).modifiers(modifiers);
+        assert!(chord.matches(&pressed(Modifiers::CONTROL.with(Modifiers::SHIFT))));
+        assert!(!chord.matches(&pressed(Modifiers::CONTROL)));
+        assert!(!chord.matches(&KeyEvent::released(LogicalKey::Character("z".to_string())).modifiers(chord.modifiers)));
+    }
+
+    #[test]
+    fn test_lookup_prefers_later_binding() {
+        let map = ShortcutMap::defaults().bind(LogicalKey::Escape, SaveIntent);
+        let intent = map.lookup(&KeyEvent::pressed(LogicalKey::Escape)).unwrap();
+        assert!((intent as &dyn Any).is::<SaveIntent>());
+        assert_eq!(map.bindings().count(), 5);
+    }
+
+    #[test]
+    fn test_actions_invoke_enabled_action_for_intent_type() {
+        let saves = Rc::new(Cell::new(0));
+        let enabled = Rc::new(Cell::new(false));
+        let (count, flag) = (saves.clone(), enabled.clone());
+        let actions = ActionMap::new()
+            .action(Action::new(move |_: &SaveIntent| count.set(count.get() + 1)).enabled_when(move || flag.get()));
+
+        assert!(!actions.invoke(&SaveIntent));
+        enabled.set(true);
+        assert!(actions.invoke(&SaveIntent));
+        assert!(!actions.invoke(&DismissIntent));
+        assert_eq!(saves.get(), 1);
+    }
+}
@@ -69,12 +69,13 @@ impl ChangeNotifier {
         listeners.push(Box::new(listener));
     }
 
-    /// Notifies all listeners that a change has occurred
+    /// Notifies all listeners that a change has occurred and schedules a frame
     pub fn notify(&self) {
         let listeners = self.listeners.lock().unwrap();
         for listener in listeners.iter() {
             listener();
         }
+        crate::scheduler::request_layout();
     }
 
     /// Removes all listeners
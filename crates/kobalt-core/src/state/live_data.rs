@@ -99,7 +99,7 @@ where
         listeners.len() - 1
     }
 
-    /// Notifies all observers with the current value
+    /// Notifies all observers with the current value and schedules a frame
     fn notify(&self) {
         let value = self.value.lock().unwrap();
         let listeners = self.listeners.lock().unwrap();
@@ -107,6 +107,7 @@ where
         for listener in listeners.iter() {
             listener(&*value);
         }
+        crate::scheduler::request_layout();
     }
 
     /// Removes all observers
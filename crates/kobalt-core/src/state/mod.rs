@@ -11,3 +11,4 @@ mod change_notifier;
 
 pub use live_data::LiveData;
 pub use value_notifier::ValueNotifier;
+pub use change_notifier::ChangeNotifier;
@@ -92,7 +92,7 @@ where
         listeners.push(Box::new(listener));
     }
 
-    /// Notifies all listeners with the current value
+    /// Notifies all listeners with the current value and schedules a frame
     fn notify_listeners(&self) {
         let value = self.value.lock().unwrap();
         let listeners = self.listeners.lock().unwrap();
@@ -100,6 +100,7 @@ where
         for listener in listeners.iter() {
             listener(&*value);
         }
+        crate::scheduler::request_layout();
     }
 
     /// Removes all listeners
@@ -139,6 +140,7 @@ where
 mod tests {
     use super::*;
     use std::cell::RefCell;
+    use std::rc::Rc;
 
     #[test]
     fn test_value_notifier_basic() {
@@ -152,7 +154,7 @@ mod tests {
     #[test]
     fn test_value_notifier_listener() {
         let notifier = ValueNotifier::new(0);
-        let captured = Arc::new(RefCell::new(0));
+        let captured = Rc::new(RefCell::new(0));
 
         let c = captured.clone();
         notifier.add_listener(move |value| {
@@ -169,8 +171,8 @@ mod tests {
     #[test]
     fn test_value_notifier_multiple_listeners() {
         let notifier = ValueNotifier::new(0);
-        let captured1 = Arc::new(RefCell::new(0));
-        let captured2 = Arc::new(RefCell::new(0));
+        let captured1 = Rc::new(RefCell::new(0));
+        let captured2 = Rc::new(RefCell::new(0));
 
         let c1 = captured1.clone();
         notifier.add_listener(move |value| {
@@ -201,7 +203,7 @@ mod tests {
     #[test]
     fn test_value_notifier_clear_listeners() {
         let notifier = ValueNotifier::new(0);
-        let captured = Arc::new(RefCell::new(0));
+        let captured = Rc::new(RefCell::new(0));
 
         let c = captured.clone();
         notifier.add_listener(move |value| {
@@ -244,7 +246,7 @@ mod tests {
     #[test]
     fn test_value_notifier_string() {
         let notifier = ValueNotifier::new(String::from("Hello"));
-        let captured = Arc::new(RefCell::new(String::new()));
+        let captured = Rc::new(RefCell::new(String::new()));
 
         let c = captured.clone();
         notifier.add_listener(move |value| {
@@ -0,0 +1,638 @@
+//! Headless widget tests
+//!
+//! `WidgetTester` mounts a widget tree without a window, similar to
+//! Flutter's `WidgetTester`. Frames are built with `pump` on a fake clock,
+//! widgets are found by text, type, key or semantics label with `Finder`s,
+//! and taps, long presses, drags, hovers and key presses are dispatched at
+//! the bounds the widgets were laid out in, or, for keys, to the focused
+//! widget. Frames and events go through a `WidgetHost`, the same way as in
+//! a running app.
+//!
+//! This module is only available in the crate's own tests and with the
+//! `testing` feature, which other crates enable in their
+//! dev-dependencies.
+
+use crate::event::{Event, EventResult, KeyEvent, LogicalKey, Modifiers, PointerEvent, PointerPhase};
+use crate::focus::{self, FocusTree};
+use crate::gesture::{self, DEFAULT_LONG_PRESS_DELAY};
+use crate::hit_test::HitTestResult;
+use crate::host::WidgetHost;
+use crate::media_query::{MediaQuery, MediaQueryData};
+use crate::mouse::MouseCursor;
+use crate::paint::DisplayList;
+use crate::scheduler::{self, FrameScheduler};
+use crate::types::{Point, Rect, Size};
+use crate::widget::{self, Key, Widget};
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Time between frames pumped by `pump_and_settle`
+pub const FRAME_INTERVAL: Duration = Duration::from_micros(16_667);
+
+/// Longest `pump_and_settle` waits for the tree to stop requesting frames
+pub const SETTLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Number of moves a drag is split into
+const DRAG_STEPS: u32 = 5;
+
+/// Pointer id of the mouse moved by `hover`; other pointers start after it
+const HOVER_POINTER_ID: u64 = 0;
+
+/// Selects widgets in the tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finder {
+    criterion: Criterion,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Criterion {
+    Text(Cow<'static, str>),
+    WidgetType(&'static str),
+    Key(Key),
+    SemanticsLabel(Cow<'static, str>),
+}
+
+impl Finder {
+    /// Finds widgets displaying exactly this text
+    pub fn text(text: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            criterion: Criterion::Text(text.into()),
+        }
+    }
+
+    /// Finds widgets whose `widget_type` is `widget_type`
+    pub fn widget_type(widget_type: &'static str) -> Self {
+        Self {
+            criterion: Criterion::WidgetType(widget_type),
+        }
+    }
+
+    /// Finds widgets with this key
+    pub fn key(key: impl Into<Key>) -> Self {
+        Self {
+            criterion: Criterion::Key(key.into()),
+        }
+    }
+
+    /// Finds widgets with exactly this semantics label
+    pub fn semantics_label(label: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            criterion: Criterion::SemanticsLabel(label.into()),
+        }
+    }
+
+    /// Returns true if the widget is selected by this finder
+    pub fn matches(&self, widget: &dyn Widget) -> bool {
+        match &self.criterion {
+            Criterion::Text(text) => widget.text() == Some(text),
+            Criterion::WidgetType(widget_type) => widget.widget_type() == *widget_type,
+            Criterion::Key(key) => widget.key() == Some(key),
+            Criterion::SemanticsLabel(label) => widget.semantics_label() == Some(label),
+        }
+    }
+}
+
+/// A widget found in the tree, with the bounds it was laid out in
+#[derive(Clone, Copy)]
+pub struct FoundWidget<'a> {
+    /// The widget
+    pub widget: &'a dyn Widget,
+    /// The bounds it was painted in during the last frame
+    pub bounds: Rect,
+}
+
+/// Drives a widget tree in tests without a window
+///
+/// Call `pump` to lay out and paint the first frame before finding widgets
+/// or sending input; after input that changes state, `pump` again to see
+/// the result. Like the runtime, `pump` only paints again if a frame was
+/// requested.
+///
+/// The tester has its own frame scheduler and `MediaQuery` environment.
+/// Until it is dropped, they replace the global ones on the thread that
+/// created it, so tests running in parallel neither take each other's
+/// frame requests nor see each other's sizes.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_core::event::{Event, EventResult, PointerPhase};
+/// use kobalt_core::testing::{Finder, WidgetTester};
+/// use kobalt_core::types::{Rect, Size};
+/// use kobalt_core::widget::Widget;
+/// use std::cell::Cell;
+///
+/// struct Counter(Cell<u32>);
+///
+/// impl Widget for Counter {
+///     fn widget_type(&self) -> &'static str {
+///         "Counter"
+///     }
+///
+///     fn layout(&self, constraints: Size) -> Size {
+///         constraints
+///     }
+///
+///     fn handle_event(&self, event: &Event, _bounds: Rect) -> EventResult {
+///         match event {
+///             Event::Pointer(pointer) if pointer.phase == PointerPhase::Up => {
+///                 self.0.set(self.0.get() + 1);
+///                 EventResult::Handled
+///             }
+///             _ => EventResult::Ignored,
+///         }
+///     }
+/// }
+///
+/// let mut tester = WidgetTester::new(Counter(Cell::new(0))).size(Size::new(100.0, 100.0));
+/// tester.pump();
+/// tester.tap(&Finder::widget_type("Counter"));
+/// tester.pump();
+/// ```
+pub struct WidgetTester {
+    host: WidgetHost,
+    size: Size,
+    device_pixel_ratio: f32,
+    elapsed: Duration,
+    next_pointer: u64,
+    scheduler: Arc<FrameScheduler>,
+    /// The scheduler and environment the thread used before the tester
+    previous_scheduler: Option<Arc<FrameScheduler>>,
+    previous_environment: Option<MediaQueryData>,
+}
+
+impl WidgetTester {
+    /// Mounts a widget tree in an 800x600 surface
+    pub fn new<W: Widget + 'static>(root: W) -> Self {
+        let size = Size::new(800.0, 600.0);
+        let scheduler = Arc::new(FrameScheduler::new());
+        Self {
+            host: WidgetHost::new(Box::new(root)),
+            size,
+            device_pixel_ratio: 1.0,
+            elapsed: Duration::ZERO,
+            next_pointer: 1,
+            previous_scheduler: scheduler::set_thread_scheduler(Some(scheduler.clone())),
+            previous_environment: MediaQuery::set_thread_local(Some(MediaQueryData::new(size, 1.0))),
+            scheduler,
+        }
+    }
+
+    /// Sets the surface size in logical pixels
+    pub fn size(mut self, size: Size) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the device pixel ratio published to `MediaQuery`
+    pub fn device_pixel_ratio(mut self, device_pixel_ratio: f32) -> Self {
+        self.device_pixel_ratio = device_pixel_ratio;
+        self
+    }
+
+    /// Returns the host driving the tree
+    pub fn host(&self) -> &WidgetHost {
+        &self.host
+    }
+
+    /// Returns the scheduler taking the frame requests of the tree
+    pub fn scheduler(&self) -> &FrameScheduler {
+        &self.scheduler
+    }
+
+    /// Returns the root widget
+    pub fn root(&self) -> &dyn Widget {
+        self.host.root()
+    }
+
+    /// Returns the fake time of the last frame
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the display list of the last frame
+    pub fn display_list(&self) -> Option<&DisplayList> {
+        self.host.display_list()
+    }
+
+    /// Builds a frame at the current fake time, laying out and painting
+    /// what was requested since the last one
+    pub fn pump(&mut self) {
+        scheduler::set_frame_time(self.elapsed);
+        MediaQuery::set_window(self.size, self.device_pixel_ratio);
+        let request = self.scheduler.take_frame_request();
+        self.host.frame(self.size, request);
+    }
+
+    /// Advances the fake clock by `duration` and pumps a frame
+    pub fn pump_by(&mut self, duration: Duration) {
+        self.elapsed += duration;
+        self.pump();
+    }
+
+    /// Pumps frames `FRAME_INTERVAL` apart until no more are requested and
+    /// no gesture waits for a deadline, e.g. until animations have
+    /// finished, and returns how many were pumped
+    ///
+    /// # Panics
+    ///
+    /// Panics if frames are still requested after `SETTLE_TIMEOUT`.
+    pub fn pump_and_settle(&mut self) -> u32 {
+        let start = self.elapsed;
+        let mut frames = 0;
+        loop {
+            self.pump_by(FRAME_INTERVAL);
+            frames += 1;
+            if !self.scheduler.has_pending_frame() && gesture::next_deadline().is_none() {
+                return frames;
+            }
+            if self.elapsed - start >= SETTLE_TIMEOUT {
+                panic!("pump_and_settle timed out after {} frames", frames);
+            }
+        }
+    }
+
+    /// Returns every widget selected by the finder, in paint order
+    pub fn find_all(&self, finder: &Finder) -> Vec<FoundWidget<'_>> {
+        let mut found = Vec::new();
+        widget::visit_tree(self.host.root(), self.host.root_bounds(), &mut |widget, bounds| {
+            if finder.matches(widget) {
+                found.push(FoundWidget { widget, bounds });
+            }
+        });
+        found
+    }
+
+    /// Returns the only widget selected by the finder
+    ///
+    /// # Panics
+    ///
+    /// Panics if the finder selects no widget or more than one.
+    pub fn find(&self, finder: &Finder) -> FoundWidget<'_> {
+        let found = self.find_all(finder);
+        match found.as_slice() {
+            [widget] => *widget,
+            _ => panic!("expected exactly one widget for {:?}, found {}", finder, found.len()),
+        }
+    }
+
+    /// Returns how many widgets the finder selects
+    pub fn count(&self, finder: &Finder) -> usize {
+        self.find_all(finder).len()
+    }
+
+    /// Returns true if the finder selects at least one widget
+    pub fn exists(&self, finder: &Finder) -> bool {
+        self.count(finder) > 0
+    }
+
+    /// Returns the widget with the primary focus
+    pub fn focused(&self) -> Option<FoundWidget<'_>> {
+        let node = focus::primary_focus()?;
+        let tree = FocusTree::build(self.host.root(), self.host.root_bounds());
+        let entry = &tree.entries()[tree.find(&node)?];
+        Some(FoundWidget {
+            widget: entry.widget,
+            bounds: entry.bounds,
+        })
+    }
+
+    /// Returns the bounds of the only widget selected by the finder
+    pub fn bounds(&self, finder: &Finder) -> Rect {
+        self.find(finder).bounds
+    }
+
+    /// Returns the widgets under a point in the last frame
+    pub fn hit_test(&self, position: Point) -> HitTestResult {
+        self.host.hit_test(position)
+    }
+
+    /// Delivers an event to the tree at the current fake time
+    pub fn send_event(&mut self, event: &Event) -> EventResult {
+        scheduler::set_frame_time(self.elapsed);
+        self.host.dispatch_event(event)
+    }
+
+    /// Taps the center of the only widget selected by the finder
+    pub fn tap(&mut self, finder: &Finder) -> EventResult {
+        let center = self.bounds(finder).center();
+        self.tap_at(center)
+    }
+
+    /// Presses and releases a pointer at a position
+    pub fn tap_at(&mut self, position: Point) -> EventResult {
+        let id = self.next_pointer_id();
+        let down = self.send_pointer(id, PointerPhase::Down, position);
+        let up = self.send_pointer(id, PointerPhase::Up, position);
+        combine(down, up)
+    }
+
+    /// Long-presses the center of the only widget selected by the finder
+    pub fn long_press(&mut self, finder: &Finder) -> EventResult {
+        let center = self.bounds(finder).center();
+        self.long_press_at(center)
+    }
+
+    /// Presses a pointer at a position, pumps frames for the default
+    /// long-press delay and releases it
+    pub fn long_press_at(&mut self, position: Point) -> EventResult {
+        let id = self.next_pointer_id();
+        let down = self.send_pointer(id, PointerPhase::Down, position);
+        self.pump_by(DEFAULT_LONG_PRESS_DELAY);
+        let up = self.send_pointer(id, PointerPhase::Up, position);
+        combine(down, up)
+    }
+
+    /// Drags from the center of the only widget selected by the finder by
+    /// `offset`
+    pub fn drag(&mut self, finder: &Finder, offset: Point) -> EventResult {
+        let center = self.bounds(finder).center();
+        self.drag_from(center, offset)
+    }
+
+    /// Presses a pointer at `start`, moves it by `offset` in a few steps and
+    /// releases it
+    pub fn drag_from(&mut self, start: Point, offset: Point) -> EventResult {
+        let id = self.next_pointer_id();
+        let mut result = self.send_pointer(id, PointerPhase::Down, start);
+        for step in 1..=DRAG_STEPS {
+            let position = start + offset.scale(step as f32 / DRAG_STEPS as f32);
+            result = combine(result, self.send_pointer(id, PointerPhase::Move, position));
+        }
+        combine(result, self.send_pointer(id, PointerPhase::Up, start + offset))
+    }
+
+    /// Moves the mouse over the center of the only widget selected by the
+    /// finder, without pressing a button
+    pub fn hover(&mut self, finder: &Finder) -> EventResult {
+        let center = self.bounds(finder).center();
+        self.hover_at(center)
+    }
+
+    /// Moves the mouse to a position without pressing a button
+    pub fn hover_at(&mut self, position: Point) -> EventResult {
+        self.send_pointer(HOVER_POINTER_ID, PointerPhase::Hover, position)
+    }
+
+    /// Moves the mouse out of the window
+    pub fn mouse_exit(&mut self) -> EventResult {
+        // Just outside the top left corner
+        self.send_pointer(HOVER_POINTER_ID, PointerPhase::Exit, Point::new(-1.0, -1.0))
+    }
+
+    /// Returns the cursor the widgets under the mouse ask for
+    pub fn cursor(&self) -> MouseCursor {
+        self.host.cursor()
+    }
+
+    /// Presses and releases a key
+    pub fn press_key(&mut self, key: LogicalKey) -> EventResult {
+        self.press_key_with_modifiers(key, Modifiers::NONE)
+    }
+
+    /// Presses and releases a key while holding modifiers, e.g.
+    /// Shift+Tab
+    pub fn press_key_with_modifiers(&mut self, key: LogicalKey, modifiers: Modifiers) -> EventResult {
+        let pressed = self.send_event(&Event::Key(KeyEvent::pressed(key.clone()).modifiers(modifiers)));
+        let released = self.send_event(&Event::Key(KeyEvent::released(key).modifiers(modifiers)));
+        combine(pressed, released)
+    }
+
+    fn next_pointer_id(&mut self) -> u64 {
+        let id = self.next_pointer;
+        self.next_pointer += 1;
+        id
+    }
+
+    fn send_pointer(&mut self, id: u64, phase: PointerPhase, position: Point) -> EventResult {
+        self.send_event(&Event::Pointer(PointerEvent::new(id, phase, position)))
+    }
+}
+
+impl Drop for WidgetTester {
+    fn drop(&mut self) {
+        scheduler::set_thread_scheduler(self.previous_scheduler.take());
+        MediaQuery::set_thread_local(self.previous_environment.take());
+    }
+}
+
+/// Returns `Handled` if either event was handled
+fn combine(first: EventResult, second: EventResult) -> EventResult {
+    if first.is_handled() || second.is_handled() {
+        EventResult::Handled
+    } else {
+        EventResult::Ignored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::KeyState;
+    use crate::focus::FocusNode;
+    use crate::paint::PaintContext;
+    use crate::types::Color;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    /// What a pad received
+    #[derive(Default)]
+    struct Received {
+        taps: Cell<u32>,
+        moves: RefCell<Vec<Point>>,
+        typed: RefCell<String>,
+    }
+
+    /// Counts taps, records drag positions and typed characters
+    struct Pad {
+        label: &'static str,
+        received: Rc<Received>,
+        node: FocusNode,
+    }
+
+    impl Widget for Pad {
+        fn widget_type(&self) -> &'static str {
+            "Pad"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+            let label = format!("{} {}", self.label, self.received.taps.get());
+            ctx.draw_text(&label, bounds.origin(), 12.0, Color::BLACK);
+        }
+
+        fn handle_event(&self, event: &Event, _bounds: Rect) -> EventResult {
+            match event {
+                Event::Pointer(pointer) => match pointer.phase {
+                    PointerPhase::Up => {
+                        self.received.taps.set(self.received.taps.get() + 1);
+                        scheduler::request_paint();
+                    }
+                    PointerPhase::Move => self.received.moves.borrow_mut().push(pointer.position),
+                    _ => {}
+                },
+                Event::Key(key) => match &key.key {
+                    LogicalKey::Character(text) if key.state == KeyState::Pressed => {
+                        self.received.typed.borrow_mut().push_str(text)
+                    }
+                    _ => return EventResult::Ignored,
+                },
+            }
+            EventResult::Handled
+        }
+
+        fn text(&self) -> Option<&str> {
+            Some(self.label)
+        }
+
+        fn focus_node(&self) -> Option<&FocusNode> {
+            Some(&self.node)
+        }
+    }
+
+    /// Two pads on top of each other
+    struct Split(Pad, Pad);
+
+    impl Widget for Split {
+        fn widget_type(&self) -> &'static str {
+            "Split"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+            self.visit_children(bounds, &mut |child, bounds| child.paint(ctx, bounds));
+        }
+
+        fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+            let half = bounds.height / 2.0;
+            visitor(&self.0, Rect::new(bounds.x, bounds.y, bounds.width, half));
+            visitor(&self.1, Rect::new(bounds.x, bounds.y + half, bounds.width, half));
+        }
+
+        fn semantics_label(&self) -> Option<&str> {
+            Some("split")
+        }
+    }
+
+    /// Returns a tester showing a split and what its top and bottom pads
+    /// receive
+    fn split() -> (WidgetTester, Rc<Received>, Rc<Received>) {
+        let top = Rc::new(Received::default());
+        let bottom = Rc::new(Received::default());
+        let root = Split(
+            Pad {
+                label: "top",
+                received: top.clone(),
+                node: FocusNode::new(),
+            },
+            Pad {
+                label: "bottom",
+                received: bottom.clone(),
+                node: FocusNode::new(),
+            },
+        );
+        let mut tester = WidgetTester::new(root).size(Size::new(100.0, 200.0));
+        tester.pump();
+        (tester, top, bottom)
+    }
+
+    #[test]
+    fn test_finders() {
+        let (tester, _, _) = split();
+        assert_eq!(tester.bounds(&Finder::text("bottom")), Rect::new(0.0, 100.0, 100.0, 100.0));
+        assert_eq!(tester.count(&Finder::widget_type("Pad")), 2);
+        assert!(tester.exists(&Finder::semantics_label("split")));
+        assert!(!tester.exists(&Finder::text("missing")));
+        assert!(!tester.exists(&Finder::key("missing")));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected exactly one widget")]
+    fn test_find_requires_one_widget() {
+        split().0.find(&Finder::widget_type("Pad"));
+    }
+
+    #[test]
+    fn test_tap_hits_laid_out_bounds() {
+        let (mut tester, top, bottom) = split();
+        assert_eq!(tester.tap(&Finder::text("bottom")), EventResult::Handled);
+        assert_eq!(bottom.taps.get(), 1);
+        assert_eq!(top.taps.get(), 0);
+
+        tester.pump();
+        let commands = format!("{:?}", tester.display_list().unwrap().commands());
+        assert!(commands.contains("bottom 1"), "{}", commands);
+    }
+
+    #[test]
+    fn test_drag_moves_in_steps() {
+        let (mut tester, top, _) = split();
+        tester.drag(&Finder::text("top"), Point::new(50.0, 0.0));
+        let moves = top.moves.borrow();
+        assert_eq!(moves.len(), DRAG_STEPS as usize);
+        assert_eq!(moves.last(), Some(&Point::new(100.0, 50.0)));
+    }
+
+    #[test]
+    fn test_press_key() {
+        let (mut tester, top, bottom) = split();
+        // Nothing is focused, so only the root gets it
+        assert_eq!(tester.press_key(LogicalKey::Character("x".to_string())), EventResult::Ignored);
+
+        tester.press_key_with_modifiers(LogicalKey::Tab, Modifiers::SHIFT);
+        assert_eq!(tester.focused().and_then(|found| found.widget.text()), Some("bottom"));
+        tester.press_key(LogicalKey::Character("x".to_string()));
+        assert_eq!(*bottom.typed.borrow(), "x");
+        assert_eq!(*top.typed.borrow(), "");
+        assert_eq!(tester.press_key(LogicalKey::Escape), EventResult::Ignored);
+    }
+
+    #[test]
+    fn test_pump_advances_fake_clock() {
+        let (mut tester, _, _) = split();
+        tester.pump_by(Duration::from_millis(500));
+        assert_eq!(tester.elapsed(), Duration::from_millis(500));
+        assert_eq!(scheduler::frame_time(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_pump_paints_only_requested_frames() {
+        let (mut tester, _, _) = split();
+        let painted = tester.host().paint_count();
+        tester.pump();
+        assert_eq!(tester.host().paint_count(), painted);
+
+        tester.scheduler().request_paint();
+        tester.pump();
+        assert_eq!(tester.host().paint_count(), painted + 1);
+    }
+
+    #[test]
+    fn test_environment_is_per_tester() {
+        let (tester, _, _) = split();
+        assert_eq!(MediaQuery::of().size, Size::new(100.0, 200.0));
+        assert!(tester.scheduler().take_frame_request().is_empty());
+
+        let pad = Pad {
+            label: "pad",
+            received: Rc::default(),
+            node: FocusNode::new(),
+        };
+        let mut other = WidgetTester::new(pad).size(Size::new(50.0, 50.0));
+        other.pump();
+        assert_eq!(MediaQuery::of().size, Size::new(50.0, 50.0));
+        other.tap(&Finder::text("pad"));
+        assert!(!tester.scheduler().has_pending_frame());
+        assert!(other.scheduler().has_pending_frame());
+
+        drop(other);
+        assert_eq!(MediaQuery::of().size, Size::new(100.0, 200.0));
+    }
+}
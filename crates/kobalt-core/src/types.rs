@@ -5,11 +5,14 @@
 //! - `Rect`: Represents a rectangle with position and size
 //! - `Color`: Represents RGBA color values
 //! - `Point`: Represents a 2D point
+//! - `Transform`: Represents a 2D affine transformation
+//! - `Length`: A distance in density-independent, scalable or physical units
 
+use crate::media_query::{MediaQuery, MediaQueryData};
 use std::ops::{Add, Sub, Mul, Div};
 
 /// Represents a 2D point with x and y coordinates
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct Point {
     pub x: f32,
     pub y: f32,
@@ -32,6 +35,14 @@ impl Point {
         let dy = self.y - other.y;
         (dx * dx + dy * dy).sqrt()
     }
+
+    /// Scales both coordinates by a factor
+    pub fn scale(&self, factor: f32) -> Self {
+        Self {
+            x: self.x * factor,
+            y: self.y * factor,
+        }
+    }
 }
 
 impl Add for Point {
@@ -232,6 +243,17 @@ impl Rect {
         self.width <= 0.0 || self.height <= 0.0
     }
 
+    /// Scales position and size by a factor (e.g. from logical to physical
+    /// pixels)
+    pub fn scale(&self, factor: f32) -> Self {
+        Self {
+            x: self.x * factor,
+            y: self.y * factor,
+            width: self.width * factor,
+            height: self.height * factor,
+        }
+    }
+
     /// Insets this rectangle by the given amount on all sides
     pub fn inset(&self, amount: f32) -> Self {
         Self {
@@ -243,6 +265,141 @@ impl Rect {
     }
 }
 
+/// Represents a 2D affine transformation
+///
+/// Maps a point `(x, y)` to `(a * x + c * y + e, b * x + d * y + f)`,
+/// matching the layout of a CSS/Canvas `matrix(a, b, c, d, e, f)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Transform {
+    /// Creates the identity transform
+    pub const fn identity() -> Self {
+        Self {
+            a: 1.0,
+            b: 0.0,
+            c: 0.0,
+            d: 1.0,
+            e: 0.0,
+            f: 0.0,
+        }
+    }
+
+    /// Creates a transform from its six matrix components
+    pub const fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    /// Creates a translation
+    pub const fn translate(dx: f32, dy: f32) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, dx, dy)
+    }
+
+    /// Creates a scale around the origin
+    pub const fn scale(sx: f32, sy: f32) -> Self {
+        Self::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    /// Creates a clockwise rotation (in radians, y pointing down) around the origin
+    pub fn rotate(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// Returns the transform that applies `self` first and then `other`
+    pub fn then(&self, other: &Transform) -> Self {
+        Self {
+            a: other.a * self.a + other.c * self.b,
+            b: other.b * self.a + other.d * self.b,
+            c: other.a * self.c + other.c * self.d,
+            d: other.b * self.c + other.d * self.d,
+            e: other.a * self.e + other.c * self.f + other.e,
+            f: other.b * self.e + other.d * self.f + other.f,
+        }
+    }
+
+    /// Returns the transform that applies `other` first and then `self`
+    pub fn pre(&self, other: &Transform) -> Self {
+        other.then(self)
+    }
+
+    /// Returns the inverse transform, or None if it is not invertible
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() <= f32::EPSILON {
+            return None;
+        }
+        let inv = 1.0 / det;
+        Some(Self {
+            a: self.d * inv,
+            b: -self.b * inv,
+            c: -self.c * inv,
+            d: self.a * inv,
+            e: (self.c * self.f - self.d * self.e) * inv,
+            f: (self.b * self.e - self.a * self.f) * inv,
+        })
+    }
+
+    /// Applies this transform to a point
+    pub fn transform_point(&self, point: Point) -> Point {
+        Point::new(
+            self.a * point.x + self.c * point.y + self.e,
+            self.b * point.x + self.d * point.y + self.f,
+        )
+    }
+
+    /// Returns the axis-aligned bounding box of a transformed rectangle
+    pub fn transform_rect(&self, rect: &Rect) -> Rect {
+        let corners = [
+            self.transform_point(Point::new(rect.x, rect.y)),
+            self.transform_point(Point::new(rect.right(), rect.y)),
+            self.transform_point(Point::new(rect.right(), rect.bottom())),
+            self.transform_point(Point::new(rect.x, rect.bottom())),
+        ];
+        let min_x = corners.iter().map(|p| p.x).fold(f32::INFINITY, f32::min);
+        let min_y = corners.iter().map(|p| p.y).fold(f32::INFINITY, f32::min);
+        let max_x = corners.iter().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max);
+        let max_y = corners.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max);
+        Rect::new(min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    /// Returns true if this is the identity transform
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+
+    /// Returns true if this transform only translates
+    pub fn is_translation(&self) -> bool {
+        self.a == 1.0 && self.b == 0.0 && self.c == 0.0 && self.d == 1.0
+    }
+
+    /// Returns true if this transform keeps rectangles axis-aligned
+    /// (translation and scale only)
+    pub fn is_axis_aligned(&self) -> bool {
+        self.b == 0.0 && self.c == 0.0
+    }
+
+    /// Returns the largest factor by which this transform scales lengths
+    pub fn max_scale(&self) -> f32 {
+        let sx = (self.a * self.a + self.b * self.b).sqrt();
+        let sy = (self.c * self.c + self.d * self.d).sqrt();
+        sx.max(sy)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
 /// Represents an RGBA color with values from 0.0 to 1.0
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Color {
@@ -283,18 +440,18 @@ impl Color {
         let hex = hex.trim_start_matches('#');
 
         if hex.len() != 6 && hex.len() != 8 {
-            return Err(format!("Invalid hex color: must be 6 or 8 characters"));
+            return Err("Invalid hex color: must be 6 or 8 characters".to_string());
         }
 
         let r = u8::from_str_radix(&hex[0..2], 16)
-            .map_err(|_| format!("Invalid hex color"))?;
+            .map_err(|_| "Invalid hex color".to_string())?;
         let g = u8::from_str_radix(&hex[2..4], 16)
-            .map_err(|_| format!("Invalid hex color"))?;
+            .map_err(|_| "Invalid hex color".to_string())?;
         let b = u8::from_str_radix(&hex[4..6], 16)
-            .map_err(|_| format!("Invalid hex color"))?;
+            .map_err(|_| "Invalid hex color".to_string())?;
         let a = if hex.len() == 8 {
             u8::from_str_radix(&hex[6..8], 16)
-                .map_err(|_| format!("Invalid hex color"))?
+                .map_err(|_| "Invalid hex color".to_string())?
         } else {
             255
         };
@@ -326,6 +483,109 @@ impl Color {
     pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
 }
 
+/// A distance in one of the supported units
+///
+/// Layout works in logical pixels, which are the same as
+/// density-independent pixels (`dp`). Scale-independent pixels (`sp`) are
+/// additionally multiplied by the user's text scale factor and should be
+/// used for font sizes. Physical pixels (`px`) are divided by the device
+/// pixel ratio. Lengths are resolved against the current `MediaQuery` at
+/// layout time, so they follow changes to either factor.
+///
+/// Plain numbers convert to `dp`.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_core::media_query::MediaQueryData;
+/// use kobalt_core::types::{Length, Size, Units};
+///
+/// let media = MediaQueryData::new(Size::new(400.0, 800.0), 2.0).text_scale_factor(1.5);
+/// assert_eq!(16.dp().resolve(&media), 16.0);
+/// assert_eq!(14.sp().resolve(&media), 21.0);
+/// assert_eq!(3.px().resolve(&media), 1.5);
+/// assert_eq!(Length::from(8.0), 8.dp());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    /// Density-independent pixels (logical pixels)
+    Dp(f32),
+    /// Scale-independent pixels: `dp` times the text scale factor
+    Sp(f32),
+    /// Physical pixels
+    Px(f32),
+}
+
+impl Length {
+    /// Returns the length in logical pixels for the given environment
+    pub fn resolve(&self, media: &MediaQueryData) -> f32 {
+        match *self {
+            Self::Dp(value) => value,
+            Self::Sp(value) => value * media.text_scale_factor,
+            Self::Px(value) => value / media.device_pixel_ratio,
+        }
+    }
+
+    /// Returns the length in logical pixels for the current `MediaQuery`
+    pub fn to_logical(&self) -> f32 {
+        match self {
+            // Avoid touching the shared environment for the common case
+            Self::Dp(value) => *value,
+            _ => self.resolve(&MediaQuery::of()),
+        }
+    }
+}
+
+impl Default for Length {
+    fn default() -> Self {
+        Self::Dp(0.0)
+    }
+}
+
+impl From<f32> for Length {
+    fn from(value: f32) -> Self {
+        Self::Dp(value)
+    }
+}
+
+impl From<i32> for Length {
+    fn from(value: i32) -> Self {
+        Self::Dp(value as f32)
+    }
+}
+
+/// Creates `Length`s from numbers, as in `16.dp()` or `14.sp()`
+pub trait Units {
+    /// Density-independent pixels
+    fn dp(self) -> Length;
+    /// Scale-independent pixels, for font sizes
+    fn sp(self) -> Length;
+    /// Physical pixels
+    fn px(self) -> Length;
+}
+
+macro_rules! impl_units {
+    ($($ty:ty),*) => {
+        $(
+            impl Units for $ty {
+                fn dp(self) -> Length {
+                    Length::Dp(self as f32)
+                }
+
+                fn sp(self) -> Length {
+                    Length::Sp(self as f32)
+                }
+
+                fn px(self) -> Length {
+                    Length::Px(self as f32)
+                }
+            }
+        )*
+    };
+}
+
+impl_units!(f32, f64, i32, u32);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -377,6 +637,46 @@ mod tests {
         assert_eq!(intersection, Rect::new(50.0, 50.0, 50.0, 50.0));
     }
 
+    #[test]
+    fn test_transform_compose_and_invert() {
+        let t = Transform::translate(10.0, 20.0).then(&Transform::scale(2.0, 3.0));
+        assert_eq!(t.transform_point(Point::new(1.0, 1.0)), Point::new(22.0, 63.0));
+
+        let inverse = t.inverse().unwrap();
+        let p = inverse.transform_point(Point::new(22.0, 63.0));
+        assert!((p.x - 1.0).abs() < 1e-5 && (p.y - 1.0).abs() < 1e-5);
+
+        assert!(Transform::scale(0.0, 1.0).inverse().is_none());
+    }
+
+    #[test]
+    fn test_transform_rotate_rect_bounds() {
+        let t = Transform::rotate(std::f32::consts::FRAC_PI_2);
+        let p = t.transform_point(Point::new(1.0, 0.0));
+        assert!(p.x.abs() < 1e-6 && (p.y - 1.0).abs() < 1e-6);
+
+        let bounds = t.transform_rect(&Rect::new(0.0, 0.0, 10.0, 5.0));
+        assert!((bounds.width - 5.0).abs() < 1e-4);
+        assert!((bounds.height - 10.0).abs() < 1e-4);
+        assert!(!t.is_axis_aligned());
+    }
+
+    #[test]
+    fn test_rect_scale() {
+        let rect = Rect::new(10.0, 20.0, 30.0, 40.0).scale(1.5);
+        assert_eq!(rect, Rect::new(15.0, 30.0, 45.0, 60.0));
+        assert_eq!(Point::new(2.0, 3.0).scale(2.0), Point::new(4.0, 6.0));
+    }
+
+    #[test]
+    fn test_length_resolve() {
+        let media = MediaQueryData::new(Size::new(100.0, 100.0), 3.0).text_scale_factor(2.0);
+        assert_eq!(Length::from(5.0).resolve(&media), 5.0);
+        assert_eq!(1.5.sp().resolve(&media), 3.0);
+        assert_eq!(6u32.px().resolve(&media), 2.0);
+        assert_eq!(Length::default().resolve(&media), 0.0);
+    }
+
     #[test]
     fn test_color_from_rgb8() {
         let color = Color::from_rgb8(255, 128, 64);
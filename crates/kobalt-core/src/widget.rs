@@ -3,7 +3,39 @@
 //! This module provides the core widget system inspired by Flutter and Compose.
 //! All UI elements in Kobalt are widgets that implement the Widget trait.
 
-use crate::types::{Rect, Size};
+use crate::event::{Event, EventResult};
+use crate::focus::FocusNode;
+use crate::mouse::MouseTarget;
+use crate::shortcuts::{ActionMap, ShortcutMap};
+use crate::hit_test::{self, HitTestBehavior, HitTestResult};
+use crate::paint::PaintContext;
+use crate::repaint::RepaintHandle;
+use crate::types::{Point, Rect, Size};
+use std::borrow::Cow;
+
+/// Identifies a widget, e.g. to find it in tests
+///
+/// Attach a key to a subtree with the `KeyedSubtree` widget.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Key(Cow<'static, str>);
+
+impl Key {
+    /// Creates a key from a name
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self(name.into())
+    }
+
+    /// Returns the name of the key
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&'static str> for Key {
+    fn from(name: &'static str) -> Self {
+        Self::new(name)
+    }
+}
 
 /// Base trait for all widgets in Kobalt
 ///
@@ -28,6 +60,111 @@ pub trait Widget {
     fn bounds(&self) -> Option<Rect> {
         None
     }
+
+    /// Records how this widget looks into the paint context
+    ///
+    /// # Arguments
+    /// * `ctx` - The paint context to record draw commands into
+    /// * `bounds` - The rectangle assigned to this widget by its parent
+    ///
+    /// Container widgets are responsible for painting their children.
+    fn paint(&self, _ctx: &mut PaintContext, _bounds: Rect) {}
+
+    /// Calls `visitor` with each child and the bounds it is painted in, in
+    /// paint order
+    ///
+    /// Container widgets override this so that the tree can be walked
+    /// without painting it, for hit testing and event delivery. `bounds`
+    /// is the rectangle assigned to this widget.
+    fn visit_children<'a>(&'a self, _bounds: Rect, _visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {}
+
+    /// Returns how this widget responds to a point inside its bounds
+    ///
+    /// Widgets that only arrange their children return
+    /// `HitTestBehavior::DeferToChild`.
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        HitTestBehavior::Opaque
+    }
+
+    /// Adds this widget and its descendants under `position` to `result`,
+    /// returning true if it absorbs the point so widgets behind it are not
+    /// tested
+    ///
+    /// Override to ignore or absorb pointers; the default tests the
+    /// children and applies `hit_test_behavior`.
+    fn hit_test(&self, result: &mut HitTestResult, bounds: Rect, position: Point) -> bool {
+        hit_test::hit_test_widget(self, result, bounds, position)
+    }
+
+    /// Tests the children front to back until one absorbs the point
+    ///
+    /// Override to clip the test or to map the point through the transform
+    /// the children are painted with (see `HitTestResult::with_transform`).
+    fn hit_test_children(&self, result: &mut HitTestResult, bounds: Rect, position: Point) -> bool {
+        hit_test::hit_test_children(self, result, bounds, position)
+    }
+
+    /// Handles an input event
+    ///
+    /// Pointer events are offered to the widgets hit by the pointer,
+    /// innermost and frontmost first, until one returns
+    /// `EventResult::Handled`. `bounds` is the rectangle this widget was
+    /// painted in, and pointer positions are in the same coordinates. Key
+    /// events are offered to the focused widget and then its ancestors.
+    fn handle_event(&self, _event: &Event, _bounds: Rect) -> EventResult {
+        EventResult::Ignored
+    }
+
+    /// Returns the focus node of this widget, if it can take keyboard focus
+    fn focus_node(&self) -> Option<&FocusNode> {
+        None
+    }
+
+    /// Returns the hover callbacks and cursor of this widget, if it tracks
+    /// the mouse
+    fn mouse_target(&self) -> Option<&dyn MouseTarget> {
+        None
+    }
+
+    /// Returns the handle of this widget if it is a repaint boundary, so
+    /// paint requests from its descendants can mark it
+    fn repaint_handle(&self) -> Option<&RepaintHandle> {
+        None
+    }
+
+    /// Returns the shortcuts this widget binds for key events bubbling
+    /// through it, if any
+    fn shortcuts(&self) -> Option<&ShortcutMap> {
+        None
+    }
+
+    /// Returns the actions this widget provides to intents from shortcuts
+    /// of itself or its ancestors while it or a descendant is focused
+    fn actions(&self) -> Option<&ActionMap> {
+        None
+    }
+
+    /// Returns the key identifying this widget, if any
+    fn key(&self) -> Option<&Key> {
+        None
+    }
+
+    /// Returns the text this widget displays, if any
+    fn text(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns a description of this widget for assistive technologies
+    fn semantics_label(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Calls `visitor` with a widget and all its descendants in paint order,
+/// together with the bounds each one is painted in
+pub fn visit_tree<'a>(widget: &'a dyn Widget, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+    visitor(widget, bounds);
+    widget.visit_children(bounds, &mut |child, child_bounds| visit_tree(child, child_bounds, visitor));
 }
 
 /// Layout constraints for widgets
@@ -79,10 +216,128 @@ impl Constraints {
     }
 }
 
+/// The interactive states a widget is in, as a set of bits, similar to
+/// Flutter's `WidgetState`
+///
+/// Interactive widgets such as buttons keep their states in a set and
+/// resolve their colors from it when they paint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct WidgetState(u8);
+
+impl WidgetState {
+    /// No state: enabled and idle
+    pub const NONE: Self = Self(0);
+    /// A mouse is over the widget
+    pub const HOVERED: Self = Self(1);
+    /// The widget has the keyboard focus
+    pub const FOCUSED: Self = Self(1 << 1);
+    /// A pointer is pressing the widget
+    pub const PRESSED: Self = Self(1 << 2);
+    /// The widget does not respond to input
+    pub const DISABLED: Self = Self(1 << 3);
+
+    /// Returns true if the widget is in no state
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if every state in `other` is set
+    pub fn contains(&self, other: WidgetState) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns these states plus `other`
+    pub fn with(self, other: WidgetState) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Returns these states without `other`
+    pub fn without(self, other: WidgetState) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Returns these states with `other` set or cleared
+    pub fn set(self, other: WidgetState, value: bool) -> Self {
+        if value { self.with(other) } else { self.without(other) }
+    }
+
+    /// Returns true if a mouse is over the widget
+    pub fn is_hovered(&self) -> bool {
+        self.contains(Self::HOVERED)
+    }
+
+    /// Returns true if the widget has the keyboard focus
+    pub fn is_focused(&self) -> bool {
+        self.contains(Self::FOCUSED)
+    }
+
+    /// Returns true if a pointer is pressing the widget
+    pub fn is_pressed(&self) -> bool {
+        self.contains(Self::PRESSED)
+    }
+
+    /// Returns true if the widget does not respond to input
+    pub fn is_disabled(&self) -> bool {
+        self.contains(Self::DISABLED)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Splits its bounds between two leaves, side by side
+    struct Pair(Leaf, Leaf);
+
+    struct Leaf(&'static str);
+
+    impl Widget for Leaf {
+        fn widget_type(&self) -> &'static str {
+            "Leaf"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn text(&self) -> Option<&str> {
+            Some(self.0)
+        }
+    }
+
+    impl Widget for Pair {
+        fn widget_type(&self) -> &'static str {
+            "Pair"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+            let half = bounds.width / 2.0;
+            visitor(&self.0, Rect::new(bounds.x, bounds.y, half, bounds.height));
+            visitor(&self.1, Rect::new(bounds.x + half, bounds.y, half, bounds.height));
+        }
+    }
+
+    #[test]
+    fn test_visit_tree_in_paint_order() {
+        let tree = Pair(Leaf("a"), Leaf("b"));
+        let mut visited = Vec::new();
+        visit_tree(&tree, Rect::new(0.0, 0.0, 10.0, 4.0), &mut |widget, bounds| {
+            visited.push((widget.text().unwrap_or(widget.widget_type()), bounds));
+        });
+        assert_eq!(
+            visited,
+            vec![
+                ("Pair", Rect::new(0.0, 0.0, 10.0, 4.0)),
+                ("a", Rect::new(0.0, 0.0, 5.0, 4.0)),
+                ("b", Rect::new(5.0, 0.0, 5.0, 4.0)),
+            ]
+        );
+    }
+
     #[test]
     fn test_constraints_tight() {
         let c = Constraints::tight(100.0, 200.0);
@@ -104,4 +359,16 @@ mod tests {
         let constrained2 = c.constrain(size2);
         assert_eq!(constrained2, Size::new(100.0, 200.0));
     }
+
+    #[test]
+    fn test_widget_state_set() {
+        let state = WidgetState::NONE.with(WidgetState::HOVERED).with(WidgetState::PRESSED);
+        assert!(state.is_hovered() && state.is_pressed());
+        assert!(!state.is_focused() && !state.is_disabled());
+        assert!(state.contains(WidgetState::HOVERED.with(WidgetState::PRESSED)));
+
+        let released = state.set(WidgetState::PRESSED, false);
+        assert_eq!(released, WidgetState::HOVERED);
+        assert!(released.without(WidgetState::HOVERED).is_empty());
+    }
 }
@@ -38,7 +38,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     content: "Flutter-inspired syntax",
                     font_size: 20.0,
                     color: Color::from_rgb8(150, 255, 150)
-                }
+                },
+
+                // Button with a callback
+                button!("Click me", on_pressed: || println!("Clicked!"))
             ]
         }
     }
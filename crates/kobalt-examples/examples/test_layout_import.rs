@@ -1,7 +1,6 @@
 //! Test to verify layout imports work correctly
 
 use kobalt_core::layout::{MainAxisAlignment, CrossAxisAlignment, EdgeInsets};
-use kobalt_core::types::Color;
 
 fn main() {
     println!("✅ Testing layout imports...");
@@ -7,7 +7,7 @@
 //! ```ignore
 //! column! {
 //!     text!("Hello, World!")
-//!     button!("Click me", on_click = || { println!("Clicked!") })
+//!     button!("Click me", on_pressed: || { println!("Clicked!") })
 //! }
 //! ```
 
@@ -72,6 +72,35 @@ macro_rules! text {
     }};
 }
 
+/// Macro for creating a filled Button with a label
+///
+/// Properties are the button's builder methods, e.g. `on_pressed`,
+/// `on_long_press`, `style` and `autofocus`. Without `on_pressed` or
+/// `on_long_press` the button is disabled.
+///
+/// # Examples
+///
+/// Disabled button:
+/// ```ignore
+/// button!("Save")
+/// ```
+///
+/// With properties:
+/// ```ignore
+/// button!("Save", on_pressed: || println!("saved"), autofocus: true)
+/// ```
+#[macro_export]
+macro_rules! button {
+    // Label with optional properties
+    ($label:expr $(, $key:ident: $value:expr)* $(,)?) => {{
+        let mut button = kobalt_widgets::Button::label($label);
+        $(
+            button = button.$key($value);
+        )*
+        button
+    }};
+}
+
 /// Macro for creating Column layouts with full Flutter-style properties
 ///
 /// # Examples
@@ -171,6 +200,7 @@ macro_rules! text_style {
 ///     title: text!("My App"),
 ///     size: (800, 600),
 ///     background: Color::BLACK,
+///     renderer: RendererConfig::new().present_mode(PresentMode::Mailbox).sample_count(4),
 ///     home: column! {
 ///         text!("Hello")
 ///     }
@@ -182,6 +212,7 @@ macro_rules! app {
         title: $title:expr
         $(, size: ($width:expr, $height:expr))?
         $(, background: $bg:expr)?
+        $(, renderer: $renderer:expr)?
         , home: $home:expr
         $(,)?
     ) => {{
@@ -196,6 +227,10 @@ macro_rules! app {
             app = app.background($bg);
         )?
 
-        app.home($home).run()
+        $(
+            app = app.renderer_config($renderer);
+        )?
+
+        app.home($home).run().map_err(::core::convert::Into::into)
     }};
 }
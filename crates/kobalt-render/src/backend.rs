@@ -0,0 +1,281 @@
+//! Render backends
+//!
+//! `RenderBackend` is the set of primitives a display list is made of
+//! (rects, rounded rects, paths, glyph runs, images, clips, filters, layers
+//! and repaint boundaries). `draw_list` replays a display list through them,
+//! so anything that can draw the primitives can render a widget tree.
+//!
+//! Two backends are provided: `WgpuBackend` renders on the GPU through the
+//! `SceneRenderer`, and `CpuBackend` rasterizes in software for machines
+//! without GPU drivers. Both take coordinates in logical pixels and produce
+//! RGBA8 images in physical pixels.
+
+use crate::config::RendererConfig;
+use crate::renderer::Renderer;
+use crate::scene::SceneRenderer;
+use kobalt_core::error::KobaltError;
+use kobalt_core::image::ImageData;
+use kobalt_core::paint::{DisplayList, DrawCommand, ImageFilter, Paint};
+use kobalt_core::path::Path;
+use kobalt_core::types::{Color, Point, Rect, Size, Transform};
+use std::sync::Arc;
+
+/// Something that can draw display list primitives into an image
+///
+/// Coordinates are in logical pixels; the backend scales them by its scale
+/// factor. A frame starts with `begin_frame`, is drawn with the primitive
+/// methods (or `draw_list`) and ends with `finish`, which returns the image.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_render::{CpuBackend, RenderBackend};
+/// use kobalt_core::types::{Color, Rect};
+///
+/// let mut backend = CpuBackend::new(4, 4, 1.0).unwrap();
+/// backend.begin_frame(Color::WHITE);
+/// backend.fill_rect(Rect::new(0.0, 0.0, 2.0, 4.0), Color::BLACK);
+/// let pixels = backend.finish().unwrap();
+/// assert_eq!(&pixels[..4], &[0, 0, 0, 255]);
+/// assert_eq!(&pixels[8..12], &[255, 255, 255, 255]);
+/// ```
+pub trait RenderBackend {
+    /// Returns a short name for diagnostics, e.g. "wgpu" or "cpu"
+    fn name(&self) -> &'static str;
+
+    /// Returns the size of the image in physical pixels
+    fn size(&self) -> (u32, u32);
+
+    /// Returns the number of physical pixels per logical pixel
+    fn scale_factor(&self) -> f32;
+
+    /// Starts a frame cleared to `background`
+    fn begin_frame(&mut self, background: Color);
+
+    /// Fills a rectangle with a solid color
+    fn fill_rect(&mut self, rect: Rect, color: Color);
+
+    /// Fills a rectangle with rounded corners
+    fn fill_rounded_rect(&mut self, rect: Rect, radius: f32, color: Color) {
+        let mut path = Path::new();
+        path.add_round_rect(rect, radius);
+        self.draw_path(&path, &Paint::fill(color), &Transform::identity());
+    }
+
+    /// Fills or strokes a path after applying `transform` to it
+    fn draw_path(&mut self, path: &Path, paint: &Paint, transform: &Transform);
+
+    /// Draws a run of text with its top-left corner at `position`
+    fn draw_text(&mut self, text: &str, position: Point, font_size: f32, color: Color);
+
+    /// Draws the `src` region of an image (in image pixels) into `dst`
+    fn draw_image(&mut self, image: &ImageData, src: Rect, dst: Rect, tint: Option<Color>);
+
+    /// Restricts drawing to `rect` until the matching `pop_clip`
+    fn push_clip(&mut self, rect: Rect);
+
+    /// Ends the most recent `push_clip`
+    fn pop_clip(&mut self);
+
+    /// Filters everything drawn so far inside `rect`
+    fn backdrop_filter(&mut self, rect: Rect, filter: &ImageFilter);
+
+    /// Starts a layer that is filtered onto the frame by `pop_layer`
+    fn push_layer(&mut self, bounds: Rect, filter: &ImageFilter);
+
+    /// Ends the most recent `push_layer`
+    fn pop_layer(&mut self);
+
+    /// Draws a retained subtree recorded at the origin into `rect`
+    fn draw_boundary(&mut self, id: u64, version: u64, rect: Rect, list: &Arc<DisplayList>);
+
+    /// Ends the frame and returns it as tightly packed RGBA8 rows
+    fn finish(&mut self) -> Result<Vec<u8>, KobaltError>;
+
+    /// Draws every command of a display list
+    fn draw_list(&mut self, list: &DisplayList) {
+        for command in list.commands() {
+            match command {
+                DrawCommand::Rect { rect, color } => self.fill_rect(*rect, *color),
+                DrawCommand::Text {
+                    content,
+                    position,
+                    font_size,
+                    color,
+                } => self.draw_text(content, *position, *font_size, *color),
+                DrawCommand::Path {
+                    path,
+                    paint,
+                    transform,
+                } => self.draw_path(path, paint, transform),
+                DrawCommand::Image {
+                    image,
+                    src,
+                    dst,
+                    tint,
+                } => self.draw_image(image, *src, *dst, *tint),
+                DrawCommand::PushClip { rect } => self.push_clip(*rect),
+                DrawCommand::PopClip => self.pop_clip(),
+                DrawCommand::BackdropFilter { rect, filter } => self.backdrop_filter(*rect, filter),
+                DrawCommand::PushLayer { bounds, filter } => self.push_layer(*bounds, filter),
+                DrawCommand::PopLayer => self.pop_layer(),
+                DrawCommand::RepaintBoundary {
+                    id,
+                    version,
+                    rect,
+                    list,
+                } => self.draw_boundary(*id, *version, *rect, list),
+            }
+        }
+    }
+
+    /// Renders a whole display list and returns the image
+    fn render(&mut self, list: &DisplayList, background: Color) -> Result<Vec<u8>, KobaltError> {
+        self.begin_frame(background);
+        self.draw_list(list);
+        self.finish()
+    }
+}
+
+/// Renders on the GPU into an offscreen texture
+///
+/// Primitives are recorded into a display list and rendered by a
+/// `SceneRenderer` in `finish`, so they are batched as usual.
+pub struct WgpuBackend {
+    renderer: Renderer,
+    scene: SceneRenderer,
+    list: DisplayList,
+    background: Color,
+}
+
+impl WgpuBackend {
+    /// Creates a backend for images of `width` x `height` physical pixels
+    pub async fn new(
+        width: u32,
+        height: u32,
+        scale_factor: f32,
+        renderer_config: RendererConfig,
+    ) -> Result<Self, KobaltError> {
+        let size = Size::new(width as f32, height as f32);
+        let mut renderer = Renderer::new_offscreen(size, renderer_config).await?;
+        renderer.set_scale_factor(scale_factor);
+        let scene = SceneRenderer::with_sample_count(renderer.device(), renderer.config(), renderer.sample_count())?;
+        Ok(Self {
+            renderer,
+            scene,
+            list: DisplayList::new(),
+            background: Color::TRANSPARENT,
+        })
+    }
+
+    /// Returns the underlying renderer
+    pub fn renderer(&self) -> &Renderer {
+        &self.renderer
+    }
+}
+
+impl RenderBackend for WgpuBackend {
+    fn name(&self) -> &'static str {
+        "wgpu"
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.renderer.config().width, self.renderer.config().height)
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.renderer.scale_factor()
+    }
+
+    fn begin_frame(&mut self, background: Color) {
+        self.list = DisplayList::new();
+        self.background = background;
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) {
+        self.list.push(DrawCommand::Rect { rect, color });
+    }
+
+    fn draw_path(&mut self, path: &Path, paint: &Paint, transform: &Transform) {
+        self.list.push(DrawCommand::Path {
+            path: path.clone(),
+            paint: paint.clone(),
+            transform: *transform,
+        });
+    }
+
+    fn draw_text(&mut self, text: &str, position: Point, font_size: f32, color: Color) {
+        self.list.push(DrawCommand::Text {
+            content: text.to_string(),
+            position,
+            font_size,
+            color,
+        });
+    }
+
+    fn draw_image(&mut self, image: &ImageData, src: Rect, dst: Rect, tint: Option<Color>) {
+        self.list.push(DrawCommand::Image {
+            image: image.clone(),
+            src,
+            dst,
+            tint,
+        });
+    }
+
+    fn push_clip(&mut self, rect: Rect) {
+        self.list.push(DrawCommand::PushClip { rect });
+    }
+
+    fn pop_clip(&mut self) {
+        self.list.push(DrawCommand::PopClip);
+    }
+
+    fn backdrop_filter(&mut self, rect: Rect, filter: &ImageFilter) {
+        self.list.push(DrawCommand::BackdropFilter {
+            rect,
+            filter: filter.clone(),
+        });
+    }
+
+    fn push_layer(&mut self, bounds: Rect, filter: &ImageFilter) {
+        self.list.push(DrawCommand::PushLayer {
+            bounds,
+            filter: filter.clone(),
+        });
+    }
+
+    fn pop_layer(&mut self) {
+        self.list.push(DrawCommand::PopLayer);
+    }
+
+    fn draw_boundary(&mut self, id: u64, version: u64, rect: Rect, list: &Arc<DisplayList>) {
+        self.list.push(DrawCommand::RepaintBoundary {
+            id,
+            version,
+            rect,
+            list: list.clone(),
+        });
+    }
+
+    fn finish(&mut self) -> Result<Vec<u8>, KobaltError> {
+        let list = std::mem::take(&mut self.list);
+        let frame = self
+            .renderer
+            .begin_frame()
+            .map_err(|error| KobaltError::Readback(error.to_string()))?;
+        let mut encoder = frame.create_encoder();
+        self.scene.render(&frame, &mut encoder, &list, self.background);
+        frame.read_rgba8(encoder)
+    }
+
+    fn render(&mut self, list: &DisplayList, background: Color) -> Result<Vec<u8>, KobaltError> {
+        // Skip copying the commands into our own list
+        let frame = self
+            .renderer
+            .begin_frame()
+            .map_err(|error| KobaltError::Readback(error.to_string()))?;
+        let mut encoder = frame.create_encoder();
+        self.scene.render(&frame, &mut encoder, list, background);
+        frame.read_rgba8(encoder)
+    }
+}
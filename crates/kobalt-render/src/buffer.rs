@@ -0,0 +1,147 @@
+//! Persistent GPU buffers for per-frame geometry
+//!
+//! Renderers used to create fresh vertex and index buffers every time they
+//! drew. A `RingBuffer` instead keeps one buffer alive across frames: each
+//! batch in a frame is written to its own region, the cursor rewinds at the
+//! start of the next frame, and the buffer is replaced by one twice as large
+//! when a frame no longer fits.
+
+use wgpu::util::DeviceExt;
+
+/// Corners of a quad as two triangles, shared by every instanced quad
+pub(crate) const QUAD_INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+/// Creates the static index buffer used to draw instanced quads
+pub(crate) fn create_quad_index_buffer(device: &wgpu::Device, label: &str) -> wgpu::Buffer {
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some(label),
+        contents: bytemuck::cast_slice(&QUAD_INDICES),
+        usage: wgpu::BufferUsages::INDEX,
+    })
+}
+
+/// A persistent GPU buffer that batches are appended to during a frame
+pub struct RingBuffer {
+    label: &'static str,
+    usage: wgpu::BufferUsages,
+    alignment: u64,
+    buffer: wgpu::Buffer,
+    cursor: u64,
+    /// Buffers replaced during this frame; passes recorded earlier still use them
+    retired: Vec<wgpu::Buffer>,
+    generation: u64,
+}
+
+impl RingBuffer {
+    /// Size of a newly created buffer in bytes
+    pub const INITIAL_CAPACITY: u64 = 16 * 1024;
+
+    /// Creates a ring buffer
+    ///
+    /// Every write starts at a multiple of `alignment` bytes; storage buffers
+    /// indexed by instance use their element size so that offsets map to
+    /// whole instances.
+    pub fn new(device: &wgpu::Device, label: &'static str, usage: wgpu::BufferUsages, alignment: u64) -> Self {
+        let usage = usage | wgpu::BufferUsages::COPY_DST;
+        Self {
+            label,
+            usage,
+            alignment: alignment.max(wgpu::COPY_BUFFER_ALIGNMENT),
+            buffer: Self::create(device, label, usage, Self::INITIAL_CAPACITY),
+            cursor: 0,
+            retired: Vec::new(),
+            generation: 0,
+        }
+    }
+
+    fn create(device: &wgpu::Device, label: &str, usage: wgpu::BufferUsages, size: u64) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size,
+            usage,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Returns the current buffer
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    /// Returns the size of the current buffer in bytes
+    pub fn capacity(&self) -> u64 {
+        self.buffer.size()
+    }
+
+    /// Returns a counter that changes whenever the buffer is replaced, so
+    /// bind groups referring to it can be rebuilt
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Rewinds to the start of the buffer for a new frame
+    pub fn begin_frame(&mut self) {
+        self.cursor = 0;
+        self.retired.clear();
+    }
+
+    /// Copies `data` into the buffer and returns the offset it was written at
+    ///
+    /// `data.len()` must be a multiple of `wgpu::COPY_BUFFER_ALIGNMENT`.
+    pub fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[u8]) -> wgpu::BufferAddress {
+        let len = data.len() as u64;
+        debug_assert_eq!(len % wgpu::COPY_BUFFER_ALIGNMENT, 0, "unaligned ring buffer write");
+
+        let mut offset = align_up(self.cursor, self.alignment);
+        if offset + len > self.capacity() {
+            // Size the replacement for everything written this frame, so the
+            // next frame fits without growing again
+            let capacity = grown_capacity(self.capacity(), offset + len);
+            let buffer = Self::create(device, self.label, self.usage, capacity);
+            self.retired.push(std::mem::replace(&mut self.buffer, buffer));
+            self.generation += 1;
+            offset = 0;
+        }
+
+        if len > 0 {
+            queue.write_buffer(&self.buffer, offset, data);
+        }
+        self.cursor = offset + len;
+        offset
+    }
+}
+
+/// Rounds `value` up to a multiple of `alignment`
+fn align_up(value: u64, alignment: u64) -> u64 {
+    value.div_ceil(alignment) * alignment
+}
+
+/// Doubles `capacity` until it holds `required` bytes
+fn grown_capacity(capacity: u64, required: u64) -> u64 {
+    let mut capacity = capacity.max(RingBuffer::INITIAL_CAPACITY);
+    while capacity < required {
+        capacity *= 2;
+    }
+    capacity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align_up() {
+        assert_eq!(align_up(0, 48), 0);
+        assert_eq!(align_up(1, 48), 48);
+        assert_eq!(align_up(96, 48), 96);
+        assert_eq!(align_up(97, 4), 100);
+    }
+
+    #[test]
+    fn test_grown_capacity_doubles() {
+        let initial = RingBuffer::INITIAL_CAPACITY;
+        assert_eq!(grown_capacity(initial, initial + 1), initial * 2);
+        assert_eq!(grown_capacity(initial, initial * 5), initial * 8);
+        assert_eq!(grown_capacity(0, 1), initial);
+    }
+}
@@ -0,0 +1,270 @@
+//! Renderer configuration
+//!
+//! `RendererConfig` collects the GPU and surface options chosen at startup:
+//! how frames are presented, which adapter and backends to use, multisampling
+//! and whether the window is transparent. Options the adapter or surface does
+//! not support fall back to a supported value with a warning.
+
+/// How finished frames are handed to the display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PresentMode {
+    /// Wait for vertical sync; never tears (supported everywhere)
+    #[default]
+    Vsync,
+    /// Wait for vertical sync, but replace a queued frame with a newer one
+    /// (lower latency, no tearing)
+    Mailbox,
+    /// Present immediately; lowest latency but may tear
+    Immediate,
+}
+
+/// Which GPU to prefer on systems with more than one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PowerPreference {
+    /// Let the platform decide
+    #[default]
+    Default,
+    /// Prefer the integrated GPU to save battery
+    LowPower,
+    /// Prefer the discrete GPU
+    HighPerformance,
+}
+
+/// Which graphics APIs may be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backends {
+    /// Any backend available on the platform
+    #[default]
+    All,
+    /// Vulkan, Metal, DX12 or WebGPU, whichever the platform supports best
+    Primary,
+    /// Vulkan only
+    Vulkan,
+    /// Metal only
+    Metal,
+    /// DirectX 12 only
+    Dx12,
+    /// OpenGL / WebGL only
+    Gl,
+}
+
+impl Backends {
+    pub(crate) fn to_wgpu(self) -> wgpu::Backends {
+        match self {
+            Self::All => wgpu::Backends::all(),
+            Self::Primary => wgpu::Backends::PRIMARY,
+            Self::Vulkan => wgpu::Backends::VULKAN,
+            Self::Metal => wgpu::Backends::METAL,
+            Self::Dx12 => wgpu::Backends::DX12,
+            Self::Gl => wgpu::Backends::GL,
+        }
+    }
+}
+
+/// GPU and surface options for a `Renderer`
+///
+/// # Example
+///
+/// ```
+/// use kobalt_render::{PowerPreference, PresentMode, RendererConfig};
+///
+/// let config = RendererConfig::new()
+///     .present_mode(PresentMode::Mailbox)
+///     .power_preference(PowerPreference::HighPerformance)
+///     .sample_count(4);
+/// assert_eq!(config.get_sample_count(), 4);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RendererConfig {
+    present_mode: PresentMode,
+    power_preference: PowerPreference,
+    backends: Backends,
+    sample_count: u32,
+    transparent: bool,
+    force_fallback_adapter: bool,
+}
+
+impl RendererConfig {
+    /// Sample counts accepted by `sample_count`
+    pub const SUPPORTED_SAMPLE_COUNTS: [u32; 2] = [1, 4];
+
+    /// Creates the default configuration: vsync, default power preference,
+    /// all backends, no multisampling and an opaque window
+    pub fn new() -> Self {
+        Self {
+            present_mode: PresentMode::default(),
+            power_preference: PowerPreference::default(),
+            backends: Backends::default(),
+            sample_count: 1,
+            transparent: false,
+            force_fallback_adapter: false,
+        }
+    }
+
+    /// Sets how frames are presented
+    pub fn present_mode(mut self, present_mode: PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// Sets which GPU to prefer
+    pub fn power_preference(mut self, power_preference: PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    /// Sets which graphics APIs may be used
+    pub fn backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    /// Sets the number of samples per pixel for anti-aliasing (1 or 4);
+    /// unsupported counts fall back to 1
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = if Self::SUPPORTED_SAMPLE_COUNTS.contains(&sample_count) {
+            sample_count
+        } else {
+            eprintln!("Warning: unsupported MSAA sample count {}, using 1", sample_count);
+            1
+        };
+        self
+    }
+
+    /// Sets whether the window background is transparent, letting the
+    /// desktop show through where the clear color's alpha is below 1
+    pub fn transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    /// Sets whether to use a software (fallback) adapter, e.g. on machines
+    /// without a GPU
+    pub fn force_fallback_adapter(mut self, force_fallback_adapter: bool) -> Self {
+        self.force_fallback_adapter = force_fallback_adapter;
+        self
+    }
+
+    /// Returns the present mode
+    pub fn get_present_mode(&self) -> PresentMode {
+        self.present_mode
+    }
+
+    /// Returns the power preference
+    pub fn get_power_preference(&self) -> PowerPreference {
+        self.power_preference
+    }
+
+    /// Returns the allowed backends
+    pub fn get_backends(&self) -> Backends {
+        self.backends
+    }
+
+    /// Returns the MSAA sample count
+    pub fn get_sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Returns true if the window is transparent
+    pub fn is_transparent(&self) -> bool {
+        self.transparent
+    }
+
+    /// Returns true if a software adapter is forced
+    pub fn is_fallback_adapter_forced(&self) -> bool {
+        self.force_fallback_adapter
+    }
+
+    /// Creates a WGPU instance restricted to the configured backends
+    pub fn create_instance(&self) -> wgpu::Instance {
+        wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: self.backends.to_wgpu(),
+            ..Default::default()
+        })
+    }
+
+    pub(crate) fn wgpu_power_preference(&self) -> wgpu::PowerPreference {
+        match self.power_preference {
+            PowerPreference::Default => wgpu::PowerPreference::default(),
+            PowerPreference::LowPower => wgpu::PowerPreference::LowPower,
+            PowerPreference::HighPerformance => wgpu::PowerPreference::HighPerformance,
+        }
+    }
+
+    /// Picks the present mode to use from those the surface supports
+    pub(crate) fn choose_present_mode(&self, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+        let wanted = match self.present_mode {
+            PresentMode::Vsync => return wgpu::PresentMode::Fifo,
+            PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+            PresentMode::Immediate => wgpu::PresentMode::Immediate,
+        };
+        if supported.contains(&wanted) {
+            wanted
+        } else {
+            eprintln!("Warning: present mode {:?} is not supported, using vsync", wanted);
+            wgpu::PresentMode::Fifo
+        }
+    }
+
+    /// Picks the alpha mode to use from those the surface supports
+    pub(crate) fn choose_alpha_mode(&self, supported: &[wgpu::CompositeAlphaMode]) -> wgpu::CompositeAlphaMode {
+        let preferred: &[wgpu::CompositeAlphaMode] = if self.transparent {
+            &[
+                wgpu::CompositeAlphaMode::PreMultiplied,
+                wgpu::CompositeAlphaMode::PostMultiplied,
+                wgpu::CompositeAlphaMode::Inherit,
+            ]
+        } else {
+            &[wgpu::CompositeAlphaMode::Opaque, wgpu::CompositeAlphaMode::Auto]
+        };
+        let chosen = preferred.iter().copied().find(|mode| supported.contains(mode));
+        if chosen.is_none() && self.transparent {
+            eprintln!("Warning: the surface does not support transparency");
+        }
+        chosen.or(supported.first().copied()).unwrap_or(wgpu::CompositeAlphaMode::Auto)
+    }
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsupported_sample_count_falls_back() {
+        assert_eq!(RendererConfig::new().sample_count(4).get_sample_count(), 4);
+        assert_eq!(RendererConfig::new().sample_count(3).get_sample_count(), 1);
+    }
+
+    #[test]
+    fn test_present_mode_falls_back_to_vsync() {
+        let config = RendererConfig::new().present_mode(PresentMode::Mailbox);
+        assert_eq!(
+            config.choose_present_mode(&[wgpu::PresentMode::Fifo, wgpu::PresentMode::Mailbox]),
+            wgpu::PresentMode::Mailbox
+        );
+        assert_eq!(config.choose_present_mode(&[wgpu::PresentMode::Fifo]), wgpu::PresentMode::Fifo);
+    }
+
+    #[test]
+    fn test_alpha_mode() {
+        let supported = [wgpu::CompositeAlphaMode::Opaque, wgpu::CompositeAlphaMode::PreMultiplied];
+        assert_eq!(
+            RendererConfig::new().choose_alpha_mode(&supported),
+            wgpu::CompositeAlphaMode::Opaque
+        );
+        assert_eq!(
+            RendererConfig::new().transparent(true).choose_alpha_mode(&supported),
+            wgpu::CompositeAlphaMode::PreMultiplied
+        );
+        assert_eq!(
+            RendererConfig::new().transparent(true).choose_alpha_mode(&supported[..1]),
+            wgpu::CompositeAlphaMode::Opaque
+        );
+    }
+}
@@ -0,0 +1,728 @@
+//! Software rasterizer
+//!
+//! `CpuBackend` draws display lists without a GPU. It follows the wgpu
+//! backend's rules so both produce the same pixels up to rounding:
+//!
+//! - Colors are blended in linear space into premultiplied pixels and
+//!   encoded as sRGB at the end, like an sRGB render target.
+//! - Shapes are tessellated with the same tessellator and a pixel is
+//!   covered if its center is inside a triangle; with anti-aliasing, four
+//!   samples at the standard 4x MSAA positions are tested instead.
+//! - Glyphs and images are sampled bilinearly at pixel centers, clips are
+//!   snapped outwards to whole pixels and filters use the same Gaussian
+//!   weights as the GPU passes.
+
+use crate::backend::RenderBackend;
+use crate::filter::{gaussian_weights, FilterPass};
+use crate::scene::{expand, snap};
+use crate::tessellate::tessellate;
+use crate::text_real::default_font;
+use fontdue::{Font, Metrics};
+use kobalt_core::error::KobaltError;
+use kobalt_core::image::ImageData;
+use kobalt_core::paint::{DisplayList, ImageFilter, Paint};
+use kobalt_core::path::Path;
+use kobalt_core::types::{Color, Point, Rect, Size, Transform};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Sample positions of a pixel without anti-aliasing
+const CENTER_SAMPLE: [(f32, f32); 1] = [(0.5, 0.5)];
+
+/// Sample positions of the standard 4x MSAA pattern
+const MSAA_SAMPLES: [(f32, f32); 4] = [(0.375, 0.125), (0.875, 0.375), (0.125, 0.625), (0.625, 0.875)];
+
+/// Offset added to sample positions so that samples exactly on an edge
+/// belong to one side only, like the GPU's top-left rule: left and top
+/// edges are inside, right and bottom edges are not
+const TIE_BREAK: f32 = 1.0 / 4096.0;
+
+/// A premultiplied color in linear space
+type Pixel = [f32; 4];
+
+/// A grid of premultiplied linear pixels
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<Pixel>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0.0; 4]; width as usize * height as usize],
+        }
+    }
+
+    fn size(&self) -> Size {
+        Size::new(self.width as f32, self.height as f32)
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::new(0.0, 0.0, self.width as f32, self.height as f32)
+    }
+
+    fn get(&self, x: u32, y: u32) -> Pixel {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    fn set(&mut self, x: u32, y: u32, pixel: Pixel) {
+        self.pixels[(y * self.width + x) as usize] = pixel;
+    }
+
+    /// Composites a premultiplied pixel over the one at `(x, y)`
+    fn blend(&mut self, x: u32, y: u32, src: Pixel) {
+        let dst = &mut self.pixels[(y * self.width + x) as usize];
+        let inverse = 1.0 - src[3];
+        for channel in 0..4 {
+            dst[channel] = src[channel] + dst[channel] * inverse;
+        }
+    }
+}
+
+/// A canvas on the layer stack
+struct Target {
+    canvas: Canvas,
+    /// Bounds and filter of the layer in physical pixels (None for the
+    /// frame and boundaries)
+    layer: Option<(Rect, ImageFilter)>,
+}
+
+/// Renders display lists in software
+///
+/// Output matches the wgpu backend closely enough for screenshot tests;
+/// edges and filtered pixels may differ by a few levels of rounding.
+pub struct CpuBackend {
+    width: u32,
+    height: u32,
+    scale: f32,
+    anti_alias: bool,
+    font: Font,
+    glyphs: HashMap<(char, u32), (Metrics, Vec<u8>)>,
+    targets: Vec<Target>,
+    /// Active clip rectangles in physical pixels, each already intersected
+    /// with its parent
+    clips: Vec<Rect>,
+}
+
+impl CpuBackend {
+    /// Creates a backend for images of `width` x `height` physical pixels
+    pub fn new(width: u32, height: u32, scale_factor: f32) -> Result<Self, KobaltError> {
+        Ok(Self {
+            width: width.max(1),
+            height: height.max(1),
+            scale: scale_factor,
+            anti_alias: false,
+            font: default_font()?,
+            glyphs: HashMap::new(),
+            targets: Vec::new(),
+            clips: Vec::new(),
+        })
+    }
+
+    /// Sets whether shapes are anti-aliased with 4 samples per pixel, like
+    /// the wgpu backend with `RendererConfig::sample_count(4)`
+    pub fn anti_alias(mut self, anti_alias: bool) -> Self {
+        self.anti_alias = anti_alias;
+        self
+    }
+
+    /// Returns true if shapes are anti-aliased
+    pub fn is_anti_aliased(&self) -> bool {
+        self.anti_alias
+    }
+
+    fn samples(&self) -> &'static [(f32, f32)] {
+        if self.anti_alias { &MSAA_SAMPLES } else { &CENTER_SAMPLE }
+    }
+
+    /// Returns the canvas being drawn into, starting a frame if needed
+    fn target(&mut self) -> &mut Target {
+        if self.targets.is_empty() {
+            self.begin_frame(Color::TRANSPARENT);
+        }
+        self.targets.last_mut().unwrap()
+    }
+
+    /// Returns the current clip in whole pixels of the current canvas, or
+    /// None if nothing is visible
+    fn scissor(&mut self) -> Option<Rect> {
+        let bounds = self.target().canvas.bounds();
+        let clip = match self.clips.last() {
+            Some(clip) => snap(*clip).intersection(&bounds)?,
+            None => bounds,
+        };
+        (clip.width >= 1.0 && clip.height >= 1.0).then_some(clip)
+    }
+
+    /// Fills triangles with a solid color, blending each pixel once
+    fn fill_triangles(&mut self, positions: &[[f32; 2]], indices: &[u32], color: Color) {
+        let Some(scissor) = self.scissor() else {
+            return;
+        };
+        let Some(area) = mesh_bounds(positions).and_then(|bounds| snap(bounds).intersection(&scissor)) else {
+            return;
+        };
+        let samples = self.samples();
+        let (x0, y0) = (area.x as u32, area.y as u32);
+        let (width, height) = (area.width as u32, area.height as u32);
+
+        // One bit per sample, so shared edges are not blended twice
+        let mut coverage = vec![0u8; width as usize * height as usize];
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| positions[triangle[i] as usize]);
+            let Some(bounds) = mesh_bounds(&[a, b, c]).and_then(|bounds| snap(bounds).intersection(&area)) else {
+                continue;
+            };
+            for y in bounds.y as u32..bounds.bottom() as u32 {
+                for x in bounds.x as u32..bounds.right() as u32 {
+                    let cell = &mut coverage[((y - y0) * width + (x - x0)) as usize];
+                    for (bit, (sx, sy)) in samples.iter().enumerate() {
+                        let sample = [x as f32 + sx + TIE_BREAK, y as f32 + sy + TIE_BREAK];
+                        if in_triangle(sample, a, b, c) {
+                            *cell |= 1 << bit;
+                        }
+                    }
+                }
+            }
+        }
+
+        let src = premultiply(color);
+        let canvas = &mut self.targets.last_mut().unwrap().canvas;
+        for y in 0..height {
+            for x in 0..width {
+                let bits = coverage[(y * width + x) as usize];
+                if bits != 0 {
+                    let amount = bits.count_ones() as f32 / samples.len() as f32;
+                    canvas.blend(x0 + x, y0 + y, src.map(|channel| channel * amount));
+                }
+            }
+        }
+    }
+
+    /// Fills a rectangle with colors computed at each pixel center
+    fn fill_shaded(&mut self, rect: Rect, shade: impl Fn(f32, f32) -> Pixel) {
+        let Some(scissor) = self.scissor() else {
+            return;
+        };
+        let Some(area) = snap(rect).intersection(&scissor) else {
+            return;
+        };
+        let samples = self.samples();
+        let canvas = &mut self.targets.last_mut().unwrap().canvas;
+        for y in area.y as u32..area.bottom() as u32 {
+            for x in area.x as u32..area.right() as u32 {
+                let covered = samples
+                    .iter()
+                    .filter(|(sx, sy)| {
+                        let (px, py) = (x as f32 + sx, y as f32 + sy);
+                        px >= rect.x && px < rect.right() && py >= rect.y && py < rect.bottom()
+                    })
+                    .count();
+                if covered > 0 {
+                    let amount = covered as f32 / samples.len() as f32;
+                    let src = shade(x as f32 + 0.5, y as f32 + 0.5);
+                    canvas.blend(x, y, src.map(|channel| channel * amount));
+                }
+            }
+        }
+    }
+
+    /// Returns the rasterized glyph for a character at a size
+    fn glyph(&mut self, ch: char, font_size: f32) -> &(Metrics, Vec<u8>) {
+        let font = &self.font;
+        self.glyphs
+            .entry((ch, font_size.to_bits()))
+            .or_insert_with(|| font.rasterize(ch, font_size))
+    }
+}
+
+impl RenderBackend for CpuBackend {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.scale
+    }
+
+    fn begin_frame(&mut self, background: Color) {
+        let mut canvas = Canvas::new(self.width, self.height);
+        canvas.pixels.fill(premultiply(background));
+        self.targets = vec![Target { canvas, layer: None }];
+        self.clips.clear();
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let rect = rect.scale(self.scale);
+        let positions = [
+            [rect.x, rect.y],
+            [rect.right(), rect.y],
+            [rect.right(), rect.bottom()],
+            [rect.x, rect.bottom()],
+        ];
+        self.fill_triangles(&positions, &[0, 1, 2, 0, 2, 3], color);
+    }
+
+    fn draw_path(&mut self, path: &Path, paint: &Paint, transform: &Transform) {
+        if paint.color.a <= 0.0 {
+            return;
+        }
+        let mesh = tessellate(path, paint, &transform.then(&Transform::scale(self.scale, self.scale)));
+        if !mesh.is_empty() {
+            self.fill_triangles(&mesh.positions, &mesh.indices, paint.color);
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, position: Point, font_size: f32, color: Color) {
+        // Same layout as the GPU text renderer, at the physical size
+        let font_size = font_size * self.scale;
+        let position = position.scale(self.scale);
+        let ascent = self
+            .font
+            .horizontal_line_metrics(font_size)
+            .map(|metrics| metrics.ascent)
+            .unwrap_or(font_size);
+        let baseline = position.y + ascent;
+        let mut cursor_x = position.x;
+
+        for ch in text.chars() {
+            let (metrics, bitmap) = self.glyph(ch, font_size).clone();
+            if metrics.width > 0 && metrics.height > 0 {
+                let quad = Rect::new(
+                    cursor_x + metrics.xmin as f32,
+                    baseline - (metrics.ymin + metrics.height as i32) as f32,
+                    metrics.width as f32,
+                    metrics.height as f32,
+                );
+                let src = premultiply(color);
+                self.fill_shaded(quad, |x, y| {
+                    let coverage = sample_coverage(&bitmap, metrics.width, metrics.height, x - quad.x, y - quad.y);
+                    src.map(|channel| channel * coverage)
+                });
+            }
+            cursor_x += metrics.advance_width;
+        }
+    }
+
+    fn draw_image(&mut self, image: &ImageData, src: Rect, dst: Rect, tint: Option<Color>) {
+        let dst = dst.scale(self.scale);
+        if dst.width <= 0.0 || dst.height <= 0.0 || image.width() == 0 || image.height() == 0 {
+            return;
+        }
+        let tint = tint.unwrap_or(Color::WHITE);
+
+        // Keep bilinear sampling inside the image's texels, like the GPU
+        let clamp_u = |x: f32| x.clamp(0.5, (image.width() as f32 - 0.5).max(0.5));
+        let clamp_v = |y: f32| y.clamp(0.5, (image.height() as f32 - 0.5).max(0.5));
+        let (u0, v0, u1, v1) = (clamp_u(src.x), clamp_v(src.y), clamp_u(src.right()), clamp_v(src.bottom()));
+
+        self.fill_shaded(dst, |x, y| {
+            let u = u0 + (x - dst.x) / dst.width * (u1 - u0);
+            let v = v0 + (y - dst.y) / dst.height * (v1 - v0);
+            let texel = sample_image(image, u, v);
+            let color = [texel[0] * tint.r, texel[1] * tint.g, texel[2] * tint.b, texel[3] * tint.a];
+            [color[0] * color[3], color[1] * color[3], color[2] * color[3], color[3]]
+        });
+    }
+
+    fn push_clip(&mut self, rect: Rect) {
+        let rect = rect.scale(self.scale);
+        let clip = match self.clips.last() {
+            Some(parent) => parent.intersection(&rect).unwrap_or(Rect::new(rect.x, rect.y, 0.0, 0.0)),
+            None => rect,
+        };
+        self.clips.push(clip);
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn backdrop_filter(&mut self, rect: Rect, filter: &ImageFilter) {
+        let rect = rect.scale(self.scale);
+        let filter = filter.scale(self.scale);
+        let Some(visible) = self.scissor() else {
+            return;
+        };
+        let Some(clip) = snap(rect).intersection(&visible) else {
+            return;
+        };
+        let canvas = &mut self.targets.last_mut().unwrap().canvas;
+        let (ox, oy) = filter.outset();
+        let Some(source) = expand(clip, ox, oy).intersection(&canvas.bounds()).map(snap) else {
+            return;
+        };
+
+        let filtered = apply_filter(canvas, source, &filter);
+        for y in clip.y as u32..clip.bottom() as u32 {
+            for x in clip.x as u32..clip.right() as u32 {
+                canvas.set(x, y, filtered.get(x - source.x as u32, y - source.y as u32));
+            }
+        }
+    }
+
+    fn push_layer(&mut self, bounds: Rect, filter: &ImageFilter) {
+        let size = self.target().canvas.size();
+        self.targets.push(Target {
+            canvas: Canvas::new(size.width as u32, size.height as u32),
+            layer: Some((bounds.scale(self.scale), filter.scale(self.scale))),
+        });
+    }
+
+    fn pop_layer(&mut self) {
+        if self.targets.len() < 2 || self.targets.last().is_none_or(|target| target.layer.is_none()) {
+            return;
+        }
+        let layer = self.targets.pop().unwrap();
+        let Some((bounds, filter)) = &layer.layer else {
+            return;
+        };
+        let (ox, oy) = filter.outset();
+        let Some(region) = expand(*bounds, ox, oy).intersection(&layer.canvas.bounds()).map(snap) else {
+            return;
+        };
+        let Some(clip) = self.scissor().and_then(|visible| region.intersection(&visible)) else {
+            return;
+        };
+
+        let filtered = apply_filter(&layer.canvas, region, filter);
+        let parent = &mut self.targets.last_mut().unwrap().canvas;
+        for y in clip.y as u32..clip.bottom() as u32 {
+            for x in clip.x as u32..clip.right() as u32 {
+                parent.blend(x, y, filtered.get(x - region.x as u32, y - region.y as u32));
+            }
+        }
+    }
+
+    fn draw_boundary(&mut self, _id: u64, _version: u64, rect: Rect, list: &Arc<DisplayList>) {
+        // Nothing is cached: rasterizing again is as cheap as compositing
+        let rect = rect.scale(self.scale);
+        let (width, height) = (rect.width.ceil() as u32, rect.height.ceil() as u32);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        // Nested lists start with no clip; the composite below applies ours
+        let clips = std::mem::take(&mut self.clips);
+        let depth = self.targets.len();
+        self.targets.push(Target {
+            canvas: Canvas::new(width, height),
+            layer: None,
+        });
+        self.draw_list(list);
+        while self.targets.len() > depth + 1 {
+            self.pop_layer();
+        }
+        let boundary = self.targets.pop().unwrap();
+        self.clips = clips;
+
+        // Whole-pixel placement, as for cached boundary textures
+        let dst = Rect::new(rect.x.round(), rect.y.round(), width as f32, height as f32);
+        let Some(clip) = self.scissor().and_then(|visible| dst.intersection(&visible)) else {
+            return;
+        };
+        let canvas = &mut self.targets.last_mut().unwrap().canvas;
+        for y in clip.y as u32..clip.bottom() as u32 {
+            for x in clip.x as u32..clip.right() as u32 {
+                let pixel = boundary.canvas.get((x as f32 - dst.x) as u32, (y as f32 - dst.y) as u32);
+                canvas.blend(x, y, pixel);
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<Vec<u8>, KobaltError> {
+        // Close any unbalanced layers
+        while self.targets.len() > 1 {
+            self.pop_layer();
+        }
+        let Some(frame) = self.targets.pop() else {
+            return Ok(vec![0; self.width as usize * self.height as usize * 4]);
+        };
+        self.clips.clear();
+
+        let mut bytes = Vec::with_capacity(frame.canvas.pixels.len() * 4);
+        for pixel in &frame.canvas.pixels {
+            bytes.extend_from_slice(&[
+                to_unorm8(linear_to_srgb(pixel[0])),
+                to_unorm8(linear_to_srgb(pixel[1])),
+                to_unorm8(linear_to_srgb(pixel[2])),
+                to_unorm8(pixel[3]),
+            ]);
+        }
+        Ok(bytes)
+    }
+}
+
+/// Filters `source` (whole pixels) of a canvas into a new canvas of its size
+fn apply_filter(canvas: &Canvas, source: Rect, filter: &ImageFilter) -> Canvas {
+    let mut work = Canvas::new(source.width as u32, source.height as u32);
+    for y in 0..work.height {
+        for x in 0..work.width {
+            work.set(x, y, canvas.get(source.x as u32 + x, source.y as u32 + y));
+        }
+    }
+
+    for pass in FilterPass::expand(filter) {
+        work = match pass {
+            FilterPass::Blur { horizontal, sigma } => blur(&work, horizontal, sigma),
+            FilterPass::Matrix(matrix) => {
+                for pixel in &mut work.pixels {
+                    let alpha = pixel[3];
+                    let straight = if alpha > 0.0 {
+                        Color::new(pixel[0] / alpha, pixel[1] / alpha, pixel[2] / alpha, alpha)
+                    } else {
+                        Color::new(pixel[0], pixel[1], pixel[2], alpha)
+                    };
+                    *pixel = premultiply(matrix.apply(straight));
+                }
+                work
+            }
+        };
+    }
+    work
+}
+
+/// One pass of the separable Gaussian blur, clamping at the edges
+fn blur(source: &Canvas, horizontal: bool, sigma: f32) -> Canvas {
+    let (radius, packed) = gaussian_weights(sigma);
+    let weight = |i: u32| packed[i as usize / 4][i as usize % 4];
+    let mut output = Canvas::new(source.width, source.height);
+    for y in 0..source.height {
+        for x in 0..source.width {
+            let tap = |offset: i64| {
+                if horizontal {
+                    let tx = (x as i64 + offset).clamp(0, source.width as i64 - 1) as u32;
+                    source.get(tx, y)
+                } else {
+                    let ty = (y as i64 + offset).clamp(0, source.height as i64 - 1) as u32;
+                    source.get(x, ty)
+                }
+            };
+            let mut sum = tap(0).map(|channel| channel * weight(0));
+            for i in 1..=radius {
+                let (ahead, behind) = (tap(i as i64), tap(-(i as i64)));
+                for channel in 0..4 {
+                    sum[channel] += (ahead[channel] + behind[channel]) * weight(i);
+                }
+            }
+            output.set(x, y, sum);
+        }
+    }
+    output
+}
+
+/// Returns the bounding box of a set of points
+fn mesh_bounds(positions: &[[f32; 2]]) -> Option<Rect> {
+    let first = positions.first()?;
+    let (mut min, mut max) = (*first, *first);
+    for [x, y] in positions {
+        min = [min[0].min(*x), min[1].min(*y)];
+        max = [max[0].max(*x), max[1].max(*y)];
+    }
+    Some(Rect::new(min[0], min[1], max[0] - min[0], max[1] - min[1]))
+}
+
+/// Returns true if `p` is inside the triangle (either winding)
+fn in_triangle(p: [f32; 2], a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> bool {
+    let edge = |from: [f32; 2], to: [f32; 2]| (to[0] - from[0]) * (p[1] - from[1]) - (to[1] - from[1]) * (p[0] - from[0]);
+    let (ab, bc, ca) = (edge(a, b), edge(b, c), edge(c, a));
+    (ab >= 0.0 && bc >= 0.0 && ca >= 0.0) || (ab <= 0.0 && bc <= 0.0 && ca <= 0.0)
+}
+
+/// Bilinearly samples an 8-bit coverage bitmap at a point relative to its
+/// top-left corner, reading zero outside it
+fn sample_coverage(bitmap: &[u8], width: usize, height: usize, x: f32, y: f32) -> f32 {
+    let texel = |tx: i64, ty: i64| {
+        if tx < 0 || ty < 0 || tx >= width as i64 || ty >= height as i64 {
+            0.0
+        } else {
+            bitmap[ty as usize * width + tx as usize] as f32 / 255.0
+        }
+    };
+    bilinear(x - 0.5, y - 0.5, |tx, ty| [texel(tx, ty), 0.0, 0.0, 0.0])[0]
+}
+
+/// Bilinearly samples an image at texel coordinates, returning a straight
+/// linear color
+fn sample_image(image: &ImageData, u: f32, v: f32) -> Pixel {
+    let (width, height) = (image.width() as i64, image.height() as i64);
+    let pixels = image.pixels();
+    bilinear(u - 0.5, v - 0.5, |tx, ty| {
+        let index = (ty.clamp(0, height - 1) * width + tx.clamp(0, width - 1)) as usize * 4;
+        [
+            srgb_to_linear(pixels[index] as f32 / 255.0),
+            srgb_to_linear(pixels[index + 1] as f32 / 255.0),
+            srgb_to_linear(pixels[index + 2] as f32 / 255.0),
+            pixels[index + 3] as f32 / 255.0,
+        ]
+    })
+}
+
+/// Interpolates the four texels around `(x, y)`, where texel `(i, j)` has
+/// its center at `(i, j)`
+fn bilinear(x: f32, y: f32, texel: impl Fn(i64, i64) -> Pixel) -> Pixel {
+    let (fx, fy) = (x.floor(), y.floor());
+    let (tx, ty) = (x - fx, y - fy);
+    let (ix, iy) = (fx as i64, fy as i64);
+    let [a, b, c, d] = [texel(ix, iy), texel(ix + 1, iy), texel(ix, iy + 1), texel(ix + 1, iy + 1)];
+    std::array::from_fn(|channel| {
+        let top = a[channel] + (b[channel] - a[channel]) * tx;
+        let bottom = c[channel] + (d[channel] - c[channel]) * tx;
+        top + (bottom - top) * ty
+    })
+}
+
+fn premultiply(color: Color) -> Pixel {
+    [color.r * color.a, color.g * color.a, color.b * color.a, color.a]
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn to_unorm8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kobalt_core::paint::{ColorMatrix, DrawCommand};
+
+    fn pixel(bytes: &[u8], width: u32, x: u32, y: u32) -> [u8; 4] {
+        let index = ((y * width + x) * 4) as usize;
+        bytes[index..index + 4].try_into().unwrap()
+    }
+
+    fn render(width: u32, height: u32, draw: impl FnOnce(&mut CpuBackend)) -> Vec<u8> {
+        let mut backend = CpuBackend::new(width, height, 1.0).unwrap();
+        backend.begin_frame(Color::WHITE);
+        draw(&mut backend);
+        backend.finish().unwrap()
+    }
+
+    #[test]
+    fn test_blends_in_linear_space() {
+        let bytes = render(2, 1, |backend| {
+            backend.fill_rect(Rect::new(0.0, 0.0, 1.0, 1.0), Color::new(0.0, 0.0, 0.0, 0.5));
+        });
+        // Half of linear white, encoded as sRGB
+        assert_eq!(pixel(&bytes, 2, 0, 0), [188, 188, 188, 255]);
+        assert_eq!(pixel(&bytes, 2, 1, 0), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_pixel_centers_decide_coverage() {
+        let rect = Rect::new(0.0, 0.0, 1.4, 1.0);
+        let aliased = render(2, 1, |backend| backend.fill_rect(rect, Color::BLACK));
+        assert_eq!(pixel(&aliased, 2, 1, 0), [255, 255, 255, 255]);
+
+        let mut backend = CpuBackend::new(2, 1, 1.0).unwrap().anti_alias(true);
+        let smooth = backend.render(&display_list(rect), Color::WHITE).unwrap();
+        // Two of four samples (x = 1.125 and 1.375) are covered
+        assert_eq!(pixel(&smooth, 2, 0, 0), [0, 0, 0, 255]);
+        assert_eq!(pixel(&smooth, 2, 1, 0), [188, 188, 188, 255]);
+    }
+
+    fn display_list(rect: Rect) -> DisplayList {
+        let mut list = DisplayList::new();
+        list.push(DrawCommand::Rect {
+            rect,
+            color: Color::BLACK,
+        });
+        list
+    }
+
+    #[test]
+    fn test_clip_and_scale_factor() {
+        let mut backend = CpuBackend::new(4, 4, 2.0).unwrap();
+        backend.begin_frame(Color::WHITE);
+        backend.push_clip(Rect::new(0.0, 0.0, 1.0, 2.0));
+        backend.fill_rect(Rect::new(0.0, 0.0, 2.0, 2.0), Color::BLACK);
+        backend.pop_clip();
+        let bytes = backend.finish().unwrap();
+
+        assert_eq!(pixel(&bytes, 4, 1, 3), [0, 0, 0, 255]);
+        assert_eq!(pixel(&bytes, 4, 2, 0), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_rounded_rect_leaves_corners() {
+        let bytes = render(20, 20, |backend| {
+            backend.fill_rounded_rect(Rect::new(0.0, 0.0, 20.0, 20.0), 8.0, Color::BLACK);
+        });
+        assert_eq!(pixel(&bytes, 20, 0, 0), [255, 255, 255, 255]);
+        assert_eq!(pixel(&bytes, 20, 10, 10), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_text_draws_glyphs() {
+        let bytes = render(40, 24, |backend| {
+            backend.draw_text("Hi", Point::new(2.0, 2.0), 16.0, Color::BLACK);
+        });
+        assert!(bytes.chunks(4).any(|pixel| pixel[0] < 128));
+    }
+
+    #[test]
+    fn test_image_with_tint() {
+        let image = ImageData::from_rgba8(1, 1, vec![255, 255, 255, 255]).unwrap();
+        let bytes = render(2, 2, |backend| {
+            backend.draw_image(&image, Rect::new(0.0, 0.0, 1.0, 1.0), Rect::new(0.0, 0.0, 1.0, 2.0), Some(Color::RED));
+        });
+        assert_eq!(pixel(&bytes, 2, 0, 1), [255, 0, 0, 255]);
+        assert_eq!(pixel(&bytes, 2, 1, 1), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_layer_filter_is_applied() {
+        let bytes = render(2, 1, |backend| {
+            backend.push_layer(Rect::new(0.0, 0.0, 2.0, 1.0), &ImageFilter::color_matrix(ColorMatrix::grayscale()));
+            backend.fill_rect(Rect::new(0.0, 0.0, 1.0, 1.0), Color::RED);
+            backend.pop_layer();
+        });
+        let [r, g, b, _] = pixel(&bytes, 2, 0, 0);
+        assert!(r == g && g == b && r < 255);
+    }
+
+    #[test]
+    fn test_blur_spreads_into_neighbors() {
+        let bytes = render(9, 1, |backend| {
+            backend.fill_rect(Rect::new(4.0, 0.0, 1.0, 1.0), Color::BLACK);
+            backend.backdrop_filter(Rect::new(0.0, 0.0, 9.0, 1.0), &ImageFilter::blur(1.0, 1.0));
+        });
+        assert!(pixel(&bytes, 9, 3, 0)[0] < 255);
+        assert!(pixel(&bytes, 9, 4, 0)[0] > 0);
+        assert_eq!(pixel(&bytes, 9, 0, 0)[0], 255);
+    }
+
+    #[test]
+    fn test_boundary_is_drawn_at_its_rect() {
+        let bytes = render(4, 1, |backend| {
+            let list = Arc::new(display_list(Rect::new(0.0, 0.0, 1.0, 1.0)));
+            backend.draw_boundary(1, 1, Rect::new(2.0, 0.0, 2.0, 1.0), &list);
+        });
+        assert_eq!(pixel(&bytes, 4, 1, 0), [255, 255, 255, 255]);
+        assert_eq!(pixel(&bytes, 4, 2, 0), [0, 0, 0, 255]);
+        assert_eq!(pixel(&bytes, 4, 3, 0), [255, 255, 255, 255]);
+    }
+}
@@ -0,0 +1,646 @@
+//! Image filter passes (Gaussian blur, color matrix)
+//!
+//! Filters read a region of a source texture and write the filtered result
+//! into a region of a target texture. Multi-pass filters (the separable blur
+//! and composed filters) ping-pong between two scratch textures owned by the
+//! `FilterRenderer`, so only the final pass touches the target.
+//!
+//! When the scene is multisampled, the final pass renders into the target's
+//! multisampled texture and resolves into the target, so the filtered pixels
+//! are kept by later passes into the same target.
+
+use bytemuck::{Pod, Zeroable};
+use kobalt_core::paint::{ColorMatrix, ImageFilter};
+use kobalt_core::types::{Color, Rect, Size};
+use wgpu::util::DeviceExt;
+
+/// Maximum number of blur taps on each side of the center sample
+///
+/// Larger sigmas are clamped so that `3 * sigma` fits within this radius.
+pub const MAX_BLUR_RADIUS: u32 = 63;
+
+/// How a filter's output is combined with the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterBlend {
+    /// Overwrite the target pixels (used for backdrop filters)
+    Replace,
+    /// Composite premultiplied output over the target (used for layers)
+    PremultipliedOver,
+}
+
+/// Where a filter reads its input from
+pub enum FilterInput<'a> {
+    /// The first scratch texture, previously filled with `scratch_texture`
+    Scratch,
+    /// An arbitrary texture of the given size
+    Texture {
+        view: &'a wgpu::TextureView,
+        size: Size,
+    },
+}
+
+/// Where a filter writes its output
+pub struct FilterTarget<'a> {
+    /// The texture view rendered into
+    pub view: &'a wgpu::TextureView,
+    /// The single-sample view `view` is resolved into, if `view` is
+    /// multisampled
+    pub resolve_target: Option<&'a wgpu::TextureView>,
+    /// The size of the target in pixels
+    pub size: Size,
+}
+
+/// Uniform block shared by all filter passes
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct FilterUniforms {
+    /// Destination rectangle in NDC (x0, y0, x1, y1)
+    dst_rect: [f32; 4],
+    /// Source rectangle in texture coordinates (u0, v0, u1, v1)
+    src_rect: [f32; 4],
+    /// Sampling limits so taps never read outside the source rectangle
+    uv_clamp: [f32; 4],
+    /// Offset between two blur taps in texture coordinates
+    step: [f32; 2],
+    radius: u32,
+    _padding: u32,
+    weights: [[f32; 4]; 16],
+    matrix: [[f32; 4]; 5],
+}
+
+/// A single GPU pass of a filter
+pub(crate) enum FilterPass {
+    Blur { horizontal: bool, sigma: f32 },
+    Matrix(ColorMatrix),
+}
+
+impl FilterPass {
+    /// Expands an image filter into the passes that implement it
+    pub(crate) fn expand(filter: &ImageFilter) -> Vec<FilterPass> {
+        let mut passes = Vec::new();
+        for pass in filter.passes() {
+            match pass {
+                ImageFilter::Blur { sigma_x, sigma_y } => {
+                    if *sigma_x > 0.0 {
+                        passes.push(FilterPass::Blur { horizontal: true, sigma: *sigma_x });
+                    }
+                    if *sigma_y > 0.0 {
+                        passes.push(FilterPass::Blur { horizontal: false, sigma: *sigma_y });
+                    }
+                }
+                ImageFilter::ColorMatrix(matrix) => passes.push(FilterPass::Matrix(*matrix)),
+                ImageFilter::Compose { .. } => unreachable!("passes() flattens composed filters"),
+            }
+        }
+        if passes.is_empty() {
+            passes.push(FilterPass::Matrix(ColorMatrix::identity()));
+        }
+        passes
+    }
+}
+
+/// Computes normalized one-sided Gaussian weights for the given sigma
+///
+/// Returns the radius (number of taps on each side) and the packed weights,
+/// where `weights[0]` is the center tap.
+pub(crate) fn gaussian_weights(sigma: f32) -> (u32, [[f32; 4]; 16]) {
+    let radius = ((sigma * 3.0).ceil() as u32).clamp(1, MAX_BLUR_RADIUS);
+    let sigma = sigma.min(MAX_BLUR_RADIUS as f32 / 3.0).max(f32::EPSILON);
+
+    let mut flat = [0.0f32; 64];
+    let mut total = 0.0;
+    for (i, weight) in flat.iter_mut().enumerate().take(radius as usize + 1) {
+        let x = i as f32;
+        *weight = (-(x * x) / (2.0 * sigma * sigma)).exp();
+        total += if i == 0 { *weight } else { 2.0 * *weight };
+    }
+
+    let mut packed = [[0.0f32; 4]; 16];
+    for (i, weight) in flat.iter().enumerate() {
+        packed[i / 4][i % 4] = weight / total;
+    }
+    (radius, packed)
+}
+
+/// Packs a 4x5 color matrix into four rows plus an offset vector
+fn pack_matrix(matrix: &ColorMatrix) -> [[f32; 4]; 5] {
+    let m = &matrix.0;
+    [
+        [m[0], m[1], m[2], m[3]],
+        [m[5], m[6], m[7], m[8]],
+        [m[10], m[11], m[12], m[13]],
+        [m[15], m[16], m[17], m[18]],
+        [m[4], m[9], m[14], m[19]],
+    ]
+}
+
+/// The filter pipelines for one sample count
+struct FilterPipelines {
+    blur_replace: wgpu::RenderPipeline,
+    blur_over: wgpu::RenderPipeline,
+    matrix_replace: wgpu::RenderPipeline,
+    matrix_over: wgpu::RenderPipeline,
+}
+
+impl FilterPipelines {
+    /// Returns the pipeline for a pass and blend mode
+    fn get(&self, pass: &FilterPass, blend: FilterBlend) -> &wgpu::RenderPipeline {
+        match (pass, blend) {
+            (FilterPass::Blur { .. }, FilterBlend::Replace) => &self.blur_replace,
+            (FilterPass::Blur { .. }, FilterBlend::PremultipliedOver) => &self.blur_over,
+            (FilterPass::Matrix(_), FilterBlend::Replace) => &self.matrix_replace,
+            (FilterPass::Matrix(_), FilterBlend::PremultipliedOver) => &self.matrix_over,
+        }
+    }
+}
+
+/// Renderer for image filter passes
+pub struct FilterRenderer {
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    /// Pipelines for scratch textures and single-sample targets
+    pipelines: FilterPipelines,
+    /// Pipelines for multisampled targets (None without MSAA)
+    multisampled: Option<FilterPipelines>,
+    format: wgpu::TextureFormat,
+    scratch: Vec<wgpu::Texture>,
+    scratch_size: Size,
+}
+
+impl FilterRenderer {
+    /// Creates a new filter renderer for targets of the given format and
+    /// sample count
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, sample_count: u32) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Filter Shader"),
+            source: wgpu::ShaderSource::Wgsl(FILTER_SHADER.into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Filter Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Filter Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Filter Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let create_pipeline = |entry_point: &str, blend: wgpu::BlendState, sample_count: u32| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Filter Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(entry_point),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(blend),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let create_pipelines = |sample_count: u32| FilterPipelines {
+            blur_replace: create_pipeline("fs_blur", wgpu::BlendState::REPLACE, sample_count),
+            blur_over: create_pipeline(
+                "fs_blur",
+                wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+                sample_count,
+            ),
+            matrix_replace: create_pipeline("fs_color_matrix", wgpu::BlendState::REPLACE, sample_count),
+            matrix_over: create_pipeline(
+                "fs_color_matrix",
+                wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+                sample_count,
+            ),
+        };
+
+        Self {
+            pipelines: create_pipelines(1),
+            multisampled: (sample_count > 1).then(|| create_pipelines(sample_count)),
+            bind_group_layout,
+            sampler,
+            format,
+            scratch: Vec::new(),
+            scratch_size: Size::zero(),
+        }
+    }
+
+    /// Ensures both scratch textures exist and are at least `size` large
+    fn ensure_scratch(&mut self, device: &wgpu::Device, size: Size) {
+        if !self.scratch.is_empty()
+            && self.scratch_size.width >= size.width
+            && self.scratch_size.height >= size.height
+        {
+            return;
+        }
+
+        let size = Size::new(
+            size.width.max(self.scratch_size.width).max(1.0),
+            size.height.max(self.scratch_size.height).max(1.0),
+        );
+        self.scratch = (0..2)
+            .map(|_| {
+                device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Filter Scratch Texture"),
+                    size: wgpu::Extent3d {
+                        width: size.width as u32,
+                        height: size.height as u32,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: self.format,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::COPY_DST,
+                    view_formats: &[],
+                })
+            })
+            .collect();
+        self.scratch_size = size;
+    }
+
+    /// Returns the first scratch texture, sized to hold at least `size`
+    ///
+    /// Callers copy backdrop pixels into its top-left corner and then call
+    /// `apply` with `FilterInput::Scratch`.
+    pub fn scratch_texture(&mut self, device: &wgpu::Device, size: Size) -> &wgpu::Texture {
+        self.ensure_scratch(device, size);
+        &self.scratch[0]
+    }
+
+    /// Applies `filter` to `src_rect` of the input and writes the result to
+    /// `dst_rect` of the target, limited to `clip`
+    ///
+    /// All rectangles are in pixels. `src_rect` and `dst_rect` must have the
+    /// same size.
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        input: FilterInput<'_>,
+        src_rect: Rect,
+        target: FilterTarget<'_>,
+        dst_rect: Rect,
+        clip: Rect,
+        filter: &ImageFilter,
+        blend: FilterBlend,
+    ) {
+        let passes = FilterPass::expand(filter);
+        if passes.len() > 1 || matches!(input, FilterInput::Scratch) {
+            self.ensure_scratch(device, src_rect.size());
+        }
+
+        let scratch_views: Vec<wgpu::TextureView> = self
+            .scratch
+            .iter()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()))
+            .collect();
+
+        let (mut source_view, mut source_size, mut current_scratch) = match input {
+            FilterInput::Scratch => (&scratch_views[0], self.scratch_size, Some(0)),
+            FilterInput::Texture { view, size } => (view, size, None),
+        };
+        let mut source_rect = src_rect;
+        let work_rect = Rect::new(0.0, 0.0, src_rect.width, src_rect.height);
+
+        for (index, pass) in passes.iter().enumerate() {
+            if index + 1 == passes.len() {
+                self.draw_pass(
+                    device,
+                    encoder,
+                    (source_view, source_size, source_rect),
+                    (&target, dst_rect),
+                    Some(clip),
+                    pass,
+                    blend,
+                );
+            } else {
+                let next = if current_scratch == Some(0) { 1 } else { 0 };
+                self.draw_pass(
+                    device,
+                    encoder,
+                    (source_view, source_size, source_rect),
+                    (
+                        &FilterTarget {
+                            view: &scratch_views[next],
+                            resolve_target: None,
+                            size: self.scratch_size,
+                        },
+                        work_rect,
+                    ),
+                    None,
+                    pass,
+                    FilterBlend::Replace,
+                );
+                source_view = &scratch_views[next];
+                source_size = self.scratch_size;
+                source_rect = work_rect;
+                current_scratch = Some(next);
+            }
+        }
+    }
+
+    /// Records a single filter pass
+    #[allow(clippy::too_many_arguments)]
+    fn draw_pass(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        (src_view, src_size, src_rect): (&wgpu::TextureView, Size, Rect),
+        (target, dst_rect): (&FilterTarget<'_>, Rect),
+        clip: Option<Rect>,
+        pass: &FilterPass,
+        blend: FilterBlend,
+    ) {
+        let dst_size = target.size;
+        let target_bounds = Rect::new(0.0, 0.0, dst_size.width, dst_size.height);
+        let scissor = match clip.unwrap_or(dst_rect).intersection(&target_bounds) {
+            Some(scissor) => scissor,
+            None => return,
+        };
+        let sx = scissor.x.floor().max(0.0);
+        let sy = scissor.y.floor().max(0.0);
+        let sw = (scissor.right().ceil().min(dst_size.width) - sx) as u32;
+        let sh = (scissor.bottom().ceil().min(dst_size.height) - sy) as u32;
+        if sw == 0 || sh == 0 {
+            return;
+        }
+
+        let mut uniforms = FilterUniforms {
+            dst_rect: [
+                dst_rect.x / dst_size.width * 2.0 - 1.0,
+                1.0 - dst_rect.y / dst_size.height * 2.0,
+                dst_rect.right() / dst_size.width * 2.0 - 1.0,
+                1.0 - dst_rect.bottom() / dst_size.height * 2.0,
+            ],
+            src_rect: [
+                src_rect.x / src_size.width,
+                src_rect.y / src_size.height,
+                src_rect.right() / src_size.width,
+                src_rect.bottom() / src_size.height,
+            ],
+            uv_clamp: [
+                (src_rect.x + 0.5) / src_size.width,
+                (src_rect.y + 0.5) / src_size.height,
+                (src_rect.right() - 0.5) / src_size.width,
+                (src_rect.bottom() - 0.5) / src_size.height,
+            ],
+            step: [0.0, 0.0],
+            radius: 0,
+            _padding: 0,
+            weights: [[0.0; 4]; 16],
+            matrix: pack_matrix(&ColorMatrix::identity()),
+        };
+
+        match pass {
+            FilterPass::Blur { horizontal, sigma } => {
+                let (radius, weights) = gaussian_weights(*sigma);
+                uniforms.radius = radius;
+                uniforms.weights = weights;
+                uniforms.step = if *horizontal {
+                    [1.0 / src_size.width, 0.0]
+                } else {
+                    [0.0, 1.0 / src_size.height]
+                };
+            }
+            FilterPass::Matrix(matrix) => uniforms.matrix = pack_matrix(matrix),
+        }
+        let pipelines = match (&self.multisampled, target.resolve_target) {
+            (Some(multisampled), Some(_)) => multisampled,
+            _ => &self.pipelines,
+        };
+        let pipeline = pipelines.get(pass, blend);
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Filter Uniform Buffer"),
+            contents: bytemuck::bytes_of(&uniforms),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Filter Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(src_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let load = if clip.is_none() {
+            wgpu::LoadOp::Clear(to_wgpu_color(Color::TRANSPARENT))
+        } else {
+            wgpu::LoadOp::Load
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Filter Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target.view,
+                resolve_target: target.resolve_target,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.set_scissor_rect(sx as u32, sy as u32, sw, sh);
+        render_pass.draw(0..6, 0..1);
+    }
+}
+
+/// Converts a Kobalt color to a WGPU clear color
+pub(crate) fn to_wgpu_color(color: Color) -> wgpu::Color {
+    wgpu::Color {
+        r: color.r as f64,
+        g: color.g as f64,
+        b: color.b as f64,
+        a: color.a as f64,
+    }
+}
+
+const FILTER_SHADER: &str = r#"
+struct Uniforms {
+    dst_rect: vec4<f32>,
+    src_rect: vec4<f32>,
+    uv_clamp: vec4<f32>,
+    step: vec2<f32>,
+    radius: u32,
+    padding: u32,
+    weights: array<vec4<f32>, 16>,
+    matrix: array<vec4<f32>, 5>,
+}
+
+@group(0) @binding(0)
+var src_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var src_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> u: Uniforms;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+    );
+    let corner = corners[index];
+
+    var output: VertexOutput;
+    output.clip_position = vec4<f32>(mix(u.dst_rect.xy, u.dst_rect.zw, corner), 0.0, 1.0);
+    output.uv = mix(u.src_rect.xy, u.src_rect.zw, corner);
+    return output;
+}
+
+fn sample_clamped(uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(src_texture, src_sampler, clamp(uv, u.uv_clamp.xy, u.uv_clamp.zw), 0.0);
+}
+
+fn weight(i: u32) -> f32 {
+    return u.weights[i / 4u][i % 4u];
+}
+
+@fragment
+fn fs_blur(input: VertexOutput) -> @location(0) vec4<f32> {
+    var color = sample_clamped(input.uv) * weight(0u);
+    for (var i = 1u; i <= u.radius; i = i + 1u) {
+        let offset = u.step * f32(i);
+        color = color + (sample_clamped(input.uv + offset) + sample_clamped(input.uv - offset)) * weight(i);
+    }
+    return color;
+}
+
+@fragment
+fn fs_color_matrix(input: VertexOutput) -> @location(0) vec4<f32> {
+    let color = sample_clamped(input.uv);
+    var rgb = color.rgb;
+    if (color.a > 0.0) {
+        rgb = color.rgb / color.a;
+    }
+    let straight = vec4<f32>(rgb, color.a);
+    let result = clamp(
+        vec4<f32>(
+            dot(u.matrix[0], straight),
+            dot(u.matrix[1], straight),
+            dot(u.matrix[2], straight),
+            dot(u.matrix[3], straight),
+        ) + u.matrix[4],
+        vec4<f32>(0.0),
+        vec4<f32>(1.0),
+    );
+    return vec4<f32>(result.rgb * result.a, result.a);
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gaussian_weights_are_normalized() {
+        let (radius, packed) = gaussian_weights(4.0);
+        assert_eq!(radius, 12);
+
+        let flat: Vec<f32> = packed.iter().flatten().copied().collect();
+        let total: f32 = flat[0] + 2.0 * flat[1..=radius as usize].iter().sum::<f32>();
+        assert!((total - 1.0).abs() < 1e-5);
+        assert!(flat[0] > flat[1]);
+    }
+
+    #[test]
+    fn test_gaussian_radius_is_clamped() {
+        let (radius, _) = gaussian_weights(100.0);
+        assert_eq!(radius, MAX_BLUR_RADIUS);
+    }
+
+    #[test]
+    fn test_expand_blur_into_separable_passes() {
+        assert_eq!(FilterPass::expand(&ImageFilter::blur(2.0, 3.0)).len(), 2);
+        assert_eq!(FilterPass::expand(&ImageFilter::blur(2.0, 0.0)).len(), 1);
+        assert_eq!(FilterPass::expand(&ImageFilter::blur(0.0, 0.0)).len(), 1);
+    }
+}
@@ -0,0 +1,289 @@
+//! Golden image tests
+//!
+//! A golden test lays out and paints a widget with the software backend
+//! and compares the result with a PNG stored next to the tests. Pixels may
+//! differ by a small per-channel tolerance. On a mismatch the actual image
+//! and a diff (differing pixels in red over a faded copy of the golden) are
+//! written to a `failures` directory beside the goldens.
+//!
+//! Set `KOBALT_UPDATE_GOLDENS=1` to write the goldens instead of comparing,
+//! e.g. after an intended visual change.
+
+use crate::backend::RenderBackend;
+use crate::cpu::CpuBackend;
+use crate::readback::{decode_png, save_png};
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Color, Rect, Size};
+use kobalt_core::widget::Widget;
+use std::path::PathBuf;
+
+/// Environment variable that makes golden tests write their goldens
+pub const UPDATE_GOLDENS_VAR: &str = "KOBALT_UPDATE_GOLDENS";
+
+/// Largest per-channel difference accepted by default
+pub const DEFAULT_GOLDEN_TOLERANCE: u8 = 2;
+
+/// A widget snapshot compared against a stored PNG
+///
+/// Usually created by the `assert_golden!` macro, which stores goldens in
+/// the calling crate's `tests/goldens` directory.
+///
+/// # Example
+///
+/// ```no_run
+/// use kobalt_core::types::Size;
+/// use kobalt_render::GoldenTest;
+/// # fn widget() -> Box<dyn kobalt_core::widget::Widget> { unimplemented!() }
+///
+/// GoldenTest::new("tests/goldens", "card", Size::new(200.0, 100.0))
+///     .tolerance(4)
+///     .check(&*widget())
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct GoldenTest {
+    directory: PathBuf,
+    name: String,
+    size: Size,
+    scale_factor: f32,
+    tolerance: u8,
+    background: Color,
+}
+
+impl GoldenTest {
+    /// Creates a test for a widget of `size` logical pixels, stored as
+    /// `<directory>/<name>.png`
+    pub fn new(directory: impl Into<PathBuf>, name: &str, size: Size) -> Self {
+        Self {
+            directory: directory.into(),
+            name: name.to_string(),
+            size,
+            scale_factor: 1.0,
+            tolerance: DEFAULT_GOLDEN_TOLERANCE,
+            background: Color::WHITE,
+        }
+    }
+
+    /// Sets the scale factor the widget is rendered at
+    pub fn scale_factor(mut self, scale_factor: f32) -> Self {
+        self.scale_factor = scale_factor;
+        self
+    }
+
+    /// Sets the largest per-channel difference a pixel may have
+    pub fn tolerance(mut self, tolerance: u8) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets the color behind the widget
+    pub fn background(mut self, background: Color) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Returns the path of the golden PNG
+    pub fn golden_path(&self) -> PathBuf {
+        self.directory.join(format!("{}.png", self.name))
+    }
+
+    /// Renders the widget and compares it with the golden, or writes the
+    /// golden if `KOBALT_UPDATE_GOLDENS` is set
+    ///
+    /// Returns a description of the mismatch on failure.
+    pub fn check(&self, widget: &dyn Widget) -> Result<(), String> {
+        let update = std::env::var_os(UPDATE_GOLDENS_VAR).is_some_and(|value| value != "0");
+        self.check_with(widget, update)
+    }
+
+    fn check_with(&self, widget: &dyn Widget, update: bool) -> Result<(), String> {
+        let width = (self.size.width * self.scale_factor).round().max(1.0) as u32;
+        let height = (self.size.height * self.scale_factor).round().max(1.0) as u32;
+        let actual = self.render(widget, width, height)?;
+        let golden_path = self.golden_path();
+
+        if update {
+            std::fs::create_dir_all(&self.directory).map_err(|error| error.to_string())?;
+            return save_png(&golden_path, width, height, &actual).map_err(|error| error.to_string());
+        }
+
+        let bytes = std::fs::read(&golden_path).map_err(|error| {
+            format!(
+                "golden {} could not be read ({}); run with {}=1 to create it",
+                golden_path.display(),
+                error,
+                UPDATE_GOLDENS_VAR
+            )
+        })?;
+        let (golden_width, golden_height, expected) = decode_png(&bytes).map_err(|error| error.to_string())?;
+        if (golden_width, golden_height) != (width, height) {
+            self.write_failure(width, height, &actual, None)?;
+            return Err(format!(
+                "golden {} is {}x{} but the widget rendered at {}x{}",
+                self.name, golden_width, golden_height, width, height
+            ));
+        }
+
+        let comparison = compare(&expected, &actual, self.tolerance);
+        if comparison.differing == 0 {
+            return Ok(());
+        }
+        let failures = self.write_failure(width, height, &actual, Some(&comparison.diff))?;
+        Err(format!(
+            "golden {} differs in {} of {} pixels (largest difference {}, tolerance {}); see {}",
+            self.name,
+            comparison.differing,
+            width * height,
+            comparison.max_difference,
+            self.tolerance,
+            failures.display()
+        ))
+    }
+
+    /// Lays out and paints the widget and rasterizes it
+    fn render(&self, widget: &dyn Widget, width: u32, height: u32) -> Result<Vec<u8>, String> {
+        widget.layout(self.size);
+        let mut ctx = PaintContext::new();
+        widget.paint(&mut ctx, Rect::new(0.0, 0.0, self.size.width, self.size.height));
+        let mut backend = CpuBackend::new(width, height, self.scale_factor).map_err(|error| error.to_string())?;
+        backend
+            .render(&ctx.finish(), self.background)
+            .map_err(|error| error.to_string())
+    }
+
+    /// Writes the actual image and the diff, returning their directory
+    fn write_failure(&self, width: u32, height: u32, actual: &[u8], diff: Option<&[u8]>) -> Result<PathBuf, String> {
+        let failures = self.directory.join("failures");
+        std::fs::create_dir_all(&failures).map_err(|error| error.to_string())?;
+        save_png(failures.join(format!("{}.actual.png", self.name)), width, height, actual)
+            .map_err(|error| error.to_string())?;
+        if let Some(diff) = diff {
+            save_png(failures.join(format!("{}.diff.png", self.name)), width, height, diff)
+                .map_err(|error| error.to_string())?;
+        }
+        Ok(failures)
+    }
+}
+
+/// The result of comparing two images of the same size
+struct Comparison {
+    differing: usize,
+    max_difference: u8,
+    diff: Vec<u8>,
+}
+
+/// Compares two RGBA8 images pixel by pixel
+fn compare(expected: &[u8], actual: &[u8], tolerance: u8) -> Comparison {
+    let mut differing = 0;
+    let mut max_difference = 0;
+    let mut diff = Vec::with_capacity(expected.len());
+    for (expected, actual) in expected.chunks_exact(4).zip(actual.chunks_exact(4)) {
+        let difference = expected
+            .iter()
+            .zip(actual)
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or(0);
+        max_difference = max_difference.max(difference);
+        if difference > tolerance {
+            differing += 1;
+            diff.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            // Faded golden, so the differences stand out
+            let luma = (expected[0] as u32 * 3 + expected[1] as u32 * 6 + expected[2] as u32) / 10;
+            let faded = (192 + luma / 4) as u8;
+            diff.extend_from_slice(&[faded, faded, faded, 255]);
+        }
+    }
+    Comparison {
+        differing,
+        max_difference,
+        diff,
+    }
+}
+
+/// Asserts that a widget renders like its golden image
+///
+/// Goldens are stored as `tests/goldens/<name>.png` in the crate running
+/// the test. Options of `GoldenTest` can be appended as `option = value`.
+///
+/// ```ignore
+/// assert_golden!("column_center", column, Size::new(400.0, 300.0));
+/// assert_golden!("icon_2x", icon, Size::new(48.0, 48.0), scale_factor = 2.0, tolerance = 4);
+/// ```
+#[macro_export]
+macro_rules! assert_golden {
+    ($name:expr, $widget:expr, $size:expr $(, $option:ident = $value:expr)* $(,)?) => {{
+        let golden = $crate::GoldenTest::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/goldens"),
+            $name,
+            $size,
+        )
+        $(.$option($value))*;
+        if let Err(message) = golden.check(&$widget) {
+            panic!("{}", message);
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Block(Color);
+
+    impl Widget for Block {
+        fn widget_type(&self) -> &'static str {
+            "Block"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+            ctx.draw_rect(Rect::new(bounds.x, bounds.y, bounds.width / 2.0, bounds.height), self.0);
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kobalt-golden-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_compare_within_tolerance() {
+        let comparison = compare(&[10, 10, 10, 255, 0, 0, 0, 255], &[12, 10, 10, 255, 0, 9, 0, 255], 2);
+        assert_eq!(comparison.differing, 1);
+        assert_eq!(comparison.max_difference, 9);
+        assert_eq!(&comparison.diff[4..], &[255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_missing_golden_fails_until_updated() {
+        let dir = temp_dir("missing");
+        let golden = GoldenTest::new(&dir, "block", Size::new(8.0, 4.0));
+
+        let error = golden.check_with(&Block(Color::BLACK), false).unwrap_err();
+        assert!(error.contains(UPDATE_GOLDENS_VAR));
+
+        golden.check_with(&Block(Color::BLACK), true).unwrap();
+        assert!(golden.golden_path().exists());
+        golden.check_with(&Block(Color::BLACK), false).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_mismatch_writes_diff() {
+        let dir = temp_dir("mismatch");
+        let golden = GoldenTest::new(&dir, "block", Size::new(8.0, 4.0));
+        golden.check_with(&Block(Color::BLACK), true).unwrap();
+
+        let error = golden.check_with(&Block(Color::RED), false).unwrap_err();
+        assert!(error.contains("16 of 32 pixels"), "{}", error);
+        assert!(dir.join("failures/block.diff.png").exists());
+        assert!(dir.join("failures/block.actual.png").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
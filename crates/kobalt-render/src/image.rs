@@ -0,0 +1,653 @@
+//! Image rendering and GPU texture caching
+//!
+//! Decoded images are uploaded once and kept on the GPU, keyed by
+//! `ImageData::id`. Small images are packed into shared atlas pages so that
+//! many icons can be drawn with a single bind group; larger images get a
+//! dedicated texture. Textures that have not been drawn for a while are
+//! released.
+
+use bytemuck::{Pod, Zeroable};
+use kobalt_core::image::ImageData;
+use kobalt_core::types::{Color, Rect, Size};
+use std::collections::HashMap;
+use wgpu::util::DeviceExt;
+
+/// Side length of an atlas page
+const ATLAS_SIZE: u32 = 2048;
+/// Images with both sides at most this long are packed into the atlas
+const ATLAS_MAX_IMAGE: u32 = 256;
+/// Transparent gutter between atlas entries
+const ATLAS_PADDING: u32 = 1;
+/// Maximum number of atlas pages kept alive
+const MAX_ATLAS_PAGES: usize = 4;
+/// Dedicated textures unused for this many frames are released
+const IDLE_FRAMES: u64 = 300;
+
+/// Vertex for image rendering
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct ImageVertex {
+    position: [f32; 2],
+    tex_coords: [f32; 2],
+    color: [f32; 4],
+}
+
+impl ImageVertex {
+    const ATTRS: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ImageVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRS,
+        }
+    }
+}
+
+/// Packs rectangles into rows ("shelves") of a fixed-size area
+#[derive(Debug)]
+struct ShelfAllocator {
+    width: u32,
+    height: u32,
+    shelves: Vec<Shelf>,
+    next_y: u32,
+}
+
+#[derive(Debug)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    next_x: u32,
+}
+
+impl ShelfAllocator {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            shelves: Vec::new(),
+            next_y: 0,
+        }
+    }
+
+    /// Returns the top-left corner of a free `width` x `height` area
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if width > self.width || height > self.height {
+            return None;
+        }
+
+        // Best fit: the shortest shelf that is tall enough and has room
+        let best = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= height && self.width - shelf.next_x >= width)
+            .min_by_key(|shelf| shelf.height);
+        if let Some(shelf) = best {
+            let x = shelf.next_x;
+            shelf.next_x += width;
+            return Some((x, shelf.y));
+        }
+
+        if self.height - self.next_y < height {
+            return None;
+        }
+        let y = self.next_y;
+        self.next_y += height;
+        self.shelves.push(Shelf {
+            y,
+            height,
+            next_x: width,
+        });
+        Some((0, y))
+    }
+
+    fn reset(&mut self) {
+        self.shelves.clear();
+        self.next_y = 0;
+    }
+}
+
+/// A texture with the bind group used to sample it
+struct BoundTexture {
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+}
+
+/// A shared atlas texture
+struct AtlasPage {
+    bound: BoundTexture,
+    allocator: ShelfAllocator,
+    last_used: u64,
+}
+
+/// Where an uploaded image lives
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Location {
+    Atlas { page: usize, x: u32, y: u32 },
+    Dedicated,
+}
+
+struct CachedImage {
+    location: Location,
+    width: u32,
+    height: u32,
+    last_used: u64,
+}
+
+/// Which texture a draw samples from
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Binding {
+    Page(usize),
+    Dedicated(u64),
+}
+
+/// A run of indices drawn with one texture
+struct Draw {
+    binding: Binding,
+    indices: std::ops::Range<u32>,
+}
+
+/// Renderer for images with a GPU texture cache
+pub struct ImageRenderer {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    texture_format: wgpu::TextureFormat,
+    pages: Vec<AtlasPage>,
+    dedicated: HashMap<u64, BoundTexture>,
+    images: HashMap<u64, CachedImage>,
+    frame: u64,
+    vertices: Vec<ImageVertex>,
+    indices: Vec<u32>,
+    draws: Vec<Draw>,
+    vertex_buffer: Option<wgpu::Buffer>,
+    index_buffer: Option<wgpu::Buffer>,
+}
+
+impl ImageRenderer {
+    /// Creates a new image renderer for targets with the given MSAA sample
+    /// count
+    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, sample_count: u32) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Image Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Image Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Image Shader"),
+            source: wgpu::ShaderSource::Wgsl(IMAGE_SHADER.into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Image Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Image Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[ImageVertex::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        // Decode sRGB texels when rendering into an sRGB target so images
+        // keep their colors
+        let texture_format = if config.format.is_srgb() {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Rgba8Unorm
+        };
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            sampler,
+            texture_format,
+            pages: Vec::new(),
+            dedicated: HashMap::new(),
+            images: HashMap::new(),
+            frame: 0,
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            draws: Vec::new(),
+            vertex_buffer: None,
+            index_buffer: None,
+        }
+    }
+
+    /// Starts a new frame, releasing textures that have not been used recently
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+        let frame = self.frame;
+        let dedicated = &mut self.dedicated;
+        self.images.retain(|id, image| {
+            let keep = image.location != Location::Dedicated || image.last_used + IDLE_FRAMES >= frame;
+            if !keep {
+                dedicated.remove(id);
+            }
+            keep
+        });
+    }
+
+    /// Returns the number of images currently uploaded to the GPU
+    pub fn cached_images(&self) -> usize {
+        self.images.len()
+    }
+
+    /// Returns the number of atlas pages
+    pub fn atlas_pages(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Clears all accumulated images
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+        self.draws.clear();
+    }
+
+    /// Returns true if no images have been added since the last clear
+    pub fn is_empty(&self) -> bool {
+        self.draws.is_empty()
+    }
+
+    /// Adds the `src` region of an image (in image pixels) drawn into `dst`
+    pub fn add_image(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: &ImageData,
+        src: Rect,
+        dst: Rect,
+        tint: Option<Color>,
+    ) {
+        let Some((binding, origin, texture_size)) = self.upload(device, queue, image) else {
+            return;
+        };
+
+        // Keep bilinear sampling inside the image's own texels
+        let min_u = origin.0 as f32 + 0.5;
+        let min_v = origin.1 as f32 + 0.5;
+        let max_u = (origin.0 + image.width()) as f32 - 0.5;
+        let max_v = (origin.1 + image.height()) as f32 - 0.5;
+        let u = |x: f32| (origin.0 as f32 + x).clamp(min_u, max_u.max(min_u)) / texture_size.width;
+        let v = |y: f32| (origin.1 as f32 + y).clamp(min_v, max_v.max(min_v)) / texture_size.height;
+        let (u0, v0, u1, v1) = (u(src.x), v(src.y), u(src.right()), v(src.bottom()));
+
+        let tint = tint.unwrap_or(Color::WHITE);
+        let color = [tint.r, tint.g, tint.b, tint.a];
+        let base = self.vertices.len() as u32;
+        self.vertices.extend_from_slice(&[
+            ImageVertex {
+                position: [dst.x, dst.y],
+                tex_coords: [u0, v0],
+                color,
+            },
+            ImageVertex {
+                position: [dst.right(), dst.y],
+                tex_coords: [u1, v0],
+                color,
+            },
+            ImageVertex {
+                position: [dst.right(), dst.bottom()],
+                tex_coords: [u1, v1],
+                color,
+            },
+            ImageVertex {
+                position: [dst.x, dst.bottom()],
+                tex_coords: [u0, v1],
+                color,
+            },
+        ]);
+
+        let start = self.indices.len() as u32;
+        self.indices
+            .extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        let end = self.indices.len() as u32;
+
+        match self.draws.last_mut() {
+            Some(draw) if draw.binding == binding => draw.indices.end = end,
+            _ => self.draws.push(Draw {
+                binding,
+                indices: start..end,
+            }),
+        }
+    }
+
+    /// Makes sure an image is on the GPU, returning where it lives
+    fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: &ImageData,
+    ) -> Option<(Binding, (u32, u32), Size)> {
+        let id = image.id();
+        if let Some(cached) = self.images.get_mut(&id) {
+            cached.last_used = self.frame;
+            return Some(match cached.location {
+                Location::Atlas { page, x, y } => {
+                    self.pages[page].last_used = self.frame;
+                    (Binding::Page(page), (x, y), Size::new(ATLAS_SIZE as f32, ATLAS_SIZE as f32))
+                }
+                Location::Dedicated => (
+                    Binding::Dedicated(id),
+                    (0, 0),
+                    Size::new(cached.width as f32, cached.height as f32),
+                ),
+            });
+        }
+
+        if image.width() == 0 || image.height() == 0 {
+            return None;
+        }
+
+        let fits_atlas = image.width() <= ATLAS_MAX_IMAGE && image.height() <= ATLAS_MAX_IMAGE;
+        if fits_atlas && let Some((page, x, y)) = self.allocate_in_atlas(device, image.width(), image.height()) {
+            self.write_pixels(queue, &self.pages[page].bound.texture, x, y, image);
+            self.pages[page].last_used = self.frame;
+            self.images.insert(
+                id,
+                CachedImage {
+                    location: Location::Atlas { page, x, y },
+                    width: image.width(),
+                    height: image.height(),
+                    last_used: self.frame,
+                },
+            );
+            return Some((Binding::Page(page), (x, y), Size::new(ATLAS_SIZE as f32, ATLAS_SIZE as f32)));
+        }
+
+        let max_dimension = device.limits().max_texture_dimension_2d;
+        if image.width() > max_dimension || image.height() > max_dimension {
+            eprintln!(
+                "Warning: image of {}x{} exceeds the maximum texture size of {}",
+                image.width(),
+                image.height(),
+                max_dimension
+            );
+            return None;
+        }
+
+        let bound = self.create_texture(device, "Image Texture", image.width(), image.height());
+        self.write_pixels(queue, &bound.texture, 0, 0, image);
+        self.dedicated.insert(id, bound);
+        self.images.insert(
+            id,
+            CachedImage {
+                location: Location::Dedicated,
+                width: image.width(),
+                height: image.height(),
+                last_used: self.frame,
+            },
+        );
+        Some((Binding::Dedicated(id), (0, 0), image.size()))
+    }
+
+    /// Finds room for an image in the atlas, adding or recycling pages
+    fn allocate_in_atlas(&mut self, device: &wgpu::Device, width: u32, height: u32) -> Option<(usize, u32, u32)> {
+        let padded = (width + ATLAS_PADDING, height + ATLAS_PADDING);
+
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            if let Some((x, y)) = page.allocator.allocate(padded.0, padded.1) {
+                return Some((index, x, y));
+            }
+        }
+
+        if self.pages.len() < MAX_ATLAS_PAGES {
+            let bound = self.create_texture(device, "Image Atlas", ATLAS_SIZE, ATLAS_SIZE);
+            self.pages.push(AtlasPage {
+                bound,
+                allocator: ShelfAllocator::new(ATLAS_SIZE, ATLAS_SIZE),
+                last_used: self.frame,
+            });
+            let index = self.pages.len() - 1;
+            let (x, y) = self.pages[index].allocator.allocate(padded.0, padded.1)?;
+            return Some((index, x, y));
+        }
+
+        // Recycle the least recently used page, unless it is needed this frame
+        let (index, page) = self
+            .pages
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, page)| page.last_used < self.frame)
+            .min_by_key(|(_, page)| page.last_used)?;
+        page.allocator.reset();
+        self.images
+            .retain(|_, image| !matches!(image.location, Location::Atlas { page, .. } if page == index));
+        let (x, y) = page.allocator.allocate(padded.0, padded.1)?;
+        Some((index, x, y))
+    }
+
+    fn create_texture(&self, device: &wgpu::Device, label: &str, width: u32, height: u32) -> BoundTexture {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.texture_format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Image Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+        BoundTexture { texture, bind_group }
+    }
+
+    fn write_pixels(&self, queue: &wgpu::Queue, texture: &wgpu::Texture, x: u32, y: u32, image: &ImageData) {
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            image.pixels(),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(image.width() * 4),
+                rows_per_image: Some(image.height()),
+            },
+            wgpu::Extent3d {
+                width: image.width(),
+                height: image.height(),
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Builds vertex and index buffers for the accumulated images
+    pub fn build(&mut self, device: &wgpu::Device, screen_size: Size) {
+        if self.draws.is_empty() {
+            return;
+        }
+
+        let vertices: Vec<ImageVertex> = self
+            .vertices
+            .iter()
+            .map(|vertex| ImageVertex {
+                position: [
+                    (vertex.position[0] / screen_size.width) * 2.0 - 1.0,
+                    1.0 - (vertex.position[1] / screen_size.height) * 2.0,
+                ],
+                ..*vertex
+            })
+            .collect();
+
+        self.vertex_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Image Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        }));
+        self.index_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Image Index Buffer"),
+            contents: bytemuck::cast_slice(&self.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        }));
+    }
+
+    /// Renders the accumulated images
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        let (Some(vertex_buffer), Some(index_buffer)) = (&self.vertex_buffer, &self.index_buffer) else {
+            return;
+        };
+        if self.draws.is_empty() {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        for draw in &self.draws {
+            let bind_group = match draw.binding {
+                Binding::Page(page) => &self.pages[page].bound.bind_group,
+                Binding::Dedicated(id) => match self.dedicated.get(&id) {
+                    Some(bound) => &bound.bind_group,
+                    None => continue,
+                },
+            };
+            render_pass.set_bind_group(0, bind_group, &[]);
+            render_pass.draw_indexed(draw.indices.clone(), 0, 0..1);
+        }
+    }
+}
+
+const IMAGE_SHADER: &str = r#"
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var t_image: texture_2d<f32>;
+@group(0) @binding(1)
+var s_image: sampler;
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var output: VertexOutput;
+    output.clip_position = vec4<f32>(input.position, 0.0, 1.0);
+    output.tex_coords = input.tex_coords;
+    output.color = input.color;
+    return output;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_image, s_image, input.tex_coords) * input.color;
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shelf_allocator_reuses_shelves() {
+        let mut allocator = ShelfAllocator::new(100, 100);
+        assert_eq!(allocator.allocate(40, 20), Some((0, 0)));
+        assert_eq!(allocator.allocate(40, 20), Some((40, 0)));
+        // Too wide for the first shelf, starts a new one
+        assert_eq!(allocator.allocate(30, 10), Some((0, 20)));
+        // Best fit prefers the shorter shelf
+        assert_eq!(allocator.allocate(10, 10), Some((30, 20)));
+        assert_eq!(allocator.allocate(20, 15), Some((80, 0)));
+    }
+
+    #[test]
+    fn test_shelf_allocator_full() {
+        let mut allocator = ShelfAllocator::new(50, 50);
+        assert!(allocator.allocate(60, 10).is_none());
+        assert_eq!(allocator.allocate(50, 50), Some((0, 0)));
+        assert!(allocator.allocate(1, 1).is_none());
+        allocator.reset();
+        assert_eq!(allocator.allocate(1, 1), Some((0, 0)));
+    }
+}
@@ -4,22 +4,52 @@
 //!
 //! This crate provides:
 //! - WGPU initialization and management
+//! - Renderer configuration (present mode, adapter, backends, MSAA, transparency)
 //! - Surface and swapchain handling
-//! - Basic shape rendering (rectangles, circles)
+//! - Offscreen rendering with RGBA readback and PNG export
+//! - A `RenderBackend` trait with GPU (wgpu) and software (CPU) backends
+//! - Golden image tests for widgets (`assert_golden!`)
+//! - Basic shape rendering (instanced rectangles, circles)
+//! - Persistent, geometrically growing GPU buffers
+//! - Vector path tessellation (fills, strokes, dashes)
+//! - Image rendering with a GPU texture cache and atlas
 //! - Text rendering
+//! - Image filters (Gaussian blur, color matrix)
+//! - Display list rendering
 //! - Shader management
 
 mod renderer;
+mod config;
+mod buffer;
 mod shape;
 mod text;
 mod text_real;
+mod filter;
+mod scene;
+mod tessellate;
+mod image;
+mod readback;
+mod backend;
+mod cpu;
+mod golden;
 
-pub use renderer::Renderer;
-pub use shape::{RectRenderer, Vertex};
+pub use renderer::{FrameContext, Renderer};
+pub use config::{Backends, PowerPreference, PresentMode, RendererConfig};
+pub use buffer::RingBuffer;
+pub use shape::{QuadInstance, RectRenderer, Vertex};
 pub use text::KobaltTextRenderer;
 pub use text_real::RealTextRenderer;
+pub use filter::{FilterBlend, FilterInput, FilterRenderer, FilterTarget, MAX_BLUR_RADIUS};
+pub use scene::SceneRenderer;
+pub use tessellate::{tessellate, Mesh};
+pub use image::ImageRenderer;
+pub use readback::{decode_png, encode_png, save_png};
+pub use backend::{RenderBackend, WgpuBackend};
+pub use cpu::CpuBackend;
+pub use golden::{GoldenTest, DEFAULT_GOLDEN_TOLERANCE, UPDATE_GOLDENS_VAR};
 
 pub use kobalt_core::types::{Color, Point, Rect, Size};
+pub use kobalt_core::paint::{DisplayList, DrawCommand, ImageFilter};
 
 // Re-export common WGPU types
 pub use wgpu::SurfaceError;
@@ -0,0 +1,179 @@
+//! Reading rendered frames back from the GPU
+//!
+//! Textures are copied into a mappable buffer whose rows are padded to
+//! `wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`; the padding is stripped and BGRA
+//! formats are swizzled so callers always get tightly packed RGBA8 pixels.
+
+use kobalt_core::error::KobaltError;
+use std::path::Path;
+
+/// Copies an 8-bit RGBA or BGRA texture into memory as RGBA8 bytes
+///
+/// Submits `encoder` together with the copy and blocks until the GPU has
+/// finished.
+pub(crate) fn read_texture_rgba8(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    mut encoder: wgpu::CommandEncoder,
+    texture: &wgpu::Texture,
+) -> Result<Vec<u8>, KobaltError> {
+    let bgra = match texture.format() {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        format => return Err(KobaltError::Readback(format!("unsupported texture format {:?}", format))),
+    };
+    if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+        return Err(KobaltError::Readback("the texture does not support COPY_SRC".to_string()));
+    }
+
+    let width = texture.width();
+    let height = texture.height();
+    let bytes_per_row = padded_bytes_per_row(width);
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Readback Buffer"),
+        size: bytes_per_row as u64 * height as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        texture.size(),
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device
+        .poll(wgpu::PollType::wait_indefinitely())
+        .map_err(|error| KobaltError::Readback(error.to_string()))?;
+    receiver
+        .recv()
+        .map_err(|error| KobaltError::Readback(error.to_string()))?
+        .map_err(|error| KobaltError::Readback(error.to_string()))?;
+
+    let pixels = unpad_rows(&slice.get_mapped_range(), width, height, bytes_per_row, bgra);
+    buffer.unmap();
+    Ok(pixels)
+}
+
+/// Returns the number of bytes per row of an RGBA8 copy, padded to the
+/// required alignment
+fn padded_bytes_per_row(width: u32) -> u32 {
+    (width * 4).div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT
+}
+
+/// Strips the row padding and converts BGRA to RGBA if needed
+fn unpad_rows(data: &[u8], width: u32, height: u32, bytes_per_row: u32, bgra: bool) -> Vec<u8> {
+    let row_len = width as usize * 4;
+    let mut pixels = Vec::with_capacity(row_len * height as usize);
+    for row in data.chunks(bytes_per_row as usize).take(height as usize) {
+        pixels.extend_from_slice(&row[..row_len]);
+    }
+    if bgra {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    pixels
+}
+
+/// Encodes tightly packed RGBA8 pixels as a PNG
+pub fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>, KobaltError> {
+    if rgba.len() != width as usize * height as usize * 4 {
+        return Err(KobaltError::ImageSave(format!(
+            "expected {} bytes for a {}x{} image, got {}",
+            width as usize * height as usize * 4,
+            width,
+            height,
+            rgba.len()
+        )));
+    }
+
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .map_err(|error| KobaltError::ImageSave(error.to_string()))?;
+    writer
+        .write_image_data(rgba)
+        .map_err(|error| KobaltError::ImageSave(error.to_string()))?;
+    writer
+        .finish()
+        .map_err(|error| KobaltError::ImageSave(error.to_string()))?;
+    Ok(bytes)
+}
+
+/// Decodes an 8-bit RGBA PNG into its size and tightly packed pixels
+pub fn decode_png(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>), KobaltError> {
+    let decoder = png::Decoder::new(std::io::Cursor::new(bytes));
+    let mut reader = decoder
+        .read_info()
+        .map_err(|error| KobaltError::ImageSave(error.to_string()))?;
+    let Some(buffer_size) = reader.output_buffer_size() else {
+        return Err(KobaltError::ImageSave("the image is too large".to_string()));
+    };
+    let mut pixels = vec![0; buffer_size];
+    let info = reader
+        .next_frame(&mut pixels)
+        .map_err(|error| KobaltError::ImageSave(error.to_string()))?;
+    if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+        return Err(KobaltError::ImageSave(format!(
+            "expected an 8-bit RGBA image, got {:?} {:?}",
+            info.color_type, info.bit_depth
+        )));
+    }
+    pixels.truncate(info.buffer_size());
+    Ok((info.width, info.height, pixels))
+}
+
+/// Writes tightly packed RGBA8 pixels to a PNG file
+pub fn save_png(path: impl AsRef<Path>, width: u32, height: u32, rgba: &[u8]) -> Result<(), KobaltError> {
+    let bytes = encode_png(width, height, rgba)?;
+    std::fs::write(path.as_ref(), bytes)
+        .map_err(|error| KobaltError::ImageSave(format!("{}: {}", path.as_ref().display(), error)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_padded_bytes_per_row() {
+        assert_eq!(padded_bytes_per_row(1), 256);
+        assert_eq!(padded_bytes_per_row(64), 256);
+        assert_eq!(padded_bytes_per_row(65), 512);
+    }
+
+    #[test]
+    fn test_unpad_rows_swizzles_bgra() {
+        let mut data = vec![0u8; 512];
+        data[..4].copy_from_slice(&[1, 2, 3, 4]);
+        data[256..260].copy_from_slice(&[5, 6, 7, 8]);
+
+        assert_eq!(unpad_rows(&data, 1, 2, 256, false), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(unpad_rows(&data, 1, 2, 256, true), vec![3, 2, 1, 4, 7, 6, 5, 8]);
+    }
+
+    #[test]
+    fn test_encode_png_round_trip() {
+        let rgba = [255, 0, 0, 255, 0, 255, 0, 128];
+        let bytes = encode_png(2, 1, &rgba).unwrap();
+
+        assert_eq!(decode_png(&bytes).unwrap(), (2, 1, rgba.to_vec()));
+
+        assert!(encode_png(2, 2, &rgba).is_err());
+    }
+}
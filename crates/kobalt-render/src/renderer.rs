@@ -1,20 +1,60 @@
 //! Core renderer implementation
+//!
+//! The surface is sized in physical pixels. Widgets work in logical pixels;
+//! the scale factor (device pixel ratio) converts between the two.
+//!
+//! With MSAA enabled the renderer owns a multisampled texture the size of
+//! the surface; frames are drawn into it and resolved into the surface
+//! texture.
+//!
+//! An offscreen renderer has no window: it draws into a texture of its own
+//! that can be read back as RGBA8 pixels or saved as a PNG, which works on
+//! machines without a display.
 
 use wgpu::{Device, Queue, Surface, SurfaceConfiguration};
+use crate::config::RendererConfig;
+use crate::readback;
+use kobalt_core::error::KobaltError;
+use kobalt_core::scheduler::FrameScheduler;
 use kobalt_core::types::{Color, Size};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Format of offscreen render targets
+const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Where a renderer's frames go
+enum RenderTarget {
+    /// A window surface
+    Surface(Surface<'static>),
+    /// An offscreen texture
+    Texture(wgpu::Texture),
+}
 
 /// Main renderer struct that manages WGPU resources
+///
+/// If the GPU device is lost (driver reset, GPU removed), `is_device_lost`
+/// reports it and `recover` creates a new device for the same surface.
 pub struct Renderer {
-    surface: Surface<'static>,
+    instance: wgpu::Instance,
+    target: RenderTarget,
     device: Device,
     queue: Queue,
     config: SurfaceConfiguration,
     size: Size,
+    scale_factor: f32,
+    renderer_config: RendererConfig,
+    sample_count: u32,
+    msaa_view: Option<wgpu::TextureView>,
+    device_lost: Arc<AtomicBool>,
+    device_generation: u64,
 }
 
 impl Renderer {
-    /// Creates a new renderer with the given surface and size
-    pub async fn new(surface: Surface<'static>, size: Size) -> Self {
+    /// Creates a new renderer with the given surface and size in physical
+    /// pixels
+    pub async fn new(surface: Surface<'static>, size: Size) -> Result<Self, KobaltError> {
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
             backends: wgpu::Backends::all(),
             ..Default::default()
@@ -28,57 +68,138 @@ impl Renderer {
         instance: wgpu::Instance,
         surface: Surface<'static>,
         size: Size,
-    ) -> Self {
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .expect("Failed to find an appropriate adapter");
-
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: Some("Kobalt Device"),
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
-                    memory_hints: Default::default(),
-                    experimental_features: Default::default(),
-                    trace: Default::default(),
-                },
-            )
-            .await
-            .expect("Failed to create device");
-
-        let surface_caps = surface.get_capabilities(&adapter);
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .unwrap_or(surface_caps.formats[0]);
+    ) -> Result<Self, KobaltError> {
+        Self::new_with_config(instance, surface, size, RendererConfig::default()).await
+    }
 
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: size.width as u32,
-            height: size.height as u32,
-            present_mode: wgpu::PresentMode::Fifo,
-            alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        };
+    /// Creates a new renderer with a provided WGPU instance and options
+    ///
+    /// The instance should be created with `RendererConfig::create_instance`
+    /// so that the backend selection applies.
+    pub async fn new_with_config(
+        instance: wgpu::Instance,
+        surface: Surface<'static>,
+        size: Size,
+        renderer_config: RendererConfig,
+    ) -> Result<Self, KobaltError> {
+        let device_lost = Arc::new(AtomicBool::new(false));
+        let (device, queue, config, sample_count) =
+            create_device(&instance, Some(&surface), size, &renderer_config, &device_lost).await?;
+        let msaa_view = create_msaa_view(&device, &config, sample_count);
 
-        surface.configure(&device, &config);
+        Ok(Self {
+            instance,
+            target: RenderTarget::Surface(surface),
+            device,
+            queue,
+            config,
+            size,
+            scale_factor: 1.0,
+            renderer_config,
+            sample_count,
+            msaa_view,
+            device_lost,
+            device_generation: 0,
+        })
+    }
 
-        Self {
-            surface,
+    /// Creates a renderer without a window that draws into a texture of the
+    /// given size in physical pixels
+    ///
+    /// Frames from `begin_frame` can be read back with
+    /// `FrameContext::read_rgba8` or saved with `FrameContext::save_png`.
+    /// The transparency option is ignored: offscreen frames keep their
+    /// alpha channel.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use kobalt_render::{Color, Renderer, RendererConfig, Size};
+    ///
+    /// let renderer = pollster::block_on(Renderer::new_offscreen(
+    ///     Size::new(256.0, 256.0),
+    ///     RendererConfig::new(),
+    /// ))?;
+    /// let frame = renderer.begin_frame()?;
+    /// let mut encoder = frame.create_encoder();
+    /// frame.begin_render_pass(&mut encoder, Color::WHITE);
+    /// frame.save_png(encoder, "thumbnail.png")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub async fn new_offscreen(size: Size, renderer_config: RendererConfig) -> Result<Self, KobaltError> {
+        let instance = renderer_config.create_instance();
+        let device_lost = Arc::new(AtomicBool::new(false));
+        let (device, queue, config, sample_count) =
+            create_device(&instance, None, size, &renderer_config, &device_lost).await?;
+        let msaa_view = create_msaa_view(&device, &config, sample_count);
+        let texture = create_offscreen_texture(&device, &config);
+
+        Ok(Self {
+            instance,
+            target: RenderTarget::Texture(texture),
             device,
             queue,
             config,
             size,
+            scale_factor: 1.0,
+            renderer_config,
+            sample_count,
+            msaa_view,
+            device_lost,
+            device_generation: 0,
+        })
+    }
+
+    /// Returns true if the renderer draws into a texture instead of a window
+    pub fn is_offscreen(&self) -> bool {
+        matches!(self.target, RenderTarget::Texture(_))
+    }
+
+    /// Returns true if the GPU device has been lost and `recover` must be
+    /// called before rendering again
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Acquire)
+    }
+
+    /// Returns a counter that changes whenever the device is recreated;
+    /// resources created with an older device must be recreated too
+    pub fn device_generation(&self) -> u64 {
+        self.device_generation
+    }
+
+    /// Creates a new device for the surface after the old one was lost
+    pub async fn recover(&mut self) -> Result<(), KobaltError> {
+        let device_lost = Arc::new(AtomicBool::new(false));
+        let surface = match &self.target {
+            RenderTarget::Surface(surface) => Some(surface),
+            RenderTarget::Texture(_) => None,
+        };
+        let (device, queue, config, sample_count) =
+            create_device(&self.instance, surface, self.size, &self.renderer_config, &device_lost)
+                .await
+            .map_err(|error| match error {
+                // Nothing to recover on, as opposed to a failed recovery
+                KobaltError::NoAdapter(_) => error,
+                error => KobaltError::DeviceLost(error.to_string()),
+            })?;
+        self.msaa_view = create_msaa_view(&device, &config, sample_count);
+        if let RenderTarget::Texture(texture) = &mut self.target {
+            *texture = create_offscreen_texture(&device, &config);
+        }
+        self.device = device;
+        self.queue = queue;
+        self.config = config;
+        self.sample_count = sample_count;
+        self.device_lost = device_lost;
+        self.device_generation += 1;
+        Ok(())
+    }
+
+    /// Configures the surface again, e.g. after it was lost or became
+    /// outdated
+    pub fn reconfigure(&mut self) {
+        if let RenderTarget::Surface(surface) = &self.target {
+            surface.configure(&self.device, &self.config);
         }
     }
 
@@ -92,31 +213,69 @@ impl Renderer {
         &self.queue
     }
 
+    /// Returns the options the renderer was created with
+    pub fn renderer_config(&self) -> &RendererConfig {
+        &self.renderer_config
+    }
+
+    /// Returns the MSAA sample count frames are rendered with (1 if
+    /// multisampling is off or unsupported)
+    ///
+    /// Pipelines drawing into `FrameContext::begin_render_pass` must be
+    /// created with this sample count.
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
     /// Returns the current surface configuration
     pub fn config(&self) -> &SurfaceConfiguration {
         &self.config
     }
 
-    /// Returns the current size
+    /// Returns the current size in physical pixels
     pub fn size(&self) -> Size {
         self.size
     }
 
-    /// Resizes the renderer
+    /// Returns the current size in logical pixels
+    pub fn logical_size(&self) -> Size {
+        self.size / self.scale_factor
+    }
+
+    /// Returns the number of physical pixels per logical pixel
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    /// Sets the number of physical pixels per logical pixel
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        if scale_factor > 0.0 && scale_factor.is_finite() {
+            self.scale_factor = scale_factor;
+        }
+    }
+
+    /// Resizes the renderer to a size in physical pixels
     pub fn resize(&mut self, new_size: Size) {
         if new_size.width > 0.0 && new_size.height > 0.0 {
             self.size = new_size;
             self.config.width = new_size.width as u32;
             self.config.height = new_size.height as u32;
-            self.surface.configure(&self.device, &self.config);
+            match &mut self.target {
+                RenderTarget::Surface(surface) => surface.configure(&self.device, &self.config),
+                RenderTarget::Texture(texture) => *texture = create_offscreen_texture(&self.device, &self.config),
+            }
+            self.msaa_view = create_msaa_view(&self.device, &self.config, self.sample_count);
         }
     }
 
     /// Begins a new frame and returns the render target
     pub fn begin_frame(&self) -> Result<FrameContext<'_>, wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
+        let output = match &self.target {
+            RenderTarget::Surface(surface) => FrameOutput::Surface(surface.get_current_texture()?),
+            RenderTarget::Texture(texture) => FrameOutput::Texture(texture.clone()),
+        };
         let view = output
-            .texture
+            .texture()
             .create_view(&wgpu::TextureViewDescriptor::default());
 
         Ok(FrameContext {
@@ -125,17 +284,198 @@ impl Renderer {
             device: &self.device,
             queue: &self.queue,
             config: &self.config,
+            size: self.size,
+            scale_factor: self.scale_factor,
+            msaa_view: self.msaa_view.as_ref(),
         })
     }
 }
 
+/// Requests an adapter and device for the surface (or for offscreen
+/// rendering if there is none) and configures it
+async fn create_device(
+    instance: &wgpu::Instance,
+    surface: Option<&Surface<'static>>,
+    size: Size,
+    renderer_config: &RendererConfig,
+    device_lost: &Arc<AtomicBool>,
+) -> Result<(Device, Queue, SurfaceConfiguration, u32), KobaltError> {
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: renderer_config.wgpu_power_preference(),
+            compatible_surface: surface,
+            force_fallback_adapter: renderer_config.is_fallback_adapter_forced(),
+        })
+        .await
+        .map_err(|error| KobaltError::NoAdapter(error.to_string()))?;
+
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("Kobalt Device"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::default(),
+                memory_hints: Default::default(),
+                experimental_features: Default::default(),
+                trace: Default::default(),
+            },
+        )
+        .await
+        .map_err(|error| KobaltError::DeviceCreation(error.to_string()))?;
+
+    let lost = device_lost.clone();
+    device.set_device_lost_callback(move |reason, message| {
+        // Dropping the device on purpose also reports it as lost
+        if reason != wgpu::DeviceLostReason::Destroyed {
+            eprintln!("Warning: GPU device lost: {}", message);
+            lost.store(true, Ordering::Release);
+            // Wake the event loop so the next frame recovers the device; the
+            // callback can run on any thread, so the global scheduler is used
+            FrameScheduler::global().request_environment_change();
+        }
+    });
+
+    let Some(surface) = surface else {
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::TEXTURE_BINDING,
+            format: OFFSCREEN_FORMAT,
+            width: (size.width as u32).max(1),
+            height: (size.height as u32).max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        let format_features = adapter.get_texture_format_features(OFFSCREEN_FORMAT);
+        let sample_count = supported_sample_count(renderer_config.get_sample_count(), format_features.flags);
+        return Ok((device, queue, config, sample_count));
+    };
+
+    let surface_caps = surface.get_capabilities(&adapter);
+    let Some(&first_format) = surface_caps.formats.first() else {
+        return Err(KobaltError::SurfaceCreation("the surface supports no formats".to_string()));
+    };
+    let surface_format = surface_caps
+        .formats
+        .iter()
+        .find(|f| f.is_srgb())
+        .copied()
+        .unwrap_or(first_format);
+
+    // Backdrop filters read back the frame, which needs COPY_SRC
+    let usage = wgpu::TextureUsages::RENDER_ATTACHMENT
+        | (surface_caps.usages & wgpu::TextureUsages::COPY_SRC);
+
+    let config = wgpu::SurfaceConfiguration {
+        usage,
+        format: surface_format,
+        width: (size.width as u32).max(1),
+        height: (size.height as u32).max(1),
+        present_mode: renderer_config.choose_present_mode(&surface_caps.present_modes),
+        alpha_mode: renderer_config.choose_alpha_mode(&surface_caps.alpha_modes),
+        view_formats: vec![],
+        desired_maximum_frame_latency: 2,
+    };
+
+    surface.configure(&device, &config);
+
+    let format_features = adapter.get_texture_format_features(surface_format);
+    let sample_count = supported_sample_count(renderer_config.get_sample_count(), format_features.flags);
+    Ok((device, queue, config, sample_count))
+}
+
+/// Returns the requested sample count if the format supports rendering and
+/// resolving with it, else 1
+fn supported_sample_count(requested: u32, flags: wgpu::TextureFormatFeatureFlags) -> u32 {
+    if requested <= 1
+        || (flags.sample_count_supported(requested)
+            && flags.contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE))
+    {
+        requested.max(1)
+    } else {
+        eprintln!("Warning: {}x MSAA is not supported by the surface format, using 1", requested);
+        1
+    }
+}
+
+/// Creates the texture an offscreen renderer draws into
+fn create_offscreen_texture(device: &Device, config: &SurfaceConfiguration) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Offscreen Texture"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: config.usage,
+        view_formats: &[],
+    })
+}
+
+/// Creates the multisampled texture frames are drawn into, if MSAA is on
+fn create_msaa_view(
+    device: &Device,
+    config: &SurfaceConfiguration,
+    sample_count: u32,
+) -> Option<wgpu::TextureView> {
+    if sample_count <= 1 {
+        return None;
+    }
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Frame MSAA Texture"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+}
+
+/// The texture a frame is rendered into
+enum FrameOutput {
+    Surface(wgpu::SurfaceTexture),
+    Texture(wgpu::Texture),
+}
+
+impl FrameOutput {
+    fn texture(&self) -> &wgpu::Texture {
+        match self {
+            Self::Surface(output) => &output.texture,
+            Self::Texture(texture) => texture,
+        }
+    }
+
+    /// Shows the frame in the window (offscreen frames need nothing)
+    fn present(self) {
+        if let Self::Surface(output) = self {
+            output.present();
+        }
+    }
+}
+
 /// Context for rendering a single frame
 pub struct FrameContext<'a> {
-    output: wgpu::SurfaceTexture,
+    output: FrameOutput,
     view: wgpu::TextureView,
     device: &'a Device,
     queue: &'a Queue,
     config: &'a SurfaceConfiguration,
+    size: Size,
+    scale_factor: f32,
+    msaa_view: Option<&'a wgpu::TextureView>,
 }
 
 impl<'a> FrameContext<'a> {
@@ -144,6 +484,32 @@ impl<'a> FrameContext<'a> {
         &self.view
     }
 
+    /// Returns the multisampled view drawn into and resolved into `view`,
+    /// if MSAA is on
+    pub fn msaa_view(&self) -> Option<&wgpu::TextureView> {
+        self.msaa_view
+    }
+
+    /// Returns the MSAA sample count of the frame
+    pub fn sample_count(&self) -> u32 {
+        self.msaa_view.map_or(1, |view| view.texture().sample_count())
+    }
+
+    /// Returns the texture being rendered to for this frame
+    pub fn texture(&self) -> &wgpu::Texture {
+        self.output.texture()
+    }
+
+    /// Returns the size of the frame in physical pixels
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the number of physical pixels per logical pixel
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
     /// Returns the device
     pub fn device(&self) -> &Device {
         self.device
@@ -168,16 +534,19 @@ impl<'a> FrameContext<'a> {
     }
 
     /// Begins a render pass with a clear color
-    pub fn begin_render_pass(
-        &'a self,
-        encoder: &'a mut wgpu::CommandEncoder,
+    ///
+    /// With MSAA on, the pass draws into the multisampled texture and
+    /// resolves into the frame.
+    pub fn begin_render_pass<'e>(
+        &self,
+        encoder: &'e mut wgpu::CommandEncoder,
         clear_color: Color,
-    ) -> wgpu::RenderPass<'a> {
+    ) -> wgpu::RenderPass<'e> {
         encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render Pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &self.view,
-                resolve_target: None,
+                view: self.msaa_view.unwrap_or(&self.view),
+                resolve_target: self.msaa_view.map(|_| &self.view),
                 ops: wgpu::Operations {
                     load: wgpu::LoadOp::Clear(wgpu::Color {
                         r: clear_color.r as f64,
@@ -200,4 +569,37 @@ impl<'a> FrameContext<'a> {
         self.queue.submit(std::iter::once(encoder.finish()));
         self.output.present();
     }
+
+    /// Submits the command encoder, waits for the GPU and returns the frame
+    /// as tightly packed RGBA8 rows, top to bottom
+    ///
+    /// Window frames are presented afterwards; reading them back requires a
+    /// surface that supports `COPY_SRC`.
+    pub fn read_rgba8(self, encoder: wgpu::CommandEncoder) -> Result<Vec<u8>, KobaltError> {
+        let pixels = readback::read_texture_rgba8(self.device, self.queue, encoder, self.output.texture());
+        self.output.present();
+        pixels
+    }
+
+    /// Submits the command encoder and writes the frame to a PNG file
+    pub fn save_png(self, encoder: wgpu::CommandEncoder, path: impl AsRef<Path>) -> Result<(), KobaltError> {
+        let (width, height) = (self.texture().width(), self.texture().height());
+        let pixels = self.read_rgba8(encoder)?;
+        readback::save_png(path, width, height, &pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supported_sample_count() {
+        let flags = wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X4
+            | wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE;
+        assert_eq!(supported_sample_count(4, flags), 4);
+        assert_eq!(supported_sample_count(1, flags), 1);
+        assert_eq!(supported_sample_count(4, wgpu::TextureFormatFeatureFlags::empty()), 1);
+        assert_eq!(supported_sample_count(4, wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X4), 1);
+    }
 }
@@ -0,0 +1,769 @@
+//! Display list rendering
+//!
+//! `SceneRenderer` turns a `DisplayList` recorded by the widget tree into GPU
+//! work. Consecutive shapes, images and text are batched into a single
+//! render pass; passes are split wherever ordering requires it, the clip changes or
+//! a filter needs to read back what has been rendered so far. Clips are
+//! applied as scissor rectangles.
+//!
+//! Display lists are in logical pixels and are scaled to physical pixels
+//! by the frame's scale factor as they are drawn, so geometry is crisp and
+//! glyphs are rasterized at their physical size on HiDPI screens.
+//!
+//! Repaint boundaries are rendered into their own textures, which are kept
+//! across frames and composited again until the boundary's content version
+//! changes.
+//!
+//! With MSAA, every target (the frame, layers and boundaries) is drawn into
+//! a multisampled texture that each pass resolves into the target texture.
+//! The multisampled texture keeps the samples between passes; the resolved
+//! texture is what filters and composites read.
+
+use crate::filter::{to_wgpu_color, FilterBlend, FilterInput, FilterRenderer, FilterTarget};
+use crate::image::ImageRenderer;
+use crate::renderer::FrameContext;
+use crate::shape::RectRenderer;
+use crate::tessellate::tessellate;
+use crate::text_real::RealTextRenderer;
+use kobalt_core::error::KobaltError;
+use kobalt_core::paint::{ColorMatrix, DisplayList, DrawCommand, ImageFilter};
+use kobalt_core::types::{Color, Rect, Size, Transform};
+use std::collections::HashMap;
+
+/// Boundary textures unused for this many frames are released
+const IDLE_FRAMES: u64 = 120;
+
+/// A render target on the layer stack
+struct Target {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    /// Multisampled texture drawn into and resolved into `texture`
+    msaa: Option<wgpu::TextureView>,
+    /// Clear color still to be applied by the first pass into this target
+    pending_clear: Option<Color>,
+    /// Bounds and filter of the layer in physical pixels (None for the
+    /// frame itself)
+    layer: Option<(Rect, ImageFilter)>,
+}
+
+/// Primitive kinds in the order a batch draws them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Stage {
+    Shapes,
+    Images,
+    Text,
+}
+
+/// The rasterized content of a repaint boundary
+struct CachedBoundary {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    version: u64,
+    last_used: u64,
+}
+
+/// Layer textures of one size, indexed by the number of layers open
+/// below them
+struct LayerPool {
+    textures: Vec<wgpu::Texture>,
+    last_used: u64,
+}
+
+/// Unused multisampled textures of one size
+struct MsaaPool {
+    free: Vec<wgpu::Texture>,
+    last_used: u64,
+}
+
+/// Renders display lists into frames
+pub struct SceneRenderer {
+    rects: RectRenderer,
+    images: ImageRenderer,
+    text: RealTextRenderer,
+    filters: FilterRenderer,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    /// Multisampled textures for layers and boundaries, keyed by size
+    msaa_pools: HashMap<(u32, u32), MsaaPool>,
+    /// Offscreen textures reused by filtered layers, keyed by size
+    layer_pools: HashMap<(u32, u32), LayerPool>,
+    /// Number of layers currently open, across nested boundaries
+    open_layers: usize,
+    /// Rasterized repaint boundaries, keyed by boundary id
+    boundaries: HashMap<u64, CachedBoundary>,
+    frame: u64,
+    /// Latest stage used by the batch being built; drawing an earlier
+    /// stage afterwards requires a flush to keep painter's order
+    batch_stage: Stage,
+    /// Active clip rectangles in physical pixels, each already intersected
+    /// with its parent
+    clips: Vec<Rect>,
+    /// Physical pixels per logical pixel for the frame being rendered
+    scale: f32,
+    warned_backdrop: bool,
+    warned_boundary_size: bool,
+}
+
+impl SceneRenderer {
+    /// Creates a new scene renderer without multisampling
+    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Result<Self, KobaltError> {
+        Self::with_sample_count(device, config, 1)
+    }
+
+    /// Creates a new scene renderer drawing with the given MSAA sample count
+    /// (usually `Renderer::sample_count`)
+    pub fn with_sample_count(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        sample_count: u32,
+    ) -> Result<Self, KobaltError> {
+        let sample_count = sample_count.max(1);
+        Ok(Self {
+            rects: RectRenderer::new(device, config, sample_count),
+            images: ImageRenderer::new(device, config, sample_count),
+            text: RealTextRenderer::new(device, config, sample_count)?,
+            filters: FilterRenderer::new(device, config.format, sample_count),
+            format: config.format,
+            sample_count,
+            msaa_pools: HashMap::new(),
+            layer_pools: HashMap::new(),
+            open_layers: 0,
+            boundaries: HashMap::new(),
+            frame: 0,
+            batch_stage: Stage::Shapes,
+            clips: Vec::new(),
+            scale: 1.0,
+            warned_backdrop: false,
+            warned_boundary_size: false,
+        })
+    }
+
+    /// Returns the text renderer (useful for measuring text)
+    pub fn text_renderer(&self) -> &RealTextRenderer {
+        &self.text
+    }
+
+    /// Returns the image renderer (useful for texture cache statistics)
+    pub fn image_renderer(&self) -> &ImageRenderer {
+        &self.images
+    }
+
+    /// Returns the MSAA sample count the scene is drawn with
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Returns the number of repaint boundaries cached on the GPU
+    pub fn cached_boundaries(&self) -> usize {
+        self.boundaries.len()
+    }
+
+    /// Renders a display list into the frame, clearing it first
+    pub fn render(
+        &mut self,
+        frame: &FrameContext<'_>,
+        encoder: &mut wgpu::CommandEncoder,
+        list: &DisplayList,
+        clear_color: Color,
+    ) {
+        let device = frame.device();
+        let queue = frame.queue();
+
+        self.frame += 1;
+        self.scale = frame.scale_factor();
+        let frame_number = self.frame;
+        // Layer sizes follow the window, so only keep those of the last frame
+        self.layer_pools.retain(|_, pool| frame_number - pool.last_used <= 1);
+        self.msaa_pools.retain(|_, pool| frame_number - pool.last_used <= 1);
+        self.boundaries.retain(|_, boundary| frame_number - boundary.last_used <= IDLE_FRAMES);
+        self.open_layers = 0;
+        self.rects.begin_frame();
+        self.images.begin_frame();
+        self.text.begin_frame();
+
+        // Use the frame's multisampled texture if it matches our pipelines
+        let key = texture_key(frame.texture());
+        let msaa = if frame.sample_count() == self.sample_count {
+            frame.msaa_view().cloned()
+        } else {
+            self.acquire_msaa(device, key)
+        };
+        let root = Target {
+            texture: frame.texture().clone(),
+            view: frame.view().clone(),
+            msaa,
+            pending_clear: Some(clear_color),
+            layer: None,
+        };
+        let root = self.draw_list(device, queue, encoder, root, frame.size(), list);
+        if frame.sample_count() != self.sample_count {
+            self.release_msaa(root.msaa);
+        }
+    }
+
+    /// Draws a display list into a target of the given size in physical
+    /// pixels, returning the target
+    fn draw_list(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        root: Target,
+        size: Size,
+        list: &DisplayList,
+    ) -> Target {
+        let mut stack = vec![root];
+        self.clips.clear();
+        self.begin_batch();
+
+        for command in list.commands() {
+            let scale = self.scale;
+            match command {
+                DrawCommand::Rect { rect, color } => {
+                    self.enter_stage(Stage::Shapes, device, queue, encoder, stack.last_mut().unwrap(), size);
+                    self.rects.add_rect(rect.scale(scale), *color);
+                }
+                DrawCommand::Text {
+                    content,
+                    position,
+                    font_size,
+                    color,
+                } => {
+                    self.enter_stage(Stage::Text, device, queue, encoder, stack.last_mut().unwrap(), size);
+                    // Glyphs are rasterized at their physical size
+                    self.text
+                        .add_text(queue, content, position.scale(scale), font_size * scale, *color);
+                }
+                DrawCommand::Path {
+                    path,
+                    paint,
+                    transform,
+                } => {
+                    if paint.color.a <= 0.0 {
+                        continue;
+                    }
+                    let mesh = tessellate(path, paint, &transform.then(&Transform::scale(scale, scale)));
+                    if mesh.is_empty() {
+                        continue;
+                    }
+                    self.enter_stage(Stage::Shapes, device, queue, encoder, stack.last_mut().unwrap(), size);
+                    self.rects.add_triangles(&mesh.positions, &mesh.indices, paint.color);
+                }
+                DrawCommand::Image {
+                    image,
+                    src,
+                    dst,
+                    tint,
+                } => {
+                    self.enter_stage(Stage::Images, device, queue, encoder, stack.last_mut().unwrap(), size);
+                    self.images
+                        .add_image(device, queue, image, *src, dst.scale(scale), *tint);
+                }
+                DrawCommand::PushClip { rect } => {
+                    self.flush(device, queue, encoder, stack.last_mut().unwrap(), size);
+                    let rect = &rect.scale(scale);
+                    let clip = match self.clips.last() {
+                        Some(parent) => parent
+                            .intersection(rect)
+                            .unwrap_or(Rect::new(rect.x, rect.y, 0.0, 0.0)),
+                        None => *rect,
+                    };
+                    self.clips.push(clip);
+                }
+                DrawCommand::PopClip => {
+                    self.flush(device, queue, encoder, stack.last_mut().unwrap(), size);
+                    self.clips.pop();
+                }
+                DrawCommand::BackdropFilter { rect, filter } => {
+                    self.flush(device, queue, encoder, stack.last_mut().unwrap(), size);
+                    let filter = filter.scale(scale);
+                    self.backdrop(device, encoder, stack.last().unwrap(), size, rect.scale(scale), &filter);
+                }
+                DrawCommand::PushLayer { bounds, filter } => {
+                    self.flush(device, queue, encoder, stack.last_mut().unwrap(), size);
+                    let texture = self.layer_texture(device, size);
+                    let msaa = self.acquire_msaa(device, texture_key(&texture));
+                    self.open_layers += 1;
+                    stack.push(Target {
+                        view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                        msaa,
+                        texture,
+                        pending_clear: Some(Color::TRANSPARENT),
+                        layer: Some((bounds.scale(scale), filter.scale(scale))),
+                    });
+                }
+                DrawCommand::PopLayer => {
+                    if stack.len() < 2 {
+                        continue;
+                    }
+                    let mut layer = stack.pop().unwrap();
+                    self.open_layers -= 1;
+                    self.flush(device, queue, encoder, &mut layer, size);
+                    let parent = stack.last_mut().unwrap();
+                    self.clear_if_pending(encoder, parent);
+                    self.composite_layer(device, encoder, &layer, parent, size);
+                    self.release_msaa(layer.msaa);
+                }
+                DrawCommand::RepaintBoundary {
+                    id,
+                    version,
+                    rect,
+                    list,
+                } => {
+                    self.flush(device, queue, encoder, stack.last_mut().unwrap(), size);
+                    let target = stack.last_mut().unwrap();
+                    self.clear_if_pending(encoder, target);
+                    let rect = rect.scale(scale);
+                    self.draw_boundary(device, queue, encoder, target, size, (*id, *version), rect, list);
+                }
+            }
+        }
+
+        // Close any unbalanced layers, then make sure the frame is cleared
+        while stack.len() > 1 {
+            let mut layer = stack.pop().unwrap();
+            self.open_layers -= 1;
+            self.flush(device, queue, encoder, &mut layer, size);
+            let parent = stack.last_mut().unwrap();
+            self.clear_if_pending(encoder, parent);
+            self.composite_layer(device, encoder, &layer, parent, size);
+            self.release_msaa(layer.msaa);
+        }
+        let mut root = stack.pop().unwrap();
+        self.flush(device, queue, encoder, &mut root, size);
+        self.clear_if_pending(encoder, &mut root);
+        root
+    }
+
+    /// Resets the accumulated batch
+    fn begin_batch(&mut self) {
+        self.rects.clear();
+        self.images.clear();
+        self.text.clear();
+        self.batch_stage = Stage::Shapes;
+    }
+
+    /// Prepares the batch for a primitive of the given stage, flushing first
+    /// if a later stage has already been drawn
+    fn enter_stage(
+        &mut self,
+        stage: Stage,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &mut Target,
+        size: Size,
+    ) {
+        if stage < self.batch_stage {
+            self.flush(device, queue, encoder, target, size);
+        }
+        self.batch_stage = stage;
+    }
+
+    /// Draws the accumulated shapes, images and text into the target
+    fn flush(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &mut Target,
+        size: Size,
+    ) {
+        if self.rects.is_empty() && self.images.is_empty() && self.text.is_empty() {
+            return;
+        }
+
+        let Some(scissor) = self.scissor(size) else {
+            // Everything in the batch is clipped away
+            self.begin_batch();
+            return;
+        };
+
+        self.rects.build_buffers(device, queue, size);
+        self.images.build(device, size);
+        self.text.build(device, queue, size);
+        {
+            let mut render_pass = begin_pass(encoder, target);
+            render_pass.set_scissor_rect(
+                scissor.x as u32,
+                scissor.y as u32,
+                scissor.width as u32,
+                scissor.height as u32,
+            );
+            self.rects.render(&mut render_pass);
+            self.images.render(&mut render_pass);
+            self.text.render(&mut render_pass);
+        }
+        self.begin_batch();
+    }
+
+    /// Returns the current clip in whole pixels, or None if nothing is visible
+    fn scissor(&self, size: Size) -> Option<Rect> {
+        let frame_bounds = Rect::new(0.0, 0.0, size.width, size.height);
+        let clip = match self.clips.last() {
+            Some(clip) => snap(*clip).intersection(&frame_bounds)?,
+            None => frame_bounds,
+        };
+        (clip.width >= 1.0 && clip.height >= 1.0).then_some(clip)
+    }
+
+    /// Runs an empty pass if the target has not been cleared yet
+    fn clear_if_pending(&self, encoder: &mut wgpu::CommandEncoder, target: &mut Target) {
+        if target.pending_clear.is_some() {
+            begin_pass(encoder, target);
+        }
+    }
+
+    /// Filters the target's pixels inside `rect`
+    fn backdrop(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &Target,
+        size: Size,
+        rect: Rect,
+        filter: &ImageFilter,
+    ) {
+        if !target.texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            if !self.warned_backdrop {
+                eprintln!("Warning: surface does not support COPY_SRC, backdrop filters are disabled");
+                self.warned_backdrop = true;
+            }
+            return;
+        }
+
+        let frame_bounds = Rect::new(0.0, 0.0, size.width, size.height);
+        let (ox, oy) = filter.outset();
+        let Some(visible) = self.scissor(size) else {
+            return;
+        };
+        let clip = match snap(rect).intersection(&visible) {
+            Some(clip) => clip,
+            None => return,
+        };
+        let source = match expand(clip, ox, oy).intersection(&frame_bounds) {
+            Some(source) => snap(source),
+            None => return,
+        };
+
+        let scratch = self.filters.scratch_texture(device, source.size());
+        encoder.copy_texture_to_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &target.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: source.x as u32,
+                    y: source.y as u32,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyTextureInfo {
+                texture: scratch,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width: source.width as u32,
+                height: source.height as u32,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        self.filters.apply(
+            device,
+            encoder,
+            FilterInput::Scratch,
+            Rect::new(0.0, 0.0, source.width, source.height),
+            filter_target(target, size),
+            source,
+            clip,
+            filter,
+            FilterBlend::Replace,
+        );
+    }
+
+    /// Filters a finished layer and composites it onto its parent
+    fn composite_layer(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        layer: &Target,
+        parent: &Target,
+        size: Size,
+    ) {
+        let Some((bounds, filter)) = &layer.layer else {
+            return;
+        };
+        let frame_bounds = Rect::new(0.0, 0.0, size.width, size.height);
+        let (ox, oy) = filter.outset();
+        let region = match expand(*bounds, ox, oy).intersection(&frame_bounds) {
+            Some(region) => snap(region),
+            None => return,
+        };
+        let Some(clip) = self.scissor(size).and_then(|visible| region.intersection(&visible)) else {
+            return;
+        };
+
+        self.filters.apply(
+            device,
+            encoder,
+            FilterInput::Texture {
+                view: &layer.view,
+                size: texture_size(&layer.texture),
+            },
+            region,
+            filter_target(parent, size),
+            region,
+            clip,
+            filter,
+            FilterBlend::PremultipliedOver,
+        );
+    }
+
+    /// Returns a pooled layer texture for the next layer to be opened
+    fn layer_texture(&mut self, device: &wgpu::Device, size: Size) -> wgpu::Texture {
+        let key = (size.width.max(1.0) as u32, size.height.max(1.0) as u32);
+        let depth = self.open_layers;
+        let format = self.format;
+        let pool = self.layer_pools.entry(key).or_insert_with(|| LayerPool {
+            textures: Vec::new(),
+            last_used: 0,
+        });
+        pool.last_used = self.frame;
+        while pool.textures.len() <= depth {
+            pool.textures
+                .push(create_target_texture(device, "Filter Layer Texture", format, key));
+        }
+        pool.textures[depth].clone()
+    }
+
+    /// Returns an unused multisampled texture of the given size, or None
+    /// without MSAA
+    fn acquire_msaa(&mut self, device: &wgpu::Device, key: (u32, u32)) -> Option<wgpu::TextureView> {
+        if self.sample_count <= 1 {
+            return None;
+        }
+        let pool = self.msaa_pools.entry(key).or_insert_with(|| MsaaPool {
+            free: Vec::new(),
+            last_used: 0,
+        });
+        pool.last_used = self.frame;
+        let texture = pool.free.pop().unwrap_or_else(|| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Scene MSAA Texture"),
+                size: wgpu::Extent3d {
+                    width: key.0,
+                    height: key.1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: self.sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            })
+        });
+        Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+    }
+
+    /// Returns a multisampled texture to its pool once its target is done
+    fn release_msaa(&mut self, msaa: Option<wgpu::TextureView>) {
+        let Some(msaa) = msaa else {
+            return;
+        };
+        let texture = msaa.texture().clone();
+        if let Some(pool) = self.msaa_pools.get_mut(&texture_key(&texture)) {
+            pool.free.push(texture);
+        }
+    }
+
+    /// Composites a repaint boundary, rasterizing it first if its cached
+    /// texture is missing or out of date
+    #[allow(clippy::too_many_arguments)]
+    fn draw_boundary(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &Target,
+        size: Size,
+        (id, version): (u64, u64),
+        rect: Rect,
+        list: &DisplayList,
+    ) {
+        let max_side = device.limits().max_texture_dimension_2d;
+        let width = rect.width.ceil() as u32;
+        let height = rect.height.ceil() as u32;
+        if width == 0 || height == 0 {
+            return;
+        }
+        if (width > max_side || height > max_side) && !self.warned_boundary_size {
+            eprintln!(
+                "Warning: repaint boundary of {}x{} exceeds the maximum texture size {}, its content is cut off",
+                width, height, max_side
+            );
+            self.warned_boundary_size = true;
+        }
+        let key = (width.min(max_side), height.min(max_side));
+        let texture_rect_size = Size::new(key.0 as f32, key.1 as f32);
+
+        let reusable = self
+            .boundaries
+            .get(&id)
+            .filter(|cached| (cached.texture.width(), cached.texture.height()) == key);
+        let needs_raster = reusable.is_none_or(|cached| cached.version != version);
+        let (texture, view) = match reusable {
+            Some(cached) => (cached.texture.clone(), cached.view.clone()),
+            None => {
+                let texture = create_target_texture(device, "Repaint Boundary Texture", self.format, key);
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                (texture, view)
+            }
+        };
+
+        if needs_raster {
+            // Nested lists start with no clip; the composite below applies ours
+            let clips = std::mem::take(&mut self.clips);
+            let boundary_target = Target {
+                texture: texture.clone(),
+                view: view.clone(),
+                msaa: self.acquire_msaa(device, key),
+                pending_clear: Some(Color::TRANSPARENT),
+                layer: None,
+            };
+            let boundary_target = self.draw_list(device, queue, encoder, boundary_target, texture_rect_size, list);
+            self.release_msaa(boundary_target.msaa);
+            self.clips = clips;
+        }
+        self.boundaries.insert(
+            id,
+            CachedBoundary {
+                texture,
+                view,
+                version,
+                last_used: self.frame,
+            },
+        );
+
+        // Whole-pixel placement keeps the cached pixels sharp
+        let dst = Rect::new(rect.x.round(), rect.y.round(), texture_rect_size.width, texture_rect_size.height);
+        let Some(clip) = self.scissor(size).and_then(|visible| dst.intersection(&visible)) else {
+            return;
+        };
+        let cached = &self.boundaries[&id];
+        self.filters.apply(
+            device,
+            encoder,
+            FilterInput::Texture {
+                view: &cached.view,
+                size: texture_rect_size,
+            },
+            Rect::new(0.0, 0.0, texture_rect_size.width, texture_rect_size.height),
+            filter_target(target, size),
+            dst,
+            clip,
+            &ImageFilter::color_matrix(ColorMatrix::identity()),
+            FilterBlend::PremultipliedOver,
+        );
+    }
+}
+
+/// Creates a texture that can be rendered into, filtered and read back
+fn create_target_texture(
+    device: &wgpu::Device,
+    label: &str,
+    format: wgpu::TextureFormat,
+    (width, height): (u32, u32),
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+/// Returns the size of a texture in pixels
+fn texture_size(texture: &wgpu::Texture) -> Size {
+    Size::new(texture.width() as f32, texture.height() as f32)
+}
+
+/// Returns the pool key of a texture
+fn texture_key(texture: &wgpu::Texture) -> (u32, u32) {
+    (texture.width(), texture.height())
+}
+
+/// Returns where filters write into the target: its multisampled texture
+/// resolving into it, or the target itself
+fn filter_target(target: &Target, size: Size) -> FilterTarget<'_> {
+    FilterTarget {
+        view: target.msaa.as_ref().unwrap_or(&target.view),
+        resolve_target: target.msaa.as_ref().map(|_| &target.view),
+        size,
+    }
+}
+
+/// Begins a render pass into the target, applying any pending clear
+fn begin_pass<'e>(encoder: &'e mut wgpu::CommandEncoder, target: &mut Target) -> wgpu::RenderPass<'e> {
+    let load = match target.pending_clear.take() {
+        Some(color) => wgpu::LoadOp::Clear(to_wgpu_color(color)),
+        None => wgpu::LoadOp::Load,
+    };
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Scene Render Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target.msaa.as_ref().unwrap_or(&target.view),
+            resolve_target: target.msaa.as_ref().map(|_| &target.view),
+            ops: wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            },
+            depth_slice: None,
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    })
+}
+
+/// Grows a rectangle by the given amounts on each side
+pub(crate) fn expand(rect: Rect, dx: f32, dy: f32) -> Rect {
+    Rect::new(rect.x - dx, rect.y - dy, rect.width + 2.0 * dx, rect.height + 2.0 * dy)
+}
+
+/// Snaps a rectangle outwards to whole pixels
+pub(crate) fn snap(rect: Rect) -> Rect {
+    let x = rect.x.floor();
+    let y = rect.y.floor();
+    Rect::new(x, y, rect.right().ceil() - x, rect.bottom().ceil() - y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snap_rounds_outwards() {
+        let snapped = snap(Rect::new(10.4, 5.6, 20.2, 10.1));
+        assert_eq!(snapped, Rect::new(10.0, 5.0, 21.0, 11.0));
+    }
+
+    #[test]
+    fn test_expand() {
+        let expanded = expand(Rect::new(10.0, 10.0, 20.0, 20.0), 3.0, 6.0);
+        assert_eq!(expanded, Rect::new(7.0, 4.0, 26.0, 32.0));
+    }
+}
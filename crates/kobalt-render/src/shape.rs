@@ -1,8 +1,15 @@
-//! Simple shape rendering (rectangles, etc.)
-
+//! Simple shape rendering (rectangles and tessellated paths)
+//!
+//! Rectangles are drawn as instanced quads: each one is a single entry in a
+//! storage buffer, expanded to four corners by the vertex shader using one
+//! static index buffer. Tessellated paths use ordinary vertex and index
+//! buffers. Both live in persistent ring buffers and are drawn in the order
+//! they were added.
+
+use crate::buffer::{create_quad_index_buffer, RingBuffer, QUAD_INDICES};
 use bytemuck::{Pod, Zeroable};
 use kobalt_core::types::{Color, Rect, Size};
-use wgpu::util::DeviceExt;
+use std::ops::Range;
 
 /// Vertex data for rendering shapes
 #[repr(C)]
@@ -25,93 +32,140 @@ impl Vertex {
     }
 }
 
-/// Renderer for rectangle shapes
+/// Per-instance data for a solid rectangle
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct QuadInstance {
+    /// Left, top, right and bottom edges in NDC
+    pub bounds: [f32; 4],
+    pub color: [f32; 4],
+}
+
+/// A run of consecutive primitives of the same kind
+#[derive(Debug, Clone, PartialEq)]
+enum Run {
+    /// Range into `quads`
+    Quads(Range<u32>),
+    /// Range into `mesh_indices`
+    Mesh(Range<u32>),
+}
+
+/// Renderer for solid-colored shapes (rectangles and triangle meshes)
 pub struct RectRenderer {
-    pipeline: wgpu::RenderPipeline,
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
-    num_indices: u32,
-    /// All rectangles to render
-    rectangles: Vec<(Rect, Color)>,
+    quad_pipeline: wgpu::RenderPipeline,
+    mesh_pipeline: wgpu::RenderPipeline,
+    quad_bind_group_layout: wgpu::BindGroupLayout,
+    quad_bind_group: wgpu::BindGroup,
+    /// Generation of `instances` the bind group was created for
+    quad_bind_group_generation: u64,
+    quad_indices: wgpu::Buffer,
+    instances: RingBuffer,
+    mesh_vertex_buffer: RingBuffer,
+    mesh_index_buffer: RingBuffer,
+    /// Accumulated rectangles in screen pixels
+    quads: Vec<(Rect, Color)>,
+    /// Accumulated mesh vertices in screen pixels
+    mesh_vertices: Vec<([f32; 2], Color)>,
+    /// Accumulated triangle indices into `mesh_vertices`
+    mesh_indices: Vec<u32>,
+    runs: Vec<Run>,
+    /// Runs of the last build, with quad ranges offset to the first instance
+    prepared: Vec<Run>,
+    /// Byte ranges of the last build's mesh data in the ring buffers
+    prepared_mesh: Option<(Range<u64>, Range<u64>)>,
 }
 
 impl RectRenderer {
-    /// Creates a new rectangle renderer
-    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
+    /// Creates a new rectangle renderer for targets with the given MSAA
+    /// sample count
+    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, sample_count: u32) -> Self {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Rect Shader"),
             source: wgpu::ShaderSource::Wgsl(RECT_SHADER.into()),
         });
 
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Rect Pipeline Layout"),
-            bind_group_layouts: &[],
-            push_constant_ranges: &[],
-        });
-
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Rect Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[Vertex::desc()],
-                compilation_options: Default::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: Default::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,  // ✅ Disabled culling so rect is visible from both sides
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
-        });
-
-        // Create initial empty buffers
-        let vertices: Vec<Vertex> = vec![];
-        let indices: Vec<u16> = vec![];
-
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Rect Vertex Buffer"),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        let quad_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Rect Instance Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
         });
 
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Rect Index Buffer"),
-            contents: bytemuck::cast_slice(&indices),
-            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
-        });
+        let quad_pipeline = create_pipeline(
+            device,
+            config.format,
+            sample_count,
+            "Rect Quad Pipeline",
+            &[&quad_bind_group_layout],
+            &shader,
+            "vs_quad",
+            &[],
+        );
+        let mesh_pipeline = create_pipeline(
+            device,
+            config.format,
+            sample_count,
+            "Rect Mesh Pipeline",
+            &[],
+            &shader,
+            "vs_mesh",
+            &[Vertex::desc()],
+        );
+
+        let instances = RingBuffer::new(
+            device,
+            "Rect Instance Buffer",
+            wgpu::BufferUsages::STORAGE,
+            std::mem::size_of::<QuadInstance>() as u64,
+        );
+        let quad_bind_group = Self::create_bind_group(device, &quad_bind_group_layout, &instances);
 
         Self {
-            pipeline,
-            vertex_buffer,
-            index_buffer,
-            num_indices: 0,
-            rectangles: Vec::new(),
+            quad_pipeline,
+            mesh_pipeline,
+            quad_bind_group,
+            quad_bind_group_generation: instances.generation(),
+            quad_bind_group_layout,
+            quad_indices: create_quad_index_buffer(device, "Rect Quad Index Buffer"),
+            instances,
+            mesh_vertex_buffer: RingBuffer::new(
+                device,
+                "Rect Vertex Buffer",
+                wgpu::BufferUsages::VERTEX,
+                std::mem::size_of::<Vertex>() as u64,
+            ),
+            mesh_index_buffer: RingBuffer::new(device, "Rect Index Buffer", wgpu::BufferUsages::INDEX, 4),
+            quads: Vec::new(),
+            mesh_vertices: Vec::new(),
+            mesh_indices: Vec::new(),
+            runs: Vec::new(),
+            prepared: Vec::new(),
+            prepared_mesh: None,
         }
     }
 
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        instances: &RingBuffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Rect Instance Bind Group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: instances.buffer().as_entire_binding(),
+            }],
+        })
+    }
+
     /// Converts screen coordinates to NDC (Normalized Device Coordinates)
     fn to_ndc(x: f32, y: f32, screen_size: Size) -> [f32; 2] {
         [
@@ -120,128 +174,206 @@ impl RectRenderer {
         ]
     }
 
-    /// Clears all rectangles
+    /// Rewinds the persistent buffers; call once per frame before building
+    pub fn begin_frame(&mut self) {
+        self.instances.begin_frame();
+        self.mesh_vertex_buffer.begin_frame();
+        self.mesh_index_buffer.begin_frame();
+    }
+
+    /// Clears all accumulated shapes
     pub fn clear(&mut self) {
-        self.rectangles.clear();
+        self.quads.clear();
+        self.mesh_vertices.clear();
+        self.mesh_indices.clear();
+        self.runs.clear();
+    }
+
+    /// Returns true if no shapes have been added since the last clear
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
     }
 
     /// Adds a rectangle to be rendered
     pub fn add_rect(&mut self, rect: Rect, color: Color) {
-        self.rectangles.push((rect, color));
+        let index = self.quads.len() as u32;
+        self.quads.push((rect, color));
+        match self.runs.last_mut() {
+            Some(Run::Quads(range)) => range.end = index + 1,
+            _ => self.runs.push(Run::Quads(index..index + 1)),
+        }
     }
 
-    /// Updates the renderer with new rectangle data (legacy method for single rect)
-    pub fn update(&mut self, device: &wgpu::Device, _queue: &wgpu::Queue, rect: Rect, color: Color, screen_size: Size) {
-        let color_array = [color.r, color.g, color.b, color.a];
+    /// Adds an indexed triangle mesh (positions in screen pixels) to be rendered
+    pub fn add_triangles(&mut self, positions: &[[f32; 2]], indices: &[u32], color: Color) {
+        if indices.is_empty() {
+            return;
+        }
+        let base = self.mesh_vertices.len() as u32;
+        let start = self.mesh_indices.len() as u32;
+        self.mesh_vertices.extend(positions.iter().map(|&position| (position, color)));
+        self.mesh_indices.extend(indices.iter().map(|&index| base + index));
+        let end = self.mesh_indices.len() as u32;
+        match self.runs.last_mut() {
+            Some(Run::Mesh(range)) => range.end = end,
+            _ => self.runs.push(Run::Mesh(start..end)),
+        }
+    }
 
+    /// Updates the renderer with new rectangle data (legacy method for single rect)
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, rect: Rect, color: Color, screen_size: Size) {
         println!("Rect: ({},{}) {}x{}, Color: {:?}, Screen: {}x{}",
-                 rect.x, rect.y, rect.width, rect.height, color_array, screen_size.width, screen_size.height);
-
-        let vertices = vec![
-            Vertex {
-                position: Self::to_ndc(rect.x, rect.y, screen_size),
-                color: color_array,
-            },
-            Vertex {
-                position: Self::to_ndc(rect.x + rect.width, rect.y, screen_size),
-                color: color_array,
-            },
-            Vertex {
-                position: Self::to_ndc(rect.x + rect.width, rect.y + rect.height, screen_size),
-                color: color_array,
-            },
-            Vertex {
-                position: Self::to_ndc(rect.x, rect.y + rect.height, screen_size),
-                color: color_array,
-            },
-        ];
-
-        let indices: Vec<u16> = vec![0, 1, 2, 0, 2, 3];
-
-        // Recreate buffers with new data
-        self.vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Rect Vertex Buffer"),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
+                 rect.x, rect.y, rect.width, rect.height, [color.r, color.g, color.b, color.a],
+                 screen_size.width, screen_size.height);
 
-        self.index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Rect Index Buffer"),
-            contents: bytemuck::cast_slice(&indices),
-            usage: wgpu::BufferUsages::INDEX,
-        });
-
-        self.num_indices = indices.len() as u32;
+        self.begin_frame();
+        self.clear();
+        self.add_rect(rect, color);
+        self.build_buffers(device, queue, screen_size);
     }
 
-    /// Builds buffers with all accumulated rectangles
-    pub fn build_buffers(&mut self, device: &wgpu::Device, screen_size: Size) {
-        if self.rectangles.is_empty() {
-            self.num_indices = 0;
+    /// Uploads all accumulated shapes into the persistent buffers
+    pub fn build_buffers(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, screen_size: Size) {
+        self.prepared.clear();
+        self.prepared_mesh = None;
+        if self.runs.is_empty() {
             return;
         }
 
-        let mut all_vertices = Vec::new();
-        let mut all_indices = Vec::new();
-
-        for (rect, color) in &self.rectangles {
-            let color_array = [color.r, color.g, color.b, color.a];
-            let base_index = all_vertices.len() as u16;
-
-            // Add vertices for this rectangle
-            all_vertices.push(Vertex {
-                position: Self::to_ndc(rect.x, rect.y, screen_size),
-                color: color_array,
-            });
-            all_vertices.push(Vertex {
-                position: Self::to_ndc(rect.x + rect.width, rect.y, screen_size),
-                color: color_array,
-            });
-            all_vertices.push(Vertex {
-                position: Self::to_ndc(rect.x + rect.width, rect.y + rect.height, screen_size),
-                color: color_array,
-            });
-            all_vertices.push(Vertex {
-                position: Self::to_ndc(rect.x, rect.y + rect.height, screen_size),
-                color: color_array,
-            });
-
-            // Add indices for this rectangle (two triangles)
-            all_indices.push(base_index);
-            all_indices.push(base_index + 1);
-            all_indices.push(base_index + 2);
-            all_indices.push(base_index);
-            all_indices.push(base_index + 2);
-            all_indices.push(base_index + 3);
+        let mut first_instance = 0;
+        if !self.quads.is_empty() {
+            let instances: Vec<QuadInstance> = self
+                .quads
+                .iter()
+                .map(|(rect, color)| {
+                    let [left, top] = Self::to_ndc(rect.x, rect.y, screen_size);
+                    let [right, bottom] =
+                        Self::to_ndc(rect.x + rect.width, rect.y + rect.height, screen_size);
+                    QuadInstance {
+                        bounds: [left, top, right, bottom],
+                        color: [color.r, color.g, color.b, color.a],
+                    }
+                })
+                .collect();
+            let offset = self.instances.write(device, queue, bytemuck::cast_slice(&instances));
+            first_instance = (offset / std::mem::size_of::<QuadInstance>() as u64) as u32;
+
+            if self.quad_bind_group_generation != self.instances.generation() {
+                self.quad_bind_group =
+                    Self::create_bind_group(device, &self.quad_bind_group_layout, &self.instances);
+                self.quad_bind_group_generation = self.instances.generation();
+            }
         }
 
-        // Create buffers with all rectangles
-        self.vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Rect Vertex Buffer"),
-            contents: bytemuck::cast_slice(&all_vertices),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
-
-        self.index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Rect Index Buffer"),
-            contents: bytemuck::cast_slice(&all_indices),
-            usage: wgpu::BufferUsages::INDEX,
-        });
+        if !self.mesh_indices.is_empty() {
+            let vertices: Vec<Vertex> = self
+                .mesh_vertices
+                .iter()
+                .map(|&([x, y], color)| Vertex {
+                    position: Self::to_ndc(x, y, screen_size),
+                    color: [color.r, color.g, color.b, color.a],
+                })
+                .collect();
+            let vertex_bytes: &[u8] = bytemuck::cast_slice(&vertices);
+            let index_bytes: &[u8] = bytemuck::cast_slice(&self.mesh_indices);
+            let vertex_offset = self.mesh_vertex_buffer.write(device, queue, vertex_bytes);
+            let index_offset = self.mesh_index_buffer.write(device, queue, index_bytes);
+            self.prepared_mesh = Some((
+                vertex_offset..vertex_offset + vertex_bytes.len() as u64,
+                index_offset..index_offset + index_bytes.len() as u64,
+            ));
+        }
 
-        self.num_indices = all_indices.len() as u32;
+        self.prepared.extend(self.runs.iter().map(|run| match run {
+            Run::Quads(range) => Run::Quads(range.start + first_instance..range.end + first_instance),
+            Run::Mesh(range) => Run::Mesh(range.clone()),
+        }));
     }
 
-    /// Renders the rectangle
+    /// Renders the shapes uploaded by the last `build_buffers`
     pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
-        if self.num_indices > 0 {
-            render_pass.set_pipeline(&self.pipeline);
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+        for run in &self.prepared {
+            match run {
+                Run::Quads(instances) => {
+                    render_pass.set_pipeline(&self.quad_pipeline);
+                    render_pass.set_bind_group(0, &self.quad_bind_group, &[]);
+                    render_pass.set_index_buffer(self.quad_indices.slice(..), wgpu::IndexFormat::Uint32);
+                    render_pass.draw_indexed(0..QUAD_INDICES.len() as u32, 0, instances.clone());
+                }
+                Run::Mesh(indices) => {
+                    let Some((vertex_range, index_range)) = &self.prepared_mesh else {
+                        continue;
+                    };
+                    render_pass.set_pipeline(&self.mesh_pipeline);
+                    render_pass.set_vertex_buffer(0, self.mesh_vertex_buffer.buffer().slice(vertex_range.clone()));
+                    render_pass.set_index_buffer(
+                        self.mesh_index_buffer.buffer().slice(index_range.clone()),
+                        wgpu::IndexFormat::Uint32,
+                    );
+                    render_pass.draw_indexed(indices.clone(), 0, 0..1);
+                }
+            }
         }
     }
 }
 
+/// Creates an alpha-blended triangle pipeline for one of the shape vertex stages
+#[allow(clippy::too_many_arguments)]
+fn create_pipeline(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    label: &str,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    shader: &wgpu::ShaderModule,
+    vertex_entry: &str,
+    buffers: &[wgpu::VertexBufferLayout<'_>],
+) -> wgpu::RenderPipeline {
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some(label),
+        bind_group_layouts,
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some(vertex_entry),
+            buffers,
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,  // ✅ Disabled culling so rect is visible from both sides
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+        cache: None,
+    })
+}
+
 const RECT_SHADER: &str = r#"
 struct VertexInput {
     @location(0) position: vec2<f32>,
@@ -253,8 +385,28 @@ struct VertexOutput {
     @location(0) color: vec4<f32>,
 }
 
+struct Quad {
+    bounds: vec4<f32>,
+    color: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<storage, read> quads: array<Quad>;
+
+@vertex
+fn vs_quad(@builtin(vertex_index) vertex: u32, @builtin(instance_index) instance: u32) -> VertexOutput {
+    let quad = quads[instance];
+    // Corners run clockwise from the top-left
+    let x = select(quad.bounds.x, quad.bounds.z, vertex == 1u || vertex == 2u);
+    let y = select(quad.bounds.y, quad.bounds.w, vertex >= 2u);
+    var output: VertexOutput;
+    output.clip_position = vec4<f32>(x, y, 0.0, 1.0);
+    output.color = quad.color;
+    return output;
+}
+
 @vertex
-fn vs_main(input: VertexInput) -> VertexOutput {
+fn vs_mesh(input: VertexInput) -> VertexOutput {
     var output: VertexOutput;
     output.clip_position = vec4<f32>(input.position, 0.0, 1.0);
     output.color = input.color;
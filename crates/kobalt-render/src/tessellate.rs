@@ -0,0 +1,245 @@
+//! Path tessellation
+//!
+//! Converts vector paths into triangle meshes with lyon. Paths are
+//! tessellated in their local coordinates and the resulting vertices are
+//! transformed afterwards, so strokes stay correct under rotation and
+//! non-uniform scale.
+
+use kobalt_core::paint::{Paint, PaintStyle};
+use kobalt_core::path::{FillRule, Path, PathCommand, StrokeCap, StrokeJoin, StrokeStyle};
+use kobalt_core::types::{Point, Transform};
+use lyon_tessellation::math::point;
+use lyon_tessellation::{
+    BuffersBuilder, FillOptions, FillTessellator, FillVertex, LineCap, LineJoin, StrokeOptions,
+    StrokeTessellator, StrokeVertex, VertexBuffers,
+};
+
+/// Maximum distance in pixels between a curve and its flattened approximation
+pub const TOLERANCE: f32 = 0.25;
+
+/// A triangle mesh with positions in screen pixels
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub positions: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    /// Returns true if the mesh has no triangles
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+}
+
+/// Tessellates a path drawn with `paint` and maps it through `transform`
+pub fn tessellate(path: &Path, paint: &Paint, transform: &Transform) -> Mesh {
+    if path.is_empty() {
+        return Mesh::default();
+    }
+
+    let scale = transform.max_scale().max(f32::EPSILON);
+    let tolerance = TOLERANCE / scale;
+
+    let result = match &paint.style {
+        PaintStyle::Fill => fill(path, tolerance),
+        PaintStyle::Stroke(style) => {
+            if style.is_dashed() {
+                stroke(&path.dashed(&style.dash_array, style.dash_offset, tolerance), style, scale, tolerance)
+            } else {
+                stroke(path, style, scale, tolerance)
+            }
+        }
+    };
+
+    match result {
+        Ok(mut mesh) => {
+            if !transform.is_identity() {
+                for position in &mut mesh.positions {
+                    let p = transform.transform_point(Point::new(position[0], position[1]));
+                    *position = [p.x, p.y];
+                }
+            }
+            mesh
+        }
+        Err(error) => {
+            eprintln!("Warning: failed to tessellate path: {:?}", error);
+            Mesh::default()
+        }
+    }
+}
+
+fn fill(path: &Path, tolerance: f32) -> Result<Mesh, lyon_tessellation::TessellationError> {
+    let fill_rule = match path.fill_rule() {
+        FillRule::NonZero => lyon_tessellation::FillRule::NonZero,
+        FillRule::EvenOdd => lyon_tessellation::FillRule::EvenOdd,
+    };
+    let options = FillOptions::tolerance(tolerance).with_fill_rule(fill_rule);
+
+    let mut buffers: VertexBuffers<[f32; 2], u32> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        &to_lyon(path),
+        &options,
+        &mut BuffersBuilder::new(&mut buffers, |vertex: FillVertex| vertex.position().to_array()),
+    )?;
+    Ok(Mesh {
+        positions: buffers.vertices,
+        indices: buffers.indices,
+    })
+}
+
+fn stroke(
+    path: &Path,
+    style: &StrokeStyle,
+    scale: f32,
+    tolerance: f32,
+) -> Result<Mesh, lyon_tessellation::TessellationError> {
+    // A zero width is a hairline: one device pixel regardless of transform
+    let width = if style.width > 0.0 { style.width } else { 1.0 / scale };
+    let cap = match style.cap {
+        StrokeCap::Butt => LineCap::Butt,
+        StrokeCap::Round => LineCap::Round,
+        StrokeCap::Square => LineCap::Square,
+    };
+    let join = match style.join {
+        StrokeJoin::Miter => LineJoin::Miter,
+        StrokeJoin::Round => LineJoin::Round,
+        StrokeJoin::Bevel => LineJoin::Bevel,
+    };
+    let options = StrokeOptions::tolerance(tolerance)
+        .with_line_width(width)
+        .with_line_cap(cap)
+        .with_line_join(join)
+        .with_miter_limit(style.miter_limit.max(StrokeOptions::MINIMUM_MITER_LIMIT));
+
+    let mut buffers: VertexBuffers<[f32; 2], u32> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_path(
+        &to_lyon(path),
+        &options,
+        &mut BuffersBuilder::new(&mut buffers, |vertex: StrokeVertex| vertex.position().to_array()),
+    )?;
+    Ok(Mesh {
+        positions: buffers.vertices,
+        indices: buffers.indices,
+    })
+}
+
+/// Converts a path into lyon's representation
+fn to_lyon(path: &Path) -> lyon_tessellation::path::Path {
+    let mut builder = lyon_tessellation::path::Path::builder();
+    let to_point = |p: Point| point(p.x, p.y);
+    let mut open = false;
+    let mut start = Point::zero();
+    let mut last = Point::zero();
+
+    for command in path.commands() {
+        // Drawing after a close continues from the closed subpath's start
+        if !open && !matches!(command, PathCommand::MoveTo(_) | PathCommand::Close) {
+            builder.begin(to_point(last));
+            start = last;
+            open = true;
+        }
+        match *command {
+            PathCommand::MoveTo(p) => {
+                if open {
+                    builder.end(false);
+                }
+                builder.begin(to_point(p));
+                open = true;
+                start = p;
+                last = p;
+            }
+            PathCommand::LineTo(p) => {
+                builder.line_to(to_point(p));
+                last = p;
+            }
+            PathCommand::QuadTo(c, p) => {
+                builder.quadratic_bezier_to(to_point(c), to_point(p));
+                last = p;
+            }
+            PathCommand::CubicTo(c1, c2, p) => {
+                builder.cubic_bezier_to(to_point(c1), to_point(c2), to_point(p));
+                last = p;
+            }
+            PathCommand::Close => {
+                if open {
+                    builder.end(true);
+                    open = false;
+                }
+                last = start;
+            }
+        }
+    }
+    if open {
+        builder.end(false);
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kobalt_core::types::{Color, Rect};
+
+    fn extent(mesh: &Mesh) -> Rect {
+        let (mut min_x, mut min_y) = (f32::INFINITY, f32::INFINITY);
+        let (mut max_x, mut max_y) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for [x, y] in &mesh.positions {
+            min_x = min_x.min(*x);
+            min_y = min_y.min(*y);
+            max_x = max_x.max(*x);
+            max_y = max_y.max(*y);
+        }
+        Rect::new(min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    #[test]
+    fn test_fill_rect_is_transformed() {
+        let path = Path::rect(Rect::new(0.0, 0.0, 10.0, 20.0));
+        let mesh = tessellate(&path, &Paint::fill(Color::RED), &Transform::translate(5.0, 5.0));
+        assert_eq!(mesh.indices.len() % 3, 0);
+        assert_eq!(extent(&mesh), Rect::new(5.0, 5.0, 10.0, 20.0));
+    }
+
+    #[test]
+    fn test_stroke_line_has_width() {
+        let mut path = Path::new();
+        path.move_to(Point::new(0.0, 10.0)).line_to(Point::new(100.0, 10.0));
+        let paint = Paint::stroke(Color::RED, StrokeStyle::new(4.0));
+        let mesh = tessellate(&path, &paint, &Transform::identity());
+        let bounds = extent(&mesh);
+        assert!((bounds.y - 8.0).abs() < 1e-4);
+        assert!((bounds.height - 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_stroke_scales_with_transform() {
+        let mut path = Path::new();
+        path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(10.0, 0.0));
+        let paint = Paint::stroke(Color::RED, StrokeStyle::new(2.0));
+        let mesh = tessellate(&path, &paint, &Transform::scale(3.0, 3.0));
+        assert!((extent(&mesh).height - 6.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_dashed_stroke_splits_segments() {
+        let mut path = Path::new();
+        path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(100.0, 0.0));
+        let solid = tessellate(
+            &path,
+            &Paint::stroke(Color::RED, StrokeStyle::new(2.0)),
+            &Transform::identity(),
+        );
+        let dashed = tessellate(
+            &path,
+            &Paint::stroke(Color::RED, StrokeStyle::new(2.0).dash(vec![10.0, 10.0], 0.0)),
+            &Transform::identity(),
+        );
+        assert!(dashed.indices.len() > solid.indices.len());
+    }
+
+    #[test]
+    fn test_empty_path() {
+        let mesh = tessellate(&Path::new(), &Paint::default(), &Transform::identity());
+        assert!(mesh.is_empty());
+    }
+}
@@ -16,10 +16,11 @@ pub struct KobaltTextRenderer {
 }
 
 impl KobaltTextRenderer {
-    /// Creates a new text renderer
-    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
+    /// Creates a new text renderer for targets with the given MSAA sample
+    /// count
+    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, sample_count: u32) -> Self {
         Self {
-            rect_renderer: RectRenderer::new(device, config),
+            rect_renderer: RectRenderer::new(device, config, sample_count),
         }
     }
 
@@ -52,11 +53,12 @@ impl KobaltTextRenderer {
     }
 
     /// Builds all text for rendering
-    pub fn build(&mut self, device: &wgpu::Device, screen_size: kobalt_core::types::Size) {
-        self.rect_renderer.build_buffers(device, screen_size);
+    pub fn build(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, screen_size: kobalt_core::types::Size) {
+        self.rect_renderer.build_buffers(device, queue, screen_size);
     }
 
     /// Legacy method for single text rendering
+    #[allow(clippy::too_many_arguments)]
     pub fn prepare_text(
         &mut self,
         device: &wgpu::Device,
@@ -67,9 +69,10 @@ impl KobaltTextRenderer {
         color: Color,
         screen_size: kobalt_core::types::Size,
     ) {
+        self.rect_renderer.begin_frame();
         self.clear();
         self.add_text(text, position, font_size, color);
-        self.build(device, screen_size);
+        self.build(device, queue, screen_size);
     }
 
     /// Renders the prepared text
@@ -1,35 +1,26 @@
 //! Real text rendering using fontdue
 //!
 //! This module provides actual text rendering with fonts, replacing the placeholder rectangles.
+//! Glyphs are drawn as instanced quads read from a persistent storage buffer.
 
 use bytemuck::{Pod, Zeroable};
 use fontdue::{Font, FontSettings};
-use kobalt_core::types::{Color, Point};
+use kobalt_core::error::KobaltError;
+use kobalt_core::types::{Color, Point, Size};
+use crate::buffer::{create_quad_index_buffer, RingBuffer, QUAD_INDICES};
 use std::collections::HashMap;
-use wgpu::util::DeviceExt;
 
-/// Vertex for text rendering
+/// Per-instance data for a glyph quad
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
-struct TextVertex {
-    position: [f32; 2],
-    tex_coords: [f32; 2],
+struct GlyphInstance {
+    /// Left, top, right and bottom edges in NDC
+    bounds: [f32; 4],
+    /// Left, top, right and bottom texture coordinates in the atlas
+    tex_bounds: [f32; 4],
     color: [f32; 4],
 }
 
-impl TextVertex {
-    const ATTRS: [wgpu::VertexAttribute; 3] =
-        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4];
-
-    fn desc() -> wgpu::VertexBufferLayout<'static> {
-        wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<TextVertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &Self::ATTRS,
-        }
-    }
-}
-
 /// A rasterized glyph in the atlas
 struct GlyphInfo {
     atlas_x: u32,
@@ -41,34 +32,58 @@ struct GlyphInfo {
     advance: f32,
 }
 
+/// A positioned glyph waiting to be turned into vertices
+struct GlyphQuad {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    atlas_x: u32,
+    atlas_y: u32,
+    color: [f32; 4],
+}
+
 /// Real text renderer using fontdue
 pub struct RealTextRenderer {
     font: Font,
     pipeline: wgpu::RenderPipeline,
-    bind_group_layout: wgpu::BindGroupLayout,
     atlas_texture: wgpu::Texture,
-    atlas_view: wgpu::TextureView,
-    atlas_sampler: wgpu::Sampler,
     bind_group: wgpu::BindGroup,
-    glyph_cache: HashMap<char, GlyphInfo>,
+    glyph_cache: HashMap<(char, u32), GlyphInfo>,
+    glyphs: Vec<GlyphQuad>,
     atlas_width: u32,
     atlas_height: u32,
     next_x: u32,
     next_y: u32,
     current_row_height: u32,
-    vertex_buffer: Option<wgpu::Buffer>,
-    index_buffer: Option<wgpu::Buffer>,
-    num_indices: u32,
+    instance_bind_group_layout: wgpu::BindGroupLayout,
+    instance_bind_group: wgpu::BindGroup,
+    /// Generation of `instances` the bind group was created for
+    instance_bind_group_generation: u64,
+    instances: RingBuffer,
+    quad_indices: wgpu::Buffer,
+    /// Instances uploaded by the last build
+    prepared: std::ops::Range<u32>,
+}
+
+/// Loads the embedded default font
+///
+/// A basic embedded font for now; in a real app you'd load from a file or
+/// embed a proper font.
+pub(crate) fn default_font() -> Result<Font, KobaltError> {
+    let font_data = include_bytes!("../assets/fonts/Roboto-Regular.ttf");
+    Font::from_bytes(&font_data[..], FontSettings::default()).map_err(|error| KobaltError::FontLoad(error.to_string()))
 }
 
 impl RealTextRenderer {
-    /// Creates a new real text renderer with a default font
-    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
-        // Load default font (using a basic embedded font for now)
-        // In a real app, you'd load from a file or embed a proper font
-        let font_data = include_bytes!("../assets/fonts/Roboto-Regular.ttf");
-        let font = Font::from_bytes(&font_data[..], FontSettings::default())
-            .expect("Failed to load font");
+    /// Creates a new real text renderer with a default font, for targets
+    /// with the given MSAA sample count
+    pub fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        sample_count: u32,
+    ) -> Result<Self, KobaltError> {
+        let font = default_font()?;
 
         // Create texture atlas for glyphs (1024x1024 should be enough for most cases)
         let atlas_width = 1024;
@@ -139,6 +154,28 @@ impl RealTextRenderer {
             ],
         });
 
+        let instance_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Text Instance Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let instances = RingBuffer::new(
+            device,
+            "Text Instance Buffer",
+            wgpu::BufferUsages::STORAGE,
+            std::mem::size_of::<GlyphInstance>() as u64,
+        );
+        let instance_bind_group = Self::create_instance_bind_group(device, &instance_bind_group_layout, &instances);
+
         // Create shader
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Text Shader"),
@@ -148,7 +185,7 @@ impl RealTextRenderer {
         // Create pipeline
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Text Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
+            bind_group_layouts: &[&bind_group_layout, &instance_bind_group_layout],
             push_constant_ranges: &[],
         });
 
@@ -158,7 +195,7 @@ impl RealTextRenderer {
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: Some("vs_main"),
-                buffers: &[TextVertex::desc()],
+                buffers: &[],
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
@@ -182,7 +219,7 @@ impl RealTextRenderer {
             },
             depth_stencil: None,
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -190,35 +227,46 @@ impl RealTextRenderer {
             cache: None,
         });
 
-        Self {
+        Ok(Self {
             font,
             pipeline,
-            bind_group_layout,
             atlas_texture,
-            atlas_view,
-            atlas_sampler,
             bind_group,
             glyph_cache: HashMap::new(),
+            glyphs: Vec::new(),
             atlas_width,
             atlas_height,
             next_x: 0,
             next_y: 0,
             current_row_height: 0,
-            vertex_buffer: None,
-            index_buffer: None,
-            num_indices: 0,
-        }
+            instance_bind_group,
+            instance_bind_group_generation: instances.generation(),
+            instance_bind_group_layout,
+            instances,
+            quad_indices: create_quad_index_buffer(device, "Text Quad Index Buffer"),
+            prepared: 0..0,
+        })
     }
 
-    /// Rasterizes a glyph and adds it to the atlas
-    fn rasterize_glyph(
-        &mut self,
+    fn create_instance_bind_group(
         device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        ch: char,
-        font_size: f32,
-    ) -> &GlyphInfo {
-        if !self.glyph_cache.contains_key(&ch) {
+        layout: &wgpu::BindGroupLayout,
+        instances: &RingBuffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Text Instance Bind Group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: instances.buffer().as_entire_binding(),
+            }],
+        })
+    }
+
+    /// Rasterizes a glyph and adds it to the atlas
+    fn rasterize_glyph(&mut self, queue: &wgpu::Queue, ch: char, font_size: f32) -> &GlyphInfo {
+        let key = (ch, font_size.to_bits());
+        if !self.glyph_cache.contains_key(&key) {
             let (metrics, bitmap) = self.font.rasterize(ch, font_size);
 
             // Check if we need to move to next row
@@ -232,7 +280,7 @@ impl RealTextRenderer {
             self.current_row_height = self.current_row_height.max(metrics.height as u32);
 
             // Copy glyph to atlas
-            if !bitmap.is_empty() {
+            if !bitmap.is_empty() && self.next_y + metrics.height as u32 <= self.atlas_height {
                 queue.write_texture(
                     wgpu::TexelCopyTextureInfo {
                         texture: &self.atlas_texture,
@@ -269,128 +317,153 @@ impl RealTextRenderer {
             };
 
             self.next_x += metrics.width as u32;
-            self.glyph_cache.insert(ch, glyph_info);
+            self.glyph_cache.insert(key, glyph_info);
         }
 
-        self.glyph_cache.get(&ch).unwrap()
+        self.glyph_cache.get(&key).unwrap()
     }
 
-    /// Prepares text for rendering
-    pub fn prepare_text(
+    /// Clears all accumulated text
+    pub fn clear(&mut self) {
+        self.glyphs.clear();
+    }
+
+    /// Returns true if no glyphs have been added since the last clear
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Adds a run of text to be rendered
+    ///
+    /// `position` is the top-left corner of the line box in pixels; glyphs are
+    /// placed on a baseline one ascent below it.
+    pub fn add_text(
         &mut self,
-        device: &wgpu::Device,
         queue: &wgpu::Queue,
         text: &str,
         position: Point,
         font_size: f32,
         color: Color,
-        screen_width: f32,
-        screen_height: f32,
     ) {
-        let mut vertices = Vec::new();
-        let mut indices = Vec::new();
-
+        let ascent = self
+            .font
+            .horizontal_line_metrics(font_size)
+            .map(|metrics| metrics.ascent)
+            .unwrap_or(font_size);
+        let baseline = position.y + ascent;
         let mut cursor_x = position.x;
-        let cursor_y = position.y;
-        let atlas_width = self.atlas_width;
-        let atlas_height = self.atlas_height;
+        let color_array = [color.r, color.g, color.b, color.a];
 
         for ch in text.chars() {
-            let glyph = self.rasterize_glyph(device, queue, ch, font_size);
+            let glyph = self.rasterize_glyph(queue, ch, font_size);
 
             if glyph.width > 0 && glyph.height > 0 {
-                let x = cursor_x + glyph.bearing_x as f32;
-                let y = cursor_y - glyph.bearing_y as f32;
-
-                // Convert to NDC
-                let x0 = (x / screen_width) * 2.0 - 1.0;
-                let y0 = 1.0 - (y / screen_height) * 2.0;
-                let x1 = ((x + glyph.width as f32) / screen_width) * 2.0 - 1.0;
-                let y1 = 1.0 - ((y + glyph.height as f32) / screen_height) * 2.0;
-
-                // Texture coordinates
-                let u0 = glyph.atlas_x as f32 / atlas_width as f32;
-                let v0 = glyph.atlas_y as f32 / atlas_height as f32;
-                let u1 = (glyph.atlas_x + glyph.width) as f32 / atlas_width as f32;
-                let v1 = (glyph.atlas_y + glyph.height) as f32 / atlas_height as f32;
-
-                let color_array = [color.r, color.g, color.b, color.a];
-                let base_index = vertices.len() as u16;
-
-                // Add vertices
-                vertices.push(TextVertex {
-                    position: [x0, y0],
-                    tex_coords: [u0, v0],
+                let quad = GlyphQuad {
+                    x: cursor_x + glyph.bearing_x as f32,
+                    y: baseline - (glyph.bearing_y + glyph.height as i32) as f32,
+                    width: glyph.width as f32,
+                    height: glyph.height as f32,
+                    atlas_x: glyph.atlas_x,
+                    atlas_y: glyph.atlas_y,
                     color: color_array,
-                });
-                vertices.push(TextVertex {
-                    position: [x1, y0],
-                    tex_coords: [u1, v0],
-                    color: color_array,
-                });
-                vertices.push(TextVertex {
-                    position: [x1, y1],
-                    tex_coords: [u1, v1],
-                    color: color_array,
-                });
-                vertices.push(TextVertex {
-                    position: [x0, y1],
-                    tex_coords: [u0, v1],
-                    color: color_array,
-                });
-
-                // Add indices
-                indices.extend_from_slice(&[
-                    base_index,
-                    base_index + 1,
-                    base_index + 2,
-                    base_index,
-                    base_index + 2,
-                    base_index + 3,
-                ]);
+                };
+                cursor_x += glyph.advance;
+                self.glyphs.push(quad);
+            } else {
+                cursor_x += glyph.advance;
             }
-
-            cursor_x += glyph.advance;
         }
+    }
 
-        if !vertices.is_empty() {
-            self.vertex_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Text Vertex Buffer"),
-                contents: bytemuck::cast_slice(&vertices),
-                usage: wgpu::BufferUsages::VERTEX,
-            }));
+    /// Measures the advance width of a run of text without rendering it
+    pub fn measure(&self, text: &str, font_size: f32) -> f32 {
+        text.chars()
+            .map(|ch| self.font.metrics(ch, font_size).advance_width)
+            .sum()
+    }
 
-            self.index_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Text Index Buffer"),
-                contents: bytemuck::cast_slice(&indices),
-                usage: wgpu::BufferUsages::INDEX,
-            }));
+    /// Rewinds the persistent instance buffer; call once per frame before building
+    pub fn begin_frame(&mut self) {
+        self.instances.begin_frame();
+    }
 
-            self.num_indices = indices.len() as u32;
+    /// Uploads all accumulated glyphs into the persistent instance buffer
+    pub fn build(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, screen_size: Size) {
+        self.prepared = 0..0;
+        if self.glyphs.is_empty() {
+            return;
         }
+
+        let atlas_width = self.atlas_width as f32;
+        let atlas_height = self.atlas_height as f32;
+        let instances: Vec<GlyphInstance> = self
+            .glyphs
+            .iter()
+            .map(|glyph| GlyphInstance {
+                bounds: [
+                    (glyph.x / screen_size.width) * 2.0 - 1.0,
+                    1.0 - (glyph.y / screen_size.height) * 2.0,
+                    ((glyph.x + glyph.width) / screen_size.width) * 2.0 - 1.0,
+                    1.0 - ((glyph.y + glyph.height) / screen_size.height) * 2.0,
+                ],
+                tex_bounds: [
+                    glyph.atlas_x as f32 / atlas_width,
+                    glyph.atlas_y as f32 / atlas_height,
+                    (glyph.atlas_x as f32 + glyph.width) / atlas_width,
+                    (glyph.atlas_y as f32 + glyph.height) / atlas_height,
+                ],
+                color: glyph.color,
+            })
+            .collect();
+
+        let offset = self.instances.write(device, queue, bytemuck::cast_slice(&instances));
+        if self.instance_bind_group_generation != self.instances.generation() {
+            self.instance_bind_group =
+                Self::create_instance_bind_group(device, &self.instance_bind_group_layout, &self.instances);
+            self.instance_bind_group_generation = self.instances.generation();
+        }
+
+        let first = (offset / std::mem::size_of::<GlyphInstance>() as u64) as u32;
+        self.prepared = first..first + instances.len() as u32;
+    }
+
+    /// Prepares a single run of text for rendering, replacing any previous text
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare_text(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        text: &str,
+        position: Point,
+        font_size: f32,
+        color: Color,
+        screen_width: f32,
+        screen_height: f32,
+    ) {
+        self.begin_frame();
+        self.clear();
+        self.add_text(queue, text, position, font_size, color);
+        self.build(device, queue, Size::new(screen_width, screen_height));
     }
 
     /// Renders the prepared text
     pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
-        if self.num_indices > 0 {
-            if let (Some(vertex_buffer), Some(index_buffer)) =
-                (&self.vertex_buffer, &self.index_buffer)
-            {
-                render_pass.set_pipeline(&self.pipeline);
-                render_pass.set_bind_group(0, &self.bind_group, &[]);
-                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-                render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-                render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
-            }
+        if self.prepared.is_empty() {
+            return;
         }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.instance_bind_group, &[]);
+        render_pass.set_index_buffer(self.quad_indices.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..QUAD_INDICES.len() as u32, 0, self.prepared.clone());
     }
 }
 
 const TEXT_SHADER: &str = r#"
-struct VertexInput {
-    @location(0) position: vec2<f32>,
-    @location(1) tex_coords: vec2<f32>,
-    @location(2) color: vec4<f32>,
+struct Glyph {
+    bounds: vec4<f32>,
+    tex_bounds: vec4<f32>,
+    color: vec4<f32>,
 }
 
 struct VertexOutput {
@@ -399,12 +472,27 @@ struct VertexOutput {
     @location(1) color: vec4<f32>,
 }
 
+@group(1) @binding(0)
+var<storage, read> glyphs: array<Glyph>;
+
 @vertex
-fn vs_main(input: VertexInput) -> VertexOutput {
+fn vs_main(@builtin(vertex_index) vertex: u32, @builtin(instance_index) instance: u32) -> VertexOutput {
+    let glyph = glyphs[instance];
+    // Corners run clockwise from the top-left
+    let right = vertex == 1u || vertex == 2u;
+    let bottom = vertex >= 2u;
     var output: VertexOutput;
-    output.clip_position = vec4<f32>(input.position, 0.0, 1.0);
-    output.tex_coords = input.tex_coords;
-    output.color = input.color;
+    output.clip_position = vec4<f32>(
+        select(glyph.bounds.x, glyph.bounds.z, right),
+        select(glyph.bounds.y, glyph.bounds.w, bottom),
+        0.0,
+        1.0,
+    );
+    output.tex_coords = vec2<f32>(
+        select(glyph.tex_bounds.x, glyph.tex_bounds.z, right),
+        select(glyph.tex_bounds.y, glyph.tex_bounds.w, bottom),
+    );
+    output.color = glyph.color;
     return output;
 }
 
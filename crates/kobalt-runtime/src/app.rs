@@ -3,9 +3,18 @@
 //! Provides a declarative, Flutter-style API for building Kobalt apps
 
 use crate::desktop::DesktopApp;
+use crate::device::{DeviceProfile, DEVICE_FRAME_VAR};
+use crate::input::PointerTranslator;
+use crate::keyboard::KeyTranslator;
+use crate::touch_emulation::{TouchEmulator, TOUCH_EMULATION_VAR};
+use kobalt_core::error::KobaltError;
+use kobalt_core::event::{Event as InputEvent, PointerEvent};
+use kobalt_core::host::WidgetHost;
+use kobalt_core::media_query::MediaQuery;
+use kobalt_core::scheduler::FrameScheduler;
 use kobalt_core::types::Color;
 use kobalt_core::widget::Widget;
-use kobalt_render::RealTextRenderer;
+use kobalt_render::{RendererConfig, SceneRenderer, SurfaceError};
 use winit::event::{Event, WindowEvent};
 
 /// Kobalt application builder
@@ -19,7 +28,7 @@ use winit::event::{Event, WindowEvent};
 /// use kobalt_widgets::Text;
 ///
 /// KobaltApp::build()
-///     .title("My App")
+///     .title(Text::new("My App"))
 ///     .size(800, 600)
 ///     .home(Text::new("Hello, Kobalt!"))
 ///     .run()
@@ -30,6 +39,10 @@ pub struct KobaltApp {
     width: u32,
     height: u32,
     background_color: Color,
+    text_scale_factor: f32,
+    renderer_config: RendererConfig,
+    touch_emulation: bool,
+    device_frame: Option<DeviceProfile>,
     home: Option<Box<dyn Widget>>,
 }
 
@@ -44,6 +57,10 @@ impl KobaltApp {
             width: 800,
             height: 600,
             background_color: Color::from_rgb8(20, 20, 30),
+            text_scale_factor: 1.0,
+            renderer_config: RendererConfig::default(),
+            touch_emulation: false,
+            device_frame: None,
             home: None,
         }
     }
@@ -54,7 +71,7 @@ impl KobaltApp {
         self
     }
 
-    /// Sets the window size
+    /// Sets the window size in logical pixels
     pub fn size(mut self, width: u32, height: u32) -> Self {
         self.width = width;
         self.height = height;
@@ -67,6 +84,41 @@ impl KobaltApp {
         self
     }
 
+    /// Sets the initial text scale factor applied to `sp` lengths
+    ///
+    /// It can be changed while running with
+    /// `MediaQuery::set_text_scale_factor`.
+    pub fn text_scale_factor(mut self, text_scale_factor: f32) -> Self {
+        self.text_scale_factor = text_scale_factor;
+        self
+    }
+
+    /// Sets the GPU and surface options (present mode, adapter, backends,
+    /// MSAA, transparency)
+    pub fn renderer_config(mut self, renderer_config: RendererConfig) -> Self {
+        self.renderer_config = renderer_config;
+        self
+    }
+
+    /// Delivers the mouse as touches, with Ctrl+drag for two-finger pinch
+    /// and rotate (see `TouchEmulator`)
+    ///
+    /// Can also be enabled with `KOBALT_TOUCH_EMULATION=1`.
+    pub fn touch_emulation(mut self, enabled: bool) -> Self {
+        self.touch_emulation = enabled;
+        self
+    }
+
+    /// Sizes the window like a phone and publishes its safe area as
+    /// `MediaQueryData::padding`; the window cannot be resized
+    ///
+    /// Can also be chosen by name with e.g. `KOBALT_DEVICE_FRAME=pixel-8`,
+    /// which takes precedence over `size`.
+    pub fn device_frame(mut self, profile: DeviceProfile) -> Self {
+        self.device_frame = Some(profile);
+        self
+    }
+
     /// Sets the home widget (main content)
     pub fn home<W: Widget + 'static>(mut self, widget: W) -> Self {
         self.home = Some(Box::new(widget));
@@ -74,12 +126,38 @@ impl KobaltApp {
     }
 
     /// Runs the application
-    pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+    ///
+    /// Mouse and touch input is delivered to the widgets under the pointer,
+    /// keyboard input to the focused widget (see
+    /// `WidgetHost::dispatch_event`). The cursor is the one asked for by
+    /// the `MouseRegion`s under it.
+    ///
+    /// Frames are only drawn when requested (see `kobalt_core::scheduler`);
+    /// an idle app sleeps. A lost or outdated surface is reconfigured and a
+    /// lost GPU device is recreated automatically; errors that cannot be
+    /// recovered from end the app and are returned.
+    pub fn run(self) -> Result<(), KobaltError> {
         let title_widget = self.title;
-        let width = self.width;
-        let height = self.height;
+        let renderer_config = self.renderer_config;
         let bg_color = self.background_color;
-        let home_widget = self.home.expect("Home widget not set. Call .home() before .run()");
+        MediaQuery::set_text_scale_factor(self.text_scale_factor);
+
+        let touch_emulation = std::env::var_os(TOUCH_EMULATION_VAR).map_or(self.touch_emulation, |value| value != "0");
+        let device_frame = match std::env::var(DEVICE_FRAME_VAR) {
+            Ok(name) => DeviceProfile::by_name(&name).or_else(|| {
+                eprintln!("Warning: unknown device profile {:?}", name);
+                self.device_frame
+            }),
+            Err(_) => self.device_frame,
+        };
+        let (width, height) = match device_frame {
+            Some(profile) => {
+                MediaQuery::set_padding(profile.safe_area);
+                (profile.size.width.round() as u32, profile.size.height.round() as u32)
+            }
+            None => (self.width, self.height),
+        };
+        let mut host = WidgetHost::new(self.home.ok_or(KobaltError::MissingHome)?);
 
         // Extract title text for window (temporary hack)
         let window_title = if title_widget.widget_type() == "Text" {
@@ -90,122 +168,103 @@ impl KobaltApp {
             "Kobalt App".to_string()
         };
 
-        // Text renderer state
-        let mut text_renderer: Option<RealTextRenderer> = None;
+        // Scene renderer, tagged with the generation of the device it was
+        // created with
+        let mut scene_renderer: Option<(u64, SceneRenderer)> = None;
+        let mut mouse = PointerTranslator::new();
+        let mut touch_emulator = touch_emulation.then(TouchEmulator::new);
+        let mut keyboard = KeyTranslator::new();
 
         let app = DesktopApp::new(&window_title, width, height, move |window, event| {
-            match event {
-                Event::WindowEvent {
-                    event: WindowEvent::RedrawRequested,
-                    ..
-                } => {
-                    // Initialize text renderer on first frame
-                    if text_renderer.is_none() {
-                        let renderer = window.renderer();
-                        text_renderer = Some(RealTextRenderer::new(
-                            renderer.device(),
-                            renderer.config(),
-                        ));
-                    }
+            if let Event::WindowEvent {
+                event: WindowEvent::RedrawRequested,
+                ..
+            } = event
+            {
+                if window.recover_lost_device()? {
+                    // Everything on the old device is gone, so draw it all again
+                    FrameScheduler::global().request_environment_change();
+                }
 
-                    let screen_size = window.renderer().size();
-
-                    // Collect all widgets to render
-                    let mut widgets_to_render = Vec::new();
-                    collect_widgets(&*home_widget, &mut widgets_to_render);
-
-                    // Prepare text for each widget
-                    if let Some(ref mut text_renderer) = text_renderer {
-                        for widget in &widgets_to_render {
-                            if widget.widget_type() == "Text" {
-                                add_text_widget(
-                                    *widget,
-                                    text_renderer,
-                                    window.renderer().device(),
-                                    window.renderer().queue(),
-                                    screen_size.width,
-                                    screen_size.height,
-                                );
-                            }
-                        }
-                    }
+                // Initialize the scene renderer on the first frame and after
+                // the device was recreated
+                let generation = window.renderer().device_generation();
+                if scene_renderer.as_ref().is_none_or(|(created, _)| *created != generation) {
+                    let renderer = window.renderer();
+                    let scene = SceneRenderer::with_sample_count(
+                        renderer.device(),
+                        renderer.config(),
+                        renderer.sample_count(),
+                    )?;
+                    scene_renderer = Some((generation, scene));
+                }
 
-                    // Render frame
-                    match window.renderer().begin_frame() {
-                        Ok(frame) => {
-                            let mut encoder = frame.create_encoder();
-                            {
-                                let mut render_pass = frame.begin_render_pass(&mut encoder, bg_color);
-
-                                // Render text
-                                if let Some(ref text_renderer) = text_renderer {
-                                    text_renderer.render(&mut render_pass);
-                                }
-                            }
-                            frame.present(encoder);
-                        }
-                        Err(kobalt_render::SurfaceError::Lost) => {
-                            window.renderer_mut().resize(screen_size);
-                        }
-                        Err(kobalt_render::SurfaceError::OutOfMemory) => {
-                            eprintln!("Out of memory!");
-                        }
-                        Err(e) => {
-                            eprintln!("Surface error: {:?}", e);
+                // Widgets are laid out in logical pixels
+                let logical_size = window.renderer().logical_size();
+
+                // Publish the environment; a change lays out everything again
+                MediaQuery::set_window(logical_size, window.renderer().scale_factor());
+
+                // Take the request before painting so that anything asking
+                // for a frame while painting schedules the next one
+                let request = FrameScheduler::global().take_frame_request();
+                let display_list = host.frame(logical_size, request);
+
+                // Render frame
+                match window.renderer().begin_frame() {
+                    Ok(frame) => {
+                        let mut encoder = frame.create_encoder();
+                        if let Some((_, scene_renderer)) = &mut scene_renderer {
+                            scene_renderer.render(&frame, &mut encoder, display_list, bg_color);
                         }
+                        frame.present(encoder);
+                    }
+                    Err(SurfaceError::Lost | SurfaceError::Outdated) => {
+                        // Reconfigure and try again on the next frame
+                        window.renderer_mut().reconfigure();
+                        FrameScheduler::global().request_paint();
+                    }
+                    Err(SurfaceError::Timeout) => {
+                        FrameScheduler::global().request_paint();
                     }
+                    Err(e) => {
+                        eprintln!("Warning: surface error: {:?}", e);
+                    }
+                }
+                // The widgets may have moved under the cursor
+                window.set_cursor(host.cursor());
+            } else if let Event::WindowEvent { event, .. } = event {
+                let pointers: Vec<PointerEvent> = match &mut touch_emulator {
+                    Some(emulator) => emulator.translate(event, window.scale_factor()),
+                    None => mouse.translate(event, window.scale_factor()).into_iter().collect(),
+                };
+                // Delivered to the widgets as laid out in the last frame;
+                // gesture recognizers time events by the frame clock, which
+                // the desktop loop set for this event
+                for pointer in pointers {
+                    host.dispatch_event(&InputEvent::Pointer(pointer));
+                }
+                if let Some(key) = keyboard.translate(event) {
+                    host.dispatch_event(&InputEvent::Key(key));
                 }
-                _ => {}
+                window.set_cursor(host.cursor());
             }
+            Ok(())
         });
 
-        app.run()
+        app.renderer_config(renderer_config)
+            .resizable(device_frame.is_none())
+            .run()
     }
 }
 
-/// Recursively collect all widgets from the widget tree
-fn collect_widgets<'a>(widget: &'a dyn Widget, widgets: &mut Vec<&'a dyn Widget>) {
-    match widget.widget_type() {
-        "Text" => {
-            widgets.push(widget);
-        }
-        "Column" => {
-            // Downcast to Column to access children
-            let column_ptr = widget as *const dyn Widget as *const kobalt_widgets::Column;
-            let column = unsafe { &*column_ptr };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            for child in column.children() {
-                collect_widgets(&**child, widgets);
-            }
-        }
-        _ => {
-            eprintln!("Warning: Unknown widget type: {}", widget.widget_type());
-        }
+    #[test]
+    fn test_run_without_home_fails() {
+        // Fails before any window or event loop is created
+        assert_eq!(KobaltApp::build().run(), Err(KobaltError::MissingHome));
     }
 }
-
-/// Add a text widget to the text renderer
-fn add_text_widget(
-    widget: &dyn Widget,
-    text_renderer: &mut RealTextRenderer,
-    device: &wgpu::Device,
-    queue: &wgpu::Queue,
-    screen_width: f32,
-    screen_height: f32,
-) {
-    use kobalt_widgets::Text;
-
-    let text_ptr = widget as *const dyn Widget as *const Text;
-    let text = unsafe { &*text_ptr };
-
-    text_renderer.prepare_text(
-        device,
-        queue,
-        text.content(),
-        text.position,
-        text.font_size,
-        text.color,
-        screen_width,
-        screen_height,
-    );
-}
@@ -1,43 +1,79 @@
 //! Desktop platform implementation using winit
+//!
+//! The event loop sleeps until a frame is requested through
+//! `kobalt_core::scheduler`, the window is resized, the platform asks for a
+//! redraw, or a gesture deadline (e.g. of a long press) is reached.
+//!
+//! Before each window event the frame clock (`scheduler::frame_time`) is
+//! set to the time since the app started; gesture deadlines are measured
+//! on the same clock.
+//!
+//! Window sizes given to the app are in logical pixels; the surface is
+//! sized in physical pixels and the renderer is told the window's scale
+//! factor, which is kept up to date as the window moves between monitors.
+//!
+//! Setup failures and errors returned by the event handler stop the event
+//! loop and are returned from `DesktopApp::run`.
 
+use kobalt_core::error::KobaltError;
+use kobalt_core::gesture;
+use kobalt_core::mouse::MouseCursor;
+use kobalt_core::scheduler::{self, FrameScheduler};
 use kobalt_core::types::Size;
-use kobalt_render::Renderer;
+use kobalt_render::{Renderer, RendererConfig};
 use std::sync::Arc;
+use std::time::Instant;
 use winit::application::ApplicationHandler;
-use winit::event::{Event, WindowEvent};
-use winit::event_loop::{ActiveEventLoop, EventLoop};
-use winit::window::{Window, WindowAttributes, WindowId};
+use winit::event::{Event, StartCause, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
+use winit::window::{CursorIcon, Window, WindowAttributes, WindowId};
 
 /// Desktop window wrapper
 pub struct DesktopWindow {
     window: Arc<Window>,
     renderer: Option<Renderer>,
+    cursor: MouseCursor,
 }
 
 impl DesktopWindow {
-    /// Creates a new desktop window
-    async fn new(event_loop: &ActiveEventLoop, title: &str, width: u32, height: u32) -> Self {
+    /// Creates a new desktop window with a size in logical pixels
+    async fn new(
+        event_loop: &ActiveEventLoop,
+        title: &str,
+        width: u32,
+        height: u32,
+        resizable: bool,
+        renderer_config: &RendererConfig,
+    ) -> Result<Self, KobaltError> {
         let attributes = WindowAttributes::default()
             .with_title(title)
-            .with_inner_size(winit::dpi::PhysicalSize::new(width, height));
+            .with_inner_size(winit::dpi::LogicalSize::new(width, height))
+            .with_resizable(resizable)
+            .with_transparent(renderer_config.is_transparent());
 
-        let window = Arc::new(event_loop.create_window(attributes).unwrap());
+        let window = Arc::new(
+            event_loop
+                .create_window(attributes)
+                .map_err(|error| KobaltError::WindowCreation(error.to_string()))?,
+        );
+        let physical_size = window.inner_size();
 
         // Create WGPU instance and surface
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
-        });
+        let instance = renderer_config.create_instance();
 
-        let surface = instance.create_surface(window.clone()).unwrap();
+        let surface = instance
+            .create_surface(window.clone())
+            .map_err(|error| KobaltError::SurfaceCreation(error.to_string()))?;
 
-        let size = Size::new(width as f32, height as f32);
-        let renderer = Renderer::new_with_instance(instance, surface, size).await;
+        let size = Size::new(physical_size.width.max(1) as f32, physical_size.height.max(1) as f32);
+        let mut renderer = Renderer::new_with_config(instance, surface, size, renderer_config.clone()).await?;
+        renderer.set_scale_factor(window.scale_factor() as f32);
 
-        Self {
+        Ok(Self {
             window,
             renderer: Some(renderer),
-        }
+            cursor: MouseCursor::Basic,
+        })
     }
 
     /// Returns the window
@@ -55,33 +91,112 @@ impl DesktopWindow {
         self.renderer.as_mut().unwrap()
     }
 
-    /// Resizes the window
+    /// Returns the number of physical pixels per logical pixel
+    pub fn scale_factor(&self) -> f32 {
+        self.window.scale_factor() as f32
+    }
+
+    /// Resizes the surface to a size in physical pixels
     pub fn resize(&mut self, width: u32, height: u32) {
         if let Some(renderer) = &mut self.renderer {
             renderer.resize(Size::new(width as f32, height as f32));
         }
     }
+
+    /// Recreates the GPU device if it has been lost
+    ///
+    /// Returns true if the device was recreated, in which case every GPU
+    /// resource created with the old device must be recreated as well.
+    pub fn recover_lost_device(&mut self) -> Result<bool, KobaltError> {
+        match &mut self.renderer {
+            Some(renderer) if renderer.is_device_lost() => {
+                pollster::block_on(renderer.recover())?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Shows a cursor over the window; does nothing if it is already shown
+    pub fn set_cursor(&mut self, cursor: MouseCursor) {
+        if cursor == self.cursor {
+            return;
+        }
+        match cursor_icon(cursor) {
+            Some(icon) => {
+                self.window.set_cursor(icon);
+                self.window.set_cursor_visible(true);
+            }
+            None => self.window.set_cursor_visible(false),
+        }
+        self.cursor = cursor;
+    }
+
+    /// Returns the cursor shown over the window
+    pub fn cursor(&self) -> MouseCursor {
+        self.cursor
+    }
+
+    /// Updates the scale factor after the window moved to another monitor
+    /// or the display settings changed
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        if let Some(renderer) = &mut self.renderer {
+            renderer.set_scale_factor(scale_factor);
+        }
+    }
+}
+
+/// Returns the winit icon for a cursor, or `None` for a hidden cursor
+fn cursor_icon(cursor: MouseCursor) -> Option<CursorIcon> {
+    let icon = match cursor {
+        MouseCursor::Basic => CursorIcon::Default,
+        MouseCursor::Click => CursorIcon::Pointer,
+        MouseCursor::Text => CursorIcon::Text,
+        MouseCursor::Forbidden => CursorIcon::NotAllowed,
+        MouseCursor::Wait => CursorIcon::Wait,
+        MouseCursor::Progress => CursorIcon::Progress,
+        MouseCursor::Help => CursorIcon::Help,
+        MouseCursor::Precise => CursorIcon::Crosshair,
+        MouseCursor::Move => CursorIcon::Move,
+        MouseCursor::Grab => CursorIcon::Grab,
+        MouseCursor::Grabbing => CursorIcon::Grabbing,
+        MouseCursor::ResizeLeftRight => CursorIcon::EwResize,
+        MouseCursor::ResizeUpDown => CursorIcon::NsResize,
+        MouseCursor::ResizeUpLeftDownRight => CursorIcon::NwseResize,
+        MouseCursor::ResizeUpRightDownLeft => CursorIcon::NeswResize,
+        MouseCursor::ResizeColumn => CursorIcon::ColResize,
+        MouseCursor::ResizeRow => CursorIcon::RowResize,
+        MouseCursor::ZoomIn => CursorIcon::ZoomIn,
+        MouseCursor::ZoomOut => CursorIcon::ZoomOut,
+        MouseCursor::Hidden => return None,
+    };
+    Some(icon)
 }
 
 /// Desktop application
 pub struct DesktopApp<F>
 where
-    F: FnMut(&mut DesktopWindow, &Event<()>) + 'static,
+    F: FnMut(&mut DesktopWindow, &Event<()>) -> Result<(), KobaltError> + 'static,
 {
     window: Option<DesktopWindow>,
     on_event: F,
     window_config: WindowConfig,
+    error: Option<KobaltError>,
+    /// Origin of the frame clock
+    start: Instant,
 }
 
 struct WindowConfig {
     title: String,
     width: u32,
     height: u32,
+    resizable: bool,
+    renderer: RendererConfig,
 }
 
 impl<F> DesktopApp<F>
 where
-    F: FnMut(&mut DesktopWindow, &Event<()>) + 'static,
+    F: FnMut(&mut DesktopWindow, &Event<()>) -> Result<(), KobaltError> + 'static,
 {
     /// Creates a new desktop application
     pub fn new(title: &str, width: u32, height: u32, on_event: F) -> Self {
@@ -92,21 +207,56 @@ where
                 title: title.to_string(),
                 width,
                 height,
+                resizable: true,
+                renderer: RendererConfig::default(),
             },
+            error: None,
+            start: Instant::now(),
         }
     }
 
-    /// Runs the application
-    pub fn run(mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let event_loop = EventLoop::new()?;
-        event_loop.run_app(&mut self)?;
-        Ok(())
+    /// Sets the GPU and surface options used when the window is created
+    pub fn renderer_config(mut self, renderer_config: RendererConfig) -> Self {
+        self.window_config.renderer = renderer_config;
+        self
+    }
+
+    /// Sets whether the user can resize the window
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.window_config.resizable = resizable;
+        self
+    }
+
+    /// Runs the application until the window is closed or an error occurs
+    pub fn run(mut self) -> Result<(), KobaltError> {
+        let event_loop = EventLoop::new().map_err(|error| KobaltError::EventLoop(error.to_string()))?;
+        event_loop.set_control_flow(ControlFlow::Wait);
+
+        // Frame requests from other threads (e.g. image decoders) wake the loop
+        let proxy = event_loop.create_proxy();
+        FrameScheduler::global().set_waker(move || {
+            let _ = proxy.send_event(());
+        });
+
+        let result = event_loop.run_app(&mut self);
+        FrameScheduler::global().clear_waker();
+        result.map_err(|error| KobaltError::EventLoop(error.to_string()))?;
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Stops the event loop, remembering the error to return from `run`
+    fn fail(&mut self, event_loop: &ActiveEventLoop, error: KobaltError) {
+        self.error.get_or_insert(error);
+        event_loop.exit();
     }
 }
 
 impl<F> ApplicationHandler for DesktopApp<F>
 where
-    F: FnMut(&mut DesktopWindow, &Event<()>) + 'static,
+    F: FnMut(&mut DesktopWindow, &Event<()>) -> Result<(), KobaltError> + 'static,
 {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         if self.window.is_none() {
@@ -115,8 +265,13 @@ where
                 &self.window_config.title,
                 self.window_config.width,
                 self.window_config.height,
+                self.window_config.resizable,
+                &self.window_config.renderer,
             ));
-            self.window = Some(window);
+            match window {
+                Ok(window) => self.window = Some(window),
+                Err(error) => self.fail(event_loop, error),
+            }
         }
     }
 
@@ -126,35 +281,62 @@ where
         window_id: WindowId,
         event: WindowEvent,
     ) {
-        if let Some(window) = &mut self.window {
-            if window.window.id() == window_id {
-                match event {
-                    WindowEvent::CloseRequested => {
-                        event_loop.exit();
-                    }
-                    WindowEvent::Resized(physical_size) => {
-                        window.resize(physical_size.width, physical_size.height);
-                    }
-                    WindowEvent::RedrawRequested => {
-                        // Trigger custom event handler
-                        (self.on_event)(
-                            window,
-                            &Event::WindowEvent {
-                                window_id,
-                                event: WindowEvent::RedrawRequested,
-                            },
-                        );
-                        window.window.request_redraw();
+        if let Some(window) = &mut self.window
+            && window.window.id() == window_id
+        {
+            match event {
+                WindowEvent::CloseRequested => {
+                    event_loop.exit();
+                }
+                WindowEvent::Resized(physical_size) => {
+                    window.resize(physical_size.width, physical_size.height);
+                    FrameScheduler::global().request_environment_change();
+                }
+                WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                    // The new physical size follows in a `Resized` event
+                    window.set_scale_factor(scale_factor as f32);
+                    FrameScheduler::global().request_environment_change();
+                }
+                event => {
+                    // Redraws are requested again only when a frame is scheduled
+                    scheduler::set_frame_time(self.start.elapsed());
+                    if let Err(error) = (self.on_event)(window, &Event::WindowEvent { window_id, event }) {
+                        self.fail(event_loop, error);
                     }
-                    _ => {}
                 }
             }
         }
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        if let Some(window) = &self.window {
+    fn new_events(&mut self, _event_loop: &ActiveEventLoop, cause: StartCause) {
+        // A gesture deadline was reached; the frame fires it
+        if let StartCause::ResumeTimeReached { .. } = cause
+            && let Some(window) = &self.window
+        {
+            window.window.request_redraw();
+        }
+    }
+
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, _event: ()) {
+        if let Some(window) = &self.window
+            && FrameScheduler::global().has_pending_frame()
+        {
             window.window.request_redraw();
         }
     }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if let Some(window) = &self.window
+            && FrameScheduler::global().has_pending_frame()
+        {
+            window.window.request_redraw();
+        }
+
+        // Sleep until the next gesture deadline instead of drawing frames
+        // while waiting for it
+        match gesture::next_deadline() {
+            Some(deadline) => event_loop.set_control_flow(ControlFlow::WaitUntil(self.start + deadline)),
+            None => event_loop.set_control_flow(ControlFlow::Wait),
+        }
+    }
 }
@@ -0,0 +1,100 @@
+//! Phone profiles for previewing mobile layouts on a desktop
+//!
+//! In device-frame mode (`KobaltApp::device_frame`) the window takes the
+//! logical size of a phone and cannot be resized, and the profile's safe
+//! area is published as `MediaQueryData::padding`, so notches and home
+//! indicators can be accounted for without a device at hand.
+
+use kobalt_core::layout::EdgeInsets;
+use kobalt_core::types::{Length, Size};
+
+/// Environment variable selecting a device profile by name, e.g.
+/// `KOBALT_DEVICE_FRAME=pixel-8`
+pub const DEVICE_FRAME_VAR: &str = "KOBALT_DEVICE_FRAME";
+
+/// The screen of a phone or tablet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeviceProfile {
+    /// Short name, as accepted by `DeviceProfile::by_name`
+    pub name: &'static str,
+    /// Screen size in logical pixels, in portrait
+    pub size: Size,
+    /// Parts of the screen obscured by the system, in logical pixels
+    pub safe_area: EdgeInsets,
+}
+
+impl DeviceProfile {
+    /// iPhone 15: Dynamic Island and home indicator
+    pub const IPHONE_15: DeviceProfile = DeviceProfile::new("iphone-15", 393.0, 852.0, 59.0, 34.0);
+    /// iPhone SE (3rd generation): status bar only
+    pub const IPHONE_SE: DeviceProfile = DeviceProfile::new("iphone-se", 375.0, 667.0, 20.0, 0.0);
+    /// Pixel 8: status bar and gesture navigation
+    pub const PIXEL_8: DeviceProfile = DeviceProfile::new("pixel-8", 412.0, 915.0, 24.0, 24.0);
+    /// Galaxy S23: status bar and gesture navigation
+    pub const GALAXY_S23: DeviceProfile = DeviceProfile::new("galaxy-s23", 360.0, 780.0, 24.0, 24.0);
+    /// iPad mini (6th generation)
+    pub const IPAD_MINI: DeviceProfile = DeviceProfile::new("ipad-mini", 744.0, 1133.0, 24.0, 20.0);
+
+    /// All built-in profiles
+    pub const ALL: [DeviceProfile; 5] = [
+        Self::IPHONE_15,
+        Self::IPHONE_SE,
+        Self::PIXEL_8,
+        Self::GALAXY_S23,
+        Self::IPAD_MINI,
+    ];
+
+    /// Creates a profile with a safe area at the top and bottom of the screen
+    pub const fn new(name: &'static str, width: f32, height: f32, top: f32, bottom: f32) -> Self {
+        Self {
+            name,
+            size: Size::new(width, height),
+            safe_area: EdgeInsets {
+                top: Length::Dp(top),
+                right: Length::Dp(0.0),
+                bottom: Length::Dp(bottom),
+                left: Length::Dp(0.0),
+            },
+        }
+    }
+
+    /// Returns the built-in profile with this name, ignoring case
+    pub fn by_name(name: &str) -> Option<DeviceProfile> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Returns the profile rotated to landscape
+    ///
+    /// The top and bottom safe areas move to the sides, where the notch and
+    /// home indicator end up.
+    pub fn landscape(self) -> Self {
+        let EdgeInsets { top, right, bottom, left } = self.safe_area;
+        Self {
+            size: Size::new(self.size.height, self.size.width),
+            safe_area: EdgeInsets { top: right, right: bottom, bottom: left, left: top },
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_by_name() {
+        assert_eq!(DeviceProfile::by_name("Pixel-8"), Some(DeviceProfile::PIXEL_8));
+        assert_eq!(DeviceProfile::by_name("nokia-3310"), None);
+    }
+
+    #[test]
+    fn test_landscape() {
+        let landscape = DeviceProfile::IPHONE_15.landscape();
+        assert_eq!(landscape.size, Size::new(852.0, 393.0));
+        assert_eq!(landscape.safe_area.left(), 59.0);
+        assert_eq!(landscape.safe_area.right(), 34.0);
+        assert_eq!(landscape.safe_area.vertical(), 0.0);
+    }
+}
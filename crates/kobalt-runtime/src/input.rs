@@ -0,0 +1,258 @@
+//! Translation of winit input into Kobalt events
+//!
+//! winit reports the cursor position, mouse buttons and the wheel in
+//! separate events and in physical pixels. `PointerTranslator` remembers
+//! where the cursor is and which buttons are held, and turns each winit
+//! event into a `PointerEvent` in logical pixels.
+
+use kobalt_core::event::{PointerButtons, PointerEvent, PointerKind, PointerPhase};
+use kobalt_core::types::Point;
+use winit::dpi::PhysicalPosition;
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, Touch, TouchPhase, WindowEvent};
+
+/// Pointer id of the mouse
+pub const MOUSE_POINTER_ID: u64 = 0;
+
+/// Logical pixels scrolled per line reported by a mouse wheel
+pub const SCROLL_LINE_HEIGHT: f32 = 20.0;
+
+/// Turns winit window events into pointer events
+#[derive(Debug)]
+pub struct PointerTranslator {
+    cursor: Point,
+    buttons: PointerButtons,
+}
+
+impl PointerTranslator {
+    /// Creates a translator with the cursor at the origin and no buttons held
+    pub fn new() -> Self {
+        Self {
+            cursor: Point::zero(),
+            buttons: PointerButtons::NONE,
+        }
+    }
+
+    /// Returns the last known cursor position in logical pixels
+    pub fn cursor(&self) -> Point {
+        self.cursor
+    }
+
+    /// Returns the mouse buttons currently held
+    pub fn buttons(&self) -> PointerButtons {
+        self.buttons
+    }
+
+    /// Translates a window event, or returns `None` if it is not pointer
+    /// input
+    pub fn translate(&mut self, event: &WindowEvent, scale_factor: f32) -> Option<PointerEvent> {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = logical(*position, scale_factor);
+                let phase = if self.buttons.is_empty() {
+                    PointerPhase::Hover
+                } else {
+                    PointerPhase::Move
+                };
+                Some(self.mouse_event(phase))
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let button = mouse_button(*button)?;
+                let was_down = !self.buttons.is_empty();
+                let phase = match state {
+                    ElementState::Pressed => {
+                        self.buttons = self.buttons.with(button);
+                        if was_down { PointerPhase::Move } else { PointerPhase::Down }
+                    }
+                    ElementState::Released => {
+                        if !self.buttons.contains(button) {
+                            // Pressed outside the window
+                            return None;
+                        }
+                        self.buttons = self.buttons.without(button);
+                        if self.buttons.is_empty() { PointerPhase::Up } else { PointerPhase::Move }
+                    }
+                };
+                Some(self.mouse_event(phase))
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                // winit's deltas move the content; ours move the viewport
+                let delta = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => Point::new(-x, -y).scale(SCROLL_LINE_HEIGHT),
+                    MouseScrollDelta::PixelDelta(delta) => {
+                        Point::new(-delta.x as f32, -delta.y as f32).scale(1.0 / scale_factor)
+                    }
+                };
+                Some(PointerEvent::scroll(MOUSE_POINTER_ID, self.cursor, delta).buttons(self.buttons))
+            }
+            WindowEvent::Touch(touch) => Some(touch_event(touch, scale_factor)),
+            WindowEvent::CursorLeft { .. } => Some(self.mouse_event(PointerPhase::Exit)),
+            _ => None,
+        }
+    }
+
+    fn mouse_event(&self, phase: PointerPhase) -> PointerEvent {
+        PointerEvent::new(MOUSE_POINTER_ID, phase, self.cursor)
+            .buttons(self.buttons)
+            .pressure(if self.buttons.is_empty() { 0.0 } else { 1.0 })
+    }
+}
+
+impl Default for PointerTranslator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Translates a touch; touch ids are offset past the mouse's id, and the
+/// largest id a platform can report wraps around instead of overflowing
+fn touch_event(touch: &Touch, scale_factor: f32) -> PointerEvent {
+    let phase = match touch.phase {
+        TouchPhase::Started => PointerPhase::Down,
+        TouchPhase::Moved => PointerPhase::Move,
+        TouchPhase::Ended => PointerPhase::Up,
+        TouchPhase::Cancelled => PointerPhase::Cancel,
+    };
+    let in_contact = matches!(phase, PointerPhase::Down | PointerPhase::Move);
+    let pressure = match touch.force {
+        Some(force) if in_contact => force.normalized().clamp(0.0, 1.0) as f32,
+        _ if in_contact => 1.0,
+        _ => 0.0,
+    };
+    PointerEvent::new(touch.id.wrapping_add(1), phase, logical(touch.location, scale_factor))
+        .kind(PointerKind::Touch)
+        .buttons(if in_contact {
+            PointerButtons::PRIMARY
+        } else {
+            PointerButtons::NONE
+        })
+        .pressure(pressure)
+}
+
+fn mouse_button(button: MouseButton) -> Option<PointerButtons> {
+    match button {
+        MouseButton::Left => Some(PointerButtons::PRIMARY),
+        MouseButton::Right => Some(PointerButtons::SECONDARY),
+        MouseButton::Middle => Some(PointerButtons::MIDDLE),
+        MouseButton::Back => Some(PointerButtons::BACK),
+        MouseButton::Forward => Some(PointerButtons::FORWARD),
+        MouseButton::Other(_) => None,
+    }
+}
+
+fn logical(position: PhysicalPosition<f64>, scale_factor: f32) -> Point {
+    Point::new(position.x as f32, position.y as f32).scale(1.0 / scale_factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::event::{DeviceId, Force};
+
+    fn cursor_moved(x: f64, y: f64) -> WindowEvent {
+        WindowEvent::CursorMoved {
+            device_id: DeviceId::dummy(),
+            position: PhysicalPosition::new(x, y),
+        }
+    }
+
+    fn mouse_input(state: ElementState, button: MouseButton) -> WindowEvent {
+        WindowEvent::MouseInput {
+            device_id: DeviceId::dummy(),
+            state,
+            button,
+        }
+    }
+
+    #[test]
+    fn test_mouse_press_drag_release() {
+        let mut translator = PointerTranslator::new();
+        let hover = translator.translate(&cursor_moved(20.0, 40.0), 2.0).unwrap();
+        assert_eq!(hover.phase, PointerPhase::Hover);
+        assert_eq!(hover.position, Point::new(10.0, 20.0));
+
+        let down = translator
+            .translate(&mouse_input(ElementState::Pressed, MouseButton::Left), 2.0)
+            .unwrap();
+        assert_eq!((down.phase, down.buttons), (PointerPhase::Down, PointerButtons::PRIMARY));
+        assert_eq!(down.position, Point::new(10.0, 20.0));
+
+        let right = translator
+            .translate(&mouse_input(ElementState::Pressed, MouseButton::Right), 2.0)
+            .unwrap();
+        assert_eq!(right.phase, PointerPhase::Move);
+        assert!(right.buttons.contains(PointerButtons::SECONDARY));
+
+        assert_eq!(translator.translate(&cursor_moved(40.0, 40.0), 2.0).unwrap().phase, PointerPhase::Move);
+        translator.translate(&mouse_input(ElementState::Released, MouseButton::Right), 2.0);
+        let up = translator
+            .translate(&mouse_input(ElementState::Released, MouseButton::Left), 2.0)
+            .unwrap();
+        assert_eq!((up.phase, up.position), (PointerPhase::Up, Point::new(20.0, 20.0)));
+        assert!(up.buttons.is_empty());
+
+        // A release for a press that happened outside the window
+        assert_eq!(translator.translate(&mouse_input(ElementState::Released, MouseButton::Left), 2.0), None);
+    }
+
+    #[test]
+    fn test_cursor_leaving_exits() {
+        let mut translator = PointerTranslator::new();
+        translator.translate(&cursor_moved(20.0, 40.0), 1.0);
+        let exit = translator
+            .translate(&WindowEvent::CursorLeft { device_id: DeviceId::dummy() }, 1.0)
+            .unwrap();
+        assert_eq!((exit.id, exit.phase, exit.position), (MOUSE_POINTER_ID, PointerPhase::Exit, Point::new(20.0, 40.0)));
+    }
+
+    #[test]
+    fn test_wheel_scrolls_towards_the_end() {
+        let mut translator = PointerTranslator::new();
+        let event = WindowEvent::MouseWheel {
+            device_id: DeviceId::dummy(),
+            delta: MouseScrollDelta::LineDelta(0.0, -1.0),
+            phase: TouchPhase::Moved,
+        };
+        let scroll = translator.translate(&event, 1.0).unwrap();
+        assert_eq!(scroll.phase, PointerPhase::Scroll);
+        assert_eq!(scroll.scroll_delta, Point::new(0.0, SCROLL_LINE_HEIGHT));
+    }
+
+    #[test]
+    fn test_touch() {
+        let mut translator = PointerTranslator::new();
+        let touch = |phase, force| {
+            WindowEvent::Touch(Touch {
+                device_id: DeviceId::dummy(),
+                phase,
+                location: PhysicalPosition::new(30.0, 60.0),
+                force,
+                id: 7,
+            })
+        };
+
+        let down = translator
+            .translate(&touch(TouchPhase::Started, Some(Force::Normalized(0.5))), 3.0)
+            .unwrap();
+        assert_eq!(down.id, 8);
+        assert_eq!(down.kind, PointerKind::Touch);
+        assert_eq!(down.position, Point::new(10.0, 20.0));
+        assert_eq!(down.pressure, 0.5);
+
+        let cancel = translator.translate(&touch(TouchPhase::Cancelled, None), 3.0).unwrap();
+        assert_eq!((cancel.phase, cancel.pressure), (PointerPhase::Cancel, 0.0));
+        assert!(cancel.buttons.is_empty());
+    }
+
+    #[test]
+    fn test_largest_touch_id_does_not_overflow() {
+        let event = WindowEvent::Touch(Touch {
+            device_id: DeviceId::dummy(),
+            phase: TouchPhase::Started,
+            location: PhysicalPosition::new(0.0, 0.0),
+            force: None,
+            id: u64::MAX,
+        });
+        let down = PointerTranslator::new().translate(&event, 1.0).unwrap();
+        assert_eq!((down.id, down.kind), (0, PointerKind::Touch));
+    }
+}
@@ -0,0 +1,171 @@
+//! Translation of winit keyboard input into Kobalt key events
+//!
+//! winit reports the modifier keys in a separate event from the key
+//! presses. `KeyTranslator` remembers which modifiers are held and attaches
+//! them to each `KeyEvent`, together with the logical and physical key.
+
+use kobalt_core::event::{KeyEvent, KeyState, LogicalKey, Modifiers, PhysicalKey};
+use winit::event::{ElementState, WindowEvent};
+use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey, PhysicalKey as WinitPhysicalKey};
+
+/// Turns winit window events into key events
+#[derive(Debug, Default)]
+pub struct KeyTranslator {
+    modifiers: Modifiers,
+}
+
+impl KeyTranslator {
+    /// Creates a translator with no modifiers held
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the modifiers currently held
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Translates a window event into a key event; events that are not key
+    /// presses or releases produce none
+    pub fn translate(&mut self, event: &WindowEvent) -> Option<KeyEvent> {
+        match event {
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers_from(modifiers.state());
+                None
+            }
+            // Modifiers held while the window was not focused are stale
+            WindowEvent::Focused(false) => {
+                self.modifiers = Modifiers::NONE;
+                None
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                let state = match event.state {
+                    ElementState::Pressed => KeyState::Pressed,
+                    ElementState::Released => KeyState::Released,
+                };
+                let key = KeyEvent {
+                    key: logical_key(&event.logical_key),
+                    physical_key: physical_key(&event.physical_key),
+                    state,
+                    modifiers: self.modifiers,
+                    repeat: event.repeat,
+                };
+                Some(key)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Converts winit's modifier state
+pub fn modifiers_from(state: ModifiersState) -> Modifiers {
+    [
+        (state.shift_key(), Modifiers::SHIFT),
+        (state.control_key(), Modifiers::CONTROL),
+        (state.alt_key(), Modifiers::ALT),
+        (state.super_key(), Modifiers::SUPER),
+    ]
+    .into_iter()
+    .filter(|(held, _)| *held)
+    .fold(Modifiers::NONE, |modifiers, (_, modifier)| modifiers.with(modifier))
+}
+
+/// Converts a winit logical key
+pub fn logical_key(key: &Key) -> LogicalKey {
+    match key {
+        Key::Character(text) => LogicalKey::Character(text.to_string()),
+        Key::Named(named) => match named {
+            NamedKey::Enter => LogicalKey::Enter,
+            NamedKey::Space => LogicalKey::Space,
+            NamedKey::Tab => LogicalKey::Tab,
+            NamedKey::Escape => LogicalKey::Escape,
+            NamedKey::Backspace => LogicalKey::Backspace,
+            NamedKey::Delete => LogicalKey::Delete,
+            NamedKey::Insert => LogicalKey::Insert,
+            NamedKey::ArrowUp => LogicalKey::ArrowUp,
+            NamedKey::ArrowDown => LogicalKey::ArrowDown,
+            NamedKey::ArrowLeft => LogicalKey::ArrowLeft,
+            NamedKey::ArrowRight => LogicalKey::ArrowRight,
+            NamedKey::Home => LogicalKey::Home,
+            NamedKey::End => LogicalKey::End,
+            NamedKey::PageUp => LogicalKey::PageUp,
+            NamedKey::PageDown => LogicalKey::PageDown,
+            NamedKey::Shift => LogicalKey::Shift,
+            NamedKey::Control => LogicalKey::Control,
+            NamedKey::Alt => LogicalKey::Alt,
+            NamedKey::Super | NamedKey::Meta => LogicalKey::Super,
+            NamedKey::CapsLock => LogicalKey::CapsLock,
+            NamedKey::F1 => LogicalKey::Function(1),
+            NamedKey::F2 => LogicalKey::Function(2),
+            NamedKey::F3 => LogicalKey::Function(3),
+            NamedKey::F4 => LogicalKey::Function(4),
+            NamedKey::F5 => LogicalKey::Function(5),
+            NamedKey::F6 => LogicalKey::Function(6),
+            NamedKey::F7 => LogicalKey::Function(7),
+            NamedKey::F8 => LogicalKey::Function(8),
+            NamedKey::F9 => LogicalKey::Function(9),
+            NamedKey::F10 => LogicalKey::Function(10),
+            NamedKey::F11 => LogicalKey::Function(11),
+            NamedKey::F12 => LogicalKey::Function(12),
+            _ => LogicalKey::Unidentified,
+        },
+        _ => LogicalKey::Unidentified,
+    }
+}
+
+/// Maps the winit key codes that have a `PhysicalKey` of the same name
+macro_rules! physical_keys {
+    ($code:expr, $($name:ident),* $(,)?) => {
+        match $code {
+            $(KeyCode::$name => PhysicalKey::$name,)*
+            _ => PhysicalKey::Unidentified,
+        }
+    };
+}
+
+/// Converts a winit physical key
+pub fn physical_key(key: &WinitPhysicalKey) -> PhysicalKey {
+    let WinitPhysicalKey::Code(code) = key else {
+        return PhysicalKey::Unidentified;
+    };
+    physical_keys!(
+        code,
+        KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM,
+        KeyN, KeyO, KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyY, KeyZ,
+        Digit0, Digit1, Digit2, Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9,
+        Enter, Space, Tab, Escape, Backspace, Delete, Insert,
+        ArrowUp, ArrowDown, ArrowLeft, ArrowRight, Home, End, PageUp, PageDown,
+        ShiftLeft, ShiftRight, ControlLeft, ControlRight, AltLeft, AltRight, SuperLeft, SuperRight, CapsLock,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+        Minus, Equal, BracketLeft, BracketRight, Backslash, Semicolon, Quote, Backquote, Comma, Period, Slash,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::event::Modifiers as WinitModifiers;
+    use winit::keyboard::NativeKeyCode;
+
+    #[test]
+    fn test_key_conversion() {
+        assert_eq!(logical_key(&Key::Character("é".into())), LogicalKey::Character("é".to_string()));
+        assert_eq!(logical_key(&Key::Named(NamedKey::F5)), LogicalKey::Function(5));
+        assert_eq!(physical_key(&WinitPhysicalKey::Code(KeyCode::KeyZ)), PhysicalKey::KeyZ);
+        assert_eq!(
+            physical_key(&WinitPhysicalKey::Unidentified(NativeKeyCode::Unidentified)),
+            PhysicalKey::Unidentified
+        );
+    }
+
+    #[test]
+    fn test_modifiers_are_tracked() {
+        let mut translator = KeyTranslator::new();
+        let state = ModifiersState::SHIFT | ModifiersState::SUPER;
+        assert_eq!(translator.translate(&WindowEvent::ModifiersChanged(WinitModifiers::from(state))), None);
+        assert_eq!(translator.modifiers(), Modifiers::SHIFT.with(Modifiers::SUPER));
+
+        translator.translate(&WindowEvent::Focused(false));
+        assert!(translator.modifiers().is_empty());
+    }
+}
@@ -6,11 +6,27 @@
 //! - Desktop (via winit)
 //! - Mobile (Android/iOS)
 //! - Web (WASM)
+//! - Offscreen rendering without a window
+//! - Mouse, touch and keyboard input routed to widgets, and mouse cursors
+//! - Touch emulation and phone device frames for desktop development
 
 mod desktop;
 mod app;
+mod offscreen;
+mod input;
+mod keyboard;
+mod touch_emulation;
+mod device;
 
 pub use desktop::{DesktopApp, DesktopWindow};
 pub use app::KobaltApp;
+pub use kobalt_core::host::WidgetHost;
+pub use offscreen::OffscreenRenderer;
+pub use input::{PointerTranslator, MOUSE_POINTER_ID, SCROLL_LINE_HEIGHT};
+pub use keyboard::KeyTranslator;
+pub use touch_emulation::{TouchEmulator, EMULATED_TOUCH_ID, PINCH_FINGER_DISTANCE, TOUCH_EMULATION_VAR};
+pub use device::{DeviceProfile, DEVICE_FRAME_VAR};
+pub use kobalt_core::error::KobaltError;
+pub use kobalt_render::{Backends, PowerPreference, PresentMode, RendererConfig};
 pub use winit::event::{Event, WindowEvent};
 pub use winit::event_loop::EventLoop;
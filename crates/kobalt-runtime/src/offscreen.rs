@@ -0,0 +1,137 @@
+//! Offscreen rendering - draw widgets without a window
+//!
+//! `OffscreenRenderer` renders a widget tree into a texture and reads it
+//! back as RGBA8 pixels, for thumbnails, share images and screenshot tests
+//! on machines without a display. It draws through a `RenderBackend`: the
+//! GPU one by default, or the CPU one where there are no GPU drivers.
+
+use kobalt_core::error::KobaltError;
+use kobalt_core::media_query::MediaQuery;
+use kobalt_core::paint::{DisplayList, PaintContext};
+use kobalt_core::types::{Color, Rect, Size};
+use kobalt_core::widget::Widget;
+use kobalt_render::{CpuBackend, RenderBackend, RendererConfig, WgpuBackend};
+use std::path::Path;
+
+/// Renders widgets into an offscreen texture
+///
+/// # Example
+///
+/// ```no_run
+/// use kobalt_core::types::{Color, Size};
+/// use kobalt_runtime::{OffscreenRenderer, RendererConfig};
+/// use kobalt_widgets::Text;
+///
+/// let mut offscreen = OffscreenRenderer::new(Size::new(320.0, 240.0), 2.0, RendererConfig::new())?;
+/// offscreen.save_png(&Text::new("Hello"), Color::WHITE, "hello.png")?;
+/// # Ok::<(), kobalt_core::error::KobaltError>(())
+/// ```
+pub struct OffscreenRenderer {
+    backend: Box<dyn RenderBackend>,
+}
+
+impl OffscreenRenderer {
+    /// Creates a GPU renderer for images of `size` logical pixels at the
+    /// given scale factor
+    pub fn new(size: Size, scale_factor: f32, renderer_config: RendererConfig) -> Result<Self, KobaltError> {
+        let (width, height) = physical_size(size, scale_factor);
+        let backend = pollster::block_on(WgpuBackend::new(width, height, scale_factor, renderer_config))?;
+        Ok(Self::with_backend(Box::new(backend)))
+    }
+
+    /// Creates a software renderer, which needs no GPU
+    pub fn software(size: Size, scale_factor: f32) -> Result<Self, KobaltError> {
+        let (width, height) = physical_size(size, scale_factor);
+        Ok(Self::with_backend(Box::new(CpuBackend::new(width, height, scale_factor)?)))
+    }
+
+    /// Creates a renderer drawing through any backend
+    pub fn with_backend(backend: Box<dyn RenderBackend>) -> Self {
+        Self { backend }
+    }
+
+    /// Returns the backend
+    pub fn backend(&self) -> &dyn RenderBackend {
+        &*self.backend
+    }
+
+    /// Returns the size of rendered images in physical pixels
+    pub fn physical_size(&self) -> (u32, u32) {
+        self.backend.size()
+    }
+
+    /// Returns the size of rendered images in logical pixels
+    pub fn logical_size(&self) -> Size {
+        let (width, height) = self.backend.size();
+        Size::new(width as f32, height as f32) / self.backend.scale_factor()
+    }
+
+    /// Lays out and paints a widget filling the image, and returns the
+    /// pixels as tightly packed RGBA8 rows
+    pub fn render(&mut self, widget: &dyn Widget, background: Color) -> Result<Vec<u8>, KobaltError> {
+        let size = self.logical_size();
+        MediaQuery::set_window(size, self.backend.scale_factor());
+        widget.layout(size);
+        let mut ctx = PaintContext::new();
+        widget.paint(&mut ctx, Rect::new(0.0, 0.0, size.width, size.height));
+        self.render_list(&ctx.finish(), background)
+    }
+
+    /// Renders a display list in logical pixels and returns the pixels as
+    /// tightly packed RGBA8 rows
+    pub fn render_list(&mut self, list: &DisplayList, background: Color) -> Result<Vec<u8>, KobaltError> {
+        self.backend.render(list, background)
+    }
+
+    /// Renders a widget and writes the image to a PNG file
+    pub fn save_png(
+        &mut self,
+        widget: &dyn Widget,
+        background: Color,
+        path: impl AsRef<Path>,
+    ) -> Result<(), KobaltError> {
+        let pixels = self.render(widget, background)?;
+        let (width, height) = self.physical_size();
+        kobalt_render::save_png(path, width, height, &pixels)
+    }
+}
+
+/// Converts a logical size to whole physical pixels
+fn physical_size(size: Size, scale_factor: f32) -> (u32, u32) {
+    (
+        (size.width * scale_factor).round().max(1.0) as u32,
+        (size.height * scale_factor).round().max(1.0) as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Swatch;
+
+    impl Widget for Swatch {
+        fn widget_type(&self) -> &'static str {
+            "Swatch"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+            ctx.draw_rect(Rect::new(bounds.x, bounds.y, bounds.width / 2.0, bounds.height), Color::BLACK);
+        }
+    }
+
+    #[test]
+    fn test_software_renderer_draws_widgets() {
+        let mut offscreen = OffscreenRenderer::software(Size::new(4.0, 2.0), 2.0).unwrap();
+        assert_eq!(offscreen.physical_size(), (8, 4));
+        assert_eq!(offscreen.backend().name(), "cpu");
+
+        let pixels = offscreen.render(&Swatch, Color::WHITE).unwrap();
+        assert_eq!(&pixels[..4], &[0, 0, 0, 255]);
+        assert_eq!(&pixels[28..32], &[255, 255, 255, 255]);
+    }
+}
@@ -0,0 +1,184 @@
+//! Touch emulation - mouse input delivered as touches
+//!
+//! Mobile is the main target, but development happens on desktops.
+//! `TouchEmulator` turns the left mouse button into a finger, so widgets see
+//! the same `PointerKind::Touch` events as on a phone: pressing and dragging
+//! is a touch and a swipe, and moving without a button pressed produces no
+//! events, as fingers do not hover.
+//!
+//! Holding Ctrl while pressing starts a two-finger gesture instead. The
+//! fingers start `PINCH_FINGER_DISTANCE` apart, centered on the cursor, and
+//! stay mirrored around that center: dragging right or left spreads or
+//! pinches them, dragging up or down rotates them.
+//!
+//! The mouse wheel and real touches are passed through unchanged.
+
+use crate::input::PointerTranslator;
+use kobalt_core::event::{PointerButtons, PointerEvent, PointerKind, PointerPhase};
+use kobalt_core::types::Point;
+use winit::event::WindowEvent;
+
+/// Environment variable enabling touch emulation, e.g.
+/// `KOBALT_TOUCH_EMULATION=1`
+pub const TOUCH_EMULATION_VAR: &str = "KOBALT_TOUCH_EMULATION";
+
+/// Pointer id of the emulated finger, and of the first finger of a
+/// two-finger gesture; the second finger uses the next id
+pub const EMULATED_TOUCH_ID: u64 = 1 << 32;
+
+/// Distance between the two emulated fingers when a two-finger gesture
+/// starts, in logical pixels
+pub const PINCH_FINGER_DISTANCE: f32 = 100.0;
+
+/// What the emulated fingers are doing
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Contact {
+    None,
+    /// One finger following the cursor
+    Touch,
+    /// Two fingers mirrored around `center`
+    Pinch { center: Point },
+}
+
+/// Turns winit mouse events into touch pointer events
+#[derive(Debug)]
+pub struct TouchEmulator {
+    mouse: PointerTranslator,
+    ctrl: bool,
+    contact: Contact,
+}
+
+impl TouchEmulator {
+    /// Creates an emulator with no finger down
+    pub fn new() -> Self {
+        Self {
+            mouse: PointerTranslator::new(),
+            ctrl: false,
+            contact: Contact::None,
+        }
+    }
+
+    /// Returns true if an emulated finger is down
+    pub fn is_touching(&self) -> bool {
+        self.contact != Contact::None
+    }
+
+    /// Translates a window event into the pointer events of the emulated
+    /// fingers; most events produce none, a two-finger gesture two
+    pub fn translate(&mut self, event: &WindowEvent, scale_factor: f32) -> Vec<PointerEvent> {
+        if let WindowEvent::ModifiersChanged(modifiers) = event {
+            self.ctrl = modifiers.state().control_key();
+            return Vec::new();
+        }
+        let Some(pointer) = self.mouse.translate(event, scale_factor) else {
+            return Vec::new();
+        };
+        if pointer.kind != PointerKind::Mouse || pointer.phase == PointerPhase::Scroll {
+            return vec![pointer];
+        }
+
+        let position = pointer.position;
+        match (pointer.phase, self.contact) {
+            (PointerPhase::Down, Contact::None) if pointer.buttons.contains(PointerButtons::PRIMARY) => {
+                if self.ctrl {
+                    self.contact = Contact::Pinch { center: position };
+                    pinch(PointerPhase::Down, position, position)
+                } else {
+                    self.contact = Contact::Touch;
+                    vec![finger(EMULATED_TOUCH_ID, PointerPhase::Down, position)]
+                }
+            }
+            (PointerPhase::Move, Contact::Touch) => vec![finger(EMULATED_TOUCH_ID, PointerPhase::Move, position)],
+            (PointerPhase::Move, Contact::Pinch { center }) => pinch(PointerPhase::Move, center, position),
+            (PointerPhase::Up | PointerPhase::Cancel, Contact::Touch) => {
+                self.contact = Contact::None;
+                vec![finger(EMULATED_TOUCH_ID, pointer.phase, position)]
+            }
+            (PointerPhase::Up | PointerPhase::Cancel, Contact::Pinch { center }) => {
+                self.contact = Contact::None;
+                pinch(pointer.phase, center, position)
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Default for TouchEmulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn finger(id: u64, phase: PointerPhase, position: Point) -> PointerEvent {
+    PointerEvent::new(id, phase, position).kind(PointerKind::Touch)
+}
+
+/// Places two fingers mirrored around `center`, offset by the cursor's
+/// movement since the gesture started at `center`
+fn pinch(phase: PointerPhase, center: Point, cursor: Point) -> Vec<PointerEvent> {
+    let offset = Point::new(PINCH_FINGER_DISTANCE / 2.0, 0.0) + (cursor - center);
+    vec![
+        finger(EMULATED_TOUCH_ID, phase, center + offset),
+        finger(EMULATED_TOUCH_ID + 1, phase, center - offset),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::dpi::PhysicalPosition;
+    use winit::event::{DeviceId, ElementState, Modifiers, MouseButton};
+    use winit::keyboard::ModifiersState;
+
+    fn cursor_moved(x: f64, y: f64) -> WindowEvent {
+        WindowEvent::CursorMoved {
+            device_id: DeviceId::dummy(),
+            position: PhysicalPosition::new(x, y),
+        }
+    }
+
+    fn left(state: ElementState) -> WindowEvent {
+        WindowEvent::MouseInput {
+            device_id: DeviceId::dummy(),
+            state,
+            button: MouseButton::Left,
+        }
+    }
+
+    #[test]
+    fn test_mouse_drag_becomes_touch() {
+        let mut emulator = TouchEmulator::new();
+        assert!(emulator.translate(&cursor_moved(10.0, 10.0), 1.0).is_empty());
+
+        let down = emulator.translate(&left(ElementState::Pressed), 1.0);
+        assert_eq!(down.len(), 1);
+        assert_eq!((down[0].id, down[0].kind, down[0].phase), (EMULATED_TOUCH_ID, PointerKind::Touch, PointerPhase::Down));
+
+        let moved = emulator.translate(&cursor_moved(30.0, 10.0), 1.0);
+        assert_eq!(moved[0].position, Point::new(30.0, 10.0));
+        assert_eq!(emulator.translate(&left(ElementState::Released), 1.0)[0].phase, PointerPhase::Up);
+        assert!(!emulator.is_touching());
+    }
+
+    #[test]
+    fn test_ctrl_drag_pinches() {
+        let mut emulator = TouchEmulator::new();
+        emulator.translate(&cursor_moved(200.0, 200.0), 1.0);
+        emulator.translate(&WindowEvent::ModifiersChanged(Modifiers::from(ModifiersState::CONTROL)), 1.0);
+
+        let down = emulator.translate(&left(ElementState::Pressed), 1.0);
+        let positions: Vec<Point> = down.iter().map(|event| event.position).collect();
+        assert_eq!(positions, vec![Point::new(250.0, 200.0), Point::new(150.0, 200.0)]);
+
+        // Dragging right spreads the fingers apart
+        let spread = emulator.translate(&cursor_moved(250.0, 200.0), 1.0);
+        assert_eq!(spread[0].position.distance_to(&spread[1].position), 2.0 * PINCH_FINGER_DISTANCE);
+        assert_eq!(spread[1].id, EMULATED_TOUCH_ID + 1);
+
+        // Releasing Ctrl does not end the gesture
+        emulator.translate(&WindowEvent::ModifiersChanged(Modifiers::from(ModifiersState::empty())), 1.0);
+        let up = emulator.translate(&left(ElementState::Released), 1.0);
+        assert_eq!(up.len(), 2);
+        assert!(up.iter().all(|event| event.phase == PointerPhase::Up));
+    }
+}
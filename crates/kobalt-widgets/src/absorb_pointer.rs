@@ -0,0 +1,92 @@
+//! AbsorbPointer widget - stops pointers at its bounds
+
+use kobalt_core::hit_test::HitTestResult;
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Point, Rect, Size};
+use kobalt_core::widget::Widget;
+
+/// A widget that swallows pointers over its child, similar to Flutter's
+/// `AbsorbPointer`
+///
+/// Neither the child nor the widgets behind it receive pointer events; the
+/// AbsorbPointer itself is hit so they stop there. Useful to disable a
+/// whole subtree, e.g. a form while it is submitting.
+pub struct AbsorbPointer {
+    absorbing: bool,
+    child: Box<dyn Widget>,
+}
+
+impl AbsorbPointer {
+    /// Creates an AbsorbPointer around a child, absorbing pointers
+    pub fn new<W: Widget + 'static>(child: W) -> Self {
+        Self {
+            absorbing: true,
+            child: Box::new(child),
+        }
+    }
+
+    /// Sets whether pointers are absorbed
+    pub fn absorbing(mut self, absorbing: bool) -> Self {
+        self.absorbing = absorbing;
+        self
+    }
+
+    /// Returns true if pointers are absorbed
+    pub fn is_absorbing(&self) -> bool {
+        self.absorbing
+    }
+
+    /// Returns the child
+    pub fn get_child(&self) -> &dyn Widget {
+        &*self.child
+    }
+}
+
+impl Widget for AbsorbPointer {
+    fn widget_type(&self) -> &'static str {
+        "AbsorbPointer"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.child.layout(constraints)
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        self.child.paint(ctx, bounds);
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        visitor(&*self.child, bounds);
+    }
+
+    fn hit_test_children(&self, result: &mut HitTestResult, bounds: Rect, position: Point) -> bool {
+        !self.absorbing && kobalt_core::hit_test::hit_test_children(self, result, bounds, position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Stack, Text};
+    use kobalt_core::testing::WidgetTester;
+
+    #[test]
+    fn test_absorbs_pointers_for_child_and_widgets_behind() {
+        let stack = Stack::new()
+            .add(Text::new("Behind"))
+            .add(AbsorbPointer::new(Text::new("Front")));
+        let mut tester = WidgetTester::new(stack);
+        tester.pump();
+
+        let paths = tester.hit_test(Point::new(1.0, 1.0)).paths();
+        assert_eq!(paths, vec![vec![1], vec![]]);
+    }
+
+    #[test]
+    fn test_not_absorbing_hits_child() {
+        let mut tester = WidgetTester::new(AbsorbPointer::new(Text::new("Front")).absorbing(false));
+        tester.pump();
+
+        assert_eq!(tester.hit_test(Point::new(1.0, 1.0)).paths(), vec![vec![0], vec![]]);
+    }
+}
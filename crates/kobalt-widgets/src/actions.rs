@@ -0,0 +1,120 @@
+//! Actions widget - carries out intents for its subtree
+
+use kobalt_core::hit_test::HitTestBehavior;
+use kobalt_core::paint::PaintContext;
+use kobalt_core::shortcuts::{Action, ActionMap, Intent};
+use kobalt_core::types::{Rect, Size};
+use kobalt_core::widget::Widget;
+
+/// A widget carrying out intents while the focus is inside its child,
+/// similar to Flutter's `Actions`
+///
+/// The intents come from `Shortcuts` around the focused widget or from
+/// the default shortcuts; an `Actions` closer to the focused widget takes
+/// precedence over one further out.
+pub struct Actions {
+    actions: ActionMap,
+    child: Box<dyn Widget>,
+}
+
+impl Actions {
+    /// Creates an Actions around a child, with no actions
+    pub fn new<W: Widget + 'static>(child: W) -> Self {
+        Self {
+            actions: ActionMap::new(),
+            child: Box::new(child),
+        }
+    }
+
+    /// Adds an action
+    pub fn action(mut self, action: Action) -> Self {
+        self.actions = self.actions.action(action);
+        self
+    }
+
+    /// Adds an action for intents of type `T`
+    pub fn on<T: Intent>(self, on_invoke: impl Fn(&T) + 'static) -> Self {
+        self.action(Action::new(on_invoke))
+    }
+
+    /// Returns the actions
+    pub fn get_actions(&self) -> &ActionMap {
+        &self.actions
+    }
+
+    /// Returns the child
+    pub fn get_child(&self) -> &dyn Widget {
+        &*self.child
+    }
+}
+
+impl Widget for Actions {
+    fn widget_type(&self) -> &'static str {
+        "Actions"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.child.layout(constraints)
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        self.child.paint(ctx, bounds);
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        visitor(&*self.child, bounds);
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        HitTestBehavior::DeferToChild
+    }
+
+    fn actions(&self) -> Option<&ActionMap> {
+        Some(&self.actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Column, Focus, Text};
+    use kobalt_core::event::LogicalKey;
+    use kobalt_core::shortcuts::{ActivateIntent, NextFocusIntent};
+    use kobalt_core::testing::WidgetTester;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_default_shortcuts_reach_actions() {
+        let activations = Rc::new(Cell::new(0));
+        let count = activations.clone();
+        let enabled = Rc::new(Cell::new(true));
+        let flag = enabled.clone();
+        let button = Actions::new(Focus::new(Text::new("OK")).autofocus(true))
+            .action(Action::new(move |_: &ActivateIntent| count.set(count.get() + 1)).enabled_when(move || flag.get()));
+        let mut tester = WidgetTester::new(button);
+        tester.pump();
+
+        tester.press_key(LogicalKey::Enter);
+        tester.press_key(LogicalKey::Space);
+        enabled.set(false);
+        assert!(!tester.press_key(LogicalKey::Enter).is_handled());
+        assert_eq!(activations.get(), 2);
+    }
+
+    #[test]
+    fn test_overriding_focus_traversal() {
+        let first = kobalt_core::focus::FocusNode::new();
+        let column = Actions::new(
+            Column::new()
+                .add(Focus::with_node(Text::new("First"), first.clone()).autofocus(true))
+                .add(Focus::new(Text::new("Second"))),
+        )
+        .on(|_: &NextFocusIntent| {});
+        let mut tester = WidgetTester::new(column);
+        tester.pump();
+
+        assert!(tester.press_key(LogicalKey::Tab).is_handled());
+        assert!(first.has_focus());
+    }
+}
@@ -0,0 +1,117 @@
+//! BackdropFilter widget - filters whatever is painted behind it
+
+use kobalt_core::paint::{ImageFilter, PaintContext};
+use kobalt_core::types::{Rect, Size};
+use kobalt_core::hit_test::HitTestBehavior;
+use kobalt_core::widget::Widget;
+
+/// A widget that applies an image filter to the content behind it
+///
+/// The filter is applied to everything already painted inside this widget's
+/// bounds, then the child is painted on top. This is how frosted-glass app
+/// bars and blurred modal backdrops are built, similar to Flutter's
+/// `BackdropFilter`.
+///
+/// Without a child the widget fills the space offered by its parent.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_core::paint::ImageFilter;
+/// use kobalt_widgets::{BackdropFilter, Text};
+///
+/// let app_bar = BackdropFilter::new(ImageFilter::blur(10.0, 10.0))
+///     .child(Text::new("Frosted"));
+/// ```
+pub struct BackdropFilter {
+    filter: ImageFilter,
+    child: Option<Box<dyn Widget>>,
+}
+
+impl BackdropFilter {
+    /// Creates a new BackdropFilter with the given filter and no child
+    pub fn new(filter: ImageFilter) -> Self {
+        Self {
+            filter,
+            child: None,
+        }
+    }
+
+    /// Sets the child painted on top of the filtered backdrop
+    pub fn child<W: Widget + 'static>(mut self, child: W) -> Self {
+        self.child = Some(Box::new(child));
+        self
+    }
+
+    /// Returns the filter
+    pub fn filter(&self) -> &ImageFilter {
+        &self.filter
+    }
+
+    /// Returns the child, if any
+    pub fn get_child(&self) -> Option<&dyn Widget> {
+        self.child.as_deref()
+    }
+}
+
+impl Widget for BackdropFilter {
+    fn widget_type(&self) -> &'static str {
+        "BackdropFilter"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        match &self.child {
+            Some(child) => child.layout(constraints),
+            None => constraints,
+        }
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        ctx.backdrop_filter(bounds, self.filter.clone());
+        if let Some(child) = &self.child {
+            child.paint(ctx, bounds);
+        }
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        if let Some(child) = &self.child {
+            visitor(&**child, bounds);
+        }
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        HitTestBehavior::DeferToChild
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Text;
+    use kobalt_core::paint::DrawCommand;
+
+    #[test]
+    fn test_backdrop_filter_paints_filter_before_child() {
+        let widget = BackdropFilter::new(ImageFilter::blur(5.0, 5.0)).child(Text::new("Hi"));
+        let bounds = Rect::new(0.0, 0.0, 200.0, 50.0);
+
+        let mut ctx = PaintContext::new();
+        widget.paint(&mut ctx, bounds);
+        let list = ctx.finish();
+
+        assert_eq!(
+            list.commands()[0],
+            DrawCommand::BackdropFilter {
+                rect: bounds,
+                filter: ImageFilter::blur(5.0, 5.0),
+            }
+        );
+        assert!(matches!(list.commands()[1], DrawCommand::Text { .. }));
+    }
+
+    #[test]
+    fn test_backdrop_filter_without_child_fills_constraints() {
+        let widget = BackdropFilter::new(ImageFilter::blur(5.0, 5.0));
+        assert_eq!(widget.layout(Size::new(300.0, 80.0)), Size::new(300.0, 80.0));
+    }
+}
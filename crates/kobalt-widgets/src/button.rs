@@ -0,0 +1,685 @@
+//! Button widget - a filled button, and the machinery shared by the button
+//! family
+
+use crate::text::Text;
+use kobalt_core::event::{Event, EventResult, PointerEvent, PointerPhase};
+use kobalt_core::focus::FocusNode;
+use kobalt_core::gesture::{self, GestureRecognizer, GestureSettings};
+use kobalt_core::layout::EdgeInsets;
+use kobalt_core::mouse::{MouseCursor, MouseTarget};
+use kobalt_core::paint::{ColorMatrix, ImageFilter, Paint, PaintContext};
+use kobalt_core::path::{Path, StrokeStyle};
+use kobalt_core::recognizer::{LongPressGestureRecognizer, TapGestureRecognizer};
+use kobalt_core::repaint::{self, RepaintHandle};
+use kobalt_core::scheduler;
+use kobalt_core::shortcuts::{ActionMap, ActivateIntent};
+use kobalt_core::types::{Color, Length, Rect, Size, Transform};
+use kobalt_core::widget::{Widget, WidgetState};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// Called when a button is pressed or long-pressed
+type PressCallback = Rc<dyn Fn()>;
+
+/// Font size of button labels
+const LABEL_SIZE: Length = Length::Sp(14.0);
+
+/// Opacity of the content of a disabled button
+const DISABLED_OPACITY: f32 = 0.38;
+
+/// How a button looks, with colors resolved from its `WidgetState`s
+///
+/// The defaults of each button follow the dark Material 3 color scheme.
+/// Change single fields with struct update syntax:
+///
+/// ```
+/// use kobalt_widgets::{ButtonStyle, Color};
+///
+/// let danger = ButtonStyle {
+///     background: Color::from_rgb8(0xB3, 0x26, 0x1E),
+///     foreground: Color::WHITE,
+///     ..ButtonStyle::filled()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ButtonStyle {
+    /// Fill of the button; transparent for text and icon buttons
+    pub background: Color,
+    /// Color of labels, and of the overlay shown while hovered, focused or
+    /// pressed
+    pub foreground: Color,
+    /// Color of the outline, if any
+    pub border: Option<Color>,
+    /// Radius of the corners, clamped to half the shorter side, so
+    /// `f32::INFINITY` gives a stadium or circle
+    pub corner_radius: f32,
+    /// Space between the edges and the child
+    pub padding: EdgeInsets,
+    /// Smallest size of the button, e.g. to keep it easy to hit
+    pub min_size: Size,
+    /// Cursor shown over the button while it is enabled
+    pub cursor: MouseCursor,
+}
+
+impl ButtonStyle {
+    /// The style of a `Button`: a filled, prominent button
+    pub fn filled() -> Self {
+        Self {
+            background: Color::from_rgb8(0xD0, 0xBC, 0xFF),
+            foreground: Color::from_rgb8(0x38, 0x1E, 0x72),
+            border: None,
+            corner_radius: f32::INFINITY,
+            padding: EdgeInsets::symmetric(10.0, 24.0),
+            min_size: Size::new(64.0, 40.0),
+            cursor: MouseCursor::Click,
+        }
+    }
+
+    /// The style of a `TextButton`: a label without fill or outline
+    pub fn text() -> Self {
+        Self {
+            background: Color::TRANSPARENT,
+            foreground: Color::from_rgb8(0xD0, 0xBC, 0xFF),
+            padding: EdgeInsets::symmetric(10.0, 12.0),
+            ..Self::filled()
+        }
+    }
+
+    /// The style of an `OutlinedButton`: a label inside an outline
+    pub fn outlined() -> Self {
+        Self {
+            border: Some(Color::from_rgb8(0x93, 0x8F, 0x99)),
+            padding: EdgeInsets::symmetric(10.0, 24.0),
+            ..Self::text()
+        }
+    }
+
+    /// The style of an `IconButton`: a round, transparent button
+    pub fn icon() -> Self {
+        Self {
+            background: Color::TRANSPARENT,
+            foreground: Color::from_rgb8(0xCA, 0xC4, 0xD0),
+            padding: EdgeInsets::all(8.0),
+            min_size: Size::square(40.0),
+            ..Self::filled()
+        }
+    }
+
+    /// Returns the fill for a set of states
+    pub fn background_for(&self, state: WidgetState) -> Color {
+        if state.is_disabled() && self.background.a > 0.0 {
+            Color::WHITE.with_alpha(0.12)
+        } else {
+            self.background
+        }
+    }
+
+    /// Returns the outline for a set of states
+    pub fn border_for(&self, state: WidgetState) -> Option<Color> {
+        let border = self.border?;
+        Some(if state.is_disabled() { Color::WHITE.with_alpha(0.12) } else { border })
+    }
+
+    /// Returns the translucent layer painted over the fill for a set of
+    /// states, if any
+    pub fn overlay_for(&self, state: WidgetState) -> Option<Color> {
+        if state.is_disabled() {
+            None
+        } else if state.is_pressed() || state.is_focused() {
+            Some(self.foreground.with_alpha(0.1))
+        } else if state.is_hovered() {
+            Some(self.foreground.with_alpha(0.08))
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for ButtonStyle {
+    fn default() -> Self {
+        Self::filled()
+    }
+}
+
+/// The state and behavior shared by all buttons
+///
+/// A button is enabled while it has an `on_pressed` or `on_long_press`
+/// callback. Enabled buttons recognize taps and long presses in the gesture
+/// arena, track the mouse and can be focused, so the default Enter and
+/// Space shortcuts activate them.
+pub(crate) struct ButtonCore {
+    child: Box<dyn Widget>,
+    /// The text of a child built from a label, rebuilt when the style
+    /// changes
+    label: Option<String>,
+    style: ButtonStyle,
+    state: Rc<InteractionState>,
+    node: FocusNode,
+    settings: GestureSettings,
+    on_pressed: Option<PressCallback>,
+    on_long_press: Option<PressCallback>,
+    tap: Option<Rc<TapGestureRecognizer>>,
+    long_press: Option<Rc<LongPressGestureRecognizer>>,
+    actions: ActionMap,
+}
+
+/// Hovered and pressed; focused and disabled are derived when needed
+#[derive(Default)]
+struct InteractionState {
+    flags: Cell<WidgetState>,
+    /// The repaint boundary the button was last painted inside
+    boundary: RefCell<Option<RepaintHandle>>,
+}
+
+impl InteractionState {
+    fn get(&self) -> WidgetState {
+        self.flags.get()
+    }
+}
+
+/// Sets or clears a state and repaints if it changed
+///
+/// Gesture callbacks can run outside event dispatch, e.g. a long press
+/// when a frame starts, so the boundary the button was painted inside is
+/// marked directly.
+fn set_state(state: &InteractionState, flag: WidgetState, value: bool) {
+    let updated = state.get().set(flag, value);
+    if updated != state.get() {
+        state.flags.set(updated);
+        match &*state.boundary.borrow() {
+            Some(boundary) => boundary.mark_needs_paint(),
+            None => scheduler::request_paint(),
+        }
+    }
+}
+
+impl ButtonCore {
+    /// Creates a disabled button around a child
+    pub(crate) fn new(child: Box<dyn Widget>, style: ButtonStyle) -> Self {
+        let node = FocusNode::new();
+        node.set_can_request_focus(false);
+        Self {
+            child,
+            label: None,
+            style,
+            state: Rc::new(InteractionState::default()),
+            node,
+            settings: GestureSettings::default(),
+            on_pressed: None,
+            on_long_press: None,
+            tap: None,
+            long_press: None,
+            actions: ActionMap::new(),
+        }
+    }
+
+    /// Creates a disabled button showing a label in the style's foreground
+    pub(crate) fn label(label: String, style: ButtonStyle) -> Self {
+        let mut core = Self::new(label_text(&label, &style), style);
+        core.label = Some(label);
+        core
+    }
+
+    pub(crate) fn set_style(&mut self, style: ButtonStyle) {
+        if let Some(label) = &self.label {
+            self.child = label_text(label, &style);
+        }
+        self.style = style;
+    }
+
+    pub(crate) fn set_node(&mut self, node: FocusNode) {
+        node.set_can_request_focus(self.is_enabled());
+        self.node = node;
+    }
+
+    pub(crate) fn set_on_pressed(&mut self, on_pressed: PressCallback) {
+        self.on_pressed = Some(on_pressed);
+        self.configure();
+    }
+
+    pub(crate) fn set_on_long_press(&mut self, on_long_press: PressCallback) {
+        self.on_long_press = Some(on_long_press);
+        self.configure();
+    }
+
+    /// Recreates the recognizers and actions for the current callbacks
+    fn configure(&mut self) {
+        let (down, tapped, cancelled) = (self.state.clone(), self.state.clone(), self.state.clone());
+        let on_pressed = self.on_pressed.clone();
+        self.tap = Some(Rc::new(
+            TapGestureRecognizer::new()
+                .on_tap_down(move |_| set_state(&down, WidgetState::PRESSED, true))
+                .on_tap(move |_| {
+                    set_state(&tapped, WidgetState::PRESSED, false);
+                    if let Some(on_pressed) = &on_pressed {
+                        on_pressed();
+                    }
+                })
+                .on_tap_cancel(move || set_state(&cancelled, WidgetState::PRESSED, false)),
+        ));
+        self.long_press = self.on_long_press.clone().map(|on_long_press| {
+            let state = self.state.clone();
+            Rc::new(LongPressGestureRecognizer::new().on_long_press(move |_| {
+                set_state(&state, WidgetState::PRESSED, false);
+                on_long_press();
+            }))
+        });
+        self.actions = match self.on_pressed.clone() {
+            Some(on_pressed) => ActionMap::new().on(move |_: &ActivateIntent| on_pressed()),
+            None => ActionMap::new(),
+        };
+        self.node.set_can_request_focus(true);
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.on_pressed.is_some() || self.on_long_press.is_some()
+    }
+
+    pub(crate) fn state(&self) -> WidgetState {
+        self.state
+            .get()
+            .set(WidgetState::FOCUSED, self.node.has_focus())
+            .set(WidgetState::DISABLED, !self.is_enabled())
+    }
+
+    pub(crate) fn style(&self) -> &ButtonStyle {
+        &self.style
+    }
+
+    pub(crate) fn node(&self) -> &FocusNode {
+        &self.node
+    }
+
+    pub(crate) fn child(&self) -> &dyn Widget {
+        &*self.child
+    }
+
+    fn insets(&self) -> (f32, f32, f32, f32) {
+        let padding = &self.style.padding;
+        (
+            padding.top.to_logical(),
+            padding.right.to_logical(),
+            padding.bottom.to_logical(),
+            padding.left.to_logical(),
+        )
+    }
+
+    pub(crate) fn layout(&self, constraints: Size) -> Size {
+        let (top, right, bottom, left) = self.insets();
+        let inner = Size::new(
+            (constraints.width - left - right).max(0.0),
+            (constraints.height - top - bottom).max(0.0),
+        );
+        let child = self.child.layout(inner);
+        let min = self.style.min_size;
+        Size::new(
+            (child.width + left + right).max(min.width).min(constraints.width),
+            (child.height + top + bottom).max(min.height).min(constraints.height),
+        )
+    }
+
+    /// Returns where the child goes: centered inside the padding
+    pub(crate) fn child_bounds(&self, bounds: Rect) -> Rect {
+        let (top, right, bottom, left) = self.insets();
+        let inner = Rect::new(
+            bounds.x + left,
+            bounds.y + top,
+            (bounds.width - left - right).max(0.0),
+            (bounds.height - top - bottom).max(0.0),
+        );
+        let child = self.child.layout(inner.size());
+        Rect::new(
+            inner.x + (inner.width - child.width) / 2.0,
+            inner.y + (inner.height - child.height) / 2.0,
+            child.width,
+            child.height,
+        )
+    }
+
+    pub(crate) fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        self.state.boundary.replace(repaint::current_boundary());
+        let state = self.state();
+        let mut shape = Path::new();
+        shape.add_round_rect(bounds, self.style.corner_radius);
+
+        let background = self.style.background_for(state);
+        if background.a > 0.0 {
+            ctx.draw_path(&shape, &Paint::fill(background), Transform::identity());
+        }
+        if let Some(overlay) = self.style.overlay_for(state) {
+            ctx.draw_path(&shape, &Paint::fill(overlay), Transform::identity());
+        }
+        if let Some(border) = self.style.border_for(state) {
+            ctx.draw_path(&shape, &Paint::stroke(border, StrokeStyle::new(1.0)), Transform::identity());
+        }
+
+        let child_bounds = self.child_bounds(bounds);
+        if state.is_disabled() {
+            let fade = ColorMatrix::modulate(Color::WHITE.with_alpha(DISABLED_OPACITY));
+            ctx.push_layer(child_bounds, ImageFilter::color_matrix(fade));
+            self.child.paint(ctx, child_bounds);
+            ctx.pop_layer();
+        } else {
+            self.child.paint(ctx, child_bounds);
+        }
+    }
+
+    pub(crate) fn handle_event(&self, event: &Event) -> EventResult {
+        let Event::Pointer(pointer) = event else {
+            return EventResult::Ignored;
+        };
+        let recognizers = self.tap.iter().map(|tap| tap.clone() as Rc<dyn GestureRecognizer>);
+        let recognizers = recognizers.chain(self.long_press.iter().map(|long_press| long_press.clone() as Rc<dyn GestureRecognizer>));
+        for recognizer in recognizers {
+            match pointer.phase {
+                PointerPhase::Down => {
+                    if recognizer.add_pointer(pointer, &self.settings) {
+                        gesture::add(pointer.id, recognizer.clone());
+                    }
+                }
+                PointerPhase::Move | PointerPhase::Up | PointerPhase::Cancel => recognizer.handle_event(pointer),
+                PointerPhase::Hover | PointerPhase::Scroll | PointerPhase::Exit => {}
+            }
+            if recognizer.deadline().is_some() {
+                gesture::schedule_deadline(recognizer);
+            }
+        }
+        // Left to the arena, so a scrollable around the button can still
+        // win a drag
+        EventResult::Ignored
+    }
+
+    pub(crate) fn actions(&self) -> Option<&ActionMap> {
+        self.on_pressed.as_ref().map(|_| &self.actions)
+    }
+}
+
+impl MouseTarget for ButtonCore {
+    fn cursor(&self) -> Option<MouseCursor> {
+        Some(if self.is_enabled() { self.style.cursor } else { MouseCursor::Basic })
+    }
+
+    fn on_enter(&self, _event: &PointerEvent) {
+        set_state(&self.state, WidgetState::HOVERED, true);
+    }
+
+    fn on_exit(&self, _event: &PointerEvent) {
+        set_state(&self.state, WidgetState::HOVERED, false);
+    }
+}
+
+fn label_text(label: &str, style: &ButtonStyle) -> Box<dyn Widget> {
+    Box::new(Text::new(label).size(LABEL_SIZE).color(style.foreground))
+}
+
+/// Implements the builders and `Widget` of a button wrapping a
+/// `ButtonCore` in a `core` field
+macro_rules! button_widget {
+    ($name:ident) => {
+        impl $name {
+            /// Sets the callback for a tap, Enter or Space; without it or
+            /// `on_long_press` the button is disabled
+            pub fn on_pressed(mut self, on_pressed: impl Fn() + 'static) -> Self {
+                self.core.set_on_pressed(::std::rc::Rc::new(on_pressed));
+                self
+            }
+
+            /// Sets the callback for a long press
+            pub fn on_long_press(mut self, on_long_press: impl Fn() + 'static) -> Self {
+                self.core.set_on_long_press(::std::rc::Rc::new(on_long_press));
+                self
+            }
+
+            /// Sets how the button looks
+            pub fn style(mut self, style: $crate::button::ButtonStyle) -> Self {
+                self.core.set_style(style);
+                self
+            }
+
+            /// Uses a focus node kept elsewhere, e.g. to call
+            /// `FocusNode::request_focus` from a callback
+            pub fn focus_node(mut self, node: ::kobalt_core::focus::FocusNode) -> Self {
+                self.core.set_node(node);
+                self
+            }
+
+            /// Sets whether the button takes the focus when it first
+            /// appears and nothing else is focused
+            pub fn autofocus(self, autofocus: bool) -> Self {
+                self.core.node().set_autofocus(autofocus);
+                self
+            }
+
+            /// Returns true if the button has a callback and responds to
+            /// input
+            pub fn is_enabled(&self) -> bool {
+                self.core.is_enabled()
+            }
+
+            /// Returns the states the button is in
+            pub fn get_state(&self) -> ::kobalt_core::widget::WidgetState {
+                self.core.state()
+            }
+
+            /// Returns how the button looks
+            pub fn get_style(&self) -> &$crate::button::ButtonStyle {
+                self.core.style()
+            }
+
+            /// Returns the focus node
+            pub fn get_node(&self) -> &::kobalt_core::focus::FocusNode {
+                self.core.node()
+            }
+
+            /// Returns the child
+            pub fn get_child(&self) -> &dyn ::kobalt_core::widget::Widget {
+                self.core.child()
+            }
+        }
+
+        impl ::kobalt_core::widget::Widget for $name {
+            fn widget_type(&self) -> &'static str {
+                stringify!($name)
+            }
+
+            fn layout(&self, constraints: ::kobalt_core::types::Size) -> ::kobalt_core::types::Size {
+                self.core.layout(constraints)
+            }
+
+            fn paint(&self, ctx: &mut ::kobalt_core::paint::PaintContext, bounds: ::kobalt_core::types::Rect) {
+                self.core.paint(ctx, bounds);
+            }
+
+            fn visit_children<'a>(
+                &'a self,
+                bounds: ::kobalt_core::types::Rect,
+                visitor: &mut dyn FnMut(&'a dyn ::kobalt_core::widget::Widget, ::kobalt_core::types::Rect),
+            ) {
+                visitor(self.core.child(), self.core.child_bounds(bounds));
+            }
+
+            fn handle_event(
+                &self,
+                event: &::kobalt_core::event::Event,
+                _bounds: ::kobalt_core::types::Rect,
+            ) -> ::kobalt_core::event::EventResult {
+                self.core.handle_event(event)
+            }
+
+            fn focus_node(&self) -> Option<&::kobalt_core::focus::FocusNode> {
+                Some(self.core.node())
+            }
+
+            fn mouse_target(&self) -> Option<&dyn ::kobalt_core::mouse::MouseTarget> {
+                Some(&self.core)
+            }
+
+            fn actions(&self) -> Option<&::kobalt_core::shortcuts::ActionMap> {
+                self.core.actions()
+            }
+        }
+    };
+}
+
+pub(crate) use button_widget;
+
+/// A filled button, similar to Flutter's `FilledButton`
+///
+/// The button is disabled until it gets an `on_pressed` or `on_long_press`
+/// callback. Its fill, outline and overlay follow its `WidgetState`s:
+/// hovered, focused, pressed and disabled. An enabled button can be
+/// focused with Tab and activated with Enter or Space.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_widgets::Button;
+///
+/// let save = Button::label("Save").on_pressed(|| println!("saved"));
+/// ```
+pub struct Button {
+    core: ButtonCore,
+}
+
+impl Button {
+    /// Creates a disabled Button around a child
+    pub fn new<W: Widget + 'static>(child: W) -> Self {
+        Self {
+            core: ButtonCore::new(Box::new(child), ButtonStyle::filled()),
+        }
+    }
+
+    /// Creates a disabled Button showing a label
+    pub fn label(label: impl Into<String>) -> Self {
+        Self {
+            core: ButtonCore::label(label.into(), ButtonStyle::filled()),
+        }
+    }
+}
+
+button_widget!(Button);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RepaintBoundary;
+    use kobalt_core::event::{LogicalKey, PointerKind};
+    use kobalt_core::paint::DrawCommand;
+    use kobalt_core::testing::{Finder, WidgetTester};
+    use kobalt_core::types::Point;
+
+    fn counter() -> (Rc<Cell<u32>>, impl Fn() + 'static) {
+        let count = Rc::new(Cell::new(0));
+        let increment = count.clone();
+        (count, move || increment.set(increment.get() + 1))
+    }
+
+    #[test]
+    fn test_tap_and_long_press() {
+        let (presses, on_pressed) = counter();
+        let (long_presses, on_long_press) = counter();
+        let button = Button::label("Save").on_pressed(on_pressed).on_long_press(on_long_press);
+        let mut tester = WidgetTester::new(button);
+        tester.pump();
+
+        tester.tap(&Finder::text("Save"));
+        tester.long_press(&Finder::text("Save"));
+        assert_eq!((presses.get(), long_presses.get()), (1, 1));
+    }
+
+    #[test]
+    fn test_hovered_and_pressed_states() {
+        let button = Button::label("Save").on_pressed(|| {});
+        let state = button.core.state.clone();
+        let mut tester = WidgetTester::new(button);
+        tester.pump();
+
+        tester.hover(&Finder::text("Save"));
+        assert_eq!(state.get(), WidgetState::HOVERED);
+        assert_eq!(tester.cursor(), MouseCursor::Click);
+
+        // Moving past the touch slop cancels the press
+        let center = tester.bounds(&Finder::text("Save")).center();
+        tester.send_event(&Event::Pointer(PointerEvent::new(1, PointerPhase::Down, center)));
+        assert!(state.get().is_pressed());
+        tester.send_event(&Event::Pointer(PointerEvent::new(1, PointerPhase::Move, center + Point::new(100.0, 0.0))));
+        assert!(!state.get().is_pressed());
+
+        tester.mouse_exit();
+        assert!(state.get().is_empty());
+    }
+
+    /// Returns the commands the boundary around the button recorded
+    fn recorded(tester: &WidgetTester) -> Vec<DrawCommand> {
+        match tester.display_list().unwrap().commands() {
+            [DrawCommand::RepaintBoundary { list, .. }] => list.commands().to_vec(),
+            other => panic!("unexpected commands {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_state_changes_repaint_inside_a_boundary() {
+        let mut tester = WidgetTester::new(RepaintBoundary::new(Button::label("Save").on_pressed(|| {})));
+        tester.pump();
+        let idle = recorded(&tester);
+
+        tester.hover(&Finder::text("Save"));
+        tester.pump();
+        let hovered = recorded(&tester);
+        assert_ne!(hovered, idle);
+
+        tester.mouse_exit();
+        tester.pump();
+        assert_eq!(recorded(&tester), idle);
+
+        // A finger, so the button is pressed without being hovered
+        let center = tester.bounds(&Finder::text("Save")).center();
+        let touch = |phase| Event::Pointer(PointerEvent::new(1, phase, center).kind(PointerKind::Touch));
+        tester.send_event(&touch(PointerPhase::Down));
+        tester.pump();
+        assert_ne!(recorded(&tester), idle);
+        tester.send_event(&touch(PointerPhase::Up));
+        tester.pump();
+        assert_eq!(recorded(&tester), idle);
+
+        tester.press_key(LogicalKey::Tab);
+        tester.pump();
+        assert_ne!(recorded(&tester), idle);
+    }
+
+    #[test]
+    fn test_keyboard_activation() {
+        let (presses, on_pressed) = counter();
+        let mut tester = WidgetTester::new(Button::label("Save").on_pressed(on_pressed).autofocus(true));
+        tester.pump();
+
+        assert_eq!(tester.focused().unwrap().widget.widget_type(), "Button");
+        tester.press_key(LogicalKey::Enter);
+        tester.press_key(LogicalKey::Space);
+        assert_eq!(presses.get(), 2);
+    }
+
+    #[test]
+    fn test_disabled_button() {
+        let button = Button::label("Save");
+        assert!(!button.is_enabled());
+        assert!(button.get_state().is_disabled());
+        let mut tester = WidgetTester::new(button.autofocus(true));
+        tester.pump();
+
+        assert!(tester.focused().is_none());
+        tester.hover(&Finder::text("Save"));
+        assert_eq!(tester.cursor(), MouseCursor::Basic);
+        assert!(!tester.press_key(LogicalKey::Enter).is_handled());
+    }
+
+    #[test]
+    fn test_style_resolves_states() {
+        let style = ButtonStyle::filled();
+        assert_eq!(style.overlay_for(WidgetState::NONE), None);
+        assert_eq!(style.overlay_for(WidgetState::HOVERED), Some(style.foreground.with_alpha(0.08)));
+        assert_eq!(style.overlay_for(WidgetState::PRESSED.with(WidgetState::DISABLED)), None);
+        assert_eq!(style.background_for(WidgetState::DISABLED), Color::WHITE.with_alpha(0.12));
+        assert_eq!(ButtonStyle::text().background_for(WidgetState::DISABLED), Color::TRANSPARENT);
+    }
+}
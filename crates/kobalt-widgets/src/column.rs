@@ -1,16 +1,23 @@
 //! Column layout widget - arranges children vertically
 
 use kobalt_core::layout::{CrossAxisAlignment, EdgeInsets, MainAxisAlignment};
+use kobalt_core::layout_cache::LayoutCache;
+use kobalt_core::paint::PaintContext;
 use kobalt_core::types::{Point, Rect, Size};
+use kobalt_core::hit_test::HitTestBehavior;
 use kobalt_core::widget::Widget;
 
 /// A layout widget that arranges its children vertically
+///
+/// Each child is laid out once per distinct set of constraints; the child
+/// sizes are cached and reused when painting.
 pub struct Column {
     children: Vec<Box<dyn Widget>>,
     position: Point,
     main_axis_alignment: MainAxisAlignment,
     cross_axis_alignment: CrossAxisAlignment,
     padding: EdgeInsets,
+    layout_cache: LayoutCache<Vec<Size>>,
 }
 
 impl Column {
@@ -22,6 +29,7 @@ impl Column {
             main_axis_alignment: MainAxisAlignment::default(),
             cross_axis_alignment: CrossAxisAlignment::default(),
             padding: EdgeInsets::default(),
+            layout_cache: LayoutCache::new(),
         }
     }
 
@@ -33,10 +41,12 @@ impl Column {
             main_axis_alignment: MainAxisAlignment::default(),
             cross_axis_alignment: CrossAxisAlignment::default(),
             padding: EdgeInsets::default(),
+            layout_cache: LayoutCache::new(),
         }
     }
 
     /// Adds a child widget to the column
+    #[allow(clippy::should_implement_trait)]
     pub fn add<W: Widget + 'static>(mut self, widget: W) -> Self {
         self.children.push(Box::new(widget));
         self
@@ -85,6 +95,79 @@ impl Column {
     pub fn get_padding(&self) -> EdgeInsets {
         self.padding
     }
+
+    /// Returns the column's layout cache
+    pub fn layout_cache(&self) -> &LayoutCache<Vec<Size>> {
+        &self.layout_cache
+    }
+
+    /// Returns the constraints given to children: the incoming constraints
+    /// minus padding
+    fn inner_constraints(&self, constraints: Size) -> Size {
+        Size::new(
+            (constraints.width - self.padding.horizontal()).max(0.0),
+            (constraints.height - self.padding.vertical()).max(0.0),
+        )
+    }
+
+    /// Computes the rectangle of each child inside the given bounds,
+    /// applying padding and both axis alignments
+    pub fn child_rects(&self, bounds: Rect) -> Vec<Rect> {
+        let inner = Rect::new(
+            bounds.x + self.padding.left(),
+            bounds.y + self.padding.top(),
+            (bounds.width - self.padding.horizontal()).max(0.0),
+            (bounds.height - self.padding.vertical()).max(0.0),
+        );
+
+        // Reuse the child sizes from layout if it produced these bounds
+        let sizes = self
+            .layout_cache
+            .data_for_size(bounds.size())
+            .filter(|sizes| sizes.len() == self.children.len())
+            .unwrap_or_else(|| {
+                self.children
+                    .iter()
+                    .map(|child| child.layout(inner.size()))
+                    .collect()
+            });
+
+        let count = sizes.len() as f32;
+        let total_height: f32 = sizes.iter().map(|size| size.height).sum();
+        let free = (inner.height - total_height).max(0.0);
+
+        let (start, gap) = match self.main_axis_alignment {
+            MainAxisAlignment::Start => (0.0, 0.0),
+            MainAxisAlignment::End => (free, 0.0),
+            MainAxisAlignment::Center => (free / 2.0, 0.0),
+            MainAxisAlignment::SpaceBetween if sizes.len() > 1 => (0.0, free / (count - 1.0)),
+            MainAxisAlignment::SpaceBetween => (0.0, 0.0),
+            MainAxisAlignment::SpaceAround => (free / count / 2.0, free / count),
+            MainAxisAlignment::SpaceEvenly => (free / (count + 1.0), free / (count + 1.0)),
+        };
+
+        let mut y = inner.y + start;
+        sizes
+            .into_iter()
+            .map(|size| {
+                let (x, width) = match self.cross_axis_alignment {
+                    CrossAxisAlignment::Start => (inner.x, size.width),
+                    CrossAxisAlignment::End => (inner.right() - size.width, size.width),
+                    CrossAxisAlignment::Center => (inner.x + (inner.width - size.width) / 2.0, size.width),
+                    CrossAxisAlignment::Stretch => (inner.x, inner.width),
+                };
+                let rect = Rect::new(x, y, width, size.height);
+                y += size.height + gap;
+                rect
+            })
+            .collect()
+    }
+}
+
+impl Default for Column {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Widget for Column {
@@ -93,20 +176,21 @@ impl Widget for Column {
     }
 
     fn layout(&self, constraints: Size) -> Size {
-        // Sum up the heights of all children
-        let total_height: f32 = self.children
-            .iter()
-            .map(|child| child.layout(constraints).height)
-            .sum();
+        self.layout_cache.layout_with(constraints, |constraints| {
+            // Lay out each child once
+            let inner = self.inner_constraints(constraints);
+            let sizes: Vec<Size> = self.children.iter().map(|child| child.layout(inner)).collect();
 
-        // Width is the maximum width of all children
-        let max_width: f32 = self.children
-            .iter()
-            .map(|child| child.layout(constraints).width)
-            .max_by(|a, b| a.partial_cmp(b).unwrap())
-            .unwrap_or(0.0);
+            // Height is the sum of the children's, width the largest
+            let total_height: f32 = sizes.iter().map(|size| size.height).sum();
+            let max_width = sizes.iter().map(|size| size.width).fold(0.0, f32::max);
 
-        Size::new(max_width, total_height)
+            let size = Size::new(
+                max_width + self.padding.horizontal(),
+                total_height + self.padding.vertical(),
+            );
+            (size, sizes)
+        })
     }
 
     fn bounds(&self) -> Option<Rect> {
@@ -117,4 +201,131 @@ impl Widget for Column {
             0.0,
         ))
     }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        for (child, rect) in self.children.iter().zip(self.child_rects(bounds)) {
+            child.paint(ctx, rect);
+        }
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        for (child, rect) in self.children.iter().zip(self.child_rects(bounds)) {
+            visitor(&**child, rect);
+        }
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        HitTestBehavior::DeferToChild
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// A fixed-size leaf that counts how often it is laid out
+    struct Leaf {
+        layouts: Rc<Cell<usize>>,
+    }
+
+    impl Widget for Leaf {
+        fn widget_type(&self) -> &'static str {
+            "Leaf"
+        }
+
+        fn layout(&self, _constraints: Size) -> Size {
+            self.layouts.set(self.layouts.get() + 1);
+            Size::new(10.0, 10.0)
+        }
+
+        fn paint(&self, _ctx: &mut PaintContext, _bounds: Rect) {}
+    }
+
+    fn leaf(layouts: &Rc<Cell<usize>>) -> Leaf {
+        Leaf {
+            layouts: layouts.clone(),
+        }
+    }
+
+    #[test]
+    fn test_children_are_stacked() {
+        let layouts = Rc::new(Cell::new(0));
+        let column = Column::new()
+            .padding(EdgeInsets::all(5.0))
+            .add(leaf(&layouts))
+            .add(leaf(&layouts));
+
+        let size = column.layout(Size::new(100.0, 100.0));
+        assert_eq!(size, Size::new(20.0, 30.0));
+
+        let rects = column.child_rects(Rect::new(0.0, 0.0, size.width, size.height));
+        assert_eq!(rects, vec![Rect::new(5.0, 5.0, 10.0, 10.0), Rect::new(5.0, 15.0, 10.0, 10.0)]);
+    }
+
+    #[test]
+    fn test_deep_tree_lays_out_in_linear_time() {
+        // Without caching, each level would lay out its child several times,
+        // so the leaf count would grow exponentially with depth
+        const DEPTH: usize = 40;
+        let layouts = Rc::new(Cell::new(0));
+        let mut tree = Column::new().add(leaf(&layouts));
+        for _ in 0..DEPTH {
+            tree = Column::new().add(tree).add(leaf(&layouts));
+        }
+
+        let constraints = Size::new(800.0, 600.0);
+        let size = tree.layout(constraints);
+        assert_eq!(layouts.get(), DEPTH + 1);
+
+        // Painting reuses the cached sizes at the root
+        let mut ctx = PaintContext::new();
+        tree.paint(&mut ctx, Rect::new(0.0, 0.0, size.width, size.height));
+        tree.layout(constraints);
+        assert!(layouts.get() <= 2 * (DEPTH + 1), "{} leaf layouts", layouts.get());
+    }
+
+    #[test]
+    fn test_invalidation_relays_out_ancestors() {
+        let layouts = Rc::new(Cell::new(0));
+        let inner = Column::new().add(leaf(&layouts));
+        let handle = inner.layout_cache().handle();
+        let outer = Column::new().add(inner);
+        let constraints = Size::new(100.0, 100.0);
+
+        outer.layout(constraints);
+        outer.layout(constraints);
+        assert_eq!(layouts.get(), 1);
+
+        handle.mark_needs_layout();
+        assert!(outer.layout_cache().handle().needs_layout());
+        outer.layout(constraints);
+        assert_eq!(layouts.get(), 2);
+    }
+
+    #[test]
+    fn test_golden_column_center() {
+        use crate::CustomPaint;
+        use kobalt_core::canvas::Canvas;
+        use kobalt_core::paint::Paint;
+        use kobalt_core::types::Color;
+        use kobalt_render::assert_golden;
+
+        fn swatch(color: Color, size: Size) -> CustomPaint {
+            CustomPaint::new(move |canvas: &mut Canvas, size: Size| {
+                canvas.draw_round_rect(Rect::new(0.0, 0.0, size.width, size.height), 8.0, &Paint::fill(color));
+            })
+            .size(size)
+        }
+
+        let column = Column::new()
+            .main_axis_alignment(MainAxisAlignment::Center)
+            .cross_axis_alignment(CrossAxisAlignment::Center)
+            .add(swatch(Color::RED, Size::new(120.0, 60.0)))
+            .add(swatch(Color::GREEN, Size::new(200.0, 40.0)))
+            .add(swatch(Color::BLUE, Size::new(80.0, 80.0)));
+
+        assert_golden!("column_center", column, Size::new(400.0, 300.0));
+    }
 }
@@ -0,0 +1,180 @@
+//! CustomPaint widget - draws with user-provided painters
+
+use kobalt_core::canvas::Canvas;
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Rect, Size};
+use kobalt_core::widget::Widget;
+
+/// Draws onto a `Canvas`, similar to Flutter's `CustomPainter`
+///
+/// Any `Fn(&mut Canvas, Size)` closure is also a painter.
+pub trait CustomPainter {
+    /// Paints into a canvas whose origin is the widget's top-left corner
+    fn paint(&self, canvas: &mut Canvas, size: Size);
+}
+
+impl<F> CustomPainter for F
+where
+    F: Fn(&mut Canvas, Size),
+{
+    fn paint(&self, canvas: &mut Canvas, size: Size) {
+        self(canvas, size)
+    }
+}
+
+/// A widget that hands a canvas to custom painters
+///
+/// The background painter runs first, then the child is painted, then the
+/// foreground painter. Without a child the widget takes its preferred
+/// `size`, limited by the space offered by its parent.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_core::paint::Paint;
+/// use kobalt_core::types::{Color, Point, Size};
+/// use kobalt_widgets::{Canvas, CustomPaint};
+///
+/// let dot = CustomPaint::new(|canvas: &mut Canvas, size: Size| {
+///     let center = Point::new(size.width / 2.0, size.height / 2.0);
+///     canvas.draw_circle(center, size.width / 2.0, &Paint::fill(Color::RED));
+/// })
+/// .size(Size::new(24.0, 24.0));
+/// ```
+pub struct CustomPaint {
+    painter: Option<Box<dyn CustomPainter>>,
+    foreground_painter: Option<Box<dyn CustomPainter>>,
+    child: Option<Box<dyn Widget>>,
+    size: Size,
+}
+
+impl CustomPaint {
+    /// Creates a new CustomPaint with a background painter
+    pub fn new<P: CustomPainter + 'static>(painter: P) -> Self {
+        Self {
+            painter: Some(Box::new(painter)),
+            foreground_painter: None,
+            child: None,
+            size: Size::zero(),
+        }
+    }
+
+    /// Sets a painter that runs after the child is painted
+    pub fn foreground_painter<P: CustomPainter + 'static>(mut self, painter: P) -> Self {
+        self.foreground_painter = Some(Box::new(painter));
+        self
+    }
+
+    /// Sets the child painted between the two painters
+    pub fn child<W: Widget + 'static>(mut self, child: W) -> Self {
+        self.child = Some(Box::new(child));
+        self
+    }
+
+    /// Sets the preferred size used when there is no child
+    pub fn size(mut self, size: Size) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Returns the preferred size
+    pub fn get_size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the child, if any
+    pub fn get_child(&self) -> Option<&dyn Widget> {
+        self.child.as_deref()
+    }
+
+    fn run_painter(painter: &dyn CustomPainter, ctx: &mut PaintContext, bounds: Rect) {
+        let mut canvas = Canvas::new(ctx, bounds.origin());
+        painter.paint(&mut canvas, bounds.size());
+    }
+}
+
+impl Widget for CustomPaint {
+    fn widget_type(&self) -> &'static str {
+        "CustomPaint"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        match &self.child {
+            Some(child) => child.layout(constraints),
+            None => Size::new(
+                self.size.width.min(constraints.width),
+                self.size.height.min(constraints.height),
+            ),
+        }
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        if let Some(painter) = &self.painter {
+            Self::run_painter(painter.as_ref(), ctx, bounds);
+        }
+        if let Some(child) = &self.child {
+            child.paint(ctx, bounds);
+        }
+        if let Some(painter) = &self.foreground_painter {
+            Self::run_painter(painter.as_ref(), ctx, bounds);
+        }
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        if let Some(child) = &self.child {
+            visitor(&**child, bounds);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Text;
+    use kobalt_core::paint::{DrawCommand, Paint};
+    use kobalt_core::types::Color;
+
+    #[test]
+    fn test_custom_paint_paints_in_local_coordinates() {
+        let widget = CustomPaint::new(|canvas: &mut Canvas, size: Size| {
+            canvas.draw_rect(Rect::new(0.0, 0.0, size.width, size.height), &Paint::fill(Color::RED));
+        });
+
+        let mut ctx = PaintContext::new();
+        widget.paint(&mut ctx, Rect::new(10.0, 20.0, 30.0, 40.0));
+        let list = ctx.finish();
+
+        assert_eq!(
+            list.commands(),
+            &[DrawCommand::Rect {
+                rect: Rect::new(10.0, 20.0, 30.0, 40.0),
+                color: Color::RED,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_custom_paint_order() {
+        let widget = CustomPaint::new(|canvas: &mut Canvas, _: Size| {
+            canvas.draw_circle(kobalt_core::types::Point::zero(), 5.0, &Paint::fill(Color::RED));
+        })
+        .child(Text::new("Hi"))
+        .foreground_painter(|canvas: &mut Canvas, _: Size| {
+            canvas.draw_rect(Rect::new(0.0, 0.0, 1.0, 1.0), &Paint::fill(Color::BLUE));
+        });
+
+        let mut ctx = PaintContext::new();
+        widget.paint(&mut ctx, Rect::new(0.0, 0.0, 100.0, 100.0));
+        let list = ctx.finish();
+
+        assert!(matches!(list.commands()[0], DrawCommand::Path { .. }));
+        assert!(matches!(list.commands()[1], DrawCommand::Text { .. }));
+        assert!(matches!(list.commands()[2], DrawCommand::Rect { .. }));
+    }
+
+    #[test]
+    fn test_custom_paint_size_is_constrained() {
+        let widget = CustomPaint::new(|_: &mut Canvas, _: Size| {}).size(Size::new(500.0, 50.0));
+        assert_eq!(widget.layout(Size::new(200.0, 100.0)), Size::new(200.0, 50.0));
+    }
+}
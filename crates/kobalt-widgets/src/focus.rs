@@ -0,0 +1,167 @@
+//! Focus widget - lets its child take keyboard focus
+
+use kobalt_core::event::{Event, EventResult, KeyEvent};
+use kobalt_core::focus::FocusNode;
+use kobalt_core::hit_test::HitTestBehavior;
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Rect, Size};
+use kobalt_core::widget::Widget;
+
+/// Called with the key events reaching a `Focus` or `FocusScope`
+pub(crate) type KeyHandler = Box<dyn Fn(&KeyEvent) -> EventResult>;
+
+/// A widget that can take keyboard focus, similar to Flutter's `Focus`
+///
+/// While focused, key events are offered to `on_key` first; events it
+/// ignores bubble to the enclosing widgets. Key events sent to a focused
+/// widget below this one bubble through `on_key` as well, so a `Focus`
+/// higher up the tree can handle keys for a whole subtree.
+pub struct Focus {
+    node: FocusNode,
+    on_key: Option<KeyHandler>,
+    child: Box<dyn Widget>,
+}
+
+impl Focus {
+    /// Creates a Focus around a child, with a new focus node
+    pub fn new<W: Widget + 'static>(child: W) -> Self {
+        Self::with_node(child, FocusNode::new())
+    }
+
+    /// Creates a Focus around a child with a node kept elsewhere, e.g. to
+    /// call `FocusNode::request_focus` from a callback
+    pub fn with_node<W: Widget + 'static>(child: W, node: FocusNode) -> Self {
+        Self {
+            node,
+            on_key: None,
+            child: Box::new(child),
+        }
+    }
+
+    /// Sets whether the node takes the focus when it first appears and
+    /// nothing else is focused
+    pub fn autofocus(self, autofocus: bool) -> Self {
+        self.node.set_autofocus(autofocus);
+        self
+    }
+
+    /// Sets the Tab order of the node; nodes with an order come before the
+    /// rest, lowest first
+    pub fn order(self, order: f32) -> Self {
+        self.node.set_traversal_order(Some(order));
+        self
+    }
+
+    /// Sets whether Tab skips the node
+    pub fn skip_traversal(self, skip_traversal: bool) -> Self {
+        self.node.set_skip_traversal(skip_traversal);
+        self
+    }
+
+    /// Sets whether the node can be focused
+    pub fn can_request_focus(self, can_request_focus: bool) -> Self {
+        self.node.set_can_request_focus(can_request_focus);
+        self
+    }
+
+    /// Sets the callback for key events; return `EventResult::Handled` to
+    /// stop them bubbling further
+    pub fn on_key(mut self, on_key: impl Fn(&KeyEvent) -> EventResult + 'static) -> Self {
+        self.on_key = Some(Box::new(on_key));
+        self
+    }
+
+    /// Sets the callback called with true when the node gains the focus
+    /// and false when it loses it
+    pub fn on_focus_change(self, on_focus_change: impl Fn(bool) + 'static) -> Self {
+        self.node.add_listener(on_focus_change);
+        self
+    }
+
+    /// Returns the focus node
+    pub fn get_node(&self) -> &FocusNode {
+        &self.node
+    }
+
+    /// Returns the child
+    pub fn get_child(&self) -> &dyn Widget {
+        &*self.child
+    }
+}
+
+impl Widget for Focus {
+    fn widget_type(&self) -> &'static str {
+        "Focus"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.child.layout(constraints)
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        self.child.paint(ctx, bounds);
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        visitor(&*self.child, bounds);
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        HitTestBehavior::DeferToChild
+    }
+
+    fn handle_event(&self, event: &Event, _bounds: Rect) -> EventResult {
+        match (event, &self.on_key) {
+            (Event::Key(key), Some(on_key)) => on_key(key),
+            _ => EventResult::Ignored,
+        }
+    }
+
+    fn focus_node(&self) -> Option<&FocusNode> {
+        Some(&self.node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Column, Text};
+    use kobalt_core::event::LogicalKey;
+    use kobalt_core::testing::{Finder, WidgetTester};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_autofocus_and_on_key() {
+        let keys = Rc::new(RefCell::new(Vec::new()));
+        let log = keys.clone();
+        let focus = Focus::new(Text::new("Field")).autofocus(true).on_key(move |key| {
+            log.borrow_mut().push(key.key.clone());
+            EventResult::Handled
+        });
+        let mut tester = WidgetTester::new(focus);
+        tester.pump();
+
+        assert!(tester.focused().is_some());
+        assert!(tester.press_key(LogicalKey::Enter).is_handled());
+        assert_eq!(*keys.borrow(), vec![LogicalKey::Enter, LogicalKey::Enter]);
+    }
+
+    #[test]
+    fn test_order_and_focus_changes() {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let log = changes.clone();
+        let column = Column::new()
+            .add(Focus::new(Text::new("First")).on_focus_change(move |focused| log.borrow_mut().push(focused)))
+            .add(Focus::new(Text::new("Second")).order(1.0));
+        let mut tester = WidgetTester::new(column);
+        tester.pump();
+
+        tester.press_key(LogicalKey::Tab);
+        let focused = tester.focused().unwrap();
+        assert_eq!(focused.bounds, tester.bounds(&Finder::text("Second")));
+        tester.press_key(LogicalKey::Tab);
+        tester.press_key(LogicalKey::Tab);
+        assert_eq!(*changes.borrow(), vec![true, false]);
+    }
+}
@@ -0,0 +1,124 @@
+//! FocusScope widget - groups focusable widgets for Tab traversal
+
+use crate::focus::KeyHandler;
+use kobalt_core::event::{Event, EventResult, KeyEvent};
+use kobalt_core::focus::FocusNode;
+use kobalt_core::hit_test::HitTestBehavior;
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Rect, Size};
+use kobalt_core::widget::Widget;
+
+/// A widget grouping the focusable widgets below it, similar to Flutter's
+/// `FocusScope`
+///
+/// While one of them is focused, Tab and Shift+Tab cycle through the
+/// widgets of the scope only, e.g. the fields of a dialog. Requesting focus
+/// for the scope's node focuses its autofocus widget, or else its first
+/// widget in Tab order.
+pub struct FocusScope {
+    node: FocusNode,
+    on_key: Option<KeyHandler>,
+    child: Box<dyn Widget>,
+}
+
+impl FocusScope {
+    /// Creates a FocusScope around a child, with a new scope node
+    pub fn new<W: Widget + 'static>(child: W) -> Self {
+        Self::with_node(child, FocusNode::scope())
+    }
+
+    /// Creates a FocusScope around a child with a node kept elsewhere
+    ///
+    /// The node should be created with `FocusNode::scope`.
+    pub fn with_node<W: Widget + 'static>(child: W, node: FocusNode) -> Self {
+        Self {
+            node,
+            on_key: None,
+            child: Box::new(child),
+        }
+    }
+
+    /// Sets whether the scope takes the focus when it first appears and
+    /// nothing else is focused
+    pub fn autofocus(self, autofocus: bool) -> Self {
+        self.node.set_autofocus(autofocus);
+        self
+    }
+
+    /// Sets the callback for key events bubbling out of the scope
+    pub fn on_key(mut self, on_key: impl Fn(&KeyEvent) -> EventResult + 'static) -> Self {
+        self.on_key = Some(Box::new(on_key));
+        self
+    }
+
+    /// Returns the scope node
+    pub fn get_node(&self) -> &FocusNode {
+        &self.node
+    }
+
+    /// Returns the child
+    pub fn get_child(&self) -> &dyn Widget {
+        &*self.child
+    }
+}
+
+impl Widget for FocusScope {
+    fn widget_type(&self) -> &'static str {
+        "FocusScope"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.child.layout(constraints)
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        self.child.paint(ctx, bounds);
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        visitor(&*self.child, bounds);
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        HitTestBehavior::DeferToChild
+    }
+
+    fn handle_event(&self, event: &Event, _bounds: Rect) -> EventResult {
+        match (event, &self.on_key) {
+            (Event::Key(key), Some(on_key)) => on_key(key),
+            _ => EventResult::Ignored,
+        }
+    }
+
+    fn focus_node(&self) -> Option<&FocusNode> {
+        Some(&self.node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Column, Focus, Text};
+    use kobalt_core::event::LogicalKey;
+    use kobalt_core::testing::{Finder, WidgetTester};
+
+    #[test]
+    fn test_tab_cycles_inside_scope() {
+        let field = FocusNode::new();
+        let column = Column::new()
+            .add(Focus::new(Text::new("Outside")))
+            .add(FocusScope::new(
+                Column::new()
+                    .add(Focus::with_node(Text::new("Name"), field.clone()))
+                    .add(Focus::new(Text::new("Email"))),
+            ).autofocus(true));
+        let mut tester = WidgetTester::new(column);
+        tester.pump();
+        assert!(field.has_focus());
+
+        tester.press_key(LogicalKey::Tab);
+        assert_eq!(tester.focused().unwrap().bounds, tester.bounds(&Finder::text("Email")));
+        tester.press_key(LogicalKey::Tab);
+        assert!(field.has_focus());
+    }
+}
@@ -0,0 +1,399 @@
+//! GestureDetector widget - recognizes gestures on its child
+
+use kobalt_core::event::{Event, EventResult, PointerPhase};
+use kobalt_core::gesture::{self, GestureRecognizer, GestureSettings};
+use kobalt_core::hit_test::HitTestBehavior;
+use kobalt_core::paint::PaintContext;
+use kobalt_core::recognizer::{
+    DoubleTapGestureRecognizer, DragAxis, DragEndDetails, DragGestureRecognizer, DragStartDetails,
+    DragUpdateDetails, LongPressDetails, LongPressGestureRecognizer, ScaleEndDetails, ScaleGestureRecognizer,
+    ScaleStartDetails, ScaleUpdateDetails, TapDetails, TapGestureRecognizer,
+};
+use kobalt_core::types::{Rect, Size};
+use kobalt_core::widget::Widget;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// A widget that calls back when gestures are made on its child, similar
+/// to Flutter's `GestureDetector`
+///
+/// A recognizer is created for each kind of gesture with a callback. The
+/// recognizers of all detectors under a pointer compete in its gesture
+/// arena, so a tap on a button inside a scrollable area is a tap if the
+/// pointer barely moves and a drag of the area otherwise. Callbacks get
+/// positions in the detector's coordinates.
+///
+/// The detector does not consume pointer events, so detectors further out
+/// take part in the arena as well. By default it is hit only where its
+/// child is; use `behavior` to make its whole area respond.
+///
+/// Recognizing both a pan and a scale is not supported, as a scale already
+/// reports the focal point moving.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_widgets::{GestureDetector, Text};
+///
+/// let button = GestureDetector::new(Text::new("Open"))
+///     .on_tap(|_| println!("tapped"))
+///     .on_long_press(|_| println!("long-pressed"));
+/// ```
+pub struct GestureDetector {
+    child: Box<dyn Widget>,
+    behavior: HitTestBehavior,
+    settings: GestureSettings,
+    tap: Option<Rc<TapGestureRecognizer>>,
+    double_tap: Option<Rc<DoubleTapGestureRecognizer>>,
+    long_press: Option<Rc<LongPressGestureRecognizer>>,
+    horizontal_drag: Option<Rc<DragGestureRecognizer>>,
+    vertical_drag: Option<Rc<DragGestureRecognizer>>,
+    pan: Option<Rc<DragGestureRecognizer>>,
+    scale: Option<Rc<ScaleGestureRecognizer>>,
+}
+
+/// Updates the recognizer in a slot, creating it with `create` if needed
+fn configure<R>(slot: &mut Option<Rc<R>>, create: impl FnOnce() -> R, update: impl FnOnce(R) -> R) {
+    let recognizer = slot.take().map_or_else(create, |recognizer| {
+        // Recognizers are only shared with arenas once events arrive
+        Rc::try_unwrap(recognizer).unwrap_or_else(|_| panic!("gesture detector configured while in use"))
+    });
+    *slot = Some(Rc::new(update(recognizer)));
+}
+
+impl GestureDetector {
+    /// Creates a GestureDetector around a child, recognizing nothing yet
+    pub fn new<W: Widget + 'static>(child: W) -> Self {
+        Self {
+            child: Box::new(child),
+            behavior: HitTestBehavior::DeferToChild,
+            settings: GestureSettings::default(),
+            tap: None,
+            double_tap: None,
+            long_press: None,
+            horizontal_drag: None,
+            vertical_drag: None,
+            pan: None,
+            scale: None,
+        }
+    }
+
+    /// Sets how the detector is hit
+    pub fn behavior(mut self, behavior: HitTestBehavior) -> Self {
+        self.behavior = behavior;
+        self
+    }
+
+    /// Sets all gesture thresholds
+    pub fn settings(mut self, settings: GestureSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Sets how far a pointer may move before it is no longer a tap or long
+    /// press and may start a drag
+    pub fn touch_slop(mut self, touch_slop: f32) -> Self {
+        self.settings.touch_slop = touch_slop;
+        self
+    }
+
+    /// Sets how long a pointer has to stay down to be a long press
+    pub fn long_press_delay(mut self, delay: Duration) -> Self {
+        self.settings.long_press_delay = delay;
+        self
+    }
+
+    /// Sets the callback for a pointer going down that may become a tap
+    pub fn on_tap_down(mut self, on_tap_down: impl Fn(TapDetails) + 'static) -> Self {
+        configure(&mut self.tap, TapGestureRecognizer::new, |tap| tap.on_tap_down(on_tap_down));
+        self
+    }
+
+    /// Sets the callback for a tap
+    pub fn on_tap(mut self, on_tap: impl Fn(TapDetails) + 'static) -> Self {
+        configure(&mut self.tap, TapGestureRecognizer::new, |tap| tap.on_tap(on_tap));
+        self
+    }
+
+    /// Sets the callback for a pointer reported to `on_tap_down` that did
+    /// not become a tap
+    pub fn on_tap_cancel(mut self, on_tap_cancel: impl Fn() + 'static) -> Self {
+        configure(&mut self.tap, TapGestureRecognizer::new, |tap| tap.on_tap_cancel(on_tap_cancel));
+        self
+    }
+
+    /// Sets the callback for a double tap
+    ///
+    /// A single tap is then only reported once the double-tap timeout has
+    /// passed.
+    pub fn on_double_tap(mut self, on_double_tap: impl Fn(TapDetails) + 'static) -> Self {
+        configure(&mut self.double_tap, DoubleTapGestureRecognizer::new, |double_tap| {
+            double_tap.on_double_tap(on_double_tap)
+        });
+        self
+    }
+
+    /// Sets the callback for a long press
+    pub fn on_long_press(mut self, on_long_press: impl Fn(LongPressDetails) + 'static) -> Self {
+        configure(&mut self.long_press, LongPressGestureRecognizer::new, |long_press| {
+            long_press.on_long_press(on_long_press)
+        });
+        self
+    }
+
+    /// Sets the callback for the pointer going up after a long press
+    pub fn on_long_press_end(mut self, on_long_press_end: impl Fn(LongPressDetails) + 'static) -> Self {
+        configure(&mut self.long_press, LongPressGestureRecognizer::new, |long_press| {
+            long_press.on_long_press_end(on_long_press_end)
+        });
+        self
+    }
+
+    /// Sets the callback for a horizontal drag starting
+    pub fn on_horizontal_drag_start(mut self, on_start: impl Fn(DragStartDetails) + 'static) -> Self {
+        configure(&mut self.horizontal_drag, horizontal, |drag| drag.on_start(on_start));
+        self
+    }
+
+    /// Sets the callback for a horizontal drag moving
+    pub fn on_horizontal_drag_update(mut self, on_update: impl Fn(DragUpdateDetails) + 'static) -> Self {
+        configure(&mut self.horizontal_drag, horizontal, |drag| drag.on_update(on_update));
+        self
+    }
+
+    /// Sets the callback for a horizontal drag ending
+    pub fn on_horizontal_drag_end(mut self, on_end: impl Fn(DragEndDetails) + 'static) -> Self {
+        configure(&mut self.horizontal_drag, horizontal, |drag| drag.on_end(on_end));
+        self
+    }
+
+    /// Sets the callback for a vertical drag starting
+    pub fn on_vertical_drag_start(mut self, on_start: impl Fn(DragStartDetails) + 'static) -> Self {
+        configure(&mut self.vertical_drag, vertical, |drag| drag.on_start(on_start));
+        self
+    }
+
+    /// Sets the callback for a vertical drag moving
+    pub fn on_vertical_drag_update(mut self, on_update: impl Fn(DragUpdateDetails) + 'static) -> Self {
+        configure(&mut self.vertical_drag, vertical, |drag| drag.on_update(on_update));
+        self
+    }
+
+    /// Sets the callback for a vertical drag ending
+    pub fn on_vertical_drag_end(mut self, on_end: impl Fn(DragEndDetails) + 'static) -> Self {
+        configure(&mut self.vertical_drag, vertical, |drag| drag.on_end(on_end));
+        self
+    }
+
+    /// Sets the callback for a pan starting
+    pub fn on_pan_start(mut self, on_start: impl Fn(DragStartDetails) + 'static) -> Self {
+        configure(&mut self.pan, pan, |drag| drag.on_start(on_start));
+        self
+    }
+
+    /// Sets the callback for a pan moving
+    pub fn on_pan_update(mut self, on_update: impl Fn(DragUpdateDetails) + 'static) -> Self {
+        configure(&mut self.pan, pan, |drag| drag.on_update(on_update));
+        self
+    }
+
+    /// Sets the callback for a pan ending
+    pub fn on_pan_end(mut self, on_end: impl Fn(DragEndDetails) + 'static) -> Self {
+        configure(&mut self.pan, pan, |drag| drag.on_end(on_end));
+        self
+    }
+
+    /// Sets the callback for a scale starting
+    pub fn on_scale_start(mut self, on_start: impl Fn(ScaleStartDetails) + 'static) -> Self {
+        configure(&mut self.scale, ScaleGestureRecognizer::new, |scale| scale.on_start(on_start));
+        self
+    }
+
+    /// Sets the callback for a scale changing
+    pub fn on_scale_update(mut self, on_update: impl Fn(ScaleUpdateDetails) + 'static) -> Self {
+        configure(&mut self.scale, ScaleGestureRecognizer::new, |scale| scale.on_update(on_update));
+        self
+    }
+
+    /// Sets the callback for a scale ending
+    pub fn on_scale_end(mut self, on_end: impl Fn(ScaleEndDetails) + 'static) -> Self {
+        configure(&mut self.scale, ScaleGestureRecognizer::new, |scale| scale.on_end(on_end));
+        self
+    }
+
+    /// Returns how the detector is hit
+    pub fn get_behavior(&self) -> HitTestBehavior {
+        self.behavior
+    }
+
+    /// Returns the gesture thresholds
+    pub fn get_settings(&self) -> GestureSettings {
+        self.settings
+    }
+
+    /// Returns the child
+    pub fn get_child(&self) -> &dyn Widget {
+        &*self.child
+    }
+
+    /// Returns the recognizers, in the order they join an arena
+    fn recognizers(&self) -> Vec<Rc<dyn GestureRecognizer>> {
+        let mut recognizers: Vec<Rc<dyn GestureRecognizer>> = Vec::new();
+        if let Some(tap) = &self.tap {
+            recognizers.push(tap.clone());
+        }
+        if let Some(double_tap) = &self.double_tap {
+            recognizers.push(double_tap.clone());
+        }
+        if let Some(long_press) = &self.long_press {
+            recognizers.push(long_press.clone());
+        }
+        for drag in [&self.horizontal_drag, &self.vertical_drag, &self.pan].into_iter().flatten() {
+            recognizers.push(drag.clone());
+        }
+        if let Some(scale) = &self.scale {
+            recognizers.push(scale.clone());
+        }
+        recognizers
+    }
+}
+
+fn horizontal() -> DragGestureRecognizer {
+    DragGestureRecognizer::new(DragAxis::Horizontal)
+}
+
+fn vertical() -> DragGestureRecognizer {
+    DragGestureRecognizer::new(DragAxis::Vertical)
+}
+
+fn pan() -> DragGestureRecognizer {
+    DragGestureRecognizer::new(DragAxis::Free)
+}
+
+impl Widget for GestureDetector {
+    fn widget_type(&self) -> &'static str {
+        "GestureDetector"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.child.layout(constraints)
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        self.child.paint(ctx, bounds);
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        visitor(&*self.child, bounds);
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        self.behavior
+    }
+
+    fn handle_event(&self, event: &Event, _bounds: Rect) -> EventResult {
+        let Event::Pointer(pointer) = event else {
+            return EventResult::Ignored;
+        };
+        for recognizer in self.recognizers() {
+            match pointer.phase {
+                PointerPhase::Down => {
+                    if recognizer.add_pointer(pointer, &self.settings) {
+                        gesture::add(pointer.id, recognizer.clone());
+                    }
+                }
+                PointerPhase::Move | PointerPhase::Up | PointerPhase::Cancel => recognizer.handle_event(pointer),
+                PointerPhase::Hover | PointerPhase::Scroll | PointerPhase::Exit => {}
+            }
+            if recognizer.deadline().is_some() {
+                gesture::schedule_deadline(recognizer);
+            }
+        }
+        // Left to the arena, so detectors further out get the event too
+        EventResult::Ignored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Text;
+    use kobalt_core::gesture::DEFAULT_DOUBLE_TAP_TIMEOUT;
+    use kobalt_core::testing::{Finder, WidgetTester};
+    use kobalt_core::types::Point;
+    use std::cell::{Cell, RefCell};
+
+    fn counter() -> (Rc<Cell<u32>>, impl Fn(TapDetails) + 'static) {
+        let count = Rc::new(Cell::new(0));
+        let increment = count.clone();
+        (count, move |_| increment.set(increment.get() + 1))
+    }
+
+    #[test]
+    fn test_tap_inside_vertical_drag() {
+        let (taps, on_tap) = counter();
+        let drags = Rc::new(RefCell::new(Vec::new()));
+        let recorded = drags.clone();
+        let scrollable = GestureDetector::new(GestureDetector::new(Text::new("Button")).on_tap(on_tap))
+            .behavior(HitTestBehavior::Opaque)
+            .on_vertical_drag_update(move |details| recorded.borrow_mut().push(details.delta.y));
+        let mut tester = WidgetTester::new(scrollable);
+        tester.pump();
+
+        // A tap goes to the button
+        tester.tap(&Finder::text("Button"));
+        assert_eq!(taps.get(), 1);
+        assert!(drags.borrow().is_empty());
+
+        // A drag goes to the scrollable
+        tester.drag(&Finder::text("Button"), Point::new(0.0, 100.0));
+        assert_eq!(taps.get(), 1);
+        assert_eq!(drags.borrow().iter().sum::<f32>(), 100.0);
+    }
+
+    #[test]
+    fn test_double_tap_and_tap() {
+        let (taps, on_tap) = counter();
+        let (double_taps, on_double_tap) = counter();
+        let detector = GestureDetector::new(Text::new("Photo"))
+            .on_tap(on_tap)
+            .on_double_tap(on_double_tap);
+        let mut tester = WidgetTester::new(detector);
+        tester.pump();
+
+        tester.tap(&Finder::text("Photo"));
+        tester.tap(&Finder::text("Photo"));
+        assert_eq!((taps.get(), double_taps.get()), (0, 1));
+
+        tester.tap(&Finder::text("Photo"));
+        tester.pump_by(DEFAULT_DOUBLE_TAP_TIMEOUT);
+        assert_eq!((taps.get(), double_taps.get()), (1, 1));
+    }
+
+    #[test]
+    fn test_long_press_with_configured_delay() {
+        let presses = Rc::new(Cell::new(0));
+        let (taps, on_tap) = counter();
+        let count = presses.clone();
+        let detector = GestureDetector::new(Text::new("Item"))
+            .long_press_delay(Duration::from_millis(200))
+            .on_tap(on_tap)
+            .on_long_press(move |_| count.set(count.get() + 1));
+        let mut tester = WidgetTester::new(detector);
+        tester.pump();
+
+        tester.long_press(&Finder::text("Item"));
+        assert_eq!((presses.get(), taps.get()), (1, 0));
+    }
+
+    #[test]
+    fn test_touch_slop_is_configurable() {
+        let (taps, on_tap) = counter();
+        let detector = GestureDetector::new(Text::new("Item")).touch_slop(2.0).on_tap(on_tap);
+        let mut tester = WidgetTester::new(detector);
+        tester.pump();
+
+        tester.drag(&Finder::text("Item"), Point::new(5.0, 0.0));
+        assert_eq!(taps.get(), 0);
+    }
+}
@@ -0,0 +1,110 @@
+//! Icon widget - a square, recolorable SVG
+
+use crate::svg::SvgDocument;
+use crate::svg_image::SvgImage;
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Color, Length, Rect, Size};
+use kobalt_core::widget::Widget;
+use std::sync::Arc;
+
+/// A square icon drawn from a single-color SVG
+///
+/// # Example
+///
+/// ```no_run
+/// use kobalt_widgets::{Color, Icon};
+///
+/// let search = Icon::asset("icons/search.svg").size(20.0).color(Color::WHITE);
+/// ```
+pub struct Icon {
+    svg: SvgImage,
+    size: Length,
+}
+
+impl Icon {
+    /// Default icon size
+    pub const DEFAULT_SIZE: Length = Length::Dp(24.0);
+
+    fn with_svg(svg: SvgImage) -> Self {
+        Self {
+            svg,
+            size: Self::DEFAULT_SIZE,
+        }
+    }
+
+    /// Creates an icon from SVG bytes
+    pub fn memory<B: Into<Arc<[u8]>>>(bytes: B) -> Self {
+        Self::with_svg(SvgImage::memory(bytes))
+    }
+
+    /// Creates an icon from an SVG in the asset bundle
+    pub fn asset(name: &str) -> Self {
+        Self::with_svg(SvgImage::asset(name))
+    }
+
+    /// Creates an icon from an already parsed document
+    pub fn from_document(document: Arc<SvgDocument>) -> Self {
+        Self::with_svg(SvgImage::from_document(document))
+    }
+
+    /// Sets the width and height of the icon
+    pub fn size(mut self, size: impl Into<Length>) -> Self {
+        self.size = size.into();
+        self
+    }
+
+    /// Sets the icon color
+    pub fn color(mut self, color: Color) -> Self {
+        self.svg = self.svg.color(color);
+        self
+    }
+
+    /// Returns the size
+    pub fn get_size(&self) -> Length {
+        self.size
+    }
+
+    /// Returns the color, if any
+    pub fn get_color(&self) -> Option<Color> {
+        self.svg.get_color()
+    }
+}
+
+impl Widget for Icon {
+    fn widget_type(&self) -> &'static str {
+        "Icon"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        let size = self.size.to_logical();
+        Size::new(size.min(constraints.width), size.min(constraints.height))
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        self.svg.paint(ctx, bounds);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kobalt_core::paint::DrawCommand;
+
+    #[test]
+    fn test_icon_is_square_and_recolored() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"><circle cx="12" cy="12" r="12" fill="#000"/></svg>"##;
+        let icon = Icon::memory(svg.as_bytes().to_vec()).size(16.0).color(Color::WHITE);
+        assert_eq!(icon.layout(Size::new(100.0, 100.0)), Size::new(16.0, 16.0));
+
+        let mut ctx = PaintContext::new();
+        icon.paint(&mut ctx, Rect::new(0.0, 0.0, 16.0, 16.0));
+        let list = ctx.finish();
+        match &list.commands()[0] {
+            DrawCommand::Image { image, .. } => {
+                let center = (8 * 16 + 8) * 4;
+                assert_eq!(&image.pixels()[center..center + 4], &[255, 255, 255, 255]);
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! IconButton widget - a round button showing an icon
+
+use crate::button::{button_widget, ButtonCore, ButtonStyle};
+use kobalt_core::widget::Widget;
+
+/// A round, transparent button around an icon, similar to Flutter's
+/// `IconButton`
+///
+/// The button is at least 40 by 40 so that small icons stay easy to hit.
+/// As an icon has no text, give the button a tooltip-like description
+/// with the `Semantics` widget.
+///
+/// # Example
+///
+/// ```no_run
+/// use kobalt_widgets::{Icon, IconButton};
+///
+/// let search = IconButton::new(Icon::asset("icons/search.svg")).on_pressed(|| println!("search"));
+/// ```
+pub struct IconButton {
+    core: ButtonCore,
+}
+
+impl IconButton {
+    /// Creates a disabled IconButton around an icon or any other child
+    pub fn new<W: Widget + 'static>(icon: W) -> Self {
+        Self {
+            core: ButtonCore::new(Box::new(icon), ButtonStyle::icon()),
+        }
+    }
+}
+
+button_widget!(IconButton);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Text;
+    use kobalt_core::testing::{Finder, WidgetTester};
+    use kobalt_core::types::{Rect, Size};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_minimum_size() {
+        let button = IconButton::new(Text::new("x").size(10.0));
+        assert_eq!(button.layout(Size::new(500.0, 500.0)), Size::square(40.0));
+        // The child is centered
+        let bounds = button.core.child_bounds(Rect::new(0.0, 0.0, 40.0, 40.0));
+        assert_eq!(bounds.center(), kobalt_core::types::Point::new(20.0, 20.0));
+    }
+
+    #[test]
+    fn test_long_press_only_enables() {
+        let long_presses = Rc::new(Cell::new(0));
+        let count = long_presses.clone();
+        let button = IconButton::new(Text::new("x")).on_long_press(move || count.set(count.get() + 1));
+        assert!(button.is_enabled());
+        let mut tester = WidgetTester::new(button);
+        tester.pump();
+
+        tester.tap(&Finder::text("x"));
+        tester.long_press(&Finder::text("x"));
+        assert_eq!(long_presses.get(), 1);
+    }
+}
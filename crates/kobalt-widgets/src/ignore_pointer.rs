@@ -0,0 +1,99 @@
+//! IgnorePointer widget - hides its child from hit testing
+
+use kobalt_core::hit_test::{self, HitTestBehavior, HitTestResult};
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Point, Rect, Size};
+use kobalt_core::widget::Widget;
+
+/// A widget that makes its child invisible to pointers, similar to
+/// Flutter's `IgnorePointer`
+///
+/// Pointer events pass through to whatever is painted behind the child.
+/// The child is still laid out and painted as usual.
+pub struct IgnorePointer {
+    ignoring: bool,
+    child: Box<dyn Widget>,
+}
+
+impl IgnorePointer {
+    /// Creates an IgnorePointer around a child, ignoring pointers
+    pub fn new<W: Widget + 'static>(child: W) -> Self {
+        Self {
+            ignoring: true,
+            child: Box::new(child),
+        }
+    }
+
+    /// Sets whether pointers are ignored
+    pub fn ignoring(mut self, ignoring: bool) -> Self {
+        self.ignoring = ignoring;
+        self
+    }
+
+    /// Returns true if pointers are ignored
+    pub fn is_ignoring(&self) -> bool {
+        self.ignoring
+    }
+
+    /// Returns the child
+    pub fn get_child(&self) -> &dyn Widget {
+        &*self.child
+    }
+}
+
+impl Widget for IgnorePointer {
+    fn widget_type(&self) -> &'static str {
+        "IgnorePointer"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.child.layout(constraints)
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        self.child.paint(ctx, bounds);
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        visitor(&*self.child, bounds);
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        HitTestBehavior::DeferToChild
+    }
+
+    fn hit_test(&self, result: &mut HitTestResult, bounds: Rect, position: Point) -> bool {
+        !self.ignoring && hit_test::hit_test_widget(self, result, bounds, position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Stack, Text};
+    use kobalt_core::testing::WidgetTester;
+
+    #[test]
+    fn test_ignored_child_lets_pointers_through() {
+        let stack = Stack::new()
+            .add(Text::new("Behind"))
+            .add(IgnorePointer::new(Text::new("Front")));
+        let mut tester = WidgetTester::new(stack);
+        tester.pump();
+
+        let paths = tester.hit_test(Point::new(1.0, 1.0)).paths();
+        assert_eq!(paths, vec![vec![0], vec![]]);
+    }
+
+    #[test]
+    fn test_not_ignoring_hits_child() {
+        let stack = Stack::new()
+            .add(Text::new("Behind"))
+            .add(IgnorePointer::new(Text::new("Front")).ignoring(false));
+        let mut tester = WidgetTester::new(stack);
+        tester.pump();
+
+        let paths = tester.hit_test(Point::new(1.0, 1.0)).paths();
+        assert_eq!(paths, vec![vec![1, 0], vec![1], vec![]]);
+    }
+}
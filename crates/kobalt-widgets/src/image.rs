@@ -0,0 +1,274 @@
+//! Image widget - displays decoded raster images
+
+use crate::image_cache::{resolve_image, resolve_image_for_layout, ImageSource, ImageState};
+use kobalt_core::image::ImageData;
+use kobalt_core::layout::{Alignment, BoxFit};
+use kobalt_core::layout_cache::LayoutCache;
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Color, Length, Rect, Size};
+use kobalt_core::widget::Widget;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Where the widget gets its pixels from
+enum Provider {
+    Source(ImageSource),
+    Data(ImageData),
+}
+
+/// A widget that displays an image
+///
+/// Encoded images (PNG, JPEG, WebP) are decoded on a background thread and
+/// shared through the global `ImageCache`; until decoding finishes the widget
+/// paints nothing. The image is fitted into the widget's bounds with `fit`
+/// and positioned with `alignment`.
+///
+/// Without an explicit `width`/`height` the widget takes the image's
+/// natural size, scaled down to fit the space offered by its parent.
+///
+/// # Example
+///
+/// ```no_run
+/// use kobalt_core::layout::BoxFit;
+/// use kobalt_widgets::{Color, Image};
+///
+/// let logo = Image::asset("images/logo.png")
+///     .width(120.0)
+///     .height(120.0)
+///     .fit(BoxFit::Cover)
+///     .tint(Color::from_rgb8(200, 220, 255));
+/// ```
+pub struct Image {
+    provider: Provider,
+    width: Option<Length>,
+    height: Option<Length>,
+    fit: BoxFit,
+    alignment: Alignment,
+    tint: Option<Color>,
+    /// Marked when the image finishes loading
+    layout_cache: LayoutCache,
+}
+
+impl Image {
+    fn with_provider(provider: Provider) -> Self {
+        Self {
+            provider,
+            width: None,
+            height: None,
+            fit: BoxFit::default(),
+            alignment: Alignment::default(),
+            tint: None,
+            layout_cache: LayoutCache::new(),
+        }
+    }
+
+    /// Creates an image from encoded bytes (e.g. from `include_bytes!`)
+    pub fn memory<B: Into<Arc<[u8]>>>(bytes: B) -> Self {
+        Self::with_provider(Provider::Source(ImageSource::memory(bytes)))
+    }
+
+    /// Creates an image from a file on disk
+    pub fn file<P: Into<PathBuf>>(path: P) -> Self {
+        Self::with_provider(Provider::Source(ImageSource::file(path)))
+    }
+
+    /// Creates an image from the asset bundle
+    pub fn asset(name: &str) -> Self {
+        Self::with_provider(Provider::Source(ImageSource::asset(name)))
+    }
+
+    /// Creates an image from already decoded pixels
+    pub fn from_data(image: ImageData) -> Self {
+        Self::with_provider(Provider::Data(image))
+    }
+
+    /// Sets the width of the widget
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.width = Some(width.into());
+        self
+    }
+
+    /// Sets the height of the widget
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.height = Some(height.into());
+        self
+    }
+
+    /// Sets how the image is fitted into the widget's bounds
+    pub fn fit(mut self, fit: BoxFit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Sets where the image is placed within the widget's bounds
+    pub fn alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Multiplies every pixel by the given color
+    pub fn tint(mut self, color: Color) -> Self {
+        self.tint = Some(color);
+        self
+    }
+
+    /// Returns the fit
+    pub fn get_fit(&self) -> BoxFit {
+        self.fit
+    }
+
+    /// Returns the alignment
+    pub fn get_alignment(&self) -> Alignment {
+        self.alignment
+    }
+
+    /// Returns the tint, if any
+    pub fn get_tint(&self) -> Option<Color> {
+        self.tint
+    }
+
+    /// Returns the decoded image, or None while it is loading or if it failed
+    pub fn image(&self) -> Option<ImageData> {
+        match &self.provider {
+            Provider::Data(image) => Some(image.clone()),
+            Provider::Source(source) => match resolve_image(source) {
+                ImageState::Ready(image) => Some(image),
+                ImageState::Loading | ImageState::Failed(_) => None,
+            },
+        }
+    }
+
+    /// Computes the size for `constraints` from the requested and natural
+    /// sizes
+    fn compute_layout(&self, constraints: Size) -> Size {
+        let natural = match &self.provider {
+            Provider::Data(image) => Some(image.size()),
+            Provider::Source(source) => match resolve_image_for_layout(source, &self.layout_cache.handle()) {
+                ImageState::Ready(image) => Some(image.size()),
+                ImageState::Loading | ImageState::Failed(_) => None,
+            },
+        }
+        .filter(|size| !size.is_empty());
+
+        let width = self.width.map(|width| width.to_logical());
+        let height = self.height.map(|height| height.to_logical());
+        let size = match (width, height, natural) {
+            (Some(width), Some(height), _) => Size::new(width, height),
+            (Some(width), None, Some(natural)) => {
+                Size::new(width, width * natural.height / natural.width)
+            }
+            (None, Some(height), Some(natural)) => {
+                Size::new(height * natural.width / natural.height, height)
+            }
+            (None, None, Some(natural)) => natural,
+            (width, height, None) => Size::new(width.unwrap_or(0.0), height.unwrap_or(0.0)),
+        };
+
+        // Scale down uniformly to fit the constraints
+        let scale = (constraints.width / size.width)
+            .min(constraints.height / size.height)
+            .min(1.0);
+        if scale.is_finite() && scale < 1.0 {
+            Size::new(size.width * scale, size.height * scale)
+        } else {
+            Size::new(size.width.min(constraints.width), size.height.min(constraints.height))
+        }
+    }
+}
+
+impl Widget for Image {
+    fn widget_type(&self) -> &'static str {
+        "Image"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.layout_cache.layout(constraints, |constraints| self.compute_layout(constraints))
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        let Some(image) = self.image() else {
+            return;
+        };
+        let image_rect = Rect::new(0.0, 0.0, image.size().width, image.size().height);
+        let fitted = self.fit.apply(image.size(), bounds.size());
+        let src = self.alignment.inscribe(fitted.source, image_rect);
+        // Fitting crops the source instead of overflowing, so no clip is needed
+        let dst = self.alignment.inscribe(fitted.destination, bounds);
+        ctx.draw_image(&image, src, dst, self.tint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kobalt_core::paint::DrawCommand;
+
+    fn pixels(width: u32, height: u32) -> ImageData {
+        ImageData::from_rgba8(width, height, vec![255; (width * height * 4) as usize]).unwrap()
+    }
+
+    #[test]
+    fn test_image_layout_uses_natural_size_and_aspect() {
+        let image = Image::from_data(pixels(200, 100));
+        assert_eq!(image.layout(Size::new(1000.0, 1000.0)), Size::new(200.0, 100.0));
+        assert_eq!(image.layout(Size::new(100.0, 1000.0)), Size::new(100.0, 50.0));
+
+        let image = Image::from_data(pixels(200, 100)).width(50.0);
+        assert_eq!(image.layout(Size::new(1000.0, 1000.0)), Size::new(50.0, 25.0));
+    }
+
+    #[test]
+    fn test_image_contain_centers_image() {
+        let data = pixels(200, 100);
+        let image = Image::from_data(data.clone()).fit(BoxFit::Contain);
+
+        let mut ctx = PaintContext::new();
+        image.paint(&mut ctx, Rect::new(0.0, 0.0, 100.0, 100.0));
+        let list = ctx.finish();
+
+        assert_eq!(
+            list.commands(),
+            &[DrawCommand::Image {
+                image: data,
+                src: Rect::new(0.0, 0.0, 200.0, 100.0),
+                dst: Rect::new(0.0, 25.0, 100.0, 50.0),
+                tint: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_image_none_fit_crops_source() {
+        let image = Image::from_data(pixels(200, 200))
+            .fit(BoxFit::None)
+            .alignment(Alignment::TOP_LEFT);
+
+        let mut ctx = PaintContext::new();
+        image.paint(&mut ctx, Rect::new(10.0, 10.0, 50.0, 50.0));
+        let list = ctx.finish();
+
+        match &list.commands()[0] {
+            DrawCommand::Image { src, dst, .. } => {
+                assert_eq!(*src, Rect::new(0.0, 0.0, 50.0, 50.0));
+                assert_eq!(*dst, Rect::new(10.0, 10.0, 50.0, 50.0));
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_image_cover_crops_source() {
+        let image = Image::from_data(pixels(200, 100)).fit(BoxFit::Cover);
+        let mut ctx = PaintContext::new();
+        image.paint(&mut ctx, Rect::new(0.0, 0.0, 100.0, 100.0));
+        let list = ctx.finish();
+
+        match &list.commands()[0] {
+            DrawCommand::Image { src, dst, .. } => {
+                assert_eq!(*src, Rect::new(50.0, 0.0, 100.0, 100.0));
+                assert_eq!(*dst, Rect::new(0.0, 0.0, 100.0, 100.0));
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,478 @@
+//! Image loading, decoding and the shared decoded-image cache
+//!
+//! Images are decoded on a small pool of background threads so that large
+//! PNG/JPEG/WebP files never stall a frame; if the threads cannot be
+//! started, images are decoded synchronously instead. Decoded images are
+//! shared through a global, size-bounded `ImageCache`: every widget showing
+//! the same source uses the same pixels (and therefore the same GPU
+//! texture).
+//! A finished decode is posted to the scheduler of the thread that asked for
+//! the image and stored when its next frame starts, marking the layouts of
+//! the widgets waiting for the image on that thread.
+
+use kobalt_core::assets;
+use kobalt_core::scheduler::{self, FrameScheduler};
+use kobalt_core::image::ImageData;
+use kobalt_core::layout_cache::LayoutHandle;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Identifies an image source in the cache
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum ImageKey {
+    /// Hash and length of in-memory encoded bytes
+    Memory(u64, usize),
+    File(PathBuf),
+    Asset(String),
+}
+
+/// Where an image's encoded bytes come from
+#[derive(Debug, Clone)]
+pub struct ImageSource {
+    key: ImageKey,
+    bytes: Option<Arc<[u8]>>,
+}
+
+impl ImageSource {
+    /// Encoded image bytes held in memory (e.g. from `include_bytes!`)
+    pub fn memory<B: Into<Arc<[u8]>>>(bytes: B) -> Self {
+        let bytes: Arc<[u8]> = bytes.into();
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        Self {
+            key: ImageKey::Memory(hasher.finish(), bytes.len()),
+            bytes: Some(bytes),
+        }
+    }
+
+    /// An image file on disk
+    pub fn file<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            key: ImageKey::File(path.into()),
+            bytes: None,
+        }
+    }
+
+    /// An image from the asset bundle (see `kobalt_core::assets`)
+    pub fn asset(name: &str) -> Self {
+        Self {
+            key: ImageKey::Asset(name.to_string()),
+            bytes: None,
+        }
+    }
+
+    /// Returns the cache key of this source
+    pub(crate) fn key(&self) -> &ImageKey {
+        &self.key
+    }
+
+    /// Reads the encoded bytes
+    pub(crate) fn load(&self) -> Result<Vec<u8>, String> {
+        match (&self.key, &self.bytes) {
+            (_, Some(bytes)) => Ok(bytes.to_vec()),
+            (ImageKey::File(path), None) => {
+                std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))
+            }
+            (ImageKey::Asset(name), None) => assets::load(name).map_err(|e| format!("{}: {}", name, e)),
+            (ImageKey::Memory(..), None) => unreachable!("memory sources always carry bytes"),
+        }
+    }
+}
+
+/// Decodes PNG, JPEG or WebP bytes into RGBA8 pixels
+pub fn decode_image(bytes: &[u8]) -> Result<ImageData, String> {
+    let decoded = image::load_from_memory(bytes).map_err(|e| e.to_string())?;
+    let rgba = decoded.into_rgba8();
+    let (width, height) = rgba.dimensions();
+    ImageData::from_rgba8(width, height, rgba.into_raw())
+        .ok_or_else(|| "decoder returned a truncated image".to_string())
+}
+
+/// A source to decode in the background
+struct Decode {
+    source: ImageSource,
+    /// The scheduler of the thread that asked for the image
+    owner: Arc<FrameScheduler>,
+}
+
+/// Loading state of a cached image
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageState {
+    /// Still being read or decoded in the background
+    Loading,
+    /// Decoded and ready to draw
+    Ready(ImageData),
+    /// Reading or decoding failed
+    Failed(String),
+}
+
+struct Entry {
+    state: ImageState,
+    last_used: u64,
+    /// Layouts to mark when a loading image is stored
+    waiters: Vec<LayoutHandle>,
+}
+
+/// A size-bounded cache of decoded images
+///
+/// When the decoded pixels exceed `max_bytes`, the least recently used
+/// images are dropped and will be decoded again when next shown. Failed
+/// loads are remembered (and not retried) until `clear` is called.
+pub struct ImageCache {
+    entries: HashMap<ImageKey, Entry>,
+    max_bytes: usize,
+    used_bytes: usize,
+    clock: u64,
+}
+
+impl ImageCache {
+    /// Default budget for decoded pixels (128 MiB)
+    pub const DEFAULT_MAX_BYTES: usize = 128 * 1024 * 1024;
+
+    /// Creates an empty cache holding at most `max_bytes` of pixels
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            max_bytes,
+            used_bytes: 0,
+            clock: 0,
+        }
+    }
+
+    /// Returns the cache shared by all `Image` widgets
+    pub fn global() -> &'static Mutex<ImageCache> {
+        static CACHE: OnceLock<Mutex<ImageCache>> = OnceLock::new();
+        CACHE.get_or_init(|| Mutex::new(ImageCache::new(Self::DEFAULT_MAX_BYTES)))
+    }
+
+    /// Returns the pixel budget
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Sets the pixel budget, evicting images if needed
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+        self.evict(None);
+    }
+
+    /// Returns the bytes used by decoded images
+    pub fn used_bytes(&self) -> usize {
+        self.used_bytes
+    }
+
+    /// Returns the number of entries (including loading and failed ones)
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes every entry
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.used_bytes = 0;
+    }
+
+    /// Returns the state of a source, marking it as recently used
+    pub fn get(&mut self, source: &ImageSource) -> Option<ImageState> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.entries.get_mut(&source.key).map(|entry| {
+            entry.last_used = clock;
+            entry.state.clone()
+        })
+    }
+
+    /// Stores the result of loading a source
+    pub fn insert(&mut self, source: &ImageSource, result: Result<ImageData, String>) {
+        self.store(source.key.clone(), result);
+    }
+
+    fn store(&mut self, key: ImageKey, result: Result<ImageData, String>) {
+        let state = match result {
+            Ok(image) => {
+                self.used_bytes += image.byte_len();
+                ImageState::Ready(image)
+            }
+            Err(error) => ImageState::Failed(error),
+        };
+        self.clock += 1;
+        let previous = self.entries.insert(
+            key.clone(),
+            Entry {
+                state,
+                last_used: self.clock,
+                waiters: Vec::new(),
+            },
+        );
+        if let Some(previous) = previous {
+            if let ImageState::Ready(image) = previous.state {
+                self.used_bytes -= image.byte_len();
+            }
+            // The image's natural size is now known, so the widgets may resize
+            for waiter in previous.waiters {
+                waiter.mark_needs_layout();
+            }
+        }
+        self.evict(Some(&key));
+    }
+
+    /// Drops least recently used images until within budget, sparing `keep`
+    fn evict(&mut self, keep: Option<&ImageKey>) {
+        while self.used_bytes > self.max_bytes {
+            let victim = self
+                .entries
+                .iter()
+                .filter(|(key, entry)| Some(*key) != keep && matches!(entry.state, ImageState::Ready(_)))
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            let Some(victim) = victim else {
+                break;
+            };
+            if let Some(Entry {
+                state: ImageState::Ready(image),
+                ..
+            }) = self.entries.remove(&victim)
+            {
+                self.used_bytes -= image.byte_len();
+            }
+        }
+    }
+}
+
+/// Returns the state of a source in the global cache, starting a background
+/// decode the first time it is requested
+///
+/// If no decoder thread could be started, the source is decoded right away
+/// on the calling thread instead.
+pub fn resolve_image(source: &ImageSource) -> ImageState {
+    resolve(source, None)
+}
+
+/// Like `resolve_image`, but marks `waiter` as needing layout once the image
+/// has loaded
+pub(crate) fn resolve_image_for_layout(source: &ImageSource, waiter: &LayoutHandle) -> ImageState {
+    resolve(source, Some(waiter))
+}
+
+fn resolve(source: &ImageSource, waiter: Option<&LayoutHandle>) -> ImageState {
+    let mut cache = ImageCache::global().lock().unwrap();
+    if let Some(state) = cache.get(source) {
+        if let (ImageState::Loading, Some(waiter)) = (&state, waiter) {
+            let waiters = &mut cache.entries.get_mut(&source.key).unwrap().waiters;
+            if !waiters.contains(waiter) {
+                waiters.push(waiter.clone());
+            }
+        }
+        return state;
+    }
+    let last_used = cache.clock;
+    cache.entries.insert(
+        source.key.clone(),
+        Entry {
+            state: ImageState::Loading,
+            last_used,
+            waiters: waiter.into_iter().cloned().collect(),
+        },
+    );
+    drop(cache);
+
+    let decode = Decode {
+        source: source.clone(),
+        owner: scheduler::current(),
+    };
+    let queued = decoder().is_some_and(|queue| queue.lock().unwrap().send(decode).is_ok());
+    if queued {
+        return ImageState::Loading;
+    }
+    // No decoder thread is running, so decode on this thread instead
+    let result = load_and_decode(source);
+    let state = match &result {
+        Ok(image) => ImageState::Ready(image.clone()),
+        Err(error) => ImageState::Failed(error.clone()),
+    };
+    ImageCache::global().lock().unwrap().store(source.key.clone(), result);
+    state
+}
+
+/// Returns the queue feeding the background decoder threads, or None if
+/// none of them could be started
+fn decoder() -> Option<&'static Mutex<Sender<Decode>>> {
+    static QUEUE: OnceLock<Option<Mutex<Sender<Decode>>>> = OnceLock::new();
+    QUEUE
+        .get_or_init(|| {
+            let (sender, receiver) = mpsc::channel::<Decode>();
+            let receiver = Arc::new(Mutex::new(receiver));
+            let workers = std::thread::available_parallelism()
+                .map(|n| n.get().clamp(1, 4))
+                .unwrap_or(2);
+            let mut spawned = 0;
+            for index in 0..workers {
+                let receiver = receiver.clone();
+                let thread = std::thread::Builder::new()
+                    .name(format!("kobalt-image-decoder-{}", index))
+                    .spawn(move || decode_worker(receiver));
+                match thread {
+                    Ok(_) => spawned += 1,
+                    Err(error) => eprintln!("Warning: failed to spawn image decoder thread: {}", error),
+                }
+            }
+            (spawned > 0).then(|| Mutex::new(sender))
+        })
+        .as_ref()
+}
+
+/// Reads and decodes a source, logging failures
+fn load_and_decode(source: &ImageSource) -> Result<ImageData, String> {
+    let result = source.load().and_then(|bytes| decode_image(&bytes));
+    if let Err(error) = &result {
+        eprintln!("Warning: failed to load image: {}", error);
+    }
+    result
+}
+
+fn decode_worker(receiver: Arc<Mutex<Receiver<Decode>>>) {
+    loop {
+        let Decode { source, owner } = match receiver.lock().unwrap().recv() {
+            Ok(decode) => decode,
+            Err(_) => return,
+        };
+        let result = load_and_decode(&source);
+        // Waiting layouts and frame requests belong to the requesting thread
+        owner.post(move || {
+            ImageCache::global().lock().unwrap().store(source.key, result);
+            // Widgets that only paint the image still have to show it
+            scheduler::request_paint();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32) -> ImageData {
+        ImageData::from_rgba8(width, height, vec![255; (width * height * 4) as usize]).unwrap()
+    }
+
+    fn png_bytes() -> Vec<u8> {
+        png(3, 2)
+    }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let pixels = image::RgbaImage::from_pixel(width, height, image::Rgba([10, 20, 30, 255]));
+        pixels
+            .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_decode_png() {
+        let decoded = decode_image(&png_bytes()).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (3, 2));
+        assert_eq!(&decoded.pixels()[..4], &[10, 20, 30, 255]);
+    }
+
+    #[test]
+    fn test_decode_garbage_fails() {
+        assert!(decode_image(b"not an image").is_err());
+    }
+
+    #[test]
+    fn test_memory_sources_share_key_by_content() {
+        let a = ImageSource::memory(png_bytes());
+        let b = ImageSource::memory(png_bytes());
+        assert_eq!(a.key, b.key);
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        // Each 10x10 image is 400 bytes
+        let mut cache = ImageCache::new(1000);
+        let a = ImageSource::asset("a.png");
+        let b = ImageSource::asset("b.png");
+        let c = ImageSource::asset("c.png");
+
+        cache.insert(&a, Ok(image(10, 10)));
+        cache.insert(&b, Ok(image(10, 10)));
+        cache.get(&a);
+        cache.insert(&c, Ok(image(10, 10)));
+
+        assert!(cache.get(&a).is_some());
+        assert!(cache.get(&b).is_none());
+        assert!(cache.get(&c).is_some());
+        assert_eq!(cache.used_bytes(), 800);
+    }
+
+    #[test]
+    fn test_cache_remembers_failures() {
+        let mut cache = ImageCache::new(1000);
+        let source = ImageSource::file("missing.png");
+        cache.insert(&source, Err("missing".to_string()));
+        assert_eq!(cache.get(&source), Some(ImageState::Failed("missing".to_string())));
+        assert_eq!(cache.used_bytes(), 0);
+    }
+
+    /// Polls `done` until it returns true or about five seconds have passed
+    fn wait_until(mut done: impl FnMut() -> bool) {
+        for _ in 0..500 {
+            if done() {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    /// Gives the test a scheduler of its own, so parallel tests do not run
+    /// each other's decodes
+    fn own_scheduler() -> Arc<FrameScheduler> {
+        let scheduler = Arc::new(FrameScheduler::new());
+        scheduler::set_thread_scheduler(Some(scheduler.clone()));
+        scheduler
+    }
+
+    #[test]
+    fn test_resolve_decodes_in_background() {
+        own_scheduler();
+        let source = ImageSource::memory(png_bytes());
+        let mut state = resolve_image(&source);
+        wait_until(|| {
+            scheduler::run_posted();
+            state = resolve_image(&source);
+            state != ImageState::Loading
+        });
+        assert!(matches!(state, ImageState::Ready(ref image) if image.width() == 3));
+        scheduler::set_thread_scheduler(None);
+    }
+
+    #[test]
+    fn test_decode_marks_waiting_layouts() {
+        use kobalt_core::layout_cache::LayoutCache;
+        use kobalt_core::types::Size;
+
+        let scheduler = own_scheduler();
+        let cache = LayoutCache::new();
+        cache.layout(Size::new(100.0, 100.0), |constraints| constraints);
+        let handle = cache.handle();
+        let source = ImageSource::memory(png(5, 4));
+        assert_eq!(resolve_image_for_layout(&source, &handle), ImageState::Loading);
+
+        // Nothing changes until this thread runs the posted decode
+        wait_until(|| scheduler.has_pending_frame());
+        assert!(!handle.needs_layout());
+        scheduler::run_posted();
+        assert!(handle.needs_layout());
+        assert!(matches!(resolve_image(&source), ImageState::Ready(ref image) if image.width() == 5));
+        scheduler::set_thread_scheduler(None);
+    }
+}
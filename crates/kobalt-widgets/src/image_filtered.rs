@@ -0,0 +1,93 @@
+//! ImageFiltered widget - applies an image filter to its child
+
+use kobalt_core::paint::{ImageFilter, PaintContext};
+use kobalt_core::types::{Rect, Size};
+use kobalt_core::hit_test::HitTestBehavior;
+use kobalt_core::widget::Widget;
+
+/// A widget that applies an image filter to its child's rendered output
+///
+/// The child is rendered into an offscreen layer, filtered, and composited
+/// back, similar to Flutter's `ImageFiltered`.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_core::paint::{ColorMatrix, ImageFilter};
+/// use kobalt_widgets::{ImageFiltered, Text};
+///
+/// let disabled = ImageFiltered::new(
+///     ImageFilter::color_matrix(ColorMatrix::grayscale()),
+///     Text::new("Unavailable"),
+/// );
+/// ```
+pub struct ImageFiltered {
+    filter: ImageFilter,
+    child: Box<dyn Widget>,
+}
+
+impl ImageFiltered {
+    /// Creates a new ImageFiltered with the given filter and child
+    pub fn new<W: Widget + 'static>(filter: ImageFilter, child: W) -> Self {
+        Self {
+            filter,
+            child: Box::new(child),
+        }
+    }
+
+    /// Returns the filter
+    pub fn filter(&self) -> &ImageFilter {
+        &self.filter
+    }
+
+    /// Returns the child
+    pub fn get_child(&self) -> &dyn Widget {
+        &*self.child
+    }
+}
+
+impl Widget for ImageFiltered {
+    fn widget_type(&self) -> &'static str {
+        "ImageFiltered"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.child.layout(constraints)
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        ctx.push_layer(bounds, self.filter.clone());
+        self.child.paint(ctx, bounds);
+        ctx.pop_layer();
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        visitor(&*self.child, bounds);
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        HitTestBehavior::DeferToChild
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Text;
+    use kobalt_core::paint::DrawCommand;
+
+    #[test]
+    fn test_image_filtered_wraps_child_in_layer() {
+        let widget = ImageFiltered::new(ImageFilter::blur(2.0, 2.0), Text::new("Blurry"));
+        let bounds = Rect::new(10.0, 10.0, 100.0, 20.0);
+
+        let mut ctx = PaintContext::new();
+        widget.paint(&mut ctx, bounds);
+        let list = ctx.finish();
+
+        assert_eq!(list.len(), 3);
+        assert!(matches!(list.commands()[0], DrawCommand::PushLayer { .. }));
+        assert!(matches!(list.commands()[1], DrawCommand::Text { .. }));
+        assert_eq!(list.commands()[2], DrawCommand::PopLayer);
+    }
+}
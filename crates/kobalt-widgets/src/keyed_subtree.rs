@@ -0,0 +1,83 @@
+//! KeyedSubtree widget - attaches a key to its child
+
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Rect, Size};
+use kobalt_core::hit_test::HitTestBehavior;
+use kobalt_core::widget::{Key, Widget};
+
+/// A widget that identifies its child with a `Key`, similar to Flutter's
+/// `KeyedSubtree`
+///
+/// # Example
+///
+/// ```
+/// use kobalt_widgets::{KeyedSubtree, Text};
+///
+/// let total = KeyedSubtree::new("cart-total", Text::new("$42.00"));
+/// ```
+pub struct KeyedSubtree {
+    key: Key,
+    child: Box<dyn Widget>,
+}
+
+impl KeyedSubtree {
+    /// Creates a KeyedSubtree with the given key and child
+    pub fn new<W: Widget + 'static>(key: impl Into<Key>, child: W) -> Self {
+        Self {
+            key: key.into(),
+            child: Box::new(child),
+        }
+    }
+
+    /// Returns the child
+    pub fn get_child(&self) -> &dyn Widget {
+        &*self.child
+    }
+}
+
+impl Widget for KeyedSubtree {
+    fn widget_type(&self) -> &'static str {
+        "KeyedSubtree"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.child.layout(constraints)
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        self.child.paint(ctx, bounds);
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        visitor(&*self.child, bounds);
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        HitTestBehavior::DeferToChild
+    }
+
+    fn key(&self) -> Option<&Key> {
+        Some(&self.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Column, Text};
+    use kobalt_core::testing::{Finder, WidgetTester};
+
+    #[test]
+    fn test_keyed_subtree_is_found_by_key() {
+        let column = Column::new()
+            .add(Text::new("Total"))
+            .add(KeyedSubtree::new("total", Text::new("$42.00")));
+        let mut tester = WidgetTester::new(column);
+        tester.pump();
+
+        let found = tester.find(&Finder::key("total"));
+        assert_eq!(found.widget.widget_type(), "KeyedSubtree");
+        assert_eq!(found.bounds, tester.bounds(&Finder::text("$42.00")));
+        assert!(found.bounds.y >= tester.bounds(&Finder::text("Total")).bottom());
+    }
+}
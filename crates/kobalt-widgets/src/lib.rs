@@ -6,11 +6,77 @@
 
 mod text;
 mod column;
+mod backdrop_filter;
+mod image_filtered;
+mod custom_paint;
+mod image;
+mod image_cache;
+mod svg;
+mod svg_image;
+mod icon;
+mod repaint_boundary;
+mod semantics;
+mod keyed_subtree;
+mod stack;
+mod ignore_pointer;
+mod absorb_pointer;
+mod gesture_detector;
+mod focus;
+mod focus_scope;
+mod shortcuts;
+mod actions;
+mod mouse_region;
+mod button;
+mod text_button;
+mod outlined_button;
+mod icon_button;
 
 pub use text::{Text, TextStyle};
 pub use column::Column;
+pub use backdrop_filter::BackdropFilter;
+pub use image_filtered::ImageFiltered;
+pub use custom_paint::{CustomPaint, CustomPainter};
+pub use image::Image;
+pub use image_cache::{decode_image, resolve_image, ImageCache, ImageSource, ImageState};
+pub use svg::{GradientStop, SpreadMethod, SvgDocument, SvgPaint, SvgShape};
+pub use svg_image::SvgImage;
+pub use icon::Icon;
+pub use repaint_boundary::RepaintBoundary;
+pub use semantics::Semantics;
+pub use keyed_subtree::KeyedSubtree;
+pub use stack::Stack;
+pub use ignore_pointer::IgnorePointer;
+pub use absorb_pointer::AbsorbPointer;
+pub use gesture_detector::GestureDetector;
+pub use focus::Focus;
+pub use focus_scope::FocusScope;
+pub use shortcuts::Shortcuts;
+pub use actions::Actions;
+pub use mouse_region::MouseRegion;
+pub use button::{Button, ButtonStyle};
+pub use text_button::TextButton;
+pub use outlined_button::OutlinedButton;
+pub use icon_button::IconButton;
 
 // Re-export core types for convenience
-pub use kobalt_core::types::{Color, Point, Rect, Size};
-pub use kobalt_core::widget::{Constraints, Widget};
-pub use kobalt_core::layout::{MainAxisAlignment, CrossAxisAlignment, EdgeInsets};
+pub use kobalt_core::types::{Color, Length, Point, Rect, Size, Units};
+pub use kobalt_core::media_query::{MediaQuery, MediaQueryData};
+pub use kobalt_core::widget::{Constraints, Key, Widget, WidgetState};
+pub use kobalt_core::hit_test::{HitTestBehavior, HitTestResult};
+pub use kobalt_core::gesture::GestureSettings;
+pub use kobalt_core::focus::FocusNode;
+pub use kobalt_core::mouse::MouseCursor;
+pub use kobalt_core::shortcuts::{
+    Action, ActionMap, ActivateIntent, DismissIntent, Intent, KeyChord, NextFocusIntent, PreviousFocusIntent, ShortcutMap,
+};
+pub use kobalt_core::recognizer::{
+    DragEndDetails, DragStartDetails, DragUpdateDetails, LongPressDetails, ScaleEndDetails, ScaleStartDetails,
+    ScaleUpdateDetails, TapDetails,
+};
+pub use kobalt_core::event::{EventResult, KeyEvent, KeyState, LogicalKey, Modifiers, PhysicalKey, PointerButtons, PointerEvent, PointerKind, PointerPhase};
+pub use kobalt_core::layout::{MainAxisAlignment, CrossAxisAlignment, EdgeInsets, Alignment, BoxFit};
+pub use kobalt_core::paint::{ColorMatrix, ImageFilter, Paint, PaintStyle};
+pub use kobalt_core::path::{FillRule, Path, StrokeCap, StrokeJoin, StrokeStyle};
+pub use kobalt_core::canvas::Canvas;
+pub use kobalt_core::image::ImageData;
+pub use kobalt_core::repaint::RepaintHandle;
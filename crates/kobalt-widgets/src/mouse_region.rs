@@ -0,0 +1,217 @@
+//! MouseRegion widget - tracks the mouse over its child
+
+use kobalt_core::event::{Event, EventResult, PointerEvent, PointerPhase};
+use kobalt_core::hit_test::HitTestBehavior;
+use kobalt_core::mouse::{MouseCursor, MouseTarget};
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Rect, Size};
+use kobalt_core::widget::Widget;
+
+/// Called with a pointer event in the region's coordinates, except for
+/// enter and exit events, which are in window coordinates
+type PointerCallback = Box<dyn Fn(&PointerEvent)>;
+
+/// A widget that calls back when a mouse enters, moves over or leaves its
+/// child and sets the cursor shown over it, similar to Flutter's
+/// `MouseRegion`
+///
+/// Regions inside each other are all entered; the innermost region with a
+/// cursor decides it. By default the region covers its whole area and
+/// hides the widgets behind it from the mouse; use `behavior` to change
+/// that. Touches do not hover, so only mice and styluses are tracked.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_core::mouse::MouseCursor;
+/// use kobalt_widgets::{MouseRegion, Text};
+///
+/// let link = MouseRegion::new(Text::new("Docs"))
+///     .cursor(MouseCursor::Click)
+///     .on_enter(|_| println!("highlight"))
+///     .on_exit(|_| println!("unhighlight"));
+/// ```
+pub struct MouseRegion {
+    child: Box<dyn Widget>,
+    behavior: HitTestBehavior,
+    cursor: Option<MouseCursor>,
+    on_enter: Option<PointerCallback>,
+    on_hover: Option<PointerCallback>,
+    on_exit: Option<PointerCallback>,
+}
+
+impl MouseRegion {
+    /// Creates a MouseRegion around a child, leaving the cursor to the
+    /// widgets around it
+    pub fn new<W: Widget + 'static>(child: W) -> Self {
+        Self {
+            child: Box::new(child),
+            behavior: HitTestBehavior::Opaque,
+            cursor: None,
+            on_enter: None,
+            on_hover: None,
+            on_exit: None,
+        }
+    }
+
+    /// Sets the cursor shown over the region
+    pub fn cursor(mut self, cursor: MouseCursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// Sets how the region is hit; `Translucent` lets the widgets behind
+    /// it be hovered as well
+    pub fn behavior(mut self, behavior: HitTestBehavior) -> Self {
+        self.behavior = behavior;
+        self
+    }
+
+    /// Sets the callback for a mouse moving onto the region
+    pub fn on_enter(mut self, on_enter: impl Fn(&PointerEvent) + 'static) -> Self {
+        self.on_enter = Some(Box::new(on_enter));
+        self
+    }
+
+    /// Sets the callback for a mouse moving over the region with no button
+    /// pressed
+    pub fn on_hover(mut self, on_hover: impl Fn(&PointerEvent) + 'static) -> Self {
+        self.on_hover = Some(Box::new(on_hover));
+        self
+    }
+
+    /// Sets the callback for a mouse leaving the region
+    pub fn on_exit(mut self, on_exit: impl Fn(&PointerEvent) + 'static) -> Self {
+        self.on_exit = Some(Box::new(on_exit));
+        self
+    }
+
+    /// Returns the cursor shown over the region, if it sets one
+    pub fn get_cursor(&self) -> Option<MouseCursor> {
+        self.cursor
+    }
+
+    /// Returns the child
+    pub fn get_child(&self) -> &dyn Widget {
+        &*self.child
+    }
+}
+
+impl Widget for MouseRegion {
+    fn widget_type(&self) -> &'static str {
+        "MouseRegion"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.child.layout(constraints)
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        self.child.paint(ctx, bounds);
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        visitor(&*self.child, bounds);
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        self.behavior
+    }
+
+    fn handle_event(&self, event: &Event, _bounds: Rect) -> EventResult {
+        if let (Event::Pointer(pointer), Some(on_hover)) = (event, &self.on_hover)
+            && pointer.phase == PointerPhase::Hover
+        {
+            on_hover(pointer);
+        }
+        // Regions further out hear about the hover as well
+        EventResult::Ignored
+    }
+
+    fn mouse_target(&self) -> Option<&dyn MouseTarget> {
+        Some(self)
+    }
+}
+
+impl MouseTarget for MouseRegion {
+    fn cursor(&self) -> Option<MouseCursor> {
+        self.cursor
+    }
+
+    fn on_enter(&self, event: &PointerEvent) {
+        if let Some(on_enter) = &self.on_enter {
+            on_enter(event);
+        }
+    }
+
+    fn on_exit(&self, event: &PointerEvent) {
+        if let Some(on_exit) = &self.on_exit {
+            on_exit(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Column, IgnorePointer, Stack, Text};
+    use kobalt_core::testing::{Finder, WidgetTester};
+    use kobalt_core::types::Point;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_enter_hover_exit() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let (enter, hover, exit) = (log.clone(), log.clone(), log.clone());
+        let column = Column::new()
+            .add(
+                MouseRegion::new(Text::new("Link"))
+                    .cursor(MouseCursor::Click)
+                    .on_enter(move |_| enter.borrow_mut().push("enter"))
+                    .on_hover(move |_| hover.borrow_mut().push("hover"))
+                    .on_exit(move |_| exit.borrow_mut().push("exit")),
+            )
+            .add(Text::new("Plain"));
+        let mut tester = WidgetTester::new(column);
+        tester.pump();
+
+        tester.hover(&Finder::text("Link"));
+        assert_eq!(tester.cursor(), MouseCursor::Click);
+        tester.hover(&Finder::text("Plain"));
+        assert_eq!(tester.cursor(), MouseCursor::Basic);
+        assert_eq!(*log.borrow(), vec!["enter", "hover", "exit"]);
+
+        tester.hover(&Finder::text("Link"));
+        tester.mouse_exit();
+        assert_eq!(log.borrow()[3..], ["enter", "hover", "exit"]);
+    }
+
+    #[test]
+    fn test_opaque_region_hides_regions_behind() {
+        let entered = Rc::new(RefCell::new(Vec::new()));
+        // The texts are not hit, so the regions decide on their own
+        let region = |name: &'static str, behavior| {
+            let entered = entered.clone();
+            MouseRegion::new(IgnorePointer::new(Text::new(name)))
+                .behavior(behavior)
+                .on_enter(move |_| entered.borrow_mut().push(name))
+        };
+        let stack = Stack::new()
+            .add(region("Behind", HitTestBehavior::Opaque))
+            .add(region("Front", HitTestBehavior::Opaque));
+        let mut tester = WidgetTester::new(stack);
+        tester.pump();
+        tester.hover_at(Point::new(1.0, 1.0));
+        assert_eq!(*entered.borrow(), vec!["Front"]);
+
+        let stack = Stack::new()
+            .add(region("Behind", HitTestBehavior::Opaque))
+            .add(region("Front", HitTestBehavior::Translucent));
+        let mut tester = WidgetTester::new(stack);
+        tester.pump();
+        entered.borrow_mut().clear();
+        tester.hover_at(Point::new(1.0, 1.0));
+        assert_eq!(*entered.borrow(), vec!["Behind", "Front"]);
+    }
+}
@@ -0,0 +1,72 @@
+//! OutlinedButton widget - a button inside an outline
+
+use crate::button::{button_widget, ButtonCore, ButtonStyle};
+use kobalt_core::widget::Widget;
+
+/// A button drawn as an outline around its label, similar to Flutter's
+/// `OutlinedButton`
+///
+/// Use it for actions that matter but are not the main one, e.g. next to
+/// a filled `Button`. The outline fades with the content while disabled.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_widgets::OutlinedButton;
+///
+/// let details = OutlinedButton::label("Details").on_pressed(|| println!("details"));
+/// ```
+pub struct OutlinedButton {
+    core: ButtonCore,
+}
+
+impl OutlinedButton {
+    /// Creates a disabled OutlinedButton around a child
+    pub fn new<W: Widget + 'static>(child: W) -> Self {
+        Self {
+            core: ButtonCore::new(Box::new(child), ButtonStyle::outlined()),
+        }
+    }
+
+    /// Creates a disabled OutlinedButton showing a label
+    pub fn label(label: impl Into<String>) -> Self {
+        Self {
+            core: ButtonCore::label(label.into(), ButtonStyle::outlined()),
+        }
+    }
+}
+
+button_widget!(OutlinedButton);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kobalt_core::paint::DrawCommand;
+    use kobalt_core::testing::WidgetTester;
+    use kobalt_core::types::Color;
+
+    /// Returns the outline colors and the number of layers painted
+    fn paint(button: OutlinedButton) -> (Vec<Color>, usize) {
+        let mut tester = WidgetTester::new(button);
+        tester.pump();
+        let commands = tester.display_list().unwrap().commands();
+        let outlines = commands
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Path { paint, .. } if !paint.is_fill() => Some(paint.color),
+                _ => None,
+            })
+            .collect();
+        let layers = commands.iter().filter(|command| matches!(command, DrawCommand::PushLayer { .. })).count();
+        (outlines, layers)
+    }
+
+    #[test]
+    fn test_outline_follows_state() {
+        let style = ButtonStyle::outlined();
+        assert_eq!(paint(OutlinedButton::label("Details").on_pressed(|| {})), (vec![style.border.unwrap()], 0));
+
+        // Disabled: a faint outline and faded content
+        assert_eq!(paint(OutlinedButton::label("Details")), (vec![Color::WHITE.with_alpha(0.12)], 1));
+    }
+}
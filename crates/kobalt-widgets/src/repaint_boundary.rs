@@ -0,0 +1,265 @@
+//! RepaintBoundary widget - caches its child's painting
+
+use kobalt_core::layout_cache::LayoutCache;
+use kobalt_core::paint::{DisplayList, PaintContext};
+use kobalt_core::repaint::{self, RepaintHandle};
+use kobalt_core::types::{Rect, Size};
+use kobalt_core::hit_test::HitTestBehavior;
+use kobalt_core::widget::Widget;
+use std::cell::{Cell, RefCell};
+use std::sync::Arc;
+
+/// The child's last recording
+struct Recorded {
+    epoch: u64,
+    layouts: u64,
+    size: Size,
+    version: u64,
+    list: Arc<DisplayList>,
+}
+
+/// A widget that records its child once and reuses the result
+///
+/// The child is painted again only when the boundary's `RepaintHandle` is
+/// marked, a layout inside it is marked, its size changes, or all
+/// boundaries are invalidated; the renderer keeps the rasterized output in
+/// a texture in the meantime. Paint requests made by the child while it
+/// paints or handles events mark the handle (see `kobalt_core::repaint`). Wrap subtrees that rarely change (headers,
+/// backgrounds) or that change independently of the rest of the screen,
+/// similar to Flutter's `RepaintBoundary`.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_widgets::{RepaintBoundary, Text};
+///
+/// let header = RepaintBoundary::new(Text::new("Inbox"));
+/// let handle = header.handle();
+///
+/// // Later, when the header's content changes:
+/// handle.mark_needs_paint();
+/// ```
+pub struct RepaintBoundary {
+    child: Box<dyn Widget>,
+    handle: RepaintHandle,
+    recorded: RefCell<Option<Recorded>>,
+    /// Marked along with the layouts of descendants
+    layout_cache: LayoutCache,
+    /// How many times the subtree has been laid out
+    layouts: Cell<u64>,
+}
+
+impl RepaintBoundary {
+    /// Creates a repaint boundary around a child
+    pub fn new<W: Widget + 'static>(child: W) -> Self {
+        Self {
+            child: Box::new(child),
+            handle: RepaintHandle::new(),
+            recorded: RefCell::new(None),
+            layout_cache: LayoutCache::new(),
+            layouts: Cell::new(0),
+        }
+    }
+
+    /// Returns a handle that marks this boundary as needing a repaint
+    pub fn handle(&self) -> RepaintHandle {
+        self.handle.clone()
+    }
+
+    /// Returns the child
+    pub fn get_child(&self) -> &dyn Widget {
+        &*self.child
+    }
+}
+
+impl Widget for RepaintBoundary {
+    fn widget_type(&self) -> &'static str {
+        "RepaintBoundary"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.layout_cache.layout(constraints, |constraints| {
+            self.layouts.set(self.layouts.get() + 1);
+            self.child.layout(constraints)
+        })
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        let size = bounds.size();
+        self.handle.set_parent(repaint::current_boundary());
+        let epoch = repaint::epoch();
+        let marked = self.handle.take_needs_paint() || self.layout_cache.handle().needs_layout();
+        let layouts = self.layouts.get();
+        let mut recorded = self.recorded.borrow_mut();
+
+        let stale = marked
+            || recorded.as_ref().is_none_or(|recorded| {
+                recorded.epoch != epoch || recorded.layouts != layouts || recorded.size != size
+            });
+        if stale {
+            // Record at the origin so the result can be moved without repainting
+            let mut child_ctx = PaintContext::new();
+            repaint::with_boundary(Some(self.handle.clone()), || {
+                self.child
+                    .paint(&mut child_ctx, Rect::new(0.0, 0.0, size.width, size.height));
+            });
+            *recorded = Some(Recorded {
+                epoch,
+                layouts: self.layouts.get(),
+                size,
+                version: repaint::next_version(),
+                list: Arc::new(child_ctx.finish()),
+            });
+        }
+
+        let recorded = recorded.as_ref().unwrap();
+        ctx.draw_repaint_boundary(self.handle.id(), recorded.version, bounds, recorded.list.clone());
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        visitor(&*self.child, bounds);
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        HitTestBehavior::DeferToChild
+    }
+
+    fn repaint_handle(&self) -> Option<&RepaintHandle> {
+        Some(&self.handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Column, Text};
+    use kobalt_core::event::{Event, EventResult, PointerPhase};
+    use kobalt_core::paint::DrawCommand;
+    use kobalt_core::scheduler;
+    use kobalt_core::testing::{Finder, WidgetTester};
+    use kobalt_core::types::Color;
+
+    fn paint(boundary: &RepaintBoundary, bounds: Rect) -> (u64, u64, Rect, Arc<DisplayList>) {
+        let mut ctx = PaintContext::new();
+        boundary.paint(&mut ctx, bounds);
+        match ctx.finish().commands() {
+            [DrawCommand::RepaintBoundary { id, version, rect, list }] => (*id, *version, *rect, list.clone()),
+            other => panic!("unexpected commands {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_records_child_at_origin() {
+        let boundary = RepaintBoundary::new(Text::new("Header"));
+        let (id, _, rect, list) = paint(&boundary, Rect::new(10.0, 20.0, 100.0, 30.0));
+
+        assert_eq!(id, boundary.handle().id());
+        assert_eq!(rect, Rect::new(10.0, 20.0, 100.0, 30.0));
+        match &list.commands()[0] {
+            DrawCommand::Text { position, .. } => assert_eq!((position.x, position.y), (0.0, 0.0)),
+            other => panic!("unexpected command {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_moving_reuses_recording() {
+        let boundary = RepaintBoundary::new(Text::new("Header"));
+        let (_, first, _, list) = paint(&boundary, Rect::new(0.0, 0.0, 100.0, 30.0));
+        let (_, second, _, moved) = paint(&boundary, Rect::new(0.0, 50.0, 100.0, 30.0));
+
+        assert_eq!(first, second);
+        assert!(Arc::ptr_eq(&list, &moved));
+    }
+
+    #[test]
+    fn test_mark_needs_paint_records_again() {
+        let boundary = RepaintBoundary::new(Text::new("Header"));
+        let bounds = Rect::new(0.0, 0.0, 100.0, 30.0);
+        let (_, first, _, _) = paint(&boundary, bounds);
+
+        boundary.handle().mark_needs_paint();
+        let (_, second, _, _) = paint(&boundary, bounds);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_marked_layout_records_again() {
+        let child = Column::new().add(Text::new("Header"));
+        let handle = child.layout_cache().handle();
+        let boundary = RepaintBoundary::new(child);
+        let bounds = Rect::new(0.0, 0.0, 100.0, 30.0);
+        boundary.layout(bounds.size());
+        let (_, first, _, _) = paint(&boundary, bounds);
+        let (_, second, _, _) = paint(&boundary, bounds);
+        assert_eq!(first, second);
+
+        // Whether or not the boundary is laid out again before painting
+        handle.mark_needs_layout();
+        let (_, third, _, _) = paint(&boundary, bounds);
+        assert_ne!(second, third);
+        handle.mark_needs_layout();
+        boundary.layout(bounds.size());
+        let (_, fourth, _, _) = paint(&boundary, bounds);
+        assert_ne!(third, fourth);
+    }
+
+    /// Turns red when pressed, requesting only a paint
+    struct Toggle(Cell<bool>);
+
+    impl Widget for Toggle {
+        fn widget_type(&self) -> &'static str {
+            "Toggle"
+        }
+
+        fn layout(&self, constraints: Size) -> Size {
+            constraints
+        }
+
+        fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+            ctx.draw_rect(bounds, if self.0.get() { Color::RED } else { Color::WHITE });
+        }
+
+        fn handle_event(&self, event: &Event, _bounds: Rect) -> EventResult {
+            match event {
+                Event::Pointer(pointer) if pointer.phase == PointerPhase::Down => {
+                    self.0.set(true);
+                    scheduler::request_paint();
+                    EventResult::Handled
+                }
+                _ => EventResult::Ignored,
+            }
+        }
+    }
+
+    /// Returns the commands recorded by the innermost boundary of a frame
+    fn innermost(list: &DisplayList) -> Vec<DrawCommand> {
+        match list.commands() {
+            [DrawCommand::RepaintBoundary { list, .. }] => innermost(list),
+            commands => commands.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_paint_requests_from_the_child_record_again() {
+        // Nested, so the outer recording must pick up the inner one's new
+        // version too
+        let tree = RepaintBoundary::new(RepaintBoundary::new(Toggle(Cell::new(false))));
+        let mut tester = WidgetTester::new(tree);
+        tester.pump();
+        let before = innermost(tester.display_list().unwrap());
+
+        tester.tap(&Finder::widget_type("Toggle"));
+        tester.pump();
+        let after = innermost(tester.display_list().unwrap());
+        assert_ne!(before, after);
+        assert!(matches!(after[..], [DrawCommand::Rect { color: Color::RED, .. }]));
+    }
+
+    #[test]
+    fn test_resize_records_again() {
+        let boundary = RepaintBoundary::new(Text::new("Header"));
+        let (_, first, _, _) = paint(&boundary, Rect::new(0.0, 0.0, 100.0, 30.0));
+        let (_, second, _, _) = paint(&boundary, Rect::new(0.0, 0.0, 120.0, 30.0));
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,93 @@
+//! Semantics widget - describes its child for assistive technologies
+
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Rect, Size};
+use kobalt_core::hit_test::HitTestBehavior;
+use kobalt_core::widget::Widget;
+
+/// A widget that labels its child, similar to Flutter's `Semantics`
+///
+/// The label describes the child to assistive technologies and lets tests
+/// find it with `Finder::semantics_label`. It does not change how the child
+/// looks.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_widgets::{Icon, Semantics};
+///
+/// let search = Semantics::new(Icon::asset("search.svg")).label("Search");
+/// ```
+pub struct Semantics {
+    label: Option<String>,
+    child: Box<dyn Widget>,
+}
+
+impl Semantics {
+    /// Creates a Semantics widget around a child
+    pub fn new<W: Widget + 'static>(child: W) -> Self {
+        Self {
+            label: None,
+            child: Box::new(child),
+        }
+    }
+
+    /// Sets the label
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Returns the label, if any
+    pub fn get_label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Returns the child
+    pub fn get_child(&self) -> &dyn Widget {
+        &*self.child
+    }
+}
+
+impl Widget for Semantics {
+    fn widget_type(&self) -> &'static str {
+        "Semantics"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.child.layout(constraints)
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        self.child.paint(ctx, bounds);
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        visitor(&*self.child, bounds);
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        HitTestBehavior::DeferToChild
+    }
+
+    fn semantics_label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Text;
+    use kobalt_core::testing::{Finder, WidgetTester};
+
+    #[test]
+    fn test_semantics_label_is_found_with_child_bounds() {
+        let mut tester = WidgetTester::new(Semantics::new(Text::new("OK")).label("Confirm"));
+        tester.pump();
+
+        let found = tester.find(&Finder::semantics_label("Confirm"));
+        assert_eq!(found.widget.widget_type(), "Semantics");
+        assert_eq!(found.bounds, tester.bounds(&Finder::text("OK")));
+    }
+}
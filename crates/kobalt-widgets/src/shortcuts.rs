@@ -0,0 +1,157 @@
+//! Shortcuts widget - binds key chords to intents for its subtree
+
+use kobalt_core::hit_test::HitTestBehavior;
+use kobalt_core::paint::PaintContext;
+use kobalt_core::shortcuts::{Intent, KeyChord, ShortcutMap};
+use kobalt_core::types::{Rect, Size};
+use kobalt_core::widget::Widget;
+
+/// A widget binding key chords to intents while the focus is inside its
+/// child, similar to Flutter's `Shortcuts`
+///
+/// The intents are carried out by the `Actions` around the focused widget
+/// (see `kobalt_core::shortcuts`).
+///
+/// # Example
+/// ```
+/// use kobalt_core::shortcuts::{Intent, KeyChord};
+/// use kobalt_widgets::{Actions, Shortcuts, Text};
+///
+/// #[derive(Debug)]
+/// struct SaveIntent;
+/// impl Intent for SaveIntent {}
+///
+/// let editor = Shortcuts::new(
+///     Actions::new(Text::new("Document")).on(|_: &SaveIntent| println!("saving")),
+/// )
+/// .bind(KeyChord::character('s').primary(), SaveIntent);
+/// ```
+pub struct Shortcuts {
+    shortcuts: ShortcutMap,
+    child: Box<dyn Widget>,
+}
+
+impl Shortcuts {
+    /// Creates a Shortcuts around a child, with no bindings
+    pub fn new<W: Widget + 'static>(child: W) -> Self {
+        Self::with_map(child, ShortcutMap::new())
+    }
+
+    /// Creates a Shortcuts around a child with a prepared keybinding map
+    pub fn with_map<W: Widget + 'static>(child: W, shortcuts: ShortcutMap) -> Self {
+        Self {
+            shortcuts,
+            child: Box::new(child),
+        }
+    }
+
+    /// Binds a chord to an intent
+    pub fn bind(mut self, chord: impl Into<KeyChord>, intent: impl Intent) -> Self {
+        self.shortcuts = self.shortcuts.bind(chord, intent);
+        self
+    }
+
+    /// Returns the bindings
+    pub fn get_shortcuts(&self) -> &ShortcutMap {
+        &self.shortcuts
+    }
+
+    /// Returns the child
+    pub fn get_child(&self) -> &dyn Widget {
+        &*self.child
+    }
+}
+
+impl Widget for Shortcuts {
+    fn widget_type(&self) -> &'static str {
+        "Shortcuts"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.child.layout(constraints)
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        self.child.paint(ctx, bounds);
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        visitor(&*self.child, bounds);
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        HitTestBehavior::DeferToChild
+    }
+
+    fn shortcuts(&self) -> Option<&ShortcutMap> {
+        Some(&self.shortcuts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Actions, Column, Focus, Text};
+    use kobalt_core::event::{LogicalKey, Modifiers};
+    use kobalt_core::focus::FocusNode;
+    use kobalt_core::shortcuts::DismissIntent;
+    use kobalt_core::testing::WidgetTester;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Debug)]
+    struct SaveIntent;
+
+    impl Intent for SaveIntent {}
+
+    #[test]
+    fn test_shortcut_invokes_action_around_focus() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let (saves, dismissals) = (log.clone(), log.clone());
+        let field = FocusNode::new();
+        let app = Shortcuts::new(
+            Actions::new(
+                Column::new()
+                    .add(Focus::with_node(Text::new("Field"), field.clone()))
+                    .add(Actions::new(Focus::new(Text::new("Dialog"))).on(move |_: &DismissIntent| {
+                        dismissals.borrow_mut().push("dismiss")
+                    })),
+            )
+            .on(move |_: &SaveIntent| saves.borrow_mut().push("save")),
+        )
+        .bind(KeyChord::character('s').primary(), SaveIntent);
+        let mut tester = WidgetTester::new(app);
+        tester.pump();
+
+        // Nothing focused: only the root sees the key, and the actions are
+        // below it
+        let save = LogicalKey::Character("s".to_string());
+        assert!(!tester.press_key_with_modifiers(save.clone(), Modifiers::PRIMARY).is_handled());
+        field.request_focus();
+        assert!(tester.press_key_with_modifiers(save, Modifiers::PRIMARY).is_handled());
+
+        // Escape only dismisses while the dialog is focused
+        assert!(!tester.press_key(LogicalKey::Escape).is_handled());
+        tester.press_key(LogicalKey::Tab);
+        assert!(tester.press_key(LogicalKey::Escape).is_handled());
+        assert_eq!(*log.borrow(), vec!["save", "dismiss"]);
+    }
+
+    #[test]
+    fn test_unhandled_intent_keeps_bubbling() {
+        let tabs = Rc::new(RefCell::new(0));
+        let count = tabs.clone();
+        let app = Focus::new(
+            Shortcuts::new(Focus::new(Text::new("Field")).autofocus(true)).bind(LogicalKey::Tab, SaveIntent),
+        )
+        .on_key(move |_| {
+            *count.borrow_mut() += 1;
+            kobalt_core::event::EventResult::Handled
+        });
+        let mut tester = WidgetTester::new(app);
+        tester.pump();
+
+        assert!(tester.press_key(LogicalKey::Tab).is_handled());
+        assert_eq!(*tabs.borrow(), 2);
+    }
+}
@@ -0,0 +1,154 @@
+//! Stack layout widget - overlays its children
+
+use kobalt_core::hit_test::HitTestBehavior;
+use kobalt_core::layout::Alignment;
+use kobalt_core::layout_cache::LayoutCache;
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Rect, Size};
+use kobalt_core::widget::Widget;
+
+/// A layout widget that places its children on top of each other
+///
+/// Children are laid out with the Stack's constraints as their maximum and
+/// positioned by the alignment. They are painted in order, so the last child
+/// is on top and is also the first to be hit by pointers.
+pub struct Stack {
+    children: Vec<Box<dyn Widget>>,
+    alignment: Alignment,
+    layout_cache: LayoutCache<Vec<Size>>,
+}
+
+impl Stack {
+    /// Creates a new empty Stack
+    pub fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            alignment: Alignment::TOP_LEFT,
+            layout_cache: LayoutCache::new(),
+        }
+    }
+
+    /// Adds a child on top of the existing ones
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<W: Widget + 'static>(mut self, widget: W) -> Self {
+        self.children.push(Box::new(widget));
+        self
+    }
+
+    /// Sets how children smaller than the Stack are positioned
+    pub fn alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Returns the children, bottom first
+    pub fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    /// Returns the alignment
+    pub fn get_alignment(&self) -> Alignment {
+        self.alignment
+    }
+
+    /// Computes the rectangle of each child inside the given bounds
+    pub fn child_rects(&self, bounds: Rect) -> Vec<Rect> {
+        // Reuse the child sizes from layout if it produced these bounds
+        let sizes = self
+            .layout_cache
+            .data_for_size(bounds.size())
+            .filter(|sizes| sizes.len() == self.children.len())
+            .unwrap_or_else(|| self.children.iter().map(|child| child.layout(bounds.size())).collect());
+
+        sizes
+            .into_iter()
+            .map(|size| self.alignment.inscribe(size, bounds))
+            .collect()
+    }
+}
+
+impl Default for Stack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for Stack {
+    fn widget_type(&self) -> &'static str {
+        "Stack"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        self.layout_cache.layout_with(constraints, |constraints| {
+            let sizes: Vec<Size> = self.children.iter().map(|child| child.layout(constraints)).collect();
+
+            // As large as the largest child
+            let size = sizes.iter().fold(Size::new(0.0, 0.0), |size, child| {
+                Size::new(size.width.max(child.width), size.height.max(child.height))
+            });
+            (size, sizes)
+        })
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        for (child, rect) in self.children.iter().zip(self.child_rects(bounds)) {
+            child.paint(ctx, rect);
+        }
+    }
+
+    fn visit_children<'a>(&'a self, bounds: Rect, visitor: &mut dyn FnMut(&'a dyn Widget, Rect)) {
+        for (child, rect) in self.children.iter().zip(self.child_rects(bounds)) {
+            visitor(&**child, rect);
+        }
+    }
+
+    fn hit_test_behavior(&self) -> HitTestBehavior {
+        HitTestBehavior::DeferToChild
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kobalt_core::hit_test::hit_test;
+    use kobalt_core::types::Point;
+
+    /// A fixed-size box that is hit inside its bounds
+    struct Block(Size);
+
+    impl Widget for Block {
+        fn widget_type(&self) -> &'static str {
+            "Block"
+        }
+
+        fn layout(&self, _constraints: Size) -> Size {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_stack_sizes_to_largest_child_and_aligns() {
+        let stack = Stack::new()
+            .add(Block(Size::new(100.0, 40.0)))
+            .add(Block(Size::new(20.0, 80.0)))
+            .alignment(Alignment::CENTER);
+        assert_eq!(stack.layout(Size::new(400.0, 300.0)), Size::new(100.0, 80.0));
+
+        let rects = stack.child_rects(Rect::new(0.0, 0.0, 100.0, 80.0));
+        assert_eq!(rects[0], Rect::new(0.0, 20.0, 100.0, 40.0));
+        assert_eq!(rects[1], Rect::new(40.0, 0.0, 20.0, 80.0));
+    }
+
+    #[test]
+    fn test_last_child_is_hit_first() {
+        let stack = Stack::new()
+            .add(Block(Size::new(100.0, 100.0)))
+            .add(Block(Size::new(50.0, 50.0)));
+        let bounds = Rect::new(0.0, 0.0, 100.0, 100.0);
+        stack.layout(bounds.size());
+
+        assert_eq!(hit_test(&stack, bounds, Point::new(10.0, 10.0)).paths(), vec![vec![1], vec![]]);
+        assert_eq!(hit_test(&stack, bounds, Point::new(80.0, 80.0)).paths(), vec![vec![0], vec![]]);
+        assert!(hit_test(&stack, bounds, Point::new(120.0, 10.0)).is_empty());
+    }
+}
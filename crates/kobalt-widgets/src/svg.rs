@@ -0,0 +1,605 @@
+//! SVG documents: parsing into vector paths and cached rasterization
+//!
+//! SVG files are parsed with usvg, which resolves CSS, `use` references,
+//! units and shapes, and converted into `kobalt_core::path::Path`s with
+//! solid or gradient paints. Documents are rasterized on the CPU at the
+//! exact pixel size they are drawn at, so they stay sharp at any size, and
+//! the rasters are cached so grids of identical icons cost one upload.
+//!
+//! Clip paths, masks, filters, patterns, embedded images and text are not
+//! supported and are skipped.
+
+use crate::image_cache::{ImageKey, ImageSource};
+use kobalt_core::image::ImageData;
+use kobalt_core::path::{FillRule, Path, PathCommand, StrokeCap, StrokeJoin, StrokeStyle};
+use kobalt_core::types::{Color, Point, Size, Transform};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+static NEXT_DOCUMENT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// How a gradient continues outside its start and end
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpreadMethod {
+    Pad,
+    Reflect,
+    Repeat,
+}
+
+/// A color stop of a gradient
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    /// Position along the gradient, from 0 to 1
+    pub offset: f32,
+    pub color: Color,
+}
+
+/// How an SVG shape is filled or stroked
+#[derive(Debug, Clone, PartialEq)]
+pub enum SvgPaint {
+    Solid(Color),
+    LinearGradient {
+        start: Point,
+        end: Point,
+        stops: Vec<GradientStop>,
+        spread: SpreadMethod,
+        /// Gradient space to shape space
+        transform: Transform,
+    },
+    RadialGradient {
+        center: Point,
+        radius: f32,
+        focal: Point,
+        focal_radius: f32,
+        stops: Vec<GradientStop>,
+        spread: SpreadMethod,
+        /// Gradient space to shape space
+        transform: Transform,
+    },
+}
+
+impl SvgPaint {
+    /// Returns this paint with every color replaced by `color`, keeping alpha
+    fn recolored(&self, color: Color) -> SvgPaint {
+        let recolor = |c: Color| Color::new(color.r, color.g, color.b, c.a * color.a);
+        let mut paint = self.clone();
+        match &mut paint {
+            SvgPaint::Solid(c) => *c = recolor(*c),
+            SvgPaint::LinearGradient { stops, .. } | SvgPaint::RadialGradient { stops, .. } => {
+                for stop in stops {
+                    stop.color = recolor(stop.color);
+                }
+            }
+        }
+        paint
+    }
+
+    fn colors(&self) -> Vec<Color> {
+        match self {
+            SvgPaint::Solid(color) => vec![*color],
+            SvgPaint::LinearGradient { stops, .. } | SvgPaint::RadialGradient { stops, .. } => {
+                stops.iter().map(|stop| stop.color).collect()
+            }
+        }
+    }
+}
+
+/// A filled and/or stroked path of an SVG document
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgShape {
+    /// Outline in the shape's own coordinates
+    pub path: Path,
+    /// Shape space to document space
+    pub transform: Transform,
+    pub fill: Option<SvgPaint>,
+    pub stroke: Option<(SvgPaint, StrokeStyle)>,
+}
+
+/// A parsed SVG document
+#[derive(Debug)]
+pub struct SvgDocument {
+    id: u64,
+    size: Size,
+    shapes: Vec<SvgShape>,
+}
+
+impl SvgDocument {
+    /// Parses an SVG (or SVGZ) document
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        let tree = usvg::Tree::from_data(data, &usvg::Options::default()).map_err(|e| e.to_string())?;
+        let mut shapes = Vec::new();
+        collect_shapes(tree.root(), 1.0, &mut shapes);
+        Ok(Self {
+            id: NEXT_DOCUMENT_ID.fetch_add(1, Ordering::Relaxed),
+            size: Size::new(tree.size().width(), tree.size().height()),
+            shapes,
+        })
+    }
+
+    /// Returns the unique id of this document
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the intrinsic size of the document
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the shapes in painting order
+    pub fn shapes(&self) -> &[SvgShape] {
+        &self.shapes
+    }
+
+    /// Returns true if every paint in the document uses the same color
+    /// (ignoring alpha), i.e. the document is a recolorable icon
+    pub fn is_single_color(&self) -> bool {
+        let mut colors = self
+            .shapes
+            .iter()
+            .flat_map(|shape| shape.fill.iter().chain(shape.stroke.iter().map(|(paint, _)| paint)))
+            .flat_map(|paint| paint.colors());
+        let Some(first) = colors.next() else {
+            return true;
+        };
+        colors.all(|c| c.r == first.r && c.g == first.g && c.b == first.b)
+    }
+
+    /// Renders the document scaled to `width` x `height` pixels
+    ///
+    /// If `color` is set, every paint is replaced by it (keeping each
+    /// paint's alpha), which recolors single-color icons.
+    pub fn rasterize(&self, width: u32, height: u32, color: Option<Color>) -> Option<ImageData> {
+        let mut pixmap = tiny_skia::Pixmap::new(width, height)?;
+        if !self.size.is_empty() {
+            let scale = Transform::scale(width as f32 / self.size.width, height as f32 / self.size.height);
+            for shape in &self.shapes {
+                draw_shape(&mut pixmap, shape, &shape.transform.then(&scale), color);
+            }
+        }
+
+        let pixels = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let c = pixel.demultiply();
+                [c.red(), c.green(), c.blue(), c.alpha()]
+            })
+            .collect();
+        ImageData::from_rgba8(width, height, pixels)
+    }
+}
+
+/// Flattens the usvg tree into shapes, folding group opacity into paints
+fn collect_shapes(group: &usvg::Group, opacity: f32, shapes: &mut Vec<SvgShape>) {
+    let opacity = opacity * group.opacity().get();
+    for node in group.children() {
+        match node {
+            usvg::Node::Group(group) => collect_shapes(group, opacity, shapes),
+            usvg::Node::Path(path) if path.is_visible() => {
+                let fill = path
+                    .fill()
+                    .and_then(|fill| convert_paint(fill.paint(), fill.opacity().get() * opacity));
+                let stroke = path.stroke().and_then(|stroke| {
+                    let paint = convert_paint(stroke.paint(), stroke.opacity().get() * opacity)?;
+                    Some((paint, convert_stroke(stroke)))
+                });
+                if fill.is_none() && stroke.is_none() {
+                    continue;
+                }
+                let mut converted = convert_path(path.data());
+                if let Some(fill) = path.fill()
+                    && fill.rule() == usvg::FillRule::EvenOdd
+                {
+                    converted.set_fill_rule(FillRule::EvenOdd);
+                }
+                shapes.push(SvgShape {
+                    path: converted,
+                    transform: convert_transform(path.abs_transform()),
+                    fill,
+                    stroke,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn convert_transform(t: usvg::Transform) -> Transform {
+    Transform::new(t.sx, t.ky, t.kx, t.sy, t.tx, t.ty)
+}
+
+fn convert_path(data: &usvg::tiny_skia_path::Path) -> Path {
+    use usvg::tiny_skia_path::PathSegment;
+
+    let point = |p: usvg::tiny_skia_path::Point| Point::new(p.x, p.y);
+    let mut path = Path::new();
+    for segment in data.segments() {
+        match segment {
+            PathSegment::MoveTo(p) => {
+                path.move_to(point(p));
+            }
+            PathSegment::LineTo(p) => {
+                path.line_to(point(p));
+            }
+            PathSegment::QuadTo(c, p) => {
+                path.quad_to(point(c), point(p));
+            }
+            PathSegment::CubicTo(c1, c2, p) => {
+                path.cubic_to(point(c1), point(c2), point(p));
+            }
+            PathSegment::Close => {
+                path.close();
+            }
+        }
+    }
+    path
+}
+
+fn convert_paint(paint: &usvg::Paint, opacity: f32) -> Option<SvgPaint> {
+    let stops = |base: &usvg::BaseGradient| {
+        base.stops()
+            .iter()
+            .map(|stop| GradientStop {
+                offset: stop.offset().get(),
+                color: convert_color(stop.color(), stop.opacity().get() * opacity),
+            })
+            .collect()
+    };
+    let spread = |base: &usvg::BaseGradient| match base.spread_method() {
+        usvg::SpreadMethod::Pad => SpreadMethod::Pad,
+        usvg::SpreadMethod::Reflect => SpreadMethod::Reflect,
+        usvg::SpreadMethod::Repeat => SpreadMethod::Repeat,
+    };
+
+    match paint {
+        usvg::Paint::Color(color) => Some(SvgPaint::Solid(convert_color(*color, opacity))),
+        usvg::Paint::LinearGradient(gradient) => Some(SvgPaint::LinearGradient {
+            start: Point::new(gradient.x1(), gradient.y1()),
+            end: Point::new(gradient.x2(), gradient.y2()),
+            stops: stops(gradient),
+            spread: spread(gradient),
+            transform: convert_transform(gradient.transform()),
+        }),
+        usvg::Paint::RadialGradient(gradient) => Some(SvgPaint::RadialGradient {
+            center: Point::new(gradient.cx(), gradient.cy()),
+            radius: gradient.r().get(),
+            focal: Point::new(gradient.fx(), gradient.fy()),
+            focal_radius: gradient.fr().get(),
+            stops: stops(gradient),
+            spread: spread(gradient),
+            transform: convert_transform(gradient.transform()),
+        }),
+        usvg::Paint::Pattern(_) => None,
+    }
+}
+
+fn convert_color(color: usvg::Color, alpha: f32) -> Color {
+    Color::new(
+        color.red as f32 / 255.0,
+        color.green as f32 / 255.0,
+        color.blue as f32 / 255.0,
+        alpha,
+    )
+}
+
+fn convert_stroke(stroke: &usvg::Stroke) -> StrokeStyle {
+    let cap = match stroke.linecap() {
+        usvg::LineCap::Butt => StrokeCap::Butt,
+        usvg::LineCap::Round => StrokeCap::Round,
+        usvg::LineCap::Square => StrokeCap::Square,
+    };
+    let join = match stroke.linejoin() {
+        usvg::LineJoin::Miter | usvg::LineJoin::MiterClip => StrokeJoin::Miter,
+        usvg::LineJoin::Round => StrokeJoin::Round,
+        usvg::LineJoin::Bevel => StrokeJoin::Bevel,
+    };
+    let mut style = StrokeStyle::new(stroke.width().get())
+        .cap(cap)
+        .join(join)
+        .miter_limit(stroke.miterlimit().get());
+    if let Some(dashes) = stroke.dasharray() {
+        style = style.dash(dashes.to_vec(), stroke.dashoffset());
+    }
+    style
+}
+
+fn to_skia_transform(t: &Transform) -> tiny_skia::Transform {
+    tiny_skia::Transform::from_row(t.a, t.b, t.c, t.d, t.e, t.f)
+}
+
+fn to_skia_path(path: &Path) -> Option<tiny_skia::Path> {
+    let mut builder = tiny_skia::PathBuilder::new();
+    for command in path.commands() {
+        match *command {
+            PathCommand::MoveTo(p) => builder.move_to(p.x, p.y),
+            PathCommand::LineTo(p) => builder.line_to(p.x, p.y),
+            PathCommand::QuadTo(c, p) => builder.quad_to(c.x, c.y, p.x, p.y),
+            PathCommand::CubicTo(c1, c2, p) => builder.cubic_to(c1.x, c1.y, c2.x, c2.y, p.x, p.y),
+            PathCommand::Close => builder.close(),
+        }
+    }
+    builder.finish()
+}
+
+fn to_skia_color(color: Color) -> tiny_skia::Color {
+    tiny_skia::Color::from_rgba(
+        color.r.clamp(0.0, 1.0),
+        color.g.clamp(0.0, 1.0),
+        color.b.clamp(0.0, 1.0),
+        color.a.clamp(0.0, 1.0),
+    )
+    .unwrap_or(tiny_skia::Color::TRANSPARENT)
+}
+
+fn to_skia_shader(paint: &SvgPaint) -> Option<tiny_skia::Shader<'static>> {
+    let stops = |stops: &[GradientStop]| {
+        stops
+            .iter()
+            .map(|stop| tiny_skia::GradientStop::new(stop.offset, to_skia_color(stop.color)))
+            .collect()
+    };
+    let mode = |spread: SpreadMethod| match spread {
+        SpreadMethod::Pad => tiny_skia::SpreadMode::Pad,
+        SpreadMethod::Reflect => tiny_skia::SpreadMode::Reflect,
+        SpreadMethod::Repeat => tiny_skia::SpreadMode::Repeat,
+    };
+
+    match paint {
+        SvgPaint::Solid(color) => Some(tiny_skia::Shader::SolidColor(to_skia_color(*color))),
+        SvgPaint::LinearGradient {
+            start,
+            end,
+            stops: gradient_stops,
+            spread,
+            transform,
+        } => tiny_skia::LinearGradient::new(
+            tiny_skia::Point::from_xy(start.x, start.y),
+            tiny_skia::Point::from_xy(end.x, end.y),
+            stops(gradient_stops),
+            mode(*spread),
+            to_skia_transform(transform),
+        ),
+        SvgPaint::RadialGradient {
+            center,
+            radius,
+            focal,
+            focal_radius,
+            stops: gradient_stops,
+            spread,
+            transform,
+        } => tiny_skia::RadialGradient::new(
+            tiny_skia::Point::from_xy(focal.x, focal.y),
+            *focal_radius,
+            tiny_skia::Point::from_xy(center.x, center.y),
+            *radius,
+            stops(gradient_stops),
+            mode(*spread),
+            to_skia_transform(transform),
+        ),
+    }
+}
+
+fn draw_shape(pixmap: &mut tiny_skia::Pixmap, shape: &SvgShape, transform: &Transform, color: Option<Color>) {
+    let Some(path) = to_skia_path(&shape.path) else {
+        return;
+    };
+    let transform = to_skia_transform(transform);
+    let skia_paint = |paint: &SvgPaint| {
+        let paint = match color {
+            Some(color) => paint.recolored(color),
+            None => paint.clone(),
+        };
+        let shader = to_skia_shader(&paint)?;
+        Some(tiny_skia::Paint {
+            shader,
+            anti_alias: true,
+            ..Default::default()
+        })
+    };
+
+    if let Some(paint) = shape.fill.as_ref().and_then(skia_paint) {
+        let rule = match shape.path.fill_rule() {
+            FillRule::NonZero => tiny_skia::FillRule::Winding,
+            FillRule::EvenOdd => tiny_skia::FillRule::EvenOdd,
+        };
+        pixmap.fill_path(&path, &paint, rule, transform, None);
+    }
+    if let Some((stroke_paint, style)) = &shape.stroke
+        && let Some(paint) = skia_paint(stroke_paint)
+    {
+        let line_cap = match style.cap {
+            StrokeCap::Butt => tiny_skia::LineCap::Butt,
+            StrokeCap::Round => tiny_skia::LineCap::Round,
+            StrokeCap::Square => tiny_skia::LineCap::Square,
+        };
+        let line_join = match style.join {
+            StrokeJoin::Miter => tiny_skia::LineJoin::Miter,
+            StrokeJoin::Round => tiny_skia::LineJoin::Round,
+            StrokeJoin::Bevel => tiny_skia::LineJoin::Bevel,
+        };
+        let stroke = tiny_skia::Stroke {
+            width: style.width,
+            miter_limit: style.miter_limit,
+            line_cap,
+            line_join,
+            dash: if style.is_dashed() {
+                tiny_skia::StrokeDash::new(style.dash_array.clone(), style.dash_offset)
+            } else {
+                None
+            },
+        };
+        pixmap.stroke_path(&path, &paint, &stroke, transform, None);
+    }
+}
+
+/// Key of a cached raster: document, pixel size and recolor color
+type RasterKey = (u64, u32, u32, Option<[u32; 4]>);
+
+/// Parsed documents and their rasters, shared by all SVG widgets
+struct SvgCache {
+    documents: HashMap<ImageKey, Option<Arc<SvgDocument>>>,
+    rasters: HashMap<RasterKey, (ImageData, u64)>,
+    raster_bytes: usize,
+    clock: u64,
+}
+
+/// Budget for cached SVG rasters (32 MiB)
+const MAX_RASTER_BYTES: usize = 32 * 1024 * 1024;
+/// Largest raster side; bigger requests are clamped
+const MAX_RASTER_SIZE: u32 = 4096;
+
+fn cache() -> &'static Mutex<SvgCache> {
+    static CACHE: OnceLock<Mutex<SvgCache>> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        Mutex::new(SvgCache {
+            documents: HashMap::new(),
+            rasters: HashMap::new(),
+            raster_bytes: 0,
+            clock: 0,
+        })
+    })
+}
+
+/// Loads and parses an SVG source, reusing earlier results
+///
+/// Failures are reported once and remembered.
+pub(crate) fn load_document(source: &ImageSource) -> Option<Arc<SvgDocument>> {
+    if let Some(document) = cache().lock().unwrap().documents.get(source.key()) {
+        return document.clone();
+    }
+
+    let result = source.load().and_then(|bytes| SvgDocument::parse(&bytes));
+    let document = match result {
+        Ok(document) => Some(Arc::new(document)),
+        Err(error) => {
+            eprintln!("Warning: failed to load SVG: {}", error);
+            None
+        }
+    };
+    cache()
+        .lock()
+        .unwrap()
+        .documents
+        .insert(source.key().clone(), document.clone());
+    document
+}
+
+/// Returns the document rasterized at the given pixel size, from the cache
+/// when possible
+pub(crate) fn rasterize_cached(document: &SvgDocument, width: u32, height: u32, color: Option<Color>) -> Option<ImageData> {
+    let width = width.clamp(1, MAX_RASTER_SIZE);
+    let height = height.clamp(1, MAX_RASTER_SIZE);
+    let key = (
+        document.id(),
+        width,
+        height,
+        color.map(|c| [c.r.to_bits(), c.g.to_bits(), c.b.to_bits(), c.a.to_bits()]),
+    );
+
+    {
+        let mut cache = cache().lock().unwrap();
+        cache.clock += 1;
+        let clock = cache.clock;
+        if let Some((image, last_used)) = cache.rasters.get_mut(&key) {
+            *last_used = clock;
+            return Some(image.clone());
+        }
+    }
+
+    let image = document.rasterize(width, height, color)?;
+
+    let mut cache = cache().lock().unwrap();
+    cache.raster_bytes += image.byte_len();
+    let clock = cache.clock;
+    cache.rasters.insert(key, (image.clone(), clock));
+    while cache.raster_bytes > MAX_RASTER_BYTES {
+        let victim = cache
+            .rasters
+            .iter()
+            .filter(|(k, _)| **k != key)
+            .min_by_key(|(_, (_, last_used))| *last_used)
+            .map(|(k, _)| *k);
+        let Some(victim) = victim else {
+            break;
+        };
+        if let Some((evicted, _)) = cache.rasters.remove(&victim) {
+            cache.raster_bytes -= evicted.byte_len();
+        }
+    }
+    Some(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ICON: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24">
+        <g transform="translate(2 2)">
+            <rect x="0" y="0" width="10" height="10" fill="#ff0000"/>
+            <path d="M0 15 L20 15" stroke="#ff0000" stroke-width="2" fill="none" stroke-linecap="round"/>
+        </g>
+    </svg>"##;
+
+    #[test]
+    fn test_parse_shapes_and_transforms() {
+        let document = SvgDocument::parse(ICON.as_bytes()).unwrap();
+        assert_eq!(document.size(), Size::new(24.0, 24.0));
+        assert_eq!(document.shapes().len(), 2);
+
+        let rect = &document.shapes()[0];
+        assert_eq!(rect.transform, Transform::translate(2.0, 2.0));
+        assert_eq!(rect.fill, Some(SvgPaint::Solid(Color::new(1.0, 0.0, 0.0, 1.0))));
+
+        let (paint, style) = document.shapes()[1].stroke.clone().unwrap();
+        assert_eq!(paint, SvgPaint::Solid(Color::new(1.0, 0.0, 0.0, 1.0)));
+        assert_eq!(style.width, 2.0);
+        assert_eq!(style.cap, StrokeCap::Round);
+        assert!(document.is_single_color());
+    }
+
+    #[test]
+    fn test_parse_gradient() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+            <defs><linearGradient id="g"><stop offset="0" stop-color="#000"/><stop offset="1" stop-color="#fff"/></linearGradient></defs>
+            <rect width="10" height="10" fill="url(#g)"/>
+        </svg>"##;
+        let document = SvgDocument::parse(svg.as_bytes()).unwrap();
+        assert!(matches!(
+            document.shapes()[0].fill,
+            Some(SvgPaint::LinearGradient { ref stops, .. }) if stops.len() == 2
+        ));
+        assert!(!document.is_single_color());
+    }
+
+    #[test]
+    fn test_rasterize_scales_and_recolors() {
+        let document = SvgDocument::parse(ICON.as_bytes()).unwrap();
+        let image = document.rasterize(48, 48, Some(Color::BLUE)).unwrap();
+        assert_eq!((image.width(), image.height()), (48, 48));
+
+        // (4..24, 4..24) is the scaled rectangle
+        let pixel = |x: usize, y: usize| {
+            let i = (y * 48 + x) * 4;
+            &image.pixels()[i..i + 4]
+        };
+        assert_eq!(pixel(10, 10), &[0, 0, 255, 255]);
+        assert_eq!(pixel(40, 5)[3], 0);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(SvgDocument::parse(b"<not svg").is_err());
+    }
+
+    #[test]
+    fn test_raster_cache_reuses_images() {
+        let document = SvgDocument::parse(ICON.as_bytes()).unwrap();
+        let a = rasterize_cached(&document, 16, 16, None).unwrap();
+        let b = rasterize_cached(&document, 16, 16, None).unwrap();
+        let c = rasterize_cached(&document, 32, 32, None).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+}
@@ -0,0 +1,227 @@
+//! SvgImage widget - displays SVG documents
+
+use crate::image_cache::ImageSource;
+use crate::svg::{load_document, rasterize_cached, SvgDocument};
+use kobalt_core::layout::{Alignment, BoxFit};
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Color, Length, Rect, Size};
+use kobalt_core::widget::Widget;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Where the widget gets its document from
+enum Provider {
+    Source(ImageSource),
+    Document(Arc<SvgDocument>),
+}
+
+/// A widget that displays an SVG document
+///
+/// The document is rasterized at exactly the size it is drawn at, so it
+/// stays sharp at any size; rasters are cached and shared between widgets.
+/// Setting a `color` recolors single-color icons.
+///
+/// # Example
+///
+/// ```no_run
+/// use kobalt_widgets::{Color, SvgImage};
+///
+/// let logo = SvgImage::asset("images/logo.svg")
+///     .width(64.0)
+///     .color(Color::WHITE);
+/// ```
+pub struct SvgImage {
+    provider: Provider,
+    width: Option<Length>,
+    height: Option<Length>,
+    fit: BoxFit,
+    alignment: Alignment,
+    color: Option<Color>,
+}
+
+impl SvgImage {
+    fn with_provider(provider: Provider) -> Self {
+        Self {
+            provider,
+            width: None,
+            height: None,
+            fit: BoxFit::default(),
+            alignment: Alignment::default(),
+            color: None,
+        }
+    }
+
+    /// Creates an SVG image from document bytes
+    pub fn memory<B: Into<Arc<[u8]>>>(bytes: B) -> Self {
+        Self::with_provider(Provider::Source(ImageSource::memory(bytes)))
+    }
+
+    /// Creates an SVG image from a file on disk
+    pub fn file<P: Into<PathBuf>>(path: P) -> Self {
+        Self::with_provider(Provider::Source(ImageSource::file(path)))
+    }
+
+    /// Creates an SVG image from the asset bundle
+    pub fn asset(name: &str) -> Self {
+        Self::with_provider(Provider::Source(ImageSource::asset(name)))
+    }
+
+    /// Creates an SVG image from an already parsed document
+    pub fn from_document(document: Arc<SvgDocument>) -> Self {
+        Self::with_provider(Provider::Document(document))
+    }
+
+    /// Sets the width of the widget
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.width = Some(width.into());
+        self
+    }
+
+    /// Sets the height of the widget
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.height = Some(height.into());
+        self
+    }
+
+    /// Sets how the document is fitted into the widget's bounds
+    pub fn fit(mut self, fit: BoxFit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Sets where the document is placed within the widget's bounds
+    pub fn alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Paints every shape with this color (keeping each shape's alpha)
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Returns the color, if any
+    pub fn get_color(&self) -> Option<Color> {
+        self.color
+    }
+
+    /// Returns the parsed document, or None if it failed to load
+    pub fn document(&self) -> Option<Arc<SvgDocument>> {
+        match &self.provider {
+            Provider::Document(document) => Some(document.clone()),
+            Provider::Source(source) => load_document(source),
+        }
+    }
+}
+
+impl Widget for SvgImage {
+    fn widget_type(&self) -> &'static str {
+        "SvgImage"
+    }
+
+    fn layout(&self, constraints: Size) -> Size {
+        let natural = self.document().map(|document| document.size()).filter(|size| !size.is_empty());
+        let width = self.width.map(|width| width.to_logical());
+        let height = self.height.map(|height| height.to_logical());
+        let size = match (width, height, natural) {
+            (Some(width), Some(height), _) => Size::new(width, height),
+            (Some(width), None, Some(natural)) => {
+                Size::new(width, width * natural.height / natural.width)
+            }
+            (None, Some(height), Some(natural)) => {
+                Size::new(height * natural.width / natural.height, height)
+            }
+            (None, None, Some(natural)) => natural,
+            (width, height, None) => Size::new(width.unwrap_or(0.0), height.unwrap_or(0.0)),
+        };
+
+        // Scale down uniformly to fit the constraints
+        let scale = (constraints.width / size.width)
+            .min(constraints.height / size.height)
+            .min(1.0);
+        if scale.is_finite() && scale < 1.0 {
+            Size::new(size.width * scale, size.height * scale)
+        } else {
+            Size::new(size.width.min(constraints.width), size.height.min(constraints.height))
+        }
+    }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        let Some(document) = self.document() else {
+            return;
+        };
+        let natural = document.size();
+        let fitted = self.fit.apply(natural, bounds.size());
+        if fitted.source.is_empty() || fitted.destination.is_empty() {
+            return;
+        }
+
+        // Rasterize the whole document at the scale it is shown at, then
+        // draw the part selected by the fit
+        let scale_x = fitted.destination.width / fitted.source.width;
+        let scale_y = fitted.destination.height / fitted.source.height;
+        let width = (natural.width * scale_x).round().max(1.0) as u32;
+        let height = (natural.height * scale_y).round().max(1.0) as u32;
+        let Some(image) = rasterize_cached(&document, width, height, self.color) else {
+            return;
+        };
+
+        let raster_scale_x = image.width() as f32 / natural.width;
+        let raster_scale_y = image.height() as f32 / natural.height;
+        let src = self
+            .alignment
+            .inscribe(fitted.source, Rect::new(0.0, 0.0, natural.width, natural.height));
+        let src = Rect::new(
+            src.x * raster_scale_x,
+            src.y * raster_scale_y,
+            src.width * raster_scale_x,
+            src.height * raster_scale_y,
+        );
+        let dst = self.alignment.inscribe(fitted.destination, bounds);
+        ctx.draw_image(&image, src, dst, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kobalt_core::paint::DrawCommand;
+
+    const SQUARE: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="20">
+        <rect width="10" height="20" fill="#000"/>
+    </svg>"##;
+
+    fn document() -> Arc<SvgDocument> {
+        Arc::new(SvgDocument::parse(SQUARE.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn test_svg_layout_keeps_aspect() {
+        let svg = SvgImage::from_document(document()).height(40.0);
+        assert_eq!(svg.layout(Size::new(100.0, 100.0)), Size::new(20.0, 40.0));
+    }
+
+    #[test]
+    fn test_svg_rasterizes_at_drawn_size() {
+        let svg = SvgImage::from_document(document());
+        let mut ctx = PaintContext::new();
+        svg.paint(&mut ctx, Rect::new(0.0, 0.0, 50.0, 50.0));
+        let list = ctx.finish();
+
+        match &list.commands()[0] {
+            DrawCommand::Image { image, src, dst, .. } => {
+                assert_eq!((image.width(), image.height()), (25, 50));
+                assert_eq!(*src, Rect::new(0.0, 0.0, 25.0, 50.0));
+                assert_eq!(*dst, Rect::new(12.5, 0.0, 25.0, 50.0));
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_svg_memory_source() {
+        let svg = SvgImage::memory(SQUARE.as_bytes().to_vec());
+        assert_eq!(svg.document().unwrap().size(), Size::new(10.0, 20.0));
+    }
+}
@@ -1,13 +1,14 @@
 //! Text widget - displays text on screen
 
-use kobalt_core::types::{Color, Point, Rect, Size};
+use kobalt_core::paint::PaintContext;
+use kobalt_core::types::{Color, Length, Point, Rect, Size};
 use kobalt_core::widget::Widget;
 
 /// Style configuration for Text widget
 #[derive(Clone, Debug)]
 pub struct TextStyle {
     pub color: Color,
-    pub size: f32,
+    pub size: Length,
     pub position: Option<Point>,
     pub font_family: Option<String>,
 }
@@ -17,7 +18,7 @@ impl TextStyle {
     pub fn new() -> Self {
         Self {
             color: Color::WHITE,
-            size: 16.0,
+            size: Text::DEFAULT_FONT_SIZE,
             position: None,
             font_family: None,
         }
@@ -29,9 +30,9 @@ impl TextStyle {
         self
     }
 
-    /// Sets the font size
-    pub fn size(mut self, size: f32) -> Self {
-        self.size = size;
+    /// Sets the font size; use `sp` to follow the user's text scale
+    pub fn size(mut self, size: impl Into<Length>) -> Self {
+        self.size = size.into();
         self
     }
 
@@ -59,11 +60,12 @@ impl Default for TextStyle {
 /// # Example
 ///
 /// ```
+/// use kobalt_core::types::Units;
 /// use kobalt_widgets::{Text, Color};
 ///
 /// let text = Text::new("Hello, World!")
 ///     .color(Color::WHITE)
-///     .size(24.0);
+///     .size(24.sp());
 /// ```
 #[derive(Clone)]
 pub struct Text {
@@ -71,21 +73,24 @@ pub struct Text {
     pub content: String,
     /// Text color
     pub color: Color,
-    /// Font size in logical pixels
-    pub font_size: f32,
-    /// Position on screen
+    /// Font size, resolved to logical pixels at layout time
+    pub font_size: Length,
+    /// Offset from the top-left corner of the bounds assigned by the parent
     pub position: Point,
     /// Calculated bounds after layout
     bounds: Option<Rect>,
 }
 
 impl Text {
+    /// Default font size, which follows the user's text scale
+    pub const DEFAULT_FONT_SIZE: Length = Length::Sp(16.0);
+
     /// Creates a new Text widget with the given content
     pub fn new(content: impl Into<String>) -> Self {
         Self {
             content: content.into(),
             color: Color::WHITE,
-            font_size: 16.0,
+            font_size: Self::DEFAULT_FONT_SIZE,
             position: Point::zero(),
             bounds: None,
         }
@@ -108,9 +113,9 @@ impl Text {
         self
     }
 
-    /// Sets the font size
-    pub fn size(mut self, font_size: f32) -> Self {
-        self.font_size = font_size;
+    /// Sets the font size; use `sp` to follow the user's text scale
+    pub fn size(mut self, font_size: impl Into<Length>) -> Self {
+        self.font_size = font_size.into();
         self
     }
 
@@ -129,9 +134,10 @@ impl Text {
     /// This is a simple approximation - real text rendering will be more accurate
     fn estimate_size(&self) -> Size {
         // Rough estimation: average character width is ~0.6 * font_size
-        let char_width = self.font_size * 0.6;
+        let font_size = self.font_size.to_logical();
+        let char_width = font_size * 0.6;
         let width = self.content.len() as f32 * char_width;
-        let height = self.font_size * 1.2; // Include line height
+        let height = font_size * 1.2; // Include line height
 
         Size::new(width, height)
     }
@@ -155,6 +161,19 @@ impl Widget for Text {
     fn bounds(&self) -> Option<Rect> {
         self.bounds
     }
+
+    fn paint(&self, ctx: &mut PaintContext, bounds: Rect) {
+        ctx.draw_text(
+            &self.content,
+            bounds.origin() + self.position,
+            self.font_size.to_logical(),
+            self.color,
+        );
+    }
+
+    fn text(&self) -> Option<&str> {
+        Some(&self.content)
+    }
 }
 
 impl std::fmt::Debug for Text {
@@ -176,7 +195,7 @@ mod tests {
     fn test_text_creation() {
         let text = Text::new("Hello");
         assert_eq!(text.content(), "Hello");
-        assert_eq!(text.font_size, 16.0);
+        assert_eq!(text.font_size, Length::Sp(16.0));
         assert_eq!(text.color, Color::WHITE);
     }
 
@@ -189,7 +208,7 @@ mod tests {
 
         assert_eq!(text.content(), "Test");
         assert_eq!(text.color, Color::RED);
-        assert_eq!(text.font_size, 24.0);
+        assert_eq!(text.font_size, Length::Dp(24.0));
         assert_eq!(text.position, Point::new(10.0, 20.0));
     }
 
@@ -0,0 +1,72 @@
+//! TextButton widget - a button without fill or outline
+
+use crate::button::{button_widget, ButtonCore, ButtonStyle};
+use kobalt_core::widget::Widget;
+
+/// A button that is just its label until hovered, focused or pressed,
+/// similar to Flutter's `TextButton`
+///
+/// Use it for the least prominent actions, e.g. in dialogs and cards. It
+/// behaves like a `Button`: disabled without callbacks, focusable and
+/// activated with Enter or Space.
+///
+/// # Example
+///
+/// ```
+/// use kobalt_widgets::TextButton;
+///
+/// let cancel = TextButton::label("Cancel").on_pressed(|| println!("cancelled"));
+/// ```
+pub struct TextButton {
+    core: ButtonCore,
+}
+
+impl TextButton {
+    /// Creates a disabled TextButton around a child
+    pub fn new<W: Widget + 'static>(child: W) -> Self {
+        Self {
+            core: ButtonCore::new(Box::new(child), ButtonStyle::text()),
+        }
+    }
+
+    /// Creates a disabled TextButton showing a label
+    pub fn label(label: impl Into<String>) -> Self {
+        Self {
+            core: ButtonCore::label(label.into(), ButtonStyle::text()),
+        }
+    }
+}
+
+button_widget!(TextButton);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kobalt_core::testing::{Finder, WidgetTester};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_tap() {
+        let taps = Rc::new(Cell::new(0));
+        let count = taps.clone();
+        let button = TextButton::label("Cancel").on_pressed(move || count.set(count.get() + 1));
+        let mut tester = WidgetTester::new(button);
+        tester.pump();
+
+        tester.tap(&Finder::text("Cancel"));
+        assert_eq!(taps.get(), 1);
+        assert_eq!(tester.count(&Finder::widget_type("TextButton")), 1);
+    }
+
+    #[test]
+    fn test_label_follows_style() {
+        let style = ButtonStyle {
+            foreground: kobalt_core::types::Color::RED,
+            ..ButtonStyle::text()
+        };
+        let button = TextButton::label("Cancel").style(style);
+        assert_eq!(button.get_style().foreground, kobalt_core::types::Color::RED);
+        assert_eq!(button.get_child().text(), Some("Cancel"));
+    }
+}
@@ -34,6 +34,7 @@
 //! - `app!` - Declarative app configuration
 //! - `column!` - Vertical layout with alignment and padding
 //! - `text!` - Text widgets with styling
+//! - `button!` - Labeled buttons with callbacks
 //! - `text_style!` - Reusable text styles
 //!
 //! ## Architecture
@@ -56,20 +57,33 @@ pub use kobalt_macros as macros;
 // Re-export commonly used items for convenience
 pub mod prelude {
     // Runtime
-    pub use kobalt_runtime::{KobaltApp, DesktopApp, DesktopWindow};
+    pub use kobalt_runtime::{KobaltApp, DesktopApp, DesktopWindow, OffscreenRenderer, DeviceProfile};
     pub use kobalt_runtime::{Event, WindowEvent};
+    pub use kobalt_render::{RendererConfig, PresentMode, PowerPreference, RenderBackend, CpuBackend};
 
     // Widget types (available but prefer using macros)
     pub use kobalt_widgets::{Text, TextStyle};
+    pub use kobalt_widgets::{BackdropFilter, ImageFiltered, CustomPaint, CustomPainter, RepaintBoundary};
+    pub use kobalt_widgets::{Image, SvgImage, Icon};
+    pub use kobalt_widgets::{Semantics, KeyedSubtree, Stack};
+    pub use kobalt_widgets::{IgnorePointer, AbsorbPointer, GestureDetector, MouseRegion, MouseCursor};
+    pub use kobalt_widgets::{Focus, FocusScope, FocusNode};
+    pub use kobalt_widgets::{Shortcuts, Actions, Intent, KeyChord};
+    pub use kobalt_widgets::{Button, TextButton, OutlinedButton, IconButton, ButtonStyle, WidgetState};
     // Note: Column type is available via kobalt::widgets::Column if needed directly
 
     // Core types
-    pub use kobalt_core::types::{Color, Point, Rect, Size};
-    pub use kobalt_core::widget::{Widget, Constraints};
-    pub use kobalt_core::layout::{MainAxisAlignment, CrossAxisAlignment, EdgeInsets};
-    
+    pub use kobalt_core::types::{Color, Length, Point, Rect, Size, Units};
+    pub use kobalt_core::media_query::{MediaQuery, MediaQueryData};
+    pub use kobalt_core::error::KobaltError;
+    pub use kobalt_core::widget::{Widget, Constraints, Key};
+    pub use kobalt_core::layout::{MainAxisAlignment, CrossAxisAlignment, EdgeInsets, Alignment, BoxFit};
+    pub use kobalt_core::paint::{ImageFilter, ColorMatrix, Paint, PaintStyle};
+    pub use kobalt_core::path::{Path, FillRule, StrokeStyle, StrokeCap, StrokeJoin};
+    pub use kobalt_core::canvas::Canvas;
+
     // Macros (preferred way to create UIs - mandatory for layout widgets)
-    pub use kobalt_macros::{text, column, app, text_style};
+    pub use kobalt_macros::{text, column, app, text_style, button};
 }
 
 // Direct re-exports for top-level convenience